@@ -0,0 +1,32 @@
+use std::{slice::Iter, sync::{Arc, Mutex}};
+
+use crate::{commands::RedisCommand, redis::{client::{CacheVal, StringCacheVal}, keyspace::Keyspace, string::live_string}, resp::{create_int_resp, types::RespType}};
+
+pub struct AppendCommand {
+    key: Vec<u8>,
+    value: Vec<u8>,
+    cache: Arc<Mutex<Keyspace>>
+}
+
+impl AppendCommand {
+    pub fn new(key: Vec<u8>, value: Vec<u8>, cache: Arc<Mutex<Keyspace>>) -> Self {
+        AppendCommand { key, value, cache }
+    }
+}
+
+impl RedisCommand for AppendCommand {
+    fn execute(&self, _: &mut Iter<'_, RespType>) -> Vec<Vec<u8>> {
+        let mut cache_guard = self.cache.lock().unwrap();
+        match live_string(&mut cache_guard, &self.key) {
+            Ok(Some(string_cache_val)) => {
+                string_cache_val.val.extend_from_slice(&self.value);
+                vec![create_int_resp(string_cache_val.val.len())]
+            },
+            Ok(None) => {
+                cache_guard.insert(self.key.clone(), CacheVal::String(StringCacheVal::new(self.value.clone())));
+                vec![create_int_resp(self.value.len())]
+            },
+            Err(err) => vec![err]
+        }
+    }
+}
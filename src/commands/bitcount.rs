@@ -0,0 +1,41 @@
+use std::{slice::Iter, sync::{Arc, Mutex}};
+
+use crate::{commands::RedisCommand, redis::{bitmap::{count_bits, BitRange}, client::CacheVal, keyspace::Keyspace, string::live_string}, resp::{create_int_resp, types::RespType}};
+
+/// BITCOUNT key [start end [BYTE|BIT]]
+pub struct BitcountCommand {
+    key: Vec<u8>,
+    cache: Arc<Mutex<Keyspace>>
+}
+
+impl BitcountCommand {
+    pub fn new(key: Vec<u8>, cache: Arc<Mutex<Keyspace>>) -> Self {
+        BitcountCommand { key, cache }
+    }
+}
+
+impl RedisCommand for BitcountCommand {
+    fn execute(&self, iter: &mut Iter<'_, RespType>) -> Vec<Vec<u8>> {
+        let mut args = vec![];
+        while let Some(RespType::String(arg)) = iter.next() {
+            args.push(arg.clone());
+        }
+        let range = match BitRange::parse(&args, true) {
+            Ok(range) => range,
+            Err(err) => return vec![err]
+        };
+
+        let mut cache_guard = self.cache.lock().unwrap();
+        let bytes = match live_string(&mut cache_guard, &self.key) {
+            Ok(Some(string_cache_val)) => &string_cache_val.val,
+            Ok(None) => return vec![create_int_resp(0)],
+            Err(err) => return vec![err]
+        };
+        let bits = match range {
+            Some(range) => range.resolve(bytes.len()),
+            None if bytes.is_empty() => None,
+            None => Some((0, bytes.len() * 8 - 1))
+        };
+        vec![create_int_resp(bits.map_or(0, |(first, last)| count_bits(bytes, first, last)))]
+    }
+}
@@ -0,0 +1,115 @@
+use std::{slice::Iter, sync::{Arc, Mutex}};
+
+use crate::{commands::{parse_arg, RedisCommand}, redis::{bitmap::{parse_bit_offset, BitfieldType, Overflow}, client::{CacheVal, StringCacheVal}, keyspace::Keyspace, string::live_string}, resp::{create_array_resp, create_basic_err_resp, create_int_resp, create_null_bulk_string_resp, types::RespType}};
+
+enum BitfieldOp {
+    Get(BitfieldType, usize),
+    Set(BitfieldType, usize, i64, Overflow),
+    Incrby(BitfieldType, usize, i64, Overflow)
+}
+
+/// Serves BITFIELD and BITFIELD_RO. The operations run in order, each OVERFLOW applying to the SET
+/// and INCRBY operations after it.
+pub struct BitfieldCommand {
+    key: Vec<u8>,
+    read_only: bool,
+    cache: Arc<Mutex<Keyspace>>
+}
+
+impl BitfieldCommand {
+    pub fn new(key: Vec<u8>, read_only: bool, cache: Arc<Mutex<Keyspace>>) -> Self {
+        BitfieldCommand { key, read_only, cache }
+    }
+
+    fn parse_ops(&self, args: &[Vec<u8>]) -> Result<Vec<BitfieldOp>, Vec<u8>> {
+        let syntax_err = || create_basic_err_resp("ERR syntax error".to_string());
+        let mut ops = vec![];
+        let mut overflow = Overflow::Wrap;
+        let mut pos = 0;
+        while pos < args.len() {
+            let subcommand = args[pos].to_ascii_lowercase();
+            if self.read_only && !subcommand.eq(b"get") {
+                return Err(create_basic_err_resp("ERR BITFIELD_RO only supports the GET subcommand".to_string()));
+            }
+            if subcommand.eq(b"overflow") {
+                overflow = match args.get(pos + 1).and_then(|arg| Overflow::parse(arg)) {
+                    Some(overflow) => overflow,
+                    None => return Err(create_basic_err_resp("ERR Invalid OVERFLOW type specified".to_string()))
+                };
+                pos += 2;
+                continue;
+            }
+
+            let arity = match subcommand.as_slice() {
+                b"get" => 3,
+                b"set" | b"incrby" => 4,
+                _ => return Err(syntax_err())
+            };
+            if pos + arity > args.len() {
+                return Err(syntax_err());
+            }
+            let ty = BitfieldType::parse(&args[pos + 1])?;
+            let offset = parse_bit_offset(&args[pos + 2], Some(ty.bits))?;
+            let val = match arity {
+                4 => match parse_arg::<i64>(&args[pos + 3]) {
+                    Some(val) => val,
+                    None => return Err(create_basic_err_resp("ERR value is not an integer or out of range".to_string()))
+                },
+                _ => 0
+            };
+            ops.push(match subcommand.as_slice() {
+                b"get" => BitfieldOp::Get(ty, offset),
+                b"set" => BitfieldOp::Set(ty, offset, val, overflow),
+                _ => BitfieldOp::Incrby(ty, offset, val, overflow)
+            });
+            pos += arity;
+        }
+        Ok(ops)
+    }
+}
+
+impl RedisCommand for BitfieldCommand {
+    fn execute(&self, iter: &mut Iter<'_, RespType>) -> Vec<Vec<u8>> {
+        let mut args = vec![];
+        while let Some(RespType::String(arg)) = iter.next() {
+            args.push(arg.clone());
+        }
+        let ops = match self.parse_ops(&args) {
+            Ok(ops) => ops,
+            Err(err) => return vec![err]
+        };
+
+        let mut cache_guard = self.cache.lock().unwrap();
+        let writes = ops.iter().any(|op| !matches!(op, BitfieldOp::Get(..)));
+        let string_cache_val = match live_string(&mut cache_guard, &self.key) {
+            Ok(Some(string_cache_val)) => string_cache_val,
+            Ok(None) if writes => match cache_guard.get_or_insert_with(self.key.clone(), || CacheVal::String(StringCacheVal::new(vec![]))) {
+                CacheVal::String(string_cache_val) => string_cache_val,
+                _ => unreachable!("the key was just created as a string")
+            },
+            Ok(None) => return vec![create_array_resp(ops.iter().map(|_| create_int_resp(0)).collect())],
+            Err(err) => return vec![err]
+        };
+
+        let bytes = &mut string_cache_val.val;
+        let replies = ops.into_iter().map(|op| match op {
+            BitfieldOp::Get(ty, offset) => create_int_resp(ty.read(bytes, offset)),
+            BitfieldOp::Set(ty, offset, val, overflow) => match ty.fit(val as i128, overflow) {
+                Some(val) => {
+                    let old_val = ty.read(bytes, offset);
+                    ty.write(bytes, offset, val);
+                    create_int_resp(old_val)
+                },
+                None => create_null_bulk_string_resp()
+            },
+            BitfieldOp::Incrby(ty, offset, increment, overflow) => match ty.fit(ty.read(bytes, offset) as i128 + increment as i128, overflow) {
+                Some(val) => {
+                    ty.write(bytes, offset, val);
+                    create_int_resp(val)
+                },
+                None => create_null_bulk_string_resp()
+            }
+        }).collect();
+        vec![create_array_resp(replies)]
+    }
+}
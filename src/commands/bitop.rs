@@ -0,0 +1,71 @@
+use std::{slice::Iter, sync::{Arc, Mutex}};
+
+use crate::{commands::RedisCommand, redis::{client::{CacheVal, StringCacheVal}, keyspace::Keyspace, string::live_string}, resp::{create_int_resp, types::RespType}};
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum BitOperation {
+    And,
+    Or,
+    Xor,
+    Not
+}
+
+impl BitOperation {
+    pub fn parse(arg: &[u8]) -> Option<Self> {
+        match arg.to_ascii_lowercase().as_slice() {
+            b"and" => Some(BitOperation::And),
+            b"or" => Some(BitOperation::Or),
+            b"xor" => Some(BitOperation::Xor),
+            b"not" => Some(BitOperation::Not),
+            _ => None
+        }
+    }
+}
+
+/// BITOP AND|OR|XOR|NOT destkey key [key ...]. Shorter and missing keys count as padded with zero
+/// bytes, and an empty result deletes the destination.
+pub struct BitopCommand {
+    operation: BitOperation,
+    destination: Vec<u8>,
+    keys: Vec<Vec<u8>>,
+    cache: Arc<Mutex<Keyspace>>
+}
+
+impl BitopCommand {
+    pub fn new(operation: BitOperation, destination: Vec<u8>, keys: Vec<Vec<u8>>, cache: Arc<Mutex<Keyspace>>) -> Self {
+        BitopCommand { operation, destination, keys, cache }
+    }
+}
+
+impl RedisCommand for BitopCommand {
+    fn execute(&self, _: &mut Iter<'_, RespType>) -> Vec<Vec<u8>> {
+        let mut cache_guard = self.cache.lock().unwrap();
+        let mut sources = vec![];
+        for key in &self.keys {
+            match live_string(&mut cache_guard, key) {
+                Ok(string_cache_val) => sources.push(string_cache_val.map_or(vec![], |val| val.val.clone())),
+                Err(err) => return vec![err]
+            }
+        }
+
+        let len = sources.iter().map(|source| source.len()).max().unwrap_or(0);
+        let byte_at = |source: &Vec<u8>, i: usize| source.get(i).copied().unwrap_or(0);
+        let result: Vec<u8> = (0..len).map(|i| {
+            let mut bytes = sources.iter().map(|source| byte_at(source, i));
+            let first = bytes.next().unwrap_or(0);
+            match self.operation {
+                BitOperation::And => bytes.fold(first, |acc, byte| acc & byte),
+                BitOperation::Or => bytes.fold(first, |acc, byte| acc | byte),
+                BitOperation::Xor => bytes.fold(first, |acc, byte| acc ^ byte),
+                BitOperation::Not => !first
+            }
+        }).collect();
+
+        if result.is_empty() {
+            cache_guard.remove(&self.destination);
+        } else {
+            cache_guard.insert(self.destination.clone(), CacheVal::String(StringCacheVal::new(result)));
+        }
+        vec![create_int_resp(len)]
+    }
+}
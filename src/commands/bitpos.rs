@@ -0,0 +1,53 @@
+use std::{slice::Iter, sync::{Arc, Mutex}};
+
+use crate::{commands::RedisCommand, redis::{bitmap::{find_bit, BitRange}, client::CacheVal, keyspace::Keyspace, string::live_string}, resp::{create_int_resp, types::RespType}};
+
+/// BITPOS key bit [start [end [BYTE|BIT]]]
+pub struct BitposCommand {
+    key: Vec<u8>,
+    bit: u8,
+    cache: Arc<Mutex<Keyspace>>
+}
+
+impl BitposCommand {
+    pub fn new(key: Vec<u8>, bit: u8, cache: Arc<Mutex<Keyspace>>) -> Self {
+        BitposCommand { key, bit, cache }
+    }
+}
+
+impl RedisCommand for BitposCommand {
+    fn execute(&self, iter: &mut Iter<'_, RespType>) -> Vec<Vec<u8>> {
+        let mut args = vec![];
+        while let Some(RespType::String(arg)) = iter.next() {
+            args.push(arg.clone());
+        }
+        let range = match BitRange::parse(&args, false) {
+            Ok(range) => range,
+            Err(err) => return vec![err]
+        };
+
+        let mut cache_guard = self.cache.lock().unwrap();
+        let bytes = match live_string(&mut cache_guard, &self.key) {
+            Ok(Some(string_cache_val)) => &string_cache_val.val,
+            // a missing key is an empty string, so all of its bits are clear
+            Ok(None) => return vec![create_int_resp(if self.bit == 1 { -1 } else { 0 })],
+            Err(err) => return vec![err]
+        };
+        let has_end = range.as_ref().is_some_and(|range| range.has_end());
+        let bits = match range {
+            Some(range) => range.resolve(bytes.len()),
+            None if bytes.is_empty() => None,
+            None => Some((0, bytes.len() * 8 - 1))
+        };
+        let (first, last) = match bits {
+            Some(bits) => bits,
+            None => return vec![create_int_resp(-1)]
+        };
+        match find_bit(bytes, self.bit, first, last) {
+            Some(pos) => vec![create_int_resp(pos)],
+            // without an explicit end the string counts as padded with clear bits
+            None if self.bit == 0 && !has_end => vec![create_int_resp(last + 1)],
+            None => vec![create_int_resp(-1)]
+        }
+    }
+}
@@ -1,86 +1,108 @@
-use std::{collections::HashMap, slice::Iter, sync::{Arc, Mutex}};
+use std::{slice::Iter, sync::{Arc, Mutex}};
 
-use crate::{commands::RedisCommand, redis::{client::{CacheVal, ListCacheVal}}, resp::{create_array_resp, create_bulk_string_resp, create_null_bulk_string_resp}, resp::types::RespType};
+use crate::{commands::RedisCommand, redis::{blocking::BlockingArgs, client::{CacheVal, ListCacheVal}, keyspace::Keyspace, replication::ReplicationLog, list::ListEnd}, resp::{create_array_resp, create_bulk_string_resp, create_null_array_resp, create_null_bulk_string_resp, create_wrong_type_err_resp}, resp::types::RespType};
 
+/// What a blocked list command does once one of its keys has elements.
+pub enum BlockingListOp {
+    /// BLPOP/BRPOP, replying `[key, element]`.
+    Pop(ListEnd),
+    /// BLMPOP, replying `[key, [element ...]]`.
+    Mpop(ListEnd, usize),
+    /// BLMOVE/BRPOPLPUSH, replying the moved element.
+    Move { destination: Vec<u8>, from: ListEnd, to: ListEnd }
+}
+
+/// A key and the elements taken from it.
+type Taken = (Vec<u8>, Vec<Vec<u8>>);
+
+/// Serves BLPOP, BRPOP, BLMPOP, BLMOVE and BRPOPLPUSH. Waiting connections queue up on every key
+/// they block on and only the one at the front of a key's queue may take from it, keys being
+/// tried in argument order.
 pub struct BlpopCommand {
-    list_key: String,
-    connection_id: String,
-    timeout_seconds: f32,
-    cache: Arc<Mutex<HashMap<String, CacheVal>>>
+    keys: Vec<Vec<u8>>,
+    op: BlockingListOp,
+    blocking: BlockingArgs,
+    cache: Arc<Mutex<Keyspace>>,
+    write_commands: Option<ReplicationLog>
 }
 
 impl BlpopCommand {
-    pub fn new(list_key: String, connection_id: String, timeout_seconds: f32, cache: Arc<Mutex<HashMap<String, CacheVal>>>) -> Self {
-        BlpopCommand {
-            list_key: list_key,
-            connection_id: connection_id,
-            timeout_seconds: timeout_seconds,
-            cache: cache
+    pub fn new(keys: Vec<Vec<u8>>, op: BlockingListOp, blocking: BlockingArgs, cache: Arc<Mutex<Keyspace>>, write_commands: Option<ReplicationLog>) -> Self {
+        BlpopCommand { keys, op, blocking, cache, write_commands }
+    }
+
+    /// Takes the elements out of the first key this connection is at the front of, returning the
+    /// key and the elements. Once `blocked`, keys overwritten with another type are waited on
+    /// instead of failing the command.
+    fn take(&self, cache: &mut Keyspace, blocked: bool) -> Result<Option<Taken>, Vec<u8>> {
+        let (end, count) = match &self.op {
+            BlockingListOp::Pop(end) => (*end, 1),
+            BlockingListOp::Mpop(end, count) => (*end, *count),
+            BlockingListOp::Move { destination, from, .. } => {
+                if cache.get(destination).is_some_and(|val| !matches!(val, CacheVal::List(_))) {
+                    return Err(create_wrong_type_err_resp());
+                }
+                (*from, 1)
+            }
+        };
+        let mut taken = None;
+        for key in &self.keys {
+            // only the connection at the front of the line may take an element
+            let first_in_line = self.blocking.enqueue(key);
+            let list_cache_val = match cache.get_mut(key) {
+                Some(CacheVal::List(list_cache_val)) => list_cache_val,
+                Some(_) if !blocked => return Err(create_wrong_type_err_resp()),
+                _ => continue
+            };
+            if taken.is_none() && first_in_line {
+                let vals: Vec<Vec<u8>> = (0..count).map_while(|_| list_cache_val.pop(end)).collect();
+                if list_cache_val.list.is_empty() {
+                    cache.remove(key);
+                }
+                taken = Some((key.clone(), vals));
+            }
+        }
+        Ok(taken)
+    }
+
+    fn propagate(&self, command: Vec<Vec<u8>>) {
+        if let Some(write_commands) = &self.write_commands {
+            write_commands.push(create_array_resp(command.into_iter().map(create_bulk_string_resp).collect()));
         }
     }
 }
 
 impl RedisCommand for BlpopCommand {
-    fn execute(&self, _: &mut Iter<'_, RespType>) -> Vec<String> {
-        let expiration = if self.timeout_seconds != 0.0 {
-            let now = std::time::SystemTime::now()
-                    .duration_since(std::time::UNIX_EPOCH)
-                    .unwrap()
-                    .as_millis();
-            Some(now + ((self.timeout_seconds * 1000.0) as u128))
-        } else {
-            None
+    fn execute(&self, _: &mut Iter<'_, RespType>) -> Vec<Vec<u8>> {
+        let (mut cache_gaurd, (key, vals)) = match self.blocking.block_on_keys(&self.cache, &self.keys, |cache, blocked| self.take(cache, blocked)) {
+            Ok(Some(taken)) => taken,
+            Ok(None) => return match self.op {
+                BlockingListOp::Mpop(..) => vec![create_null_array_resp()],
+                _ => vec![create_null_bulk_string_resp()]
+            },
+            Err(err) => return vec![err]
         };
 
-        loop {
-            let mut cache_gaurd = self.cache.lock().unwrap();
-            if !cache_gaurd.contains_key(&self.list_key) {
-                cache_gaurd.insert(self.list_key.clone(), CacheVal::List(ListCacheVal { list: vec![], block_queue: vec![] }));
-            }
-
-            match cache_gaurd.get_mut(&self.list_key) {
-                Some(CacheVal::List(list_cache_val)) => {
-                    if list_cache_val.block_queue.len() > 0 && list_cache_val.block_queue.first().is_some_and(|id| !self.connection_id.eq(id)) {
-                        // if there is a line and you are not at the front
-                        if list_cache_val.block_queue.iter().find(|&id| self.connection_id.eq(id)).is_none() {
-                            // add yourself to the queue 
-                            list_cache_val.block_queue.push(self.connection_id.clone());
-                        }
-                    } else {
-                        // you are at the front, do your logic
-                        if list_cache_val.list.len() == 0 {
-                            // no items
-                            if list_cache_val.block_queue.iter().find(|&id| self.connection_id.eq(id)).is_none() {
-                                // add yourself to the queue 
-                                list_cache_val.block_queue.push(self.connection_id.clone());
-                            }
-                        } else {
-                            let val = list_cache_val.list.remove(0);
-                            if list_cache_val.block_queue.len() > 0 {
-                                list_cache_val.block_queue.remove(0);
-                            }
-                            let bulk_strs = vec![create_bulk_string_resp(self.list_key.clone()),create_bulk_string_resp(val)];
-                            return vec![create_array_resp(bulk_strs)];
-                        }
-                    }
-
-                    // break if you have waited too long
-                    let now = std::time::SystemTime::now()
-                                    .duration_since(std::time::UNIX_EPOCH)
-                                    .unwrap()
-                                    .as_millis();
-                    if expiration.is_some() && now > expiration.unwrap() {
-                        let index = list_cache_val.block_queue.iter().position(|id| self.connection_id.eq(id));
-                        if index.is_some() {
-                            list_cache_val.block_queue.remove(index.unwrap());
-                        }
-                        break;
-                    }
-                },
-                _ => panic!("SHOULD NOT GET HERE")
+        // replicas never block, they are sent the pop that actually happened
+        match &self.op {
+            BlockingListOp::Pop(end) => {
+                self.propagate(vec![end.pop_command().into(), key.clone()]);
+                vec![create_array_resp(vec![create_bulk_string_resp(key), create_bulk_string_resp(vals[0].clone())])]
+            },
+            BlockingListOp::Mpop(end, _) => {
+                self.propagate(vec![end.pop_command().into(), key.clone(), vals.len().to_string().into_bytes()]);
+                vec![create_array_resp(vec![create_bulk_string_resp(key), create_array_resp(vals.into_iter().map(create_bulk_string_resp).collect())])]
+            },
+            BlockingListOp::Move { destination, from, to } => {
+                let val = vals[0].clone();
+                if let CacheVal::List(list_cache_val) = cache_gaurd.get_or_insert_with(destination.clone(), || CacheVal::List(ListCacheVal::new())) {
+                    list_cache_val.push(*to, val.clone());
+                }
+                drop(cache_gaurd);
+                self.blocking.notify_key(destination);
+                self.propagate(vec![b"LMOVE".to_vec(), key, destination.clone(), from.as_arg().into(), to.as_arg().into()]);
+                vec![create_bulk_string_resp(val)]
             }
         }
-
-        vec![create_null_bulk_string_resp()]
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,62 @@
+use std::{slice::Iter, sync::{Arc, Mutex}};
+
+use crate::{commands::{format_float, RedisCommand}, redis::{blocking::BlockingArgs, client::CacheVal, keyspace::Keyspace, replication::ReplicationLog}, resp::{create_array_resp, create_bulk_string_resp, create_null_array_resp, create_wrong_type_err_resp, types::RespType}};
+
+/// A key, the member popped from it and the member's score.
+type Popped = (Vec<u8>, Vec<u8>, f64);
+
+/// Serves both BZPOPMIN and BZPOPMAX. Like BLPOP, waiting connections queue up on every key they
+/// block on and the one at the front of a key's queue is served first once an element arrives.
+pub struct BzpopCommand {
+    keys: Vec<Vec<u8>>,
+    max: bool,
+    blocking: BlockingArgs,
+    cache: Arc<Mutex<Keyspace>>,
+    write_commands: Option<ReplicationLog>
+}
+
+impl BzpopCommand {
+    pub fn new(keys: Vec<Vec<u8>>, max: bool, blocking: BlockingArgs, cache: Arc<Mutex<Keyspace>>, write_commands: Option<ReplicationLog>) -> Self {
+        BzpopCommand { keys, max, blocking, cache, write_commands }
+    }
+
+    /// Pops from the first key this connection is at the front of, returning the key, the member
+    /// and its score. Once `blocked`, keys overwritten with another type are waited on instead of
+    /// failing the command.
+    fn take(&self, cache: &mut Keyspace, blocked: bool) -> Result<Option<Popped>, Vec<u8>> {
+        let mut popped = None;
+        for key in &self.keys {
+            // only the connection at the front of the line may take an element
+            let first_in_line = self.blocking.enqueue(key);
+            let zset = match cache.get_mut(key) {
+                Some(CacheVal::SortedSet(zset)) => zset,
+                Some(_) if !blocked => return Err(create_wrong_type_err_resp()),
+                _ => continue
+            };
+            if popped.is_none() && first_in_line {
+                popped = zset.pop(self.max).map(|(member, score)| (key.clone(), member, score));
+                if zset.is_empty() {
+                    cache.remove(key);
+                }
+            }
+        }
+        Ok(popped)
+    }
+}
+
+impl RedisCommand for BzpopCommand {
+    fn execute(&self, _: &mut Iter<'_, RespType>) -> Vec<Vec<u8>> {
+        let (_cache_guard, (key, member, score)) = match self.blocking.block_on_keys(&self.cache, &self.keys, |cache, blocked| self.take(cache, blocked)) {
+            Ok(Some(popped)) => popped,
+            Ok(None) => return vec![create_null_array_resp()],
+            Err(err) => return vec![err]
+        };
+
+        // replicas never block, they pop whatever the master popped
+        if let Some(write_commands) = &self.write_commands {
+            let command = if self.max { "ZPOPMAX" } else { "ZPOPMIN" };
+            write_commands.push(create_array_resp(vec![create_bulk_string_resp(command), create_bulk_string_resp(key.clone())]));
+        }
+        vec![create_array_resp(vec![create_bulk_string_resp(key), create_bulk_string_resp(member), create_bulk_string_resp(format_float(score))])]
+    }
+}
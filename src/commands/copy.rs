@@ -0,0 +1,53 @@
+use std::slice::Iter;
+
+use crate::{commands::RedisCommand, redis::{client::CacheVal, keyspace::{lock_pair, store_key, Databases, Keyspace}}, resp::{create_basic_err_resp, create_int_resp, types::RespType}};
+
+/// Copies the value at `source` to `destination`, in the selected db or the one given with DB,
+/// keeping its type and expiry. The destination is only overwritten with REPLACE.
+pub struct CopyCommand {
+    source: Vec<u8>,
+    destination: Vec<u8>,
+    replace: bool,
+    databases: Databases,
+    db: usize,
+    destination_db: usize
+}
+
+impl CopyCommand {
+    pub fn new(source: Vec<u8>, destination: Vec<u8>, replace: bool, databases: Databases, db: usize, destination_db: usize) -> Self {
+        CopyCommand { source, destination, replace, databases, db, destination_db }
+    }
+
+    /// The copy of the source and its expiry, `None` when it doesn't exist.
+    fn read(&self, cache: &Keyspace) -> Option<(CacheVal, Option<u128>)> {
+        cache.get(&self.source).map(|val| (val.duplicate(), cache.expiry(&self.source)))
+    }
+
+    fn write(&self, cache: &mut Keyspace, copy: Option<(CacheVal, Option<u128>)>) -> Vec<Vec<u8>> {
+        let (val, expiry_time) = match copy {
+            Some(copy) => copy,
+            None => return vec![create_int_resp(0)]
+        };
+        if !self.replace && cache.contains_key(&self.destination) {
+            return vec![create_int_resp(0)];
+        }
+        store_key(cache, self.destination.clone(), val, expiry_time);
+        vec![create_int_resp(1)]
+    }
+}
+
+impl RedisCommand for CopyCommand {
+    fn execute(&self, _: &mut Iter<'_, RespType>) -> Vec<Vec<u8>> {
+        if self.db != self.destination_db {
+            let (source, mut destination) = lock_pair(&self.databases, self.db, self.destination_db);
+            return self.write(&mut destination, self.read(&source));
+        }
+        if self.source == self.destination {
+            return vec![create_basic_err_resp("ERR source and destination objects are the same".to_string())];
+        }
+
+        let mut cache_guard = self.databases[self.db].lock().unwrap();
+        let copy = self.read(&cache_guard);
+        self.write(&mut cache_guard, copy)
+    }
+}
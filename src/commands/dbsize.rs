@@ -0,0 +1,20 @@
+use std::{slice::Iter, sync::{Arc, Mutex}};
+
+use crate::{commands::RedisCommand, redis::keyspace::Keyspace, resp::{create_int_resp, types::RespType}};
+
+pub struct DbsizeCommand {
+    cache: Arc<Mutex<Keyspace>>
+}
+
+impl DbsizeCommand {
+    pub fn new(cache: Arc<Mutex<Keyspace>>) -> Self {
+        DbsizeCommand { cache }
+    }
+}
+
+impl RedisCommand for DbsizeCommand {
+    fn execute(&self, _: &mut Iter<'_, RespType>) -> Vec<Vec<u8>> {
+        let cache_guard = self.cache.lock().unwrap();
+        vec![create_int_resp(cache_guard.iter().count())]
+    }
+}
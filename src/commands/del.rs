@@ -0,0 +1,39 @@
+use std::{slice::Iter, sync::{Arc, Mutex}, thread};
+
+use crate::{commands::RedisCommand, redis::{client::CacheVal, keyspace::{remove_key, Keyspace, LAZYFREE_THRESHOLD}}, resp::{create_int_resp, types::RespType}};
+
+/// Serves DEL and UNLINK. UNLINK hands the big values it removes to a background thread so
+/// dropping them doesn't hold up the request or the cache lock.
+pub struct DelCommand {
+    keys: Vec<Vec<u8>>,
+    lazy: bool,
+    cache: Arc<Mutex<Keyspace>>
+}
+
+impl DelCommand {
+    pub fn new(keys: Vec<Vec<u8>>, lazy: bool, cache: Arc<Mutex<Keyspace>>) -> Self {
+        DelCommand { keys, lazy, cache }
+    }
+}
+
+impl RedisCommand for DelCommand {
+    fn execute(&self, _: &mut Iter<'_, RespType>) -> Vec<Vec<u8>> {
+        let mut cache_guard = self.cache.lock().unwrap();
+        let mut removed = 0;
+        let mut garbage = vec![];
+        for key in &self.keys {
+            if let Some((val, _)) = remove_key(&mut cache_guard, key) {
+                removed += 1;
+                if self.lazy && val.free_effort() > LAZYFREE_THRESHOLD {
+                    garbage.push(val);
+                }
+            }
+        }
+        drop(cache_guard);
+
+        if !garbage.is_empty() {
+            thread::spawn(move || drop(garbage));
+        }
+        vec![create_int_resp(removed)]
+    }
+}
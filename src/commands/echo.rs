@@ -1,13 +1,13 @@
 use std::slice::Iter;
 
-use crate::{commands::RedisCommand, resp::{create_simple_string_resp, types::RespType}};
+use crate::{commands::RedisCommand, resp::{create_bulk_string_resp, types::RespType}};
 
 pub struct EchoCommand {
-    message: String
+    message: Vec<u8>
 }
 
 impl EchoCommand {
-    pub fn new(message: String) -> Self {
+    pub fn new(message: Vec<u8>) -> Self {
         EchoCommand {
             message: message
         }
@@ -15,7 +15,7 @@ impl EchoCommand {
 }
 
 impl RedisCommand for EchoCommand {
-    fn execute(&self, _: &mut Iter<'_, RespType>) -> Vec<String> {
-        vec![create_simple_string_resp(self.message.clone())]
+    fn execute(&self, _: &mut Iter<'_, RespType>) -> Vec<Vec<u8>> {
+        vec![create_bulk_string_resp(&self.message)]
     }
 }
\ No newline at end of file
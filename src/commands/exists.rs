@@ -0,0 +1,24 @@
+use std::{slice::Iter, sync::{Arc, Mutex}};
+
+use crate::{commands::RedisCommand, redis::{client::CacheVal, keyspace::Keyspace}, resp::{create_int_resp, types::RespType}};
+
+/// Serves EXISTS and TOUCH, which both count the keys that exist. A key given several times is
+/// counted every time. Access times aren't tracked, so touching a key has no other effect.
+pub struct ExistsCommand {
+    keys: Vec<Vec<u8>>,
+    cache: Arc<Mutex<Keyspace>>
+}
+
+impl ExistsCommand {
+    pub fn new(keys: Vec<Vec<u8>>, cache: Arc<Mutex<Keyspace>>) -> Self {
+        ExistsCommand { keys, cache }
+    }
+}
+
+impl RedisCommand for ExistsCommand {
+    fn execute(&self, _: &mut Iter<'_, RespType>) -> Vec<Vec<u8>> {
+        let cache_guard = self.cache.lock().unwrap();
+        let count = self.keys.iter().filter(|key| cache_guard.contains_key(key)).count();
+        vec![create_int_resp(count)]
+    }
+}
@@ -0,0 +1,101 @@
+use std::{slice::Iter, sync::{Arc, Mutex}};
+
+use crate::{commands::{parse_arg, RedisCommand}, redis::{keyspace::{remove_key, Keyspace}, replication::ReplicationLog, stream::now_ms}, resp::{create_array_resp, create_basic_err_resp, create_bulk_string_resp, create_int_resp, types::RespType}};
+
+/// The NX, XX, GT and LT flags of the EXPIRE family. A key without an expiry counts as never
+/// expiring, so GT never updates it and LT always does.
+#[derive(Clone, Copy, Default)]
+pub struct ExpireFlags {
+    nx: bool,
+    xx: bool,
+    gt: bool,
+    lt: bool
+}
+
+impl ExpireFlags {
+    pub fn parse(args: &[Vec<u8>]) -> Result<Self, Vec<u8>> {
+        let mut flags = ExpireFlags::default();
+        for arg in args {
+            match arg.to_ascii_lowercase().as_slice() {
+                b"nx" => flags.nx = true,
+                b"xx" => flags.xx = true,
+                b"gt" => flags.gt = true,
+                b"lt" => flags.lt = true,
+                _ => return Err(create_basic_err_resp(format!("ERR Unsupported option {}", String::from_utf8_lossy(arg))))
+            }
+        }
+        if flags.nx && (flags.xx || flags.gt || flags.lt) {
+            return Err(create_basic_err_resp("ERR NX and XX, GT or LT options at the same time are not compatible".to_string()));
+        }
+        if flags.gt && flags.lt {
+            return Err(create_basic_err_resp("ERR GT and LT options at the same time are not compatible".to_string()));
+        }
+        Ok(flags)
+    }
+
+    fn allows(&self, current: Option<u128>, at: i64) -> bool {
+        match current {
+            Some(_) if self.nx => false,
+            Some(current) if self.gt => at as i128 > current as i128,
+            Some(current) if self.lt => (at as i128) < current as i128,
+            Some(_) => true,
+            None => !self.xx && !self.gt
+        }
+    }
+}
+
+/// Resolves the time given to EXPIRE, PEXPIRE, EXPIREAT or PEXPIREAT to unix milliseconds, which
+/// may be in the past.
+pub fn parse_expire_time(command: &str, time: &[u8], now: u128) -> Result<i64, Vec<u8>> {
+    let time = match parse_arg::<i64>(time) {
+        Some(time) => time,
+        None => return Err(create_basic_err_resp("ERR value is not an integer or out of range".to_string()))
+    };
+    let at = match command {
+        "expire" => time.checked_mul(1000).and_then(|time| time.checked_add(now as i64)),
+        "pexpire" => time.checked_add(now as i64),
+        "expireat" => time.checked_mul(1000),
+        _ => Some(time)
+    };
+    at.ok_or_else(|| create_basic_err_resp(format!("ERR invalid expire time in '{}' command", command)))
+}
+
+/// Sets when a key of any type expires. A time that already passed deletes the key.
+pub struct ExpireCommand {
+    key: Vec<u8>,
+    at: i64,
+    flags: ExpireFlags,
+    cache: Arc<Mutex<Keyspace>>,
+    write_commands: Option<ReplicationLog>
+}
+
+impl ExpireCommand {
+    pub fn new(key: Vec<u8>, at: i64, flags: ExpireFlags, cache: Arc<Mutex<Keyspace>>, write_commands: Option<ReplicationLog>) -> Self {
+        ExpireCommand { key, at, flags, cache, write_commands }
+    }
+
+    fn propagate(&self, command: Vec<Vec<u8>>) {
+        if let Some(write_commands) = &self.write_commands {
+            write_commands.push(create_array_resp(command.into_iter().map(create_bulk_string_resp).collect()));
+        }
+    }
+}
+
+impl RedisCommand for ExpireCommand {
+    fn execute(&self, _: &mut Iter<'_, RespType>) -> Vec<Vec<u8>> {
+        let mut cache_guard = self.cache.lock().unwrap();
+        if !cache_guard.contains_key(&self.key) || !self.flags.allows(cache_guard.expiry(&self.key), self.at) {
+            return vec![create_int_resp(0)];
+        }
+
+        // replicas get the absolute expiry, or the deletion when it already passed
+        if self.at <= now_ms() as i64 {
+            remove_key(&mut cache_guard, &self.key);
+            self.propagate(vec![b"DEL".to_vec(), self.key.clone()]);
+        } else {
+            cache_guard.set_expiry(&self.key, Some(self.at as u128));
+            self.propagate(vec![b"PEXPIREAT".to_vec(), self.key.clone(), self.at.to_string().into_bytes()]);
+        }
+        vec![create_int_resp(1)]
+    }
+}
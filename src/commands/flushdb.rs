@@ -0,0 +1,27 @@
+use std::{slice::Iter, sync::{Arc, Mutex}, thread};
+
+use crate::{commands::RedisCommand, redis::keyspace::Keyspace, resp::{create_simple_string_resp, types::RespType}};
+
+/// Serves FLUSHDB, given the selected db, and FLUSHALL, given every db. With ASYNC the values are
+/// dropped on a background thread instead of while the request waits.
+pub struct FlushCommand {
+    databases: Vec<Arc<Mutex<Keyspace>>>,
+    lazy: bool
+}
+
+impl FlushCommand {
+    pub fn new(databases: Vec<Arc<Mutex<Keyspace>>>, lazy: bool) -> Self {
+        FlushCommand { databases, lazy }
+    }
+}
+
+impl RedisCommand for FlushCommand {
+    fn execute(&self, _: &mut Iter<'_, RespType>) -> Vec<Vec<u8>> {
+        // one db at a time, so connections on the others aren't held up
+        let garbage: Vec<_> = self.databases.iter().map(|cache| cache.lock().unwrap().flush()).collect();
+        if self.lazy {
+            thread::spawn(move || drop(garbage));
+        }
+        vec![create_simple_string_resp("OK".to_string())]
+    }
+}
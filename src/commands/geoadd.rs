@@ -0,0 +1,59 @@
+use std::{slice::Iter, sync::{Arc, Mutex}};
+
+use crate::{commands::{parse_arg, zadd::ZaddCommand, RedisCommand}, redis::{client::CacheVal, keyspace::Keyspace, geo::{encode, is_valid_lon_lat}}, resp::{create_basic_err_resp, create_wrong_args_err_resp, types::RespType}};
+
+/// GEOADD is a ZADD of the members scored by their geohash.
+pub struct GeoaddCommand {
+    key: Vec<u8>,
+    cache: Arc<Mutex<Keyspace>>
+}
+
+impl GeoaddCommand {
+    pub fn new(key: Vec<u8>, cache: Arc<Mutex<Keyspace>>) -> Self {
+        GeoaddCommand { key, cache }
+    }
+}
+
+impl RedisCommand for GeoaddCommand {
+    fn execute(&self, iter: &mut Iter<'_, RespType>) -> Vec<Vec<u8>> {
+        let mut args = vec![];
+        while let Some(RespType::String(arg)) = iter.next() {
+            args.push(arg.clone());
+        }
+
+        let (mut nx, mut xx) = (false, false);
+        let mut zadd_args = vec![];
+        let mut pos = 0;
+        while pos < args.len() {
+            match args[pos].to_ascii_lowercase().as_slice() {
+                b"nx" => nx = true,
+                b"xx" => xx = true,
+                b"ch" => {},
+                _ => break
+            }
+            zadd_args.push(RespType::String(args[pos].clone()));
+            pos += 1;
+        }
+
+        let triplets = &args[pos..];
+        if triplets.is_empty() && pos == 0 {
+            return vec![create_wrong_args_err_resp("geoadd")];
+        }
+        if triplets.is_empty() || triplets.len() % 3 != 0 || (nx && xx) {
+            return vec![create_basic_err_resp("ERR syntax error".to_string())];
+        }
+        for triplet in triplets.chunks(3) {
+            let (lon, lat) = match (parse_arg::<f64>(&triplet[0]), parse_arg::<f64>(&triplet[1])) {
+                (Some(lon), Some(lat)) => (lon, lat),
+                _ => return vec![create_basic_err_resp("ERR value is not a valid float".to_string())]
+            };
+            if !is_valid_lon_lat(lon, lat) {
+                return vec![create_basic_err_resp(format!("ERR invalid longitude,latitude pair {:.6},{:.6}", lon, lat))];
+            }
+            zadd_args.push(RespType::String(encode(lon, lat).to_string().into_bytes()));
+            zadd_args.push(RespType::String(triplet[2].clone()));
+        }
+
+        ZaddCommand::new(self.key.clone(), self.cache.clone()).execute(&mut zadd_args.iter())
+    }
+}
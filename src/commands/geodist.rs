@@ -0,0 +1,35 @@
+use std::{slice::Iter, sync::{Arc, Mutex}};
+
+use crate::{commands::RedisCommand, redis::{client::CacheVal, keyspace::Keyspace, geo::{decode, distance}}, resp::{create_bulk_string_resp, create_null_bulk_string_resp, create_wrong_type_err_resp, types::RespType}};
+
+pub struct GeodistCommand {
+    key: Vec<u8>,
+    member1: Vec<u8>,
+    member2: Vec<u8>,
+    unit: f64,
+    cache: Arc<Mutex<Keyspace>>
+}
+
+impl GeodistCommand {
+    pub fn new(key: Vec<u8>, member1: Vec<u8>, member2: Vec<u8>, unit: f64, cache: Arc<Mutex<Keyspace>>) -> Self {
+        GeodistCommand { key, member1, member2, unit, cache }
+    }
+}
+
+impl RedisCommand for GeodistCommand {
+    fn execute(&self, _: &mut Iter<'_, RespType>) -> Vec<Vec<u8>> {
+        let cache_guard = self.cache.lock().unwrap();
+        let zset = match cache_guard.get(&self.key) {
+            Some(CacheVal::SortedSet(zset)) => zset,
+            Some(_) => return vec![create_wrong_type_err_resp()],
+            None => return vec![create_null_bulk_string_resp()]
+        };
+        match (zset.score(&self.member1), zset.score(&self.member2)) {
+            (Some(score1), Some(score2)) => {
+                let ((lon1, lat1), (lon2, lat2)) = (decode(score1), decode(score2));
+                vec![create_bulk_string_resp(format!("{:.4}", distance(lon1, lat1, lon2, lat2) / self.unit))]
+            },
+            _ => vec![create_null_bulk_string_resp()]
+        }
+    }
+}
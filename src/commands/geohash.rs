@@ -0,0 +1,35 @@
+use std::{slice::Iter, sync::{Arc, Mutex}};
+
+use crate::{commands::RedisCommand, redis::{client::CacheVal, keyspace::Keyspace, geo::geohash_string}, resp::{create_array_resp, create_bulk_string_resp, create_null_bulk_string_resp, create_wrong_type_err_resp, types::RespType}};
+
+pub struct GeohashCommand {
+    key: Vec<u8>,
+    cache: Arc<Mutex<Keyspace>>
+}
+
+impl GeohashCommand {
+    pub fn new(key: Vec<u8>, cache: Arc<Mutex<Keyspace>>) -> Self {
+        GeohashCommand { key, cache }
+    }
+}
+
+impl RedisCommand for GeohashCommand {
+    fn execute(&self, iter: &mut Iter<'_, RespType>) -> Vec<Vec<u8>> {
+        let mut members = vec![];
+        while let Some(RespType::String(member)) = iter.next() {
+            members.push(member.clone());
+        }
+
+        let cache_guard = self.cache.lock().unwrap();
+        let zset = match cache_guard.get(&self.key) {
+            Some(CacheVal::SortedSet(zset)) => Some(zset),
+            Some(_) => return vec![create_wrong_type_err_resp()],
+            None => None
+        };
+        let hashes = members.iter().map(|member| match zset.and_then(|zset| zset.score(member)) {
+            Some(score) => create_bulk_string_resp(geohash_string(score)),
+            None => create_null_bulk_string_resp()
+        }).collect();
+        vec![create_array_resp(hashes)]
+    }
+}
@@ -0,0 +1,41 @@
+use std::{slice::Iter, sync::{Arc, Mutex}};
+
+use crate::{commands::RedisCommand, redis::{client::CacheVal, keyspace::Keyspace, geo::decode}, resp::{create_array_resp, create_bulk_string_resp, create_null_array_resp, create_wrong_args_err_resp, create_wrong_type_err_resp, types::RespType}};
+
+pub struct GeoposCommand {
+    key: Vec<u8>,
+    cache: Arc<Mutex<Keyspace>>
+}
+
+impl GeoposCommand {
+    pub fn new(key: Vec<u8>, cache: Arc<Mutex<Keyspace>>) -> Self {
+        GeoposCommand { key, cache }
+    }
+}
+
+impl RedisCommand for GeoposCommand {
+    fn execute(&self, iter: &mut Iter<'_, RespType>) -> Vec<Vec<u8>> {
+        let mut members = vec![];
+        while let Some(RespType::String(member)) = iter.next() {
+            members.push(member.clone());
+        }
+        if members.is_empty() {
+            return vec![create_wrong_args_err_resp("geopos")];
+        }
+
+        let cache_guard = self.cache.lock().unwrap();
+        let zset = match cache_guard.get(&self.key) {
+            Some(CacheVal::SortedSet(zset)) => Some(zset),
+            Some(_) => return vec![create_wrong_type_err_resp()],
+            None => None
+        };
+        let positions = members.iter().map(|member| match zset.and_then(|zset| zset.score(member)) {
+            Some(score) => {
+                let (lon, lat) = decode(score);
+                create_array_resp(vec![create_bulk_string_resp(lon.to_string()), create_bulk_string_resp(lat.to_string())])
+            },
+            None => create_null_array_resp()
+        }).collect();
+        vec![create_array_resp(positions)]
+    }
+}
@@ -0,0 +1,196 @@
+use std::{slice::Iter, sync::{Arc, Mutex}};
+
+use crate::{commands::{parse_arg, zset_ops, RedisCommand}, redis::{client::CacheVal, keyspace::Keyspace, geo::{self, decode, is_valid_lon_lat, parse_unit, GeoMatch, GeoShape}}, resp::{create_array_resp, create_basic_err_resp, create_bulk_string_resp, create_int_resp, create_wrong_args_err_resp, create_wrong_type_err_resp, types::RespType}};
+
+enum GeoOrigin {
+    Member(Vec<u8>),
+    LonLat(f64, f64)
+}
+
+struct GeosearchArgs {
+    origin: GeoOrigin,
+    shape: GeoShape,
+    /// Meters per unit of the BYRADIUS/BYBOX unit, which distances are replied in.
+    unit: f64,
+    desc: Option<bool>,
+    count: Option<usize>,
+    any: bool,
+    with_coord: bool,
+    with_dist: bool,
+    with_hash: bool,
+    store_dist: bool
+}
+
+fn syntax_err() -> Vec<u8> {
+    create_basic_err_resp("ERR syntax error".to_string())
+}
+
+fn parse_float(arg: Option<&Vec<u8>>) -> Result<f64, Vec<u8>> {
+    match arg.map(|arg| parse_arg::<f64>(arg)) {
+        Some(Some(val)) => Ok(val),
+        Some(None) => Err(create_basic_err_resp("ERR value is not a valid float".to_string())),
+        None => Err(syntax_err())
+    }
+}
+
+fn parse_unit_arg(arg: Option<&Vec<u8>>) -> Result<f64, Vec<u8>> {
+    match arg.map(|arg| parse_unit(arg)) {
+        Some(Some(unit)) => Ok(unit),
+        Some(None) => Err(create_basic_err_resp("ERR unsupported unit provided. please use M, KM, FT, MI".to_string())),
+        None => Err(syntax_err())
+    }
+}
+
+impl GeosearchArgs {
+    fn parse(args: &[Vec<u8>], store: bool) -> Result<Self, Vec<u8>> {
+        let command = if store { "GEOSEARCHSTORE" } else { "GEOSEARCH" };
+        let (mut origin, mut shape, mut unit) = (None, None, 1.0);
+        let (mut desc, mut count, mut any) = (None, None, false);
+        let (mut with_coord, mut with_dist, mut with_hash, mut store_dist) = (false, false, false, false);
+        let mut pos = 0;
+        while pos < args.len() {
+            match args[pos].to_ascii_lowercase().as_slice() {
+                b"frommember" if origin.is_none() => {
+                    origin = Some(GeoOrigin::Member(args.get(pos + 1).ok_or_else(syntax_err)?.clone()));
+                    pos += 1;
+                },
+                b"fromlonlat" if origin.is_none() => {
+                    let (lon, lat) = (parse_float(args.get(pos + 1))?, parse_float(args.get(pos + 2))?);
+                    if !is_valid_lon_lat(lon, lat) {
+                        return Err(create_basic_err_resp(format!("ERR invalid longitude,latitude pair {:.6},{:.6}", lon, lat)));
+                    }
+                    origin = Some(GeoOrigin::LonLat(lon, lat));
+                    pos += 2;
+                },
+                b"frommember" | b"fromlonlat" => return Err(create_basic_err_resp(format!("ERR exactly one of FROMMEMBER or FROMLONLAT can be specified for {}", command))),
+                b"byradius" if shape.is_none() => {
+                    let radius = parse_float(args.get(pos + 1))?;
+                    if radius < 0.0 {
+                        return Err(create_basic_err_resp("ERR radius cannot be negative".to_string()));
+                    }
+                    unit = parse_unit_arg(args.get(pos + 2))?;
+                    shape = Some(GeoShape::Radius(radius * unit));
+                    pos += 2;
+                },
+                b"bybox" if shape.is_none() => {
+                    let (width, height) = (parse_float(args.get(pos + 1))?, parse_float(args.get(pos + 2))?);
+                    if width < 0.0 || height < 0.0 {
+                        return Err(create_basic_err_resp("ERR height or width cannot be negative".to_string()));
+                    }
+                    unit = parse_unit_arg(args.get(pos + 3))?;
+                    shape = Some(GeoShape::Box { width: width * unit, height: height * unit });
+                    pos += 3;
+                },
+                b"byradius" | b"bybox" => return Err(create_basic_err_resp(format!("ERR exactly one of BYRADIUS and BYBOX can be specified for {}", command))),
+                b"asc" => desc = Some(false),
+                b"desc" => desc = Some(true),
+                b"count" => {
+                    count = match args.get(pos + 1).map(|count| parse_arg::<i64>(count)) {
+                        Some(Some(count)) if count > 0 => Some(count as usize),
+                        Some(Some(_)) => return Err(create_basic_err_resp("ERR COUNT must be > 0".to_string())),
+                        Some(None) => return Err(create_basic_err_resp("ERR value is not an integer or out of range".to_string())),
+                        None => return Err(syntax_err())
+                    };
+                    pos += 1;
+                    if args.get(pos + 1).is_some_and(|arg| arg.eq_ignore_ascii_case(b"any")) {
+                        any = true;
+                        pos += 1;
+                    }
+                },
+                b"any" => return Err(create_basic_err_resp("ERR the ANY argument requires COUNT argument".to_string())),
+                b"withcoord" if !store => with_coord = true,
+                b"withdist" if !store => with_dist = true,
+                b"withhash" if !store => with_hash = true,
+                b"storedist" if store => store_dist = true,
+                _ => return Err(syntax_err())
+            }
+            pos += 1;
+        }
+        let origin = origin.ok_or_else(|| create_basic_err_resp(format!("ERR exactly one of FROMMEMBER or FROMLONLAT can be specified for {}", command)))?;
+        let shape = shape.ok_or_else(|| create_basic_err_resp(format!("ERR exactly one of BYRADIUS and BYBOX can be specified for {}", command)))?;
+        // only the first COUNT matches are returned, so they have to be the closest ones
+        if count.is_some() && !any && desc.is_none() {
+            desc = Some(false);
+        }
+        Ok(GeosearchArgs { origin, shape, unit, desc, count, any, with_coord, with_dist, with_hash, store_dist })
+    }
+
+    fn reply_item(&self, geo_match: GeoMatch) -> Vec<u8> {
+        if !self.with_dist && !self.with_hash && !self.with_coord {
+            return create_bulk_string_resp(geo_match.member);
+        }
+        let mut item = vec![create_bulk_string_resp(geo_match.member)];
+        if self.with_dist {
+            item.push(create_bulk_string_resp(format!("{:.4}", geo_match.dist / self.unit)));
+        }
+        if self.with_hash {
+            item.push(create_int_resp(geo_match.score as u64));
+        }
+        if self.with_coord {
+            item.push(create_array_resp(vec![create_bulk_string_resp(geo_match.lon.to_string()), create_bulk_string_resp(geo_match.lat.to_string())]));
+        }
+        create_array_resp(item)
+    }
+}
+
+/// Serves both GEOSEARCH and GEOSEARCHSTORE, the latter when a destination is given.
+pub struct GeosearchCommand {
+    destination: Option<Vec<u8>>,
+    cache: Arc<Mutex<Keyspace>>
+}
+
+impl GeosearchCommand {
+    pub fn new(destination: Option<Vec<u8>>, cache: Arc<Mutex<Keyspace>>) -> Self {
+        GeosearchCommand { destination, cache }
+    }
+}
+
+impl RedisCommand for GeosearchCommand {
+    fn execute(&self, iter: &mut Iter<'_, RespType>) -> Vec<Vec<u8>> {
+        let mut args = vec![];
+        while let Some(RespType::String(arg)) = iter.next() {
+            args.push(arg.clone());
+        }
+        if args.is_empty() {
+            return vec![create_wrong_args_err_resp(if self.destination.is_some() { "geosearchstore" } else { "geosearch" })];
+        }
+        let search_args = match GeosearchArgs::parse(&args[1..], self.destination.is_some()) {
+            Ok(search_args) => search_args,
+            Err(err) => return vec![err]
+        };
+
+        let mut cache_guard = self.cache.lock().unwrap();
+        let mut matches = match cache_guard.get(&args[0]) {
+            Some(CacheVal::SortedSet(zset)) => {
+                let (lon, lat) = match &search_args.origin {
+                    GeoOrigin::Member(member) => match zset.score(member) {
+                        Some(score) => decode(score),
+                        None => return vec![create_basic_err_resp("ERR could not decode requested zset member".to_string())]
+                    },
+                    GeoOrigin::LonLat(lon, lat) => (*lon, *lat)
+                };
+                let limit = if search_args.any { search_args.count } else { None };
+                geo::search(zset, lon, lat, search_args.shape, limit)
+            },
+            Some(_) => return vec![create_wrong_type_err_resp()],
+            None => vec![]
+        };
+        match search_args.desc {
+            Some(false) => matches.sort_by(|a, b| a.dist.total_cmp(&b.dist)),
+            Some(true) => matches.sort_by(|a, b| b.dist.total_cmp(&a.dist)),
+            None => {}
+        }
+        if let Some(count) = search_args.count {
+            matches.truncate(count);
+        }
+
+        if self.destination.is_some() {
+            let result = matches.into_iter().map(|geo_match| {
+                let score = if search_args.store_dist { geo_match.dist / search_args.unit } else { geo_match.score };
+                (geo_match.member, score)
+            }).collect();
+            return zset_ops::reply_or_store(&mut cache_guard, &self.destination, result, false);
+        }
+        vec![create_array_resp(matches.into_iter().map(|geo_match| search_args.reply_item(geo_match)).collect())]
+    }
+}
@@ -0,0 +1,25 @@
+use std::{slice::Iter, sync::{Arc, Mutex}};
+
+use crate::{commands::RedisCommand, redis::{bitmap::get_bit, client::CacheVal, keyspace::Keyspace, string::live_string}, resp::{create_int_resp, types::RespType}};
+
+pub struct GetbitCommand {
+    key: Vec<u8>,
+    offset: usize,
+    cache: Arc<Mutex<Keyspace>>
+}
+
+impl GetbitCommand {
+    pub fn new(key: Vec<u8>, offset: usize, cache: Arc<Mutex<Keyspace>>) -> Self {
+        GetbitCommand { key, offset, cache }
+    }
+}
+
+impl RedisCommand for GetbitCommand {
+    fn execute(&self, _: &mut Iter<'_, RespType>) -> Vec<Vec<u8>> {
+        let mut cache_guard = self.cache.lock().unwrap();
+        match live_string(&mut cache_guard, &self.key) {
+            Ok(string_cache_val) => vec![create_int_resp(string_cache_val.map_or(0, |val| get_bit(&val.val, self.offset)))],
+            Err(err) => vec![err]
+        }
+    }
+}
@@ -0,0 +1,28 @@
+use std::{slice::Iter, sync::{Arc, Mutex}};
+
+use crate::{commands::RedisCommand, redis::{client::CacheVal, keyspace::Keyspace, string::live_string}, resp::{create_bulk_string_resp, create_null_bulk_string_resp, types::RespType}};
+
+pub struct GetdelCommand {
+    key: Vec<u8>,
+    cache: Arc<Mutex<Keyspace>>
+}
+
+impl GetdelCommand {
+    pub fn new(key: Vec<u8>, cache: Arc<Mutex<Keyspace>>) -> Self {
+        GetdelCommand { key, cache }
+    }
+}
+
+impl RedisCommand for GetdelCommand {
+    fn execute(&self, _: &mut Iter<'_, RespType>) -> Vec<Vec<u8>> {
+        let mut cache_guard = self.cache.lock().unwrap();
+        match live_string(&mut cache_guard, &self.key) {
+            Ok(Some(_)) => match cache_guard.remove(&self.key) {
+                Some(CacheVal::String(string_cache_val)) => vec![create_bulk_string_resp(&string_cache_val.val)],
+                _ => vec![create_null_bulk_string_resp()]
+            },
+            Ok(None) => vec![create_null_bulk_string_resp()],
+            Err(err) => vec![err]
+        }
+    }
+}
@@ -0,0 +1,56 @@
+use std::{slice::Iter, sync::{Arc, Mutex}};
+
+use crate::{commands::{set::parse_expiry, RedisCommand}, redis::{client::CacheVal, keyspace::Keyspace, replication::ReplicationLog, stream::now_ms, string::live_string}, resp::{create_array_resp, create_basic_err_resp, create_bulk_string_resp, create_null_bulk_string_resp, types::RespType}};
+
+/// GETEX key [EX seconds|PX ms|EXAT unix-seconds|PXAT unix-ms|PERSIST], a GET that also sets or
+/// clears the time to live.
+pub struct GetexCommand {
+    key: Vec<u8>,
+    cache: Arc<Mutex<Keyspace>>,
+    write_commands: Option<ReplicationLog>
+}
+
+impl GetexCommand {
+    pub fn new(key: Vec<u8>, cache: Arc<Mutex<Keyspace>>, write_commands: Option<ReplicationLog>) -> Self {
+        GetexCommand { key, cache, write_commands }
+    }
+}
+
+impl RedisCommand for GetexCommand {
+    fn execute(&self, iter: &mut Iter<'_, RespType>) -> Vec<Vec<u8>> {
+        let mut args = vec![];
+        while let Some(RespType::String(arg)) = iter.next() {
+            args.push(arg.clone());
+        }
+        // `None` leaves the time to live alone, `Some(None)` is PERSIST
+        let expiry_time: Option<Option<u128>> = match args.as_slice() {
+            [] => None,
+            [option] if option.eq_ignore_ascii_case(b"persist") => Some(None),
+            [option, time] if matches!(option.to_ascii_lowercase().as_slice(), b"ex" | b"px" | b"exat" | b"pxat") => match parse_expiry(option, Some(time), now_ms(), "getex") {
+                Ok(at) => Some(Some(at)),
+                Err(err) => return vec![err]
+            },
+            _ => return vec![create_basic_err_resp("ERR syntax error".to_string())]
+        };
+
+        let mut cache_guard = self.cache.lock().unwrap();
+        let reply = match live_string(&mut cache_guard, &self.key) {
+            Ok(Some(string_cache_val)) => create_bulk_string_resp(&string_cache_val.val),
+            Ok(None) => return vec![create_null_bulk_string_resp()],
+            Err(err) => return vec![err]
+        };
+        if let Some(expiry_time) = expiry_time {
+            cache_guard.set_expiry(&self.key, expiry_time);
+            // replicas get the absolute expiry, relative ones would be counted from when they receive it
+            if let Some(write_commands) = &self.write_commands {
+                let mut command = vec![b"GETEX".to_vec(), self.key.clone()];
+                match expiry_time {
+                    Some(at) => command.extend([b"PXAT".to_vec(), at.to_string().into_bytes()]),
+                    None => command.push(b"PERSIST".to_vec())
+                }
+                write_commands.push(create_array_resp(command.into_iter().map(create_bulk_string_resp).collect()));
+            }
+        }
+        vec![reply]
+    }
+}
@@ -0,0 +1,33 @@
+use std::{slice::Iter, sync::{Arc, Mutex}};
+
+use crate::{commands::RedisCommand, redis::{client::CacheVal, keyspace::Keyspace, string::{clamp_offsets, live_string}}, resp::{create_bulk_string_resp, types::RespType}};
+
+/// GETRANGE key start end, both inclusive byte offsets that may count from the end.
+pub struct GetrangeCommand {
+    key: Vec<u8>,
+    start: i64,
+    end: i64,
+    cache: Arc<Mutex<Keyspace>>
+}
+
+impl GetrangeCommand {
+    pub fn new(key: Vec<u8>, start: i64, end: i64, cache: Arc<Mutex<Keyspace>>) -> Self {
+        GetrangeCommand { key, start, end, cache }
+    }
+}
+
+impl RedisCommand for GetrangeCommand {
+    fn execute(&self, _: &mut Iter<'_, RespType>) -> Vec<Vec<u8>> {
+        let mut cache_guard = self.cache.lock().unwrap();
+        let bytes = match live_string(&mut cache_guard, &self.key) {
+            Ok(Some(string_cache_val)) => &string_cache_val.val,
+            Ok(None) => return vec![create_bulk_string_resp("")],
+            Err(err) => return vec![err]
+        };
+
+        match clamp_offsets(self.start, self.end, bytes.len()) {
+            Some((start, end)) => vec![create_bulk_string_resp(&bytes[start..=end])],
+            None => vec![create_bulk_string_resp("")]
+        }
+    }
+}
@@ -0,0 +1,40 @@
+use std::{slice::Iter, sync::{Arc, Mutex}};
+
+use crate::{commands::RedisCommand, redis::{client::CacheVal, keyspace::Keyspace}, resp::{create_int_resp, create_wrong_args_err_resp, create_wrong_type_err_resp, types::RespType}};
+
+pub struct HdelCommand {
+    key: Vec<u8>,
+    cache: Arc<Mutex<Keyspace>>
+}
+
+impl HdelCommand {
+    pub fn new(key: Vec<u8>, cache: Arc<Mutex<Keyspace>>) -> Self {
+        HdelCommand { key, cache }
+    }
+}
+
+impl RedisCommand for HdelCommand {
+    fn execute(&self, iter: &mut Iter<'_, RespType>) -> Vec<Vec<u8>> {
+        let mut fields = vec![];
+        while let Some(RespType::String(field)) = iter.next() {
+            fields.push(field.clone());
+        }
+        if fields.is_empty() {
+            return vec![create_wrong_args_err_resp("hdel")];
+        }
+
+        let mut cache_guard = self.cache.lock().unwrap();
+        let hash_cache_val = match cache_guard.get_mut(&self.key) {
+            Some(CacheVal::Hash(hash_cache_val)) => hash_cache_val,
+            Some(_) => return vec![create_wrong_type_err_resp()],
+            None => return vec![create_int_resp(0)]
+        };
+
+        let removed = fields.iter().filter(|field| hash_cache_val.hash.remove(*field).is_some()).count();
+        if hash_cache_val.hash.is_empty() {
+            // redis never keeps empty aggregates around
+            cache_guard.remove(&self.key);
+        }
+        vec![create_int_resp(removed)]
+    }
+}
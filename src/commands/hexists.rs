@@ -0,0 +1,26 @@
+use std::{slice::Iter, sync::{Arc, Mutex}};
+
+use crate::{commands::RedisCommand, redis::{client::CacheVal, keyspace::Keyspace}, resp::{create_int_resp, create_wrong_type_err_resp, types::RespType}};
+
+pub struct HexistsCommand {
+    key: Vec<u8>,
+    field: Vec<u8>,
+    cache: Arc<Mutex<Keyspace>>
+}
+
+impl HexistsCommand {
+    pub fn new(key: Vec<u8>, field: Vec<u8>, cache: Arc<Mutex<Keyspace>>) -> Self {
+        HexistsCommand { key, field, cache }
+    }
+}
+
+impl RedisCommand for HexistsCommand {
+    fn execute(&self, _: &mut Iter<'_, RespType>) -> Vec<Vec<u8>> {
+        let cache_guard = self.cache.lock().unwrap();
+        match cache_guard.get(&self.key) {
+            Some(CacheVal::Hash(hash_cache_val)) => vec![create_int_resp(hash_cache_val.hash.contains_key(&self.field) as i64)],
+            Some(_) => vec![create_wrong_type_err_resp()],
+            None => vec![create_int_resp(0)]
+        }
+    }
+}
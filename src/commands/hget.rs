@@ -0,0 +1,29 @@
+use std::{slice::Iter, sync::{Arc, Mutex}};
+
+use crate::{commands::RedisCommand, redis::{client::CacheVal, keyspace::Keyspace}, resp::{create_bulk_string_resp, create_null_bulk_string_resp, create_wrong_type_err_resp, types::RespType}};
+
+pub struct HgetCommand {
+    key: Vec<u8>,
+    field: Vec<u8>,
+    cache: Arc<Mutex<Keyspace>>
+}
+
+impl HgetCommand {
+    pub fn new(key: Vec<u8>, field: Vec<u8>, cache: Arc<Mutex<Keyspace>>) -> Self {
+        HgetCommand { key, field, cache }
+    }
+}
+
+impl RedisCommand for HgetCommand {
+    fn execute(&self, _: &mut Iter<'_, RespType>) -> Vec<Vec<u8>> {
+        let cache_guard = self.cache.lock().unwrap();
+        match cache_guard.get(&self.key) {
+            Some(CacheVal::Hash(hash_cache_val)) => match hash_cache_val.hash.get(&self.field) {
+                Some(val) => vec![create_bulk_string_resp(val.clone())],
+                None => vec![create_null_bulk_string_resp()]
+            },
+            Some(_) => vec![create_wrong_type_err_resp()],
+            None => vec![create_null_bulk_string_resp()]
+        }
+    }
+}
@@ -0,0 +1,30 @@
+use std::{slice::Iter, sync::{Arc, Mutex}};
+
+use crate::{commands::RedisCommand, redis::{client::CacheVal, keyspace::Keyspace}, resp::{create_array_resp, create_bulk_string_resp, create_wrong_type_err_resp, types::RespType}};
+
+pub struct HgetallCommand {
+    key: Vec<u8>,
+    cache: Arc<Mutex<Keyspace>>
+}
+
+impl HgetallCommand {
+    pub fn new(key: Vec<u8>, cache: Arc<Mutex<Keyspace>>) -> Self {
+        HgetallCommand { key, cache }
+    }
+}
+
+impl RedisCommand for HgetallCommand {
+    fn execute(&self, _: &mut Iter<'_, RespType>) -> Vec<Vec<u8>> {
+        let cache_guard = self.cache.lock().unwrap();
+        match cache_guard.get(&self.key) {
+            Some(CacheVal::Hash(hash_cache_val)) => {
+                let field_vals = hash_cache_val.hash.iter().flat_map(|(field, val)| {
+                    vec![create_bulk_string_resp(field.clone()), create_bulk_string_resp(val.clone())]
+                }).collect();
+                vec![create_array_resp(field_vals)]
+            },
+            Some(_) => vec![create_wrong_type_err_resp()],
+            None => vec![create_array_resp(vec![])]
+        }
+    }
+}
@@ -0,0 +1,40 @@
+use std::{collections::HashMap, slice::Iter, sync::{Arc, Mutex}};
+
+use crate::{commands::{parse_arg, RedisCommand}, redis::{client::{CacheVal, HashCacheVal}, keyspace::Keyspace}, resp::{create_basic_err_resp, create_int_resp, create_wrong_type_err_resp, types::RespType}};
+
+pub struct HincrbyCommand {
+    key: Vec<u8>,
+    field: Vec<u8>,
+    increment: i64,
+    cache: Arc<Mutex<Keyspace>>
+}
+
+impl HincrbyCommand {
+    pub fn new(key: Vec<u8>, field: Vec<u8>, increment: i64, cache: Arc<Mutex<Keyspace>>) -> Self {
+        HincrbyCommand { key, field, increment, cache }
+    }
+}
+
+impl RedisCommand for HincrbyCommand {
+    fn execute(&self, _: &mut Iter<'_, RespType>) -> Vec<Vec<u8>> {
+        let mut cache_guard = self.cache.lock().unwrap();
+        let hash_cache_val = match cache_guard.get_or_insert_with(self.key.clone(), || CacheVal::Hash(HashCacheVal { hash: HashMap::new() })) {
+            CacheVal::Hash(hash_cache_val) => hash_cache_val,
+            _ => return vec![create_wrong_type_err_resp()]
+        };
+
+        let current = match hash_cache_val.hash.get(&self.field) {
+            Some(val) => match parse_arg::<i64>(val) {
+                Some(v) => v,
+                None => return vec![create_basic_err_resp("ERR hash value is not an integer".to_string())]
+            },
+            None => 0
+        };
+        let new_val = match current.checked_add(self.increment) {
+            Some(v) => v,
+            None => return vec![create_basic_err_resp("ERR increment or decrement would overflow".to_string())]
+        };
+        hash_cache_val.hash.insert(self.field.clone(), new_val.to_string().into_bytes());
+        vec![create_int_resp(new_val)]
+    }
+}
@@ -0,0 +1,51 @@
+use std::{collections::HashMap, slice::Iter, sync::{Arc, Mutex}};
+
+use crate::{commands::{parse_arg, format_plain_float, RedisCommand}, redis::{client::{CacheVal, HashCacheVal}, keyspace::Keyspace, replication::ReplicationLog}, resp::{create_array_resp, create_basic_err_resp, create_bulk_string_resp, create_wrong_type_err_resp, types::RespType}};
+
+pub struct HincrbyfloatCommand {
+    key: Vec<u8>,
+    field: Vec<u8>,
+    increment: f64,
+    cache: Arc<Mutex<Keyspace>>,
+    write_commands: Option<ReplicationLog>
+}
+
+impl HincrbyfloatCommand {
+    pub fn new(key: Vec<u8>, field: Vec<u8>, increment: f64, cache: Arc<Mutex<Keyspace>>, write_commands: Option<ReplicationLog>) -> Self {
+        HincrbyfloatCommand { key, field, increment, cache, write_commands }
+    }
+}
+
+impl RedisCommand for HincrbyfloatCommand {
+    fn execute(&self, _: &mut Iter<'_, RespType>) -> Vec<Vec<u8>> {
+        let mut cache_guard = self.cache.lock().unwrap();
+        let hash_cache_val = match cache_guard.get_or_insert_with(self.key.clone(), || CacheVal::Hash(HashCacheVal { hash: HashMap::new() })) {
+            CacheVal::Hash(hash_cache_val) => hash_cache_val,
+            _ => return vec![create_wrong_type_err_resp()]
+        };
+
+        let current = match hash_cache_val.hash.get(&self.field) {
+            Some(val) => match parse_arg::<f64>(val) {
+                Some(v) if v.is_finite() => v,
+                _ => return vec![create_basic_err_resp("ERR hash value is not a float".to_string())]
+            },
+            None => 0.0
+        };
+        let new_val = current + self.increment;
+        if !new_val.is_finite() {
+            return vec![create_basic_err_resp("ERR increment would produce NaN or Infinity".to_string())];
+        }
+
+        let formatted = format_plain_float(new_val);
+        hash_cache_val.hash.insert(self.field.clone(), formatted.clone().into_bytes());
+
+        // float addition can differ between machines, so replicas get the final value instead of the increment
+        if let Some(write_commands) = &self.write_commands {
+            write_commands.push(create_array_resp(vec![
+                create_bulk_string_resp("HSET"), create_bulk_string_resp(&self.key),
+                create_bulk_string_resp(&self.field), create_bulk_string_resp(formatted.clone())
+            ]));
+        }
+        vec![create_bulk_string_resp(formatted)]
+    }
+}
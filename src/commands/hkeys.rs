@@ -0,0 +1,25 @@
+use std::{slice::Iter, sync::{Arc, Mutex}};
+
+use crate::{commands::RedisCommand, redis::{client::CacheVal, keyspace::Keyspace}, resp::{create_array_resp, create_bulk_string_resp, create_wrong_type_err_resp, types::RespType}};
+
+pub struct HkeysCommand {
+    key: Vec<u8>,
+    cache: Arc<Mutex<Keyspace>>
+}
+
+impl HkeysCommand {
+    pub fn new(key: Vec<u8>, cache: Arc<Mutex<Keyspace>>) -> Self {
+        HkeysCommand { key, cache }
+    }
+}
+
+impl RedisCommand for HkeysCommand {
+    fn execute(&self, _: &mut Iter<'_, RespType>) -> Vec<Vec<u8>> {
+        let cache_guard = self.cache.lock().unwrap();
+        match cache_guard.get(&self.key) {
+            Some(CacheVal::Hash(hash_cache_val)) => vec![create_array_resp(hash_cache_val.hash.keys().map(|field| create_bulk_string_resp(field.clone())).collect())],
+            Some(_) => vec![create_wrong_type_err_resp()],
+            None => vec![create_array_resp(vec![])]
+        }
+    }
+}
@@ -0,0 +1,25 @@
+use std::{slice::Iter, sync::{Arc, Mutex}};
+
+use crate::{commands::RedisCommand, redis::{client::CacheVal, keyspace::Keyspace}, resp::{create_int_resp, create_wrong_type_err_resp, types::RespType}};
+
+pub struct HlenCommand {
+    key: Vec<u8>,
+    cache: Arc<Mutex<Keyspace>>
+}
+
+impl HlenCommand {
+    pub fn new(key: Vec<u8>, cache: Arc<Mutex<Keyspace>>) -> Self {
+        HlenCommand { key, cache }
+    }
+}
+
+impl RedisCommand for HlenCommand {
+    fn execute(&self, _: &mut Iter<'_, RespType>) -> Vec<Vec<u8>> {
+        let cache_guard = self.cache.lock().unwrap();
+        match cache_guard.get(&self.key) {
+            Some(CacheVal::Hash(hash_cache_val)) => vec![create_int_resp(hash_cache_val.hash.len())],
+            Some(_) => vec![create_wrong_type_err_resp()],
+            None => vec![create_int_resp(0)]
+        }
+    }
+}
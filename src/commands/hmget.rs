@@ -0,0 +1,41 @@
+use std::{slice::Iter, sync::{Arc, Mutex}};
+
+use crate::{commands::RedisCommand, redis::{client::CacheVal, keyspace::Keyspace}, resp::{create_array_resp, create_bulk_string_resp, create_null_bulk_string_resp, create_wrong_args_err_resp, create_wrong_type_err_resp, types::RespType}};
+
+pub struct HmgetCommand {
+    key: Vec<u8>,
+    cache: Arc<Mutex<Keyspace>>
+}
+
+impl HmgetCommand {
+    pub fn new(key: Vec<u8>, cache: Arc<Mutex<Keyspace>>) -> Self {
+        HmgetCommand { key, cache }
+    }
+}
+
+impl RedisCommand for HmgetCommand {
+    fn execute(&self, iter: &mut Iter<'_, RespType>) -> Vec<Vec<u8>> {
+        let mut fields = vec![];
+        while let Some(RespType::String(field)) = iter.next() {
+            fields.push(field.clone());
+        }
+        if fields.is_empty() {
+            return vec![create_wrong_args_err_resp("hmget")];
+        }
+
+        let cache_guard = self.cache.lock().unwrap();
+        let hash = match cache_guard.get(&self.key) {
+            Some(CacheVal::Hash(hash_cache_val)) => Some(&hash_cache_val.hash),
+            Some(_) => return vec![create_wrong_type_err_resp()],
+            None => None
+        };
+
+        let vals = fields.iter().map(|field| {
+            match hash.and_then(|hash| hash.get(field)) {
+                Some(val) => create_bulk_string_resp(val.clone()),
+                None => create_null_bulk_string_resp()
+            }
+        }).collect();
+        vec![create_array_resp(vals)]
+    }
+}
@@ -0,0 +1,58 @@
+use std::{slice::Iter, sync::{Arc, Mutex}};
+
+use rand::{rngs::SmallRng, seq::{IteratorRandom, SliceRandom}, SeedableRng};
+
+use crate::{commands::RedisCommand, redis::{client::CacheVal, keyspace::Keyspace}, resp::{create_array_resp, create_bulk_string_resp, create_null_bulk_string_resp, create_wrong_type_err_resp, types::RespType}};
+
+pub struct HrandfieldCommand {
+    key: Vec<u8>,
+    /// At least `-MAX_RANDOM_REPEATS`, as `parse_random_count` makes sure.
+    count: Option<i64>,
+    with_values: bool,
+    cache: Arc<Mutex<Keyspace>>
+}
+
+impl HrandfieldCommand {
+    pub fn new(key: Vec<u8>, count: Option<i64>, with_values: bool, cache: Arc<Mutex<Keyspace>>) -> Self {
+        HrandfieldCommand { key, count, with_values, cache }
+    }
+}
+
+impl RedisCommand for HrandfieldCommand {
+    fn execute(&self, _: &mut Iter<'_, RespType>) -> Vec<Vec<u8>> {
+        let cache_guard = self.cache.lock().unwrap();
+        let hash = match cache_guard.get(&self.key) {
+            Some(CacheVal::Hash(hash_cache_val)) => &hash_cache_val.hash,
+            Some(_) => return vec![create_wrong_type_err_resp()],
+            None if self.count.is_some() => return vec![create_array_resp(vec![])],
+            None => return vec![create_null_bulk_string_resp()]
+        };
+
+        let mut rng = SmallRng::from_entropy();
+        let count = match self.count {
+            Some(count) => count,
+            None => {
+                let (field, _) = hash.iter().choose(&mut rng).expect("stored hashes are never empty");
+                return vec![create_bulk_string_resp(field.clone())];
+            }
+        };
+
+        let entries: Vec<(&Vec<u8>, &Vec<u8>)> = hash.iter().collect();
+        let picked: Vec<(&Vec<u8>, &Vec<u8>)> = if count >= 0 {
+            // positive counts return distinct fields
+            entries.choose_multiple(&mut rng, count as usize).cloned().collect()
+        } else {
+            // negative counts may return the same field several times
+            (0..count.unsigned_abs()).map(|_| *entries.choose(&mut rng).expect("stored hashes are never empty")).collect()
+        };
+
+        let items = picked.into_iter().flat_map(|(field, val)| {
+            if self.with_values {
+                vec![create_bulk_string_resp(field.clone()), create_bulk_string_resp(val.clone())]
+            } else {
+                vec![create_bulk_string_resp(field.clone())]
+            }
+        }).collect();
+        vec![create_array_resp(items)]
+    }
+}
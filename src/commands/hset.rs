@@ -0,0 +1,44 @@
+use std::{collections::HashMap, slice::Iter, sync::{Arc, Mutex}};
+
+use crate::{commands::RedisCommand, redis::{client::{CacheVal, HashCacheVal}, keyspace::Keyspace}, resp::{create_int_resp, create_wrong_args_err_resp, create_wrong_type_err_resp, types::RespType}};
+
+pub struct HsetCommand {
+    key: Vec<u8>,
+    cache: Arc<Mutex<Keyspace>>
+}
+
+impl HsetCommand {
+    pub fn new(key: Vec<u8>, cache: Arc<Mutex<Keyspace>>) -> Self {
+        HsetCommand { key, cache }
+    }
+}
+
+impl RedisCommand for HsetCommand {
+    fn execute(&self, iter: &mut Iter<'_, RespType>) -> Vec<Vec<u8>> {
+        let mut field_vals = vec![];
+        loop {
+            match (iter.next(), iter.next()) {
+                (Some(RespType::String(field)), Some(RespType::String(val))) => field_vals.push((field.clone(), val.clone())),
+                (None, None) => break,
+                _ => return vec![create_wrong_args_err_resp("hset")]
+            }
+        }
+        if field_vals.is_empty() {
+            return vec![create_wrong_args_err_resp("hset")];
+        }
+
+        let mut cache_guard = self.cache.lock().unwrap();
+        let hash_cache_val = match cache_guard.get_or_insert_with(self.key.clone(), || CacheVal::Hash(HashCacheVal { hash: HashMap::new() })) {
+            CacheVal::Hash(hash_cache_val) => hash_cache_val,
+            _ => return vec![create_wrong_type_err_resp()]
+        };
+
+        let mut added = 0;
+        for (field, val) in field_vals {
+            if hash_cache_val.hash.insert(field, val).is_none() {
+                added += 1;
+            }
+        }
+        vec![create_int_resp(added)]
+    }
+}
@@ -0,0 +1,32 @@
+use std::{collections::HashMap, slice::Iter, sync::{Arc, Mutex}};
+
+use crate::{commands::RedisCommand, redis::{client::{CacheVal, HashCacheVal}, keyspace::Keyspace}, resp::{create_int_resp, create_wrong_type_err_resp, types::RespType}};
+
+pub struct HsetnxCommand {
+    key: Vec<u8>,
+    field: Vec<u8>,
+    value: Vec<u8>,
+    cache: Arc<Mutex<Keyspace>>
+}
+
+impl HsetnxCommand {
+    pub fn new(key: Vec<u8>, field: Vec<u8>, value: Vec<u8>, cache: Arc<Mutex<Keyspace>>) -> Self {
+        HsetnxCommand { key, field, value, cache }
+    }
+}
+
+impl RedisCommand for HsetnxCommand {
+    fn execute(&self, _: &mut Iter<'_, RespType>) -> Vec<Vec<u8>> {
+        let mut cache_guard = self.cache.lock().unwrap();
+        let hash_cache_val = match cache_guard.get_or_insert_with(self.key.clone(), || CacheVal::Hash(HashCacheVal { hash: HashMap::new() })) {
+            CacheVal::Hash(hash_cache_val) => hash_cache_val,
+            _ => return vec![create_wrong_type_err_resp()]
+        };
+
+        if hash_cache_val.hash.contains_key(&self.field) {
+            return vec![create_int_resp(0)];
+        }
+        hash_cache_val.hash.insert(self.field.clone(), self.value.clone());
+        vec![create_int_resp(1)]
+    }
+}
@@ -1,39 +1,40 @@
-use std::{collections::HashMap, slice::Iter, sync::{Arc, Mutex}};
+use std::{slice::Iter, sync::{Arc, Mutex}};
 
-use crate::{commands::RedisCommand, redis::{client::{CacheVal, StringCacheVal}}, resp::{create_basic_err_resp, create_int_resp}, resp::types::RespType};
+use crate::{commands::{parse_integer, RedisCommand}, redis::{client::{CacheVal, StringCacheVal}, keyspace::Keyspace, string::{live_string, update_string}}, resp::{create_basic_err_resp, create_int_resp}, resp::types::RespType};
 
+/// Serves INCR, INCRBY, DECR and DECRBY, the decrements being negative increments.
 pub struct IncrCommand {
-    key: String,
-    cache: Arc<Mutex<HashMap<String, CacheVal>>>
+    key: Vec<u8>,
+    increment: i64,
+    cache: Arc<Mutex<Keyspace>>
 }
 
 impl IncrCommand {
-    pub fn new(key: String, cache: Arc<Mutex<HashMap<String, CacheVal>>>) -> Self {
+    pub fn new(key: Vec<u8>, increment: i64, cache: Arc<Mutex<Keyspace>>) -> Self {
         IncrCommand {
             key: key,
+            increment,
             cache: cache
         }
     }
 }
 
 impl RedisCommand for IncrCommand {
-    fn execute(&self, _: &mut Iter<'_, RespType>) -> Vec<String> {
+    fn execute(&self, _: &mut Iter<'_, RespType>) -> Vec<Vec<u8>> {
         let mut cache_guard = self.cache.lock().unwrap();
-        return match cache_guard.get_mut(&self.key) {
-            Some(CacheVal::String(v)) =>  {
-                let expiry_time = v.expiry_time;
-                let new_val = match v.val.parse::<i64>() {
-                    Ok(v) => v + 1,
-                    Err(_) => return vec![create_basic_err_resp("ERR value is not an integer or out of range".to_string())],
-                };
-                cache_guard.insert(self.key.clone(), CacheVal::String(StringCacheVal { val: new_val.to_string(), expiry_time: expiry_time }));
-                vec![create_int_resp(new_val)]
-            }
-            None => {
-                cache_guard.insert(self.key.clone(), CacheVal::String(StringCacheVal { val: "1".to_string(), expiry_time: None }));
-                vec![create_int_resp(1)]
-            }
-            _ => vec![create_basic_err_resp("ERR value is not an integer or out of range".to_string())]
-        }
+        let current = match live_string(&mut cache_guard, &self.key) {
+            Ok(Some(v)) => match parse_integer(&v.val) {
+                Some(current) => current,
+                _ => return vec![create_basic_err_resp("ERR value is not an integer or out of range".to_string())],
+            },
+            Ok(None) => 0,
+            Err(err) => return vec![err]
+        };
+        let new_val = match current.checked_add(self.increment) {
+            Some(new_val) => new_val,
+            None => return vec![create_basic_err_resp("ERR increment or decrement would overflow".to_string())]
+        };
+        update_string(&mut cache_guard, &self.key, new_val.to_string().into_bytes());
+        vec![create_int_resp(new_val)]
     }
-}
\ No newline at end of file
+}
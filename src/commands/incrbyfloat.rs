@@ -0,0 +1,46 @@
+use std::{slice::Iter, sync::{Arc, Mutex}};
+
+use crate::{commands::{parse_arg, format_plain_float, RedisCommand}, redis::{client::{CacheVal, StringCacheVal}, keyspace::Keyspace, replication::ReplicationLog, string::{live_string, update_string}}, resp::{create_array_resp, create_basic_err_resp, create_bulk_string_resp, types::RespType}};
+
+pub struct IncrbyfloatCommand {
+    key: Vec<u8>,
+    increment: f64,
+    cache: Arc<Mutex<Keyspace>>,
+    write_commands: Option<ReplicationLog>
+}
+
+impl IncrbyfloatCommand {
+    pub fn new(key: Vec<u8>, increment: f64, cache: Arc<Mutex<Keyspace>>, write_commands: Option<ReplicationLog>) -> Self {
+        IncrbyfloatCommand { key, increment, cache, write_commands }
+    }
+}
+
+impl RedisCommand for IncrbyfloatCommand {
+    fn execute(&self, _: &mut Iter<'_, RespType>) -> Vec<Vec<u8>> {
+        let mut cache_guard = self.cache.lock().unwrap();
+        let current = match live_string(&mut cache_guard, &self.key) {
+            Ok(Some(string_cache_val)) => match parse_arg::<f64>(&string_cache_val.val) {
+                Some(v) if v.is_finite() => v,
+                _ => return vec![create_basic_err_resp("ERR value is not a valid float".to_string())]
+            },
+            Ok(None) => 0.0,
+            Err(err) => return vec![err]
+        };
+        let new_val = current + self.increment;
+        if !new_val.is_finite() {
+            return vec![create_basic_err_resp("ERR increment would produce NaN or Infinity".to_string())];
+        }
+
+        let formatted = format_plain_float(new_val);
+        update_string(&mut cache_guard, &self.key, formatted.clone().into_bytes());
+
+        // float addition can differ between machines, so replicas get the final value instead of the increment
+        if let Some(write_commands) = &self.write_commands {
+            write_commands.push(create_array_resp(vec![
+                create_bulk_string_resp("SET"), create_bulk_string_resp(&self.key),
+                create_bulk_string_resp(formatted.clone()), create_bulk_string_resp("KEEPTTL")
+            ]));
+        }
+        vec![create_bulk_string_resp(formatted)]
+    }
+}
@@ -19,7 +19,7 @@ impl InfoCommand {
 }
 
 impl RedisCommand for InfoCommand {
-    fn execute(&self, _: &mut Iter<'_, RespType>) -> Vec<String> {
+    fn execute(&self, _: &mut Iter<'_, RespType>) -> Vec<Vec<u8>> {
         let mut info_string = format!("role:{}", self.role);
         if let Some(master_id) = &self.master_repl_id {
             info_string += format!("\r\nmaster_replid:{master_id}").as_str()
@@ -1,25 +1,26 @@
-use std::{collections::HashMap, slice::Iter, sync::{Arc, Mutex}};
+use std::{slice::Iter, sync::{Arc, Mutex}};
 
-use crate::{commands::RedisCommand, redis::{client::CacheVal}, resp::{create_array_resp, create_bulk_string_resp}, resp::types::RespType};
+use crate::{commands::RedisCommand, redis::{glob::glob_match, keyspace::Keyspace}, resp::{create_array_resp, create_bulk_string_resp}, resp::types::RespType};
 
 pub struct KeysCommand {
-    pattern: String,
-    cache: Arc<Mutex<HashMap<String, CacheVal>>>,
+    pattern: Vec<u8>,
+    cache: Arc<Mutex<Keyspace>>,
 }
 
 impl KeysCommand {
-    pub fn new(pattern: String, cache: Arc<Mutex<HashMap<String, CacheVal>>>) -> Self {
+    pub fn new(pattern: Vec<u8>, cache: Arc<Mutex<Keyspace>>) -> Self {
         KeysCommand { pattern, cache }
     }
 }
 
 impl RedisCommand for KeysCommand {
-    fn execute(&self, _: &mut Iter<'_, RespType>) -> Vec<String> {
+    fn execute(&self, _: &mut Iter<'_, RespType>) -> Vec<Vec<u8>> {
         let mut keys = vec![];
-        // for now always assume the pattern is *
         for (key, _) in self.cache.lock().unwrap().iter() {
-            keys.push(key.clone());
+            if glob_match(&self.pattern, key) {
+                keys.push(key.clone());
+            }
         }
-        return vec![create_array_resp(keys.into_iter().map(|x| create_bulk_string_resp(x)).collect())];
+        vec![create_array_resp(keys.into_iter().map(create_bulk_string_resp).collect())]
     }
 }
\ No newline at end of file
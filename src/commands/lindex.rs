@@ -0,0 +1,29 @@
+use std::{slice::Iter, sync::{Arc, Mutex}};
+
+use crate::{commands::RedisCommand, redis::{client::CacheVal, keyspace::Keyspace}, resp::{create_bulk_string_resp, create_null_bulk_string_resp, create_wrong_type_err_resp, types::RespType}};
+
+pub struct LindexCommand {
+    key: Vec<u8>,
+    index: i64,
+    cache: Arc<Mutex<Keyspace>>
+}
+
+impl LindexCommand {
+    pub fn new(key: Vec<u8>, index: i64, cache: Arc<Mutex<Keyspace>>) -> Self {
+        LindexCommand { key, index, cache }
+    }
+}
+
+impl RedisCommand for LindexCommand {
+    fn execute(&self, _: &mut Iter<'_, RespType>) -> Vec<Vec<u8>> {
+        let cache_guard = self.cache.lock().unwrap();
+        match cache_guard.get(&self.key) {
+            Some(CacheVal::List(list_cache_val)) => match list_cache_val.index(self.index) {
+                Some(index) => vec![create_bulk_string_resp(list_cache_val.list[index].clone())],
+                None => vec![create_null_bulk_string_resp()]
+            },
+            Some(_) => vec![create_wrong_type_err_resp()],
+            None => vec![create_null_bulk_string_resp()]
+        }
+    }
+}
@@ -0,0 +1,36 @@
+use std::{slice::Iter, sync::{Arc, Mutex}};
+
+use crate::{commands::RedisCommand, redis::{client::CacheVal, keyspace::Keyspace}, resp::{create_int_resp, create_wrong_type_err_resp, types::RespType}};
+
+/// LINSERT key BEFORE|AFTER pivot element, inserting next to the first occurrence of the pivot.
+pub struct LinsertCommand {
+    key: Vec<u8>,
+    after: bool,
+    pivot: Vec<u8>,
+    element: Vec<u8>,
+    cache: Arc<Mutex<Keyspace>>
+}
+
+impl LinsertCommand {
+    pub fn new(key: Vec<u8>, after: bool, pivot: Vec<u8>, element: Vec<u8>, cache: Arc<Mutex<Keyspace>>) -> Self {
+        LinsertCommand { key, after, pivot, element, cache }
+    }
+}
+
+impl RedisCommand for LinsertCommand {
+    fn execute(&self, _: &mut Iter<'_, RespType>) -> Vec<Vec<u8>> {
+        let mut cache_guard = self.cache.lock().unwrap();
+        let list_cache_val = match cache_guard.get_mut(&self.key) {
+            Some(CacheVal::List(list_cache_val)) if !list_cache_val.list.is_empty() => list_cache_val,
+            Some(CacheVal::List(_)) | None => return vec![create_int_resp(0)],
+            Some(_) => return vec![create_wrong_type_err_resp()]
+        };
+        match list_cache_val.list.iter().position(|val| self.pivot.eq(val)) {
+            Some(pos) => {
+                list_cache_val.list.insert(if self.after { pos + 1 } else { pos }, self.element.clone());
+                vec![create_int_resp(list_cache_val.list.len())]
+            },
+            None => vec![create_int_resp(-1)]
+        }
+    }
+}
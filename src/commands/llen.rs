@@ -1,14 +1,14 @@
-use std::{collections::HashMap, slice::Iter, sync::{Arc, Mutex}};
+use std::{slice::Iter, sync::{Arc, Mutex}};
 
-use crate::{commands::RedisCommand, redis::{client::CacheVal}, resp::{create_int_resp, types::RespType}};
+use crate::{commands::RedisCommand, redis::{client::CacheVal, keyspace::Keyspace}, resp::{create_int_resp, types::RespType}};
 
 pub struct LlenCommand {
-    list_key: String,
-    cache: Arc<Mutex<HashMap<String, CacheVal>>>
+    list_key: Vec<u8>,
+    cache: Arc<Mutex<Keyspace>>
 }
 
 impl LlenCommand {
-    pub fn new(list_key: String, cache: Arc<Mutex<HashMap<String, CacheVal>>>) -> Self {
+    pub fn new(list_key: Vec<u8>, cache: Arc<Mutex<Keyspace>>) -> Self {
         LlenCommand {
             list_key: list_key,
             cache: cache
@@ -17,7 +17,7 @@ impl LlenCommand {
 }
 
 impl RedisCommand for LlenCommand {
-    fn execute(&self, _: &mut Iter<'_, RespType>) -> Vec<String> {
+    fn execute(&self, _: &mut Iter<'_, RespType>) -> Vec<Vec<u8>> {
         let cache_guard = self.cache.lock().unwrap();
         return match cache_guard.get(&self.list_key) {
             Some(CacheVal::List(val)) => {
@@ -0,0 +1,45 @@
+use std::{slice::Iter, sync::{Arc, Mutex}};
+
+use crate::{commands::RedisCommand, redis::{client::{CacheVal, ListCacheVal}, keyspace::Keyspace, list::ListEnd}, resp::{create_bulk_string_resp, create_null_bulk_string_resp, create_wrong_type_err_resp, types::RespType}};
+
+/// Serves LMOVE and RPOPLPUSH, the latter being LMOVE source destination RIGHT LEFT.
+pub struct LmoveCommand {
+    source: Vec<u8>,
+    destination: Vec<u8>,
+    from: ListEnd,
+    to: ListEnd,
+    cache: Arc<Mutex<Keyspace>>
+}
+
+impl LmoveCommand {
+    pub fn new(source: Vec<u8>, destination: Vec<u8>, from: ListEnd, to: ListEnd, cache: Arc<Mutex<Keyspace>>) -> Self {
+        LmoveCommand { source, destination, from, to, cache }
+    }
+}
+
+impl RedisCommand for LmoveCommand {
+    fn execute(&self, _: &mut Iter<'_, RespType>) -> Vec<Vec<u8>> {
+        let mut cache_guard = self.cache.lock().unwrap();
+        match cache_guard.get(&self.destination) {
+            Some(CacheVal::List(_)) | None => {},
+            Some(_) => return vec![create_wrong_type_err_resp()]
+        }
+        let source_cache_val = match cache_guard.get_mut(&self.source) {
+            Some(CacheVal::List(list_cache_val)) => list_cache_val,
+            Some(_) => return vec![create_wrong_type_err_resp()],
+            None => return vec![create_null_bulk_string_resp()]
+        };
+
+        let val = match source_cache_val.pop(self.from) {
+            Some(val) => val,
+            None => return vec![create_null_bulk_string_resp()]
+        };
+        if source_cache_val.list.is_empty() {
+            cache_guard.remove(&self.source);
+        }
+        if let CacheVal::List(destination_cache_val) = cache_guard.get_or_insert_with(self.destination.clone(), || CacheVal::List(ListCacheVal::new())) {
+            destination_cache_val.push(self.to, val.clone());
+        }
+        vec![create_bulk_string_resp(val)]
+    }
+}
@@ -0,0 +1,45 @@
+use std::{slice::Iter, sync::{Arc, Mutex}};
+
+use crate::{commands::RedisCommand, redis::{client::CacheVal, keyspace::Keyspace, list::parse_mpop_args}, resp::{create_array_resp, create_bulk_string_resp, create_null_array_resp, create_wrong_args_err_resp, create_wrong_type_err_resp, types::RespType}};
+
+/// LMPOP numkeys key [key ...] LEFT|RIGHT [COUNT count], pops from the first non empty key.
+pub struct LmpopCommand {
+    cache: Arc<Mutex<Keyspace>>
+}
+
+impl LmpopCommand {
+    pub fn new(cache: Arc<Mutex<Keyspace>>) -> Self {
+        LmpopCommand { cache }
+    }
+}
+
+impl RedisCommand for LmpopCommand {
+    fn execute(&self, iter: &mut Iter<'_, RespType>) -> Vec<Vec<u8>> {
+        let mut args = vec![];
+        while let Some(RespType::String(arg)) = iter.next() {
+            args.push(arg.clone());
+        }
+        if args.len() < 3 {
+            return vec![create_wrong_args_err_resp("lmpop")];
+        }
+        let (keys, end, count) = match parse_mpop_args(&args) {
+            Ok(parsed) => parsed,
+            Err(err) => return vec![err]
+        };
+
+        let mut cache_guard = self.cache.lock().unwrap();
+        for key in keys {
+            let list_cache_val = match cache_guard.get_mut(&key) {
+                Some(CacheVal::List(list_cache_val)) if !list_cache_val.list.is_empty() => list_cache_val,
+                Some(CacheVal::List(_)) | None => continue,
+                Some(_) => return vec![create_wrong_type_err_resp()]
+            };
+            let vals: Vec<Vec<u8>> = (0..count).map_while(|_| list_cache_val.pop(end)).collect();
+            if list_cache_val.list.is_empty() {
+                cache_guard.remove(&key);
+            }
+            return vec![create_array_resp(vec![create_bulk_string_resp(key), create_array_resp(vals.into_iter().map(create_bulk_string_resp).collect())])];
+        }
+        vec![create_null_array_resp()]
+    }
+}
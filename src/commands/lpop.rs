@@ -1,17 +1,20 @@
-use std::{collections::HashMap, slice::Iter, sync::{Arc, Mutex}};
+use std::{slice::Iter, sync::{Arc, Mutex}};
 
-use crate::{commands::RedisCommand, redis::{client::CacheVal}, resp::{create_array_resp, create_bulk_string_resp, create_null_bulk_string_resp}, resp::types::RespType};
+use crate::{commands::RedisCommand, redis::{client::CacheVal, keyspace::Keyspace, list::ListEnd}, resp::{create_array_resp, create_bulk_string_resp, create_null_array_resp, create_null_bulk_string_resp, create_wrong_type_err_resp}, resp::types::RespType};
 
+/// Serves both LPOP and RPOP.
 pub struct LpopCommand {
-    list_key: String,
+    list_key: Vec<u8>,
+    end: ListEnd,
     count: Option<usize>,
-    cache: Arc<Mutex<HashMap<String, CacheVal>>>
+    cache: Arc<Mutex<Keyspace>>
 }
 
 impl LpopCommand {
-    pub fn new(list_key: String, count: Option<usize>, cache: Arc<Mutex<HashMap<String, CacheVal>>>) -> Self {
+    pub fn new(list_key: Vec<u8>, end: ListEnd, count: Option<usize>, cache: Arc<Mutex<Keyspace>>) -> Self {
         LpopCommand {
             list_key: list_key,
+            end,
             count: count,
             cache: cache
         }
@@ -19,30 +22,24 @@ impl LpopCommand {
 }
 
 impl RedisCommand for LpopCommand {
-    fn execute(&self, _: &mut Iter<'_, RespType>) -> Vec<String> {
+    fn execute(&self, _: &mut Iter<'_, RespType>) -> Vec<Vec<u8>> {
         let mut cache_guard = self.cache.lock().unwrap();
-        return match cache_guard.get_mut(&self.list_key) {
-            Some(CacheVal::List(val)) if val.list.len() > 0 => {
-                match self.count {
-                    Some(count_to_pop) if count_to_pop == 1 => {
-                        let val = val.list.remove(0);
-                        vec![create_bulk_string_resp(val)]
-                    },
-                    Some(count_to_pop) => {
-                        let mut vals = vec![];
-                        for _ in 0..count_to_pop {
-                            vals.push(val.list.remove(0))
-                        }
-                        let bulk_strs: Vec<String> = vals.iter().map(|item| create_bulk_string_resp(item.to_string())).collect();
-                        vec![create_array_resp(bulk_strs)]
-                    },
-                    None => {
-                        let val = val.list.remove(0);
-                        vec![create_bulk_string_resp(val)]
-                    }
-                }
-            }
-            _ => vec![create_null_bulk_string_resp()]
+        let list_cache_val = match cache_guard.get_mut(&self.list_key) {
+            Some(CacheVal::List(val)) if !val.list.is_empty() => val,
+            Some(CacheVal::List(_)) | None if self.count.is_some() => return vec![create_null_array_resp()],
+            Some(CacheVal::List(_)) | None => return vec![create_null_bulk_string_resp()],
+            Some(_) => return vec![create_wrong_type_err_resp()]
+        };
+        let res = match self.count {
+            Some(count_to_pop) => {
+                let vals: Vec<Vec<u8>> = (0..count_to_pop).map_while(|_| list_cache_val.pop(self.end)).collect();
+                create_array_resp(vals.into_iter().map(create_bulk_string_resp).collect())
+            },
+            None => create_bulk_string_resp(list_cache_val.pop(self.end).unwrap())
+        };
+        if list_cache_val.list.is_empty() {
+            cache_guard.remove(&self.list_key);
         }
+        vec![res]
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,77 @@
+use std::{slice::Iter, sync::{Arc, Mutex}};
+
+use crate::{commands::{parse_arg, RedisCommand}, redis::{client::CacheVal, keyspace::Keyspace}, resp::{create_array_resp, create_basic_err_resp, create_int_resp, create_null_bulk_string_resp, create_wrong_type_err_resp, types::RespType}};
+
+/// LPOS key element [RANK rank] [COUNT num-matches] [MAXLEN len]. A negative rank searches from
+/// the tail, and asking for a COUNT replies with an array of positions instead of a single one.
+pub struct LposCommand {
+    key: Vec<u8>,
+    element: Vec<u8>,
+    cache: Arc<Mutex<Keyspace>>
+}
+
+impl LposCommand {
+    pub fn new(key: Vec<u8>, element: Vec<u8>, cache: Arc<Mutex<Keyspace>>) -> Self {
+        LposCommand { key, element, cache }
+    }
+}
+
+impl RedisCommand for LposCommand {
+    fn execute(&self, iter: &mut Iter<'_, RespType>) -> Vec<Vec<u8>> {
+        let mut rank: i64 = 1;
+        let mut count = None;
+        let mut max_len = 0;
+        while let Some(RespType::String(option)) = iter.next() {
+            let val = match iter.next() {
+                Some(RespType::String(val)) => match parse_arg::<i64>(val) {
+                    Some(val) => val,
+                    None => return vec![create_basic_err_resp("ERR value is not an integer or out of range".to_string())]
+                },
+                _ => return vec![create_basic_err_resp("ERR syntax error".to_string())]
+            };
+            match option.to_ascii_lowercase().as_slice() {
+                b"rank" if val == 0 => return vec![create_basic_err_resp("ERR RANK can't be zero: use 1 to start from the first match, 2 from the second ... or use negative to start from the end of the list".to_string())],
+                b"rank" => rank = val,
+                b"count" if val < 0 => return vec![create_basic_err_resp("ERR COUNT can't be negative".to_string())],
+                b"count" => count = Some(val as usize),
+                b"maxlen" if val < 0 => return vec![create_basic_err_resp("ERR MAXLEN can't be negative".to_string())],
+                b"maxlen" => max_len = val as usize,
+                _ => return vec![create_basic_err_resp("ERR syntax error".to_string())]
+            }
+        }
+
+        let cache_guard = self.cache.lock().unwrap();
+        let list = match cache_guard.get(&self.key) {
+            Some(CacheVal::List(list_cache_val)) => &list_cache_val.list,
+            Some(_) => return vec![create_wrong_type_err_resp()],
+            None if count.is_some() => return vec![create_array_resp(vec![])],
+            None => return vec![create_null_bulk_string_resp()]
+        };
+
+        // zero means no limit for both COUNT and MAXLEN
+        let max_len = if max_len == 0 { list.len() } else { max_len.min(list.len()) };
+        let wanted = match count {
+            Some(0) => usize::MAX,
+            Some(count) => count,
+            None => 1
+        };
+        let positions: Box<dyn Iterator<Item = usize>> = if rank > 0 {
+            Box::new(0..max_len)
+        } else {
+            Box::new((list.len() - max_len..list.len()).rev())
+        };
+        let matches: Vec<usize> = positions
+            .filter(|pos| self.element.eq(&list[*pos]))
+            .skip(rank.unsigned_abs() as usize - 1)
+            .take(wanted)
+            .collect();
+
+        match count {
+            Some(_) => vec![create_array_resp(matches.into_iter().map(create_int_resp).collect())],
+            None => match matches.first() {
+                Some(pos) => vec![create_int_resp(pos)],
+                None => vec![create_null_bulk_string_resp()]
+            }
+        }
+    }
+}
@@ -0,0 +1,34 @@
+use std::{slice::Iter, sync::{Arc, Mutex}};
+
+use crate::{commands::RedisCommand, redis::{client::CacheVal, keyspace::Keyspace, list::ListEnd}, resp::{create_int_resp, create_wrong_type_err_resp, types::RespType}};
+
+/// Serves both LPUSHX and RPUSHX, which only push onto lists that already exist.
+pub struct LpushxCommand {
+    key: Vec<u8>,
+    end: ListEnd,
+    elements: Vec<Vec<u8>>,
+    cache: Arc<Mutex<Keyspace>>
+}
+
+impl LpushxCommand {
+    pub fn new(key: Vec<u8>, end: ListEnd, elements: Vec<Vec<u8>>, cache: Arc<Mutex<Keyspace>>) -> Self {
+        LpushxCommand { key, end, elements, cache }
+    }
+}
+
+impl RedisCommand for LpushxCommand {
+    fn execute(&self, _: &mut Iter<'_, RespType>) -> Vec<Vec<u8>> {
+        let mut cache_guard = self.cache.lock().unwrap();
+        match cache_guard.get_mut(&self.key) {
+            // an empty list only stands in for blocked connections, the key does not exist
+            Some(CacheVal::List(list_cache_val)) if !list_cache_val.list.is_empty() => {
+                for element in &self.elements {
+                    list_cache_val.push(self.end, element.clone());
+                }
+                vec![create_int_resp(list_cache_val.list.len())]
+            },
+            Some(CacheVal::List(_)) | None => vec![create_int_resp(0)],
+            Some(_) => vec![create_wrong_type_err_resp()]
+        }
+    }
+}
@@ -1,16 +1,16 @@
-use std::{collections::HashMap, slice::Iter, sync::{Arc, Mutex}};
+use std::{slice::Iter, sync::{Arc, Mutex}};
 
-use crate::{commands::RedisCommand, redis::{client::{CacheVal, ListCacheVal, StringCacheVal}}, resp::{create_array_resp, create_bulk_string_resp}, resp::types::RespType};
+use crate::{commands::RedisCommand, redis::{client::{CacheVal, ListCacheVal, StringCacheVal}, keyspace::Keyspace, list::clamp_range}, resp::{create_array_resp, create_bulk_string_resp}, resp::types::RespType};
 
 pub struct LrangeCommand {
-    list_key: String,
+    list_key: Vec<u8>,
     start: i64,
     end: i64,
-    cache: Arc<Mutex<HashMap<String, CacheVal>>>
+    cache: Arc<Mutex<Keyspace>>
 }
 
 impl LrangeCommand {
-    pub fn new(list_key: String, start: i64, end: i64, cache: Arc<Mutex<HashMap<String, CacheVal>>>) -> Self {
+    pub fn new(list_key: Vec<u8>, start: i64, end: i64, cache: Arc<Mutex<Keyspace>>) -> Self {
         LrangeCommand {
             list_key: list_key,
             start: start,
@@ -21,27 +21,15 @@ impl LrangeCommand {
 }
 
 impl RedisCommand for LrangeCommand {
-    fn execute(&self, _: &mut Iter<'_, RespType>) -> Vec<String> {
+    fn execute(&self, _: &mut Iter<'_, RespType>) -> Vec<Vec<u8>> {
         let mut cache_gaurd = self.cache.lock().unwrap();
         return match cache_gaurd.get_mut(&self.list_key) {
             Some(CacheVal::List(list_cache_val)) => {
-                let mut start_idx = self.start;
-                let mut end_idx = self.end;
-
-                if start_idx < 0 {
-                    start_idx += list_cache_val.list.len() as i64;
-                }
-                if end_idx < 0 {
-                    end_idx += list_cache_val.list.len() as i64;
-                }
-                if start_idx >= list_cache_val.list.len() as i64 || start_idx > end_idx {
-                    return vec![create_array_resp(vec![])];
-                }
-                
-                end_idx = end_idx.min(list_cache_val.list.len() as i64 - 1);
-                start_idx = start_idx.max(0);
-                let vals =  list_cache_val.list[(start_idx as usize)..=(end_idx as usize)].to_vec();
-                let bulk_strs: Vec<String> = vals.iter().map(|item| create_bulk_string_resp(item.to_string())).collect();
+                let (start_idx, end_idx) = match clamp_range(self.start, self.end, list_cache_val.list.len()) {
+                    Some(range) => range,
+                    None => return vec![create_array_resp(vec![])]
+                };
+                let bulk_strs: Vec<Vec<u8>> = list_cache_val.list.range(start_idx..=end_idx).map(|item| create_bulk_string_resp(item.clone())).collect();
                 vec![create_array_resp(bulk_strs)]
             },
             _ =>  vec![create_array_resp(vec![])]
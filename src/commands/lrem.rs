@@ -0,0 +1,55 @@
+use std::{slice::Iter, sync::{Arc, Mutex}};
+
+use crate::{commands::RedisCommand, redis::{client::CacheVal, keyspace::Keyspace}, resp::{create_int_resp, create_wrong_type_err_resp, types::RespType}};
+
+/// LREM key count element. A positive count removes from the head, a negative one from the tail
+/// and zero removes every occurrence.
+pub struct LremCommand {
+    key: Vec<u8>,
+    count: i64,
+    element: Vec<u8>,
+    cache: Arc<Mutex<Keyspace>>
+}
+
+impl LremCommand {
+    pub fn new(key: Vec<u8>, count: i64, element: Vec<u8>, cache: Arc<Mutex<Keyspace>>) -> Self {
+        LremCommand { key, count, element, cache }
+    }
+}
+
+impl RedisCommand for LremCommand {
+    fn execute(&self, _: &mut Iter<'_, RespType>) -> Vec<Vec<u8>> {
+        let mut cache_guard = self.cache.lock().unwrap();
+        let list_cache_val = match cache_guard.get_mut(&self.key) {
+            Some(CacheVal::List(list_cache_val)) => list_cache_val,
+            Some(_) => return vec![create_wrong_type_err_resp()],
+            None => return vec![create_int_resp(0)]
+        };
+
+        let limit = if self.count == 0 { usize::MAX } else { self.count.unsigned_abs() as usize };
+        let mut matches: Vec<usize> = list_cache_val.list.iter().enumerate()
+            .filter(|(_, val)| self.element.eq(*val))
+            .map(|(pos, _)| pos)
+            .collect();
+        if self.count < 0 {
+            matches.reverse();
+        }
+        matches.truncate(limit);
+        matches.sort_unstable();
+        let removed = matches.len();
+
+        // a single pass over the list instead of shifting it once per removal
+        let mut matches = matches.into_iter().peekable();
+        let mut pos = 0;
+        list_cache_val.list.retain(|_| {
+            let keep = matches.next_if_eq(&pos).is_none();
+            pos += 1;
+            keep
+        });
+
+        if list_cache_val.list.is_empty() {
+            cache_guard.remove(&self.key);
+        }
+        vec![create_int_resp(removed)]
+    }
+}
@@ -0,0 +1,34 @@
+use std::{slice::Iter, sync::{Arc, Mutex}};
+
+use crate::{commands::RedisCommand, redis::{client::CacheVal, keyspace::Keyspace}, resp::{create_basic_err_resp, create_simple_string_resp, create_wrong_type_err_resp, types::RespType}};
+
+pub struct LsetCommand {
+    key: Vec<u8>,
+    index: i64,
+    element: Vec<u8>,
+    cache: Arc<Mutex<Keyspace>>
+}
+
+impl LsetCommand {
+    pub fn new(key: Vec<u8>, index: i64, element: Vec<u8>, cache: Arc<Mutex<Keyspace>>) -> Self {
+        LsetCommand { key, index, element, cache }
+    }
+}
+
+impl RedisCommand for LsetCommand {
+    fn execute(&self, _: &mut Iter<'_, RespType>) -> Vec<Vec<u8>> {
+        let mut cache_guard = self.cache.lock().unwrap();
+        let list_cache_val = match cache_guard.get_mut(&self.key) {
+            Some(CacheVal::List(list_cache_val)) if !list_cache_val.list.is_empty() => list_cache_val,
+            Some(CacheVal::List(_)) | None => return vec![create_basic_err_resp("ERR no such key".to_string())],
+            Some(_) => return vec![create_wrong_type_err_resp()]
+        };
+        match list_cache_val.index(self.index) {
+            Some(index) => {
+                list_cache_val.list[index] = self.element.clone();
+                vec![create_simple_string_resp("OK".to_string())]
+            },
+            None => vec![create_basic_err_resp("ERR index out of range".to_string())]
+        }
+    }
+}
@@ -0,0 +1,38 @@
+use std::{slice::Iter, sync::{Arc, Mutex}};
+
+use crate::{commands::RedisCommand, redis::{client::CacheVal, keyspace::Keyspace, list::clamp_range}, resp::{create_simple_string_resp, create_wrong_type_err_resp, types::RespType}};
+
+pub struct LtrimCommand {
+    key: Vec<u8>,
+    start: i64,
+    end: i64,
+    cache: Arc<Mutex<Keyspace>>
+}
+
+impl LtrimCommand {
+    pub fn new(key: Vec<u8>, start: i64, end: i64, cache: Arc<Mutex<Keyspace>>) -> Self {
+        LtrimCommand { key, start, end, cache }
+    }
+}
+
+impl RedisCommand for LtrimCommand {
+    fn execute(&self, _: &mut Iter<'_, RespType>) -> Vec<Vec<u8>> {
+        let mut cache_guard = self.cache.lock().unwrap();
+        let list_cache_val = match cache_guard.get_mut(&self.key) {
+            Some(CacheVal::List(list_cache_val)) => list_cache_val,
+            Some(_) => return vec![create_wrong_type_err_resp()],
+            None => return vec![create_simple_string_resp("OK".to_string())]
+        };
+        match clamp_range(self.start, self.end, list_cache_val.list.len()) {
+            Some((start, end)) => {
+                list_cache_val.list.truncate(end + 1);
+                list_cache_val.list.drain(..start);
+            },
+            None => list_cache_val.list.clear()
+        }
+        if list_cache_val.list.is_empty() {
+            cache_guard.remove(&self.key);
+        }
+        vec![create_simple_string_resp("OK".to_string())]
+    }
+}
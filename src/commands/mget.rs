@@ -0,0 +1,26 @@
+use std::{slice::Iter, sync::{Arc, Mutex}};
+
+use crate::{commands::RedisCommand, redis::{client::CacheVal, keyspace::Keyspace, stream::now_ms}, resp::{create_array_resp, create_bulk_string_resp, create_null_bulk_string_resp, types::RespType}};
+
+/// MGET key [key ...], replying nil for every key that does not hold a string.
+pub struct MgetCommand {
+    keys: Vec<Vec<u8>>,
+    cache: Arc<Mutex<Keyspace>>
+}
+
+impl MgetCommand {
+    pub fn new(keys: Vec<Vec<u8>>, cache: Arc<Mutex<Keyspace>>) -> Self {
+        MgetCommand { keys, cache }
+    }
+}
+
+impl RedisCommand for MgetCommand {
+    fn execute(&self, _: &mut Iter<'_, RespType>) -> Vec<Vec<u8>> {
+        let cache_guard = self.cache.lock().unwrap();
+        let vals = self.keys.iter().map(|key| match cache_guard.get(key) {
+            Some(CacheVal::String(val)) => create_bulk_string_resp(&val.val),
+            _ => create_null_bulk_string_resp()
+        }).collect();
+        vec![create_array_resp(vals)]
+    }
+}
@@ -1,30 +1,182 @@
-use std::slice::Iter;
-use crate::{resp::types::RespType};
+use std::{slice::Iter, str::FromStr};
+use crate::{resp::{create_basic_err_resp, types::RespType}};
 
 pub mod ping;
 pub mod echo;
 pub mod get;
 pub mod set;
+pub mod append;
+pub mod strlen;
+pub mod getrange;
+pub mod setrange;
+pub mod mset;
+pub mod mget;
+pub mod getdel;
+pub mod getex;
 pub mod rpush;
 pub mod lpush;
 pub mod lrange;
 pub mod llen;
 pub mod lpop;
 pub mod blpop;
+pub mod lindex;
+pub mod lset;
+pub mod linsert;
+pub mod lrem;
+pub mod ltrim;
+pub mod lpos;
+pub mod lmove;
+pub mod lmpop;
+pub mod lpushx;
 pub mod type_command;
 pub mod xadd;
 pub mod xrange;
 pub mod xread;
 pub mod incr;
+pub mod incrbyfloat;
+pub mod setbit;
+pub mod getbit;
+pub mod bitcount;
+pub mod bitpos;
+pub mod bitop;
+pub mod bitfield;
+pub mod pfadd;
+pub mod pfcount;
+pub mod pfmerge;
 pub mod info;
 pub mod replconf;
 pub mod psync;
 pub mod publish;
 pub mod keys;
+pub mod del;
+pub mod exists;
+pub mod rename;
+pub mod copy;
+pub mod randomkey;
+pub mod dbsize;
+pub mod expire;
+pub mod ttl;
+pub mod persist;
+pub mod scan;
+pub mod move_command;
+pub mod swapdb;
+pub mod flushdb;
+pub mod save;
 pub mod unsubscribe;
 pub mod subscribe;
 pub mod wait;
+pub mod hset;
+pub mod hsetnx;
+pub mod hget;
+pub mod hmget;
+pub mod hdel;
+pub mod hexists;
+pub mod hlen;
+pub mod hkeys;
+pub mod hvals;
+pub mod hgetall;
+pub mod hincrby;
+pub mod hincrbyfloat;
+pub mod hstrlen;
+pub mod hrandfield;
+pub mod set_ops;
+pub mod sadd;
+pub mod srem;
+pub mod sismember;
+pub mod smismember;
+pub mod scard;
+pub mod smembers;
+pub mod spop;
+pub mod srandmember;
+pub mod smove;
+pub mod sinter;
+pub mod sunion;
+pub mod sdiff;
+pub mod sintercard;
+pub mod zadd;
+pub mod zrem;
+pub mod zscore;
+pub mod zmscore;
+pub mod zincrby;
+pub mod zcard;
+pub mod zcount;
+pub mod zrank;
+pub mod zrange;
+pub mod zset_ops;
+pub mod zunion;
+pub mod zinter;
+pub mod zdiff;
+pub mod zpop;
+pub mod bzpop;
+pub mod zmpop;
+pub mod geoadd;
+pub mod geodist;
+pub mod geopos;
+pub mod geohash;
+pub mod geosearch;
+pub mod xgroup;
+pub mod xreadgroup;
+pub mod xack;
+pub mod xpending;
+pub mod xclaim;
+pub mod xautoclaim;
+pub mod xlen;
+pub mod xdel;
+pub mod xtrim;
+pub mod xinfo;
 
 pub trait RedisCommand {
-    fn execute(&self, iter: &mut Iter<'_, RespType>) -> Vec<String>;
-}
\ No newline at end of file
+    fn execute(&self, iter: &mut Iter<'_, RespType>) -> Vec<Vec<u8>>;
+}
+
+/// Formats a score the way redis replies with it, using the shortest representation that round trips.
+/// Like `%.17g`, exponents below -4 or from 17 on are written in exponent notation, as in `1e+20`.
+pub fn format_float(val: f64) -> String {
+    if !val.is_finite() || val == 0.0 {
+        return format!("{}", val);
+    }
+    let scientific = format!("{:e}", val);
+    let (mantissa, exponent) = scientific.split_once('e').expect("exponent notation has an exponent");
+    let exponent: i32 = exponent.parse().expect("exponents are integers");
+    if (-4..17).contains(&exponent) {
+        format!("{}", val)
+    } else {
+        format!("{}e{}{:02}", mantissa, if exponent < 0 { '-' } else { '+' }, exponent.unsigned_abs())
+    }
+}
+
+/// Formats the result of INCRBYFLOAT or HINCRBYFLOAT like redis's long double formatting, never in
+/// exponent notation and without trailing zeros, so `1e20` comes back as `100000000000000000000`.
+pub fn format_plain_float(val: f64) -> String {
+    format!("{}", val)
+}
+
+/// The most elements a negative HRANDFIELD or SRANDMEMBER count may ask for. Those replies may
+/// repeat elements so their size doesn't depend on the collection, bigger ones would exhaust memory
+/// while holding the keyspace lock.
+pub const MAX_RANDOM_REPEATS: i64 = 1 << 20;
+
+/// Parses the count of HRANDFIELD or SRANDMEMBER, negative counts allowing repeated elements.
+pub fn parse_random_count(arg: &[u8]) -> Result<i64, Vec<u8>> {
+    match parse_arg::<i64>(arg) {
+        Some(count) if count >= -MAX_RANDOM_REPEATS => Ok(count),
+        Some(_) => Err(create_basic_err_resp(format!("ERR value is out of range, value must between {} and {}", -MAX_RANDOM_REPEATS, i64::MAX))),
+        None => Err(create_basic_err_resp("ERR value is not an integer or out of range".to_string()))
+    }
+}
+
+/// Parses a numeric argument, arguments arriving as raw bytes.
+pub fn parse_arg<T>(arg: &[u8]) -> Option<T> where T: FromStr {
+    std::str::from_utf8(arg).ok().and_then(|arg| arg.parse::<T>().ok())
+}
+
+/// Parses an integer the way redis does, only accepting the form it would print back: no sign
+/// but a minus, no leading zeros and no `-0`.
+pub fn parse_integer(arg: &[u8]) -> Option<i64> {
+    let digits = arg.strip_prefix(b"-").unwrap_or(arg);
+    match digits {
+        b"0" if digits.len() == arg.len() => Some(0),
+        [b'1'..=b'9', rest @ ..] if rest.iter().all(u8::is_ascii_digit) => parse_arg(arg),
+        _ => None
+    }
+}
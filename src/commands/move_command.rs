@@ -0,0 +1,38 @@
+use std::slice::Iter;
+
+use crate::{commands::RedisCommand, redis::{client::CacheVal, keyspace::{lock_pair, remove_key, store_key, Databases}}, resp::{create_basic_err_resp, create_int_resp, types::RespType}};
+
+/// Moves `key` from the db the connection has selected to `destination_db`, along with its expiry.
+/// Nothing moves when the key already exists there.
+pub struct MoveCommand {
+    key: Vec<u8>,
+    databases: Databases,
+    db: usize,
+    destination_db: usize
+}
+
+impl MoveCommand {
+    pub fn new(key: Vec<u8>, databases: Databases, db: usize, destination_db: usize) -> Self {
+        MoveCommand { key, databases, db, destination_db }
+    }
+}
+
+impl RedisCommand for MoveCommand {
+    fn execute(&self, _: &mut Iter<'_, RespType>) -> Vec<Vec<u8>> {
+        if self.db == self.destination_db {
+            return vec![create_basic_err_resp("ERR source and destination objects are the same".to_string())];
+        }
+
+        let (mut source, mut destination) = lock_pair(&self.databases, self.db, self.destination_db);
+        if destination.contains_key(&self.key) {
+            return vec![create_int_resp(0)];
+        }
+        match remove_key(&mut source, &self.key) {
+            Some((val, expiry_time)) => {
+                store_key(&mut destination, self.key.clone(), val, expiry_time);
+                vec![create_int_resp(1)]
+            },
+            None => vec![create_int_resp(0)]
+        }
+    }
+}
@@ -0,0 +1,37 @@
+use std::{slice::Iter, sync::{Arc, Mutex}};
+
+use crate::{commands::RedisCommand, redis::{client::{CacheVal, StringCacheVal}, keyspace::Keyspace, stream::now_ms}, resp::{create_int_resp, create_simple_string_resp, types::RespType}};
+
+/// Serves MSET, MSETNX and SETNX. All the pairs are written under one hold of the cache lock, and
+/// with `nx` nothing is written if any of the keys exists.
+pub struct MsetCommand {
+    pairs: Vec<(Vec<u8>, Vec<u8>)>,
+    nx: bool,
+    cache: Arc<Mutex<Keyspace>>
+}
+
+impl MsetCommand {
+    pub fn new(pairs: Vec<(Vec<u8>, Vec<u8>)>, nx: bool, cache: Arc<Mutex<Keyspace>>) -> Self {
+        MsetCommand { pairs, nx, cache }
+    }
+}
+
+impl RedisCommand for MsetCommand {
+    fn execute(&self, _: &mut Iter<'_, RespType>) -> Vec<Vec<u8>> {
+        let mut cache_guard = self.cache.lock().unwrap();
+        if self.nx {
+            let any_exists = self.pairs.iter().any(|(key, _)| cache_guard.contains_key(key));
+            if any_exists {
+                return vec![create_int_resp(0)];
+            }
+        }
+        for (key, value) in &self.pairs {
+            cache_guard.insert(key.clone(), CacheVal::String(StringCacheVal::new(value.clone())));
+        }
+        if self.nx {
+            vec![create_int_resp(1)]
+        } else {
+            vec![create_simple_string_resp("OK".to_string())]
+        }
+    }
+}
@@ -0,0 +1,26 @@
+use std::{slice::Iter, sync::{Arc, Mutex}};
+
+use crate::{commands::RedisCommand, redis::keyspace::Keyspace, resp::{create_int_resp, types::RespType}};
+
+/// Drops the expiry of a key, replying whether it had one.
+pub struct PersistCommand {
+    key: Vec<u8>,
+    cache: Arc<Mutex<Keyspace>>
+}
+
+impl PersistCommand {
+    pub fn new(key: Vec<u8>, cache: Arc<Mutex<Keyspace>>) -> Self {
+        PersistCommand { key, cache }
+    }
+}
+
+impl RedisCommand for PersistCommand {
+    fn execute(&self, _: &mut Iter<'_, RespType>) -> Vec<Vec<u8>> {
+        let mut cache_guard = self.cache.lock().unwrap();
+        if cache_guard.expiry(&self.key).is_none() {
+            return vec![create_int_resp(0)];
+        }
+        cache_guard.set_expiry(&self.key, None);
+        vec![create_int_resp(1)]
+    }
+}
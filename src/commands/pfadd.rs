@@ -0,0 +1,36 @@
+use std::{slice::Iter, sync::{Arc, Mutex}};
+
+use crate::{commands::RedisCommand, redis::{client::{CacheVal, StringCacheVal}, keyspace::Keyspace, hyperloglog::HyperLogLog, string::{live_string, update_string}}, resp::{create_int_resp, types::RespType}};
+
+pub struct PfaddCommand {
+    key: Vec<u8>,
+    elements: Vec<Vec<u8>>,
+    cache: Arc<Mutex<Keyspace>>
+}
+
+impl PfaddCommand {
+    pub fn new(key: Vec<u8>, elements: Vec<Vec<u8>>, cache: Arc<Mutex<Keyspace>>) -> Self {
+        PfaddCommand { key, elements, cache }
+    }
+}
+
+impl RedisCommand for PfaddCommand {
+    fn execute(&self, _: &mut Iter<'_, RespType>) -> Vec<Vec<u8>> {
+        let mut cache_guard = self.cache.lock().unwrap();
+        let (mut hll, mut changed) = match live_string(&mut cache_guard, &self.key) {
+            Ok(Some(string_cache_val)) => match HyperLogLog::decode(&string_cache_val.val) {
+                Ok(hll) => (hll, false),
+                Err(err) => return vec![err]
+            },
+            Ok(None) => (HyperLogLog::default(), true),
+            Err(err) => return vec![err]
+        };
+        for element in &self.elements {
+            changed |= hll.add(element);
+        }
+        if changed {
+            update_string(&mut cache_guard, &self.key, hll.encode());
+        }
+        vec![create_int_resp(changed as usize)]
+    }
+}
@@ -0,0 +1,52 @@
+use std::{slice::Iter, sync::{Arc, Mutex}};
+
+use crate::{commands::RedisCommand, redis::{client::CacheVal, keyspace::Keyspace, hyperloglog::{cache_cardinality, cached_cardinality, HyperLogLog}, string::live_string}, resp::{create_int_resp, types::RespType}};
+
+/// A single key's cardinality is cached in its header until the next write, the union of several
+/// keys is estimated from scratch every time.
+pub struct PfcountCommand {
+    keys: Vec<Vec<u8>>,
+    cache: Arc<Mutex<Keyspace>>
+}
+
+impl PfcountCommand {
+    pub fn new(keys: Vec<Vec<u8>>, cache: Arc<Mutex<Keyspace>>) -> Self {
+        PfcountCommand { keys, cache }
+    }
+}
+
+impl RedisCommand for PfcountCommand {
+    fn execute(&self, _: &mut Iter<'_, RespType>) -> Vec<Vec<u8>> {
+        let mut cache_guard = self.cache.lock().unwrap();
+        if let [key] = self.keys.as_slice() {
+            return match live_string(&mut cache_guard, key) {
+                Ok(Some(string_cache_val)) => match HyperLogLog::decode(&string_cache_val.val) {
+                    Ok(hll) => {
+                        let cardinality = cached_cardinality(&string_cache_val.val).unwrap_or_else(|| {
+                            let cardinality = hll.count();
+                            cache_cardinality(&mut string_cache_val.val, cardinality);
+                            cardinality
+                        });
+                        vec![create_int_resp(cardinality as usize)]
+                    },
+                    Err(err) => vec![err]
+                },
+                Ok(None) => vec![create_int_resp(0)],
+                Err(err) => vec![err]
+            };
+        }
+
+        let mut union = HyperLogLog::default();
+        for key in &self.keys {
+            match live_string(&mut cache_guard, key) {
+                Ok(Some(string_cache_val)) => match HyperLogLog::decode(&string_cache_val.val) {
+                    Ok(hll) => union.merge(&hll),
+                    Err(err) => return vec![err]
+                },
+                Ok(None) => {},
+                Err(err) => return vec![err]
+            }
+        }
+        vec![create_int_resp(union.count() as usize)]
+    }
+}
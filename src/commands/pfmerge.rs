@@ -0,0 +1,36 @@
+use std::{slice::Iter, sync::{Arc, Mutex}};
+
+use crate::{commands::RedisCommand, redis::{client::{CacheVal, StringCacheVal}, keyspace::Keyspace, hyperloglog::HyperLogLog, string::{live_string, update_string}}, resp::{create_simple_string_resp, types::RespType}};
+
+/// Merges the sources into the destination, which takes part in the union itself and is always
+/// left dense.
+pub struct PfmergeCommand {
+    destination: Vec<u8>,
+    sources: Vec<Vec<u8>>,
+    cache: Arc<Mutex<Keyspace>>
+}
+
+impl PfmergeCommand {
+    pub fn new(destination: Vec<u8>, sources: Vec<Vec<u8>>, cache: Arc<Mutex<Keyspace>>) -> Self {
+        PfmergeCommand { destination, sources, cache }
+    }
+}
+
+impl RedisCommand for PfmergeCommand {
+    fn execute(&self, _: &mut Iter<'_, RespType>) -> Vec<Vec<u8>> {
+        let mut cache_guard = self.cache.lock().unwrap();
+        let mut union = HyperLogLog::default();
+        for key in std::iter::once(&self.destination).chain(&self.sources) {
+            match live_string(&mut cache_guard, key) {
+                Ok(Some(string_cache_val)) => match HyperLogLog::decode(&string_cache_val.val) {
+                    Ok(hll) => union.merge(&hll),
+                    Err(err) => return vec![err]
+                },
+                Ok(None) => {},
+                Err(err) => return vec![err]
+            }
+        }
+        update_string(&mut cache_guard, &self.destination, union.into_dense().encode());
+        vec![create_simple_string_resp("OK".to_string())]
+    }
+}
@@ -13,7 +13,7 @@ impl PingCommand {
 }
 
 impl RedisCommand for PingCommand {
-    fn execute(&self, _: &mut Iter<'_, RespType>) -> Vec<String> {
+    fn execute(&self, _: &mut Iter<'_, RespType>) -> Vec<Vec<u8>> {
         if self.is_subscribe_context {
             vec![create_array_resp(vec![create_bulk_string_resp("pong".to_string()), create_bulk_string_resp("".to_string())])]
         } else {
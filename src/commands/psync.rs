@@ -17,7 +17,7 @@ impl PsyncCommand {
 }
 
 impl RedisCommand for PsyncCommand {
-    fn execute(&self, _: &mut Iter<'_, RespType>) -> Vec<String> {
+    fn execute(&self, _: &mut Iter<'_, RespType>) -> Vec<Vec<u8>> {
         return vec![
             create_simple_string_resp(format!("FULLRESYNC {} {}", self.master_repl_id, self.master_repl_offset)),
             "EMPTY_RDB".into()
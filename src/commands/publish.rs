@@ -3,27 +3,27 @@ use std::{collections::HashMap, io::Write, net::TcpStream, slice::Iter, sync::{A
 use crate::{commands::RedisCommand, resp::{create_array_resp, create_bulk_string_resp, create_int_resp}, resp::types::RespType};
 
 pub struct PublishCommand {
-    channel: String,
-    message: String,
-    channel_to_subscribers: Arc<Mutex<HashMap<String, Vec<String>>>>,
+    channel: Vec<u8>,
+    message: Vec<u8>,
+    channel_to_subscribers: Arc<Mutex<HashMap<Vec<u8>, Vec<String>>>>,
     client_to_stream: Arc<Mutex<HashMap<String, TcpStream>>>,
 }
 
 impl PublishCommand {
-    pub fn new(channel: String, message: String, channel_to_subscribers: Arc<Mutex<HashMap<String, Vec<String>>>>, client_to_stream: Arc<Mutex<HashMap<String, TcpStream>>>) -> Self {
+    pub fn new(channel: Vec<u8>, message: Vec<u8>, channel_to_subscribers: Arc<Mutex<HashMap<Vec<u8>, Vec<String>>>>, client_to_stream: Arc<Mutex<HashMap<String, TcpStream>>>) -> Self {
         PublishCommand { channel, message, channel_to_subscribers, client_to_stream }
     }
 }
 
 impl RedisCommand for PublishCommand {
-    fn execute(&self, _: &mut Iter<'_, RespType>) -> Vec<String> {
+    fn execute(&self, _: &mut Iter<'_, RespType>) -> Vec<Vec<u8>> {
         let channel_to_subscribers_gaurd = self.channel_to_subscribers.lock().unwrap();
-        match channel_to_subscribers_gaurd.get(self.channel.as_str()) {
+        match channel_to_subscribers_gaurd.get(&self.channel) {
             Some(subs) => {
                 for sub in subs.iter() {
                     match self.client_to_stream.lock().unwrap().get(sub) {
                         Some(mut stream) => {
-                            stream.write_all(create_array_resp(vec![create_bulk_string_resp("message".into()), create_bulk_string_resp(self.channel.clone().into()), create_bulk_string_resp(self.message.clone().into())]).as_bytes()).unwrap();
+                            stream.write_all(create_array_resp(vec![create_bulk_string_resp("message"), create_bulk_string_resp(&self.channel), create_bulk_string_resp(&self.message)]).as_slice()).unwrap();
                         },
                         _ => {
                             println!("SUB {} NOT FOUND", sub);
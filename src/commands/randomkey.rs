@@ -0,0 +1,26 @@
+use std::{slice::Iter, sync::{Arc, Mutex}};
+
+use rand::{rngs::SmallRng, seq::IteratorRandom, SeedableRng};
+
+use crate::{commands::RedisCommand, redis::{client::CacheVal, keyspace::Keyspace}, resp::{create_bulk_string_resp, create_null_bulk_string_resp, types::RespType}};
+
+pub struct RandomkeyCommand {
+    cache: Arc<Mutex<Keyspace>>
+}
+
+impl RandomkeyCommand {
+    pub fn new(cache: Arc<Mutex<Keyspace>>) -> Self {
+        RandomkeyCommand { cache }
+    }
+}
+
+impl RedisCommand for RandomkeyCommand {
+    fn execute(&self, _: &mut Iter<'_, RespType>) -> Vec<Vec<u8>> {
+        let cache_guard = self.cache.lock().unwrap();
+        let mut rng = SmallRng::from_entropy();
+        match cache_guard.iter().choose(&mut rng) {
+            Some((key, _)) => vec![create_bulk_string_resp(key)],
+            None => vec![create_null_bulk_string_resp()]
+        }
+    }
+}
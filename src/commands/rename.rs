@@ -0,0 +1,44 @@
+use std::{slice::Iter, sync::{Arc, Mutex}};
+
+use crate::{commands::RedisCommand, redis::{client::CacheVal, keyspace::{remove_key, store_key, Keyspace}}, resp::{create_basic_err_resp, create_int_resp, create_simple_string_resp, types::RespType}};
+
+/// Serves RENAME and RENAMENX. The value moves as is, keeping its type and expiry.
+pub struct RenameCommand {
+    key: Vec<u8>,
+    new_key: Vec<u8>,
+    nx: bool,
+    cache: Arc<Mutex<Keyspace>>
+}
+
+impl RenameCommand {
+    pub fn new(key: Vec<u8>, new_key: Vec<u8>, nx: bool, cache: Arc<Mutex<Keyspace>>) -> Self {
+        RenameCommand { key, new_key, nx, cache }
+    }
+
+    fn reply(&self, renamed: bool) -> Vec<Vec<u8>> {
+        if self.nx {
+            vec![create_int_resp(renamed as usize)]
+        } else {
+            vec![create_simple_string_resp("OK".to_string())]
+        }
+    }
+}
+
+impl RedisCommand for RenameCommand {
+    fn execute(&self, _: &mut Iter<'_, RespType>) -> Vec<Vec<u8>> {
+        let mut cache_guard = self.cache.lock().unwrap();
+        if !cache_guard.contains_key(&self.key) {
+            return vec![create_basic_err_resp("ERR no such key".to_string())];
+        }
+        if self.key == self.new_key {
+            return self.reply(false);
+        }
+        if self.nx && cache_guard.contains_key(&self.new_key) {
+            return self.reply(false);
+        }
+
+        let (val, expiry_time) = remove_key(&mut cache_guard, &self.key).expect("checked the key exists");
+        store_key(&mut cache_guard, self.new_key.clone(), val, expiry_time);
+        self.reply(true)
+    }
+}
@@ -11,21 +11,21 @@ impl ReplConfCommand {
 }
 
 impl RedisCommand for ReplConfCommand {
-    fn execute(&self, iter: &mut Iter<'_, RespType>) -> Vec<String> {
+    fn execute(&self, iter: &mut Iter<'_, RespType>) -> Vec<Vec<u8>> {
         let keyword = match iter.next().expect("Should have a keyword key") {
             RespType::String(keyword) => keyword,
             _ => panic!("REPLCONF command expects a keyword")
         };
-        if keyword.eq("listening-port") || keyword.eq("capa") {
+        if keyword.eq(b"listening-port") || keyword.eq(b"capa") {
             return vec![create_simple_string_resp("OK".to_string())]
         }
 
-        assert!(keyword.to_lowercase().eq("getack"));
+        assert!(keyword.eq_ignore_ascii_case(b"getack"));
         let star = match iter.next().expect("Should have * key") {
             RespType::String(star) => star,
             _ => panic!("REPLCONF command expects a *")
         };
-        assert!(star.to_lowercase().eq("*"));
+        assert!(star.eq_ignore_ascii_case(b"*"));
         return vec!["SEND_REPLCONF_ACK".into()];
     }
 }
\ No newline at end of file
@@ -1,14 +1,14 @@
-use std::{collections::HashMap, slice::Iter, sync::{Arc, Mutex}};
+use std::{collections::VecDeque, slice::Iter, sync::{Arc, Mutex}};
 
-use crate::{commands::RedisCommand, redis::{client::{CacheVal, ListCacheVal}}, resp::{create_int_resp, types::RespType}};
+use crate::{commands::RedisCommand, redis::{client::{CacheVal, ListCacheVal}, keyspace::Keyspace}, resp::{create_int_resp, create_wrong_type_err_resp, types::RespType}};
 
 pub struct RpushCommand {
-    list_key: String,
-    cache: Arc<Mutex<HashMap<String, CacheVal>>>
+    list_key: Vec<u8>,
+    cache: Arc<Mutex<Keyspace>>
 }
 
 impl RpushCommand {
-    pub fn new(list_key: String, cache: Arc<Mutex<HashMap<String, CacheVal>>>) -> Self {
+    pub fn new(list_key: Vec<u8>, cache: Arc<Mutex<Keyspace>>) -> Self {
         RpushCommand {
             list_key: list_key,
             cache: cache,
@@ -17,26 +17,26 @@ impl RpushCommand {
 }
 
 impl RedisCommand for RpushCommand {
-    fn execute(&self, iter: &mut Iter<'_, RespType>) -> Vec<String> {
+    fn execute(&self, iter: &mut Iter<'_, RespType>) -> Vec<Vec<u8>> {
         let mut cache_gaurd = self.cache.lock().unwrap();
         return match cache_gaurd.get_mut(&self.list_key) {
             Some(CacheVal::List(list_cache_val)) => {
                 while let Some(RespType::String(val)) = iter.next() {
-                    list_cache_val.list.push(val.into());
+                    list_cache_val.list.push_back(val.clone());
                 }
                 vec![create_int_resp(list_cache_val.list.len())]
             },
             None => {
-                let mut list = vec![];
+                let mut list = VecDeque::new();
                 while let Some(RespType::String(val)) = iter.next() {
-                    list.push(val.into());
+                    list.push_back(val.clone());
                 }
 
                 let len = list.len();
-                cache_gaurd.insert(self.list_key.clone(), CacheVal::List(ListCacheVal { list: list, block_queue: vec![] }));
+                cache_gaurd.insert(self.list_key.clone(), CacheVal::List(ListCacheVal { list }));
                 vec![create_int_resp(len)]
             },
-            _ => vec![create_int_resp(0)]
+            _ => vec![create_wrong_type_err_resp()]
         }
     }
 }
\ No newline at end of file
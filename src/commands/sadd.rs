@@ -0,0 +1,35 @@
+use std::{collections::HashSet, slice::Iter, sync::{Arc, Mutex}};
+
+use crate::{commands::RedisCommand, redis::{client::{CacheVal, SetCacheVal}, keyspace::Keyspace}, resp::{create_int_resp, create_wrong_args_err_resp, create_wrong_type_err_resp, types::RespType}};
+
+pub struct SaddCommand {
+    key: Vec<u8>,
+    cache: Arc<Mutex<Keyspace>>
+}
+
+impl SaddCommand {
+    pub fn new(key: Vec<u8>, cache: Arc<Mutex<Keyspace>>) -> Self {
+        SaddCommand { key, cache }
+    }
+}
+
+impl RedisCommand for SaddCommand {
+    fn execute(&self, iter: &mut Iter<'_, RespType>) -> Vec<Vec<u8>> {
+        let mut members = vec![];
+        while let Some(RespType::String(member)) = iter.next() {
+            members.push(member.clone());
+        }
+        if members.is_empty() {
+            return vec![create_wrong_args_err_resp("sadd")];
+        }
+
+        let mut cache_guard = self.cache.lock().unwrap();
+        let set_cache_val = match cache_guard.get_or_insert_with(self.key.clone(), || CacheVal::Set(SetCacheVal { set: HashSet::new() })) {
+            CacheVal::Set(set_cache_val) => set_cache_val,
+            _ => return vec![create_wrong_type_err_resp()]
+        };
+
+        let added = members.into_iter().filter(|member| set_cache_val.set.insert(member.clone())).count();
+        vec![create_int_resp(added)]
+    }
+}
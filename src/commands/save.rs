@@ -0,0 +1,35 @@
+use std::{fs, slice::Iter, thread};
+
+use crate::{commands::RedisCommand, rdb::rdb::Rdb, redis::keyspace::Databases, resp::{create_basic_err_resp, create_simple_string_resp, types::RespType}};
+
+/// Serves SAVE and BGSAVE, writing every db to the configured RDB file. BGSAVE snapshots and
+/// writes on a background thread instead of while the request waits.
+pub struct SaveCommand {
+    databases: Databases,
+    path: String,
+    background: bool
+}
+
+impl SaveCommand {
+    pub fn new(databases: Databases, path: String, background: bool) -> Self {
+        SaveCommand { databases, path, background }
+    }
+}
+
+impl RedisCommand for SaveCommand {
+    fn execute(&self, _: &mut Iter<'_, RespType>) -> Vec<Vec<u8>> {
+        if self.background {
+            let (databases, path) = (self.databases.clone(), self.path.clone());
+            thread::spawn(move || {
+                if let Err(err) = fs::write(&path, Rdb::dump(&databases)) {
+                    println!("Background saving to {} failed: {}", path, err);
+                }
+            });
+            return vec![create_simple_string_resp("Background saving started".to_string())];
+        }
+        match fs::write(&self.path, Rdb::dump(&self.databases)) {
+            Ok(()) => vec![create_simple_string_resp("OK".to_string())],
+            Err(err) => vec![create_basic_err_resp(format!("ERR saving to {} failed: {}", self.path, err))]
+        }
+    }
+}
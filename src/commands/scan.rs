@@ -0,0 +1,69 @@
+use std::{slice::Iter, sync::{Arc, Mutex}};
+
+use crate::{commands::{format_float, RedisCommand}, redis::{client::CacheVal, keyspace::Keyspace, scan::{scan_elements, ScanArgs}}, resp::{create_array_resp, create_bulk_string_resp, create_wrong_type_err_resp, types::RespType}};
+
+/// What gets scanned: the keyspace for SCAN or the collection at a key for HSCAN, SSCAN and ZSCAN.
+pub enum ScanTarget {
+    Keys,
+    Hash(Vec<u8>),
+    Set(Vec<u8>),
+    SortedSet(Vec<u8>)
+}
+
+/// Each call returns the next cursor and a batch of keys, fields and values, members or members
+/// and scores. MATCH filters a batch after it is taken, so it may come back empty before the end.
+pub struct ScanCommand {
+    target: ScanTarget,
+    args: ScanArgs,
+    cache: Arc<Mutex<Keyspace>>
+}
+
+impl ScanCommand {
+    pub fn new(target: ScanTarget, args: ScanArgs, cache: Arc<Mutex<Keyspace>>) -> Self {
+        ScanCommand { target, args, cache }
+    }
+
+    fn scan_keys(&self, cache: &Keyspace) -> (u64, Vec<Vec<u8>>) {
+        let (cursor, keys) = cache.scan(self.args.cursor, self.args.count);
+        let keys = keys.into_iter()
+            .filter(|(key, _)| self.args.matches(key))
+            .filter(|(_, val)| self.args.type_name.as_ref().is_none_or(|type_name| type_name.eq(val.type_name().as_bytes())))
+            .map(|(key, _)| create_bulk_string_resp(key))
+            .collect();
+        (cursor, keys)
+    }
+
+    fn scan_collection(&self, val: &CacheVal) -> Result<(u64, Vec<Vec<u8>>), Vec<u8>> {
+        let (cursor, count) = (self.args.cursor, self.args.count);
+        let (cursor, pairs) = match (&self.target, val) {
+            (ScanTarget::Hash(_), CacheVal::Hash(hash_cache_val)) => scan_elements(hash_cache_val.hash.iter().map(|(field, value)| (field.as_slice(), (field, Some(value.clone())))), cursor, count),
+            (ScanTarget::Set(_), CacheVal::Set(set_cache_val)) => scan_elements(set_cache_val.set.iter().map(|member| (member.as_slice(), (member, None))), cursor, count),
+            (ScanTarget::SortedSet(_), CacheVal::SortedSet(zset)) => scan_elements(zset.dict.iter().map(|(member, score)| (member.as_slice(), (member, Some(format_float(*score).into_bytes())))), cursor, count),
+            _ => return Err(create_wrong_type_err_resp())
+        };
+
+        let mut items = vec![];
+        for (element, value) in pairs.into_iter().filter(|(element, _)| self.args.matches(element)) {
+            items.push(create_bulk_string_resp(element));
+            items.extend(value.map(create_bulk_string_resp));
+        }
+        Ok((cursor, items))
+    }
+}
+
+impl RedisCommand for ScanCommand {
+    fn execute(&self, _: &mut Iter<'_, RespType>) -> Vec<Vec<u8>> {
+        let cache_guard = self.cache.lock().unwrap();
+        let (cursor, items) = match &self.target {
+            ScanTarget::Keys => self.scan_keys(&cache_guard),
+            ScanTarget::Hash(key) | ScanTarget::Set(key) | ScanTarget::SortedSet(key) => match cache_guard.get(key) {
+                Some(val) => match self.scan_collection(val) {
+                    Ok(scanned) => scanned,
+                    Err(err) => return vec![err]
+                },
+                None => (0, vec![])
+            }
+        };
+        vec![create_array_resp(vec![create_bulk_string_resp(cursor.to_string()), create_array_resp(items)])]
+    }
+}
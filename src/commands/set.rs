@@ -1,35 +1,140 @@
-use std::{collections::HashMap, slice::Iter, sync::{Arc, Mutex}};
+use std::{slice::Iter, sync::{Arc, Mutex}};
 
-use crate::{commands::RedisCommand, redis::{client::{CacheVal, StringCacheVal}}, resp::{create_simple_string_resp, types::RespType}};
+use crate::{commands::{parse_arg, RedisCommand}, redis::{client::{CacheVal, StringCacheVal}, keyspace::Keyspace, replication::ReplicationLog, stream::now_ms}, resp::{create_array_resp, create_basic_err_resp, create_bulk_string_resp, create_null_bulk_string_resp, create_simple_string_resp, create_wrong_type_err_resp, types::RespType}};
+
+/// NX and XX, what the key has to look like for SET to write it.
+#[derive(Clone, Copy, PartialEq)]
+pub enum SetCondition {
+    Always,
+    NotExists,
+    Exists
+}
+
+/// What SET does with the time to live of the key.
+#[derive(Clone, Copy, PartialEq)]
+pub enum SetExpiry {
+    Clear,
+    Keep,
+    /// Unix time in milliseconds.
+    At(u128)
+}
+
+pub struct SetOptions {
+    pub condition: SetCondition,
+    pub expiry: SetExpiry,
+    pub get: bool
+}
+
+impl SetOptions {
+    /// Parses `[NX|XX] [GET] [EX seconds|PX ms|EXAT unix-seconds|PXAT unix-ms|KEEPTTL]` in any
+    /// order, relative expirations being resolved against `now`.
+    pub fn parse(args: &[Vec<u8>], now: u128) -> Result<Self, Vec<u8>> {
+        let mut options = SetOptions { condition: SetCondition::Always, expiry: SetExpiry::Clear, get: false };
+        let mut args = args.iter();
+        while let Some(arg) = args.next() {
+            let option = arg.to_ascii_lowercase();
+            match option.as_slice() {
+                b"nx" | b"xx" if options.condition != SetCondition::Always => return Err(create_basic_err_resp("ERR syntax error".to_string())),
+                b"nx" => options.condition = SetCondition::NotExists,
+                b"xx" => options.condition = SetCondition::Exists,
+                b"get" => options.get = true,
+                b"keepttl" | b"ex" | b"px" | b"exat" | b"pxat" if options.expiry != SetExpiry::Clear => return Err(create_basic_err_resp("ERR syntax error".to_string())),
+                b"keepttl" => options.expiry = SetExpiry::Keep,
+                b"ex" | b"px" | b"exat" | b"pxat" => options.expiry = SetExpiry::At(parse_expiry(&option, args.next(), now, "set")?),
+                _ => return Err(create_basic_err_resp("ERR syntax error".to_string()))
+            }
+        }
+        Ok(options)
+    }
+}
+
+/// Resolves the time given to an EX, PX, EXAT or PXAT option of `command` to unix milliseconds.
+pub fn parse_expiry(option: &[u8], time: Option<&Vec<u8>>, now: u128, command: &str) -> Result<u128, Vec<u8>> {
+    let time = match time.map(|time| parse_arg::<i64>(time)) {
+        Some(Some(time)) => time,
+        Some(None) => return Err(create_basic_err_resp("ERR value is not an integer or out of range".to_string())),
+        None => return Err(create_basic_err_resp("ERR syntax error".to_string()))
+    };
+    let invalid_expire_err = create_basic_err_resp(format!("ERR invalid expire time in '{}' command", command));
+    if time <= 0 {
+        return Err(invalid_expire_err);
+    }
+    let time = time as u128;
+    let at = match option.to_ascii_lowercase().as_slice() {
+        b"ex" => now + time * 1000,
+        b"px" => now + time,
+        b"exat" => time * 1000,
+        _ => time
+    };
+    if at > i64::MAX as u128 {
+        return Err(invalid_expire_err);
+    }
+    Ok(at)
+}
 
 pub struct SetCommand {
-    key: String,
-    value: String,
-    expiration: Option<u128>,
-    cache: Arc<Mutex<HashMap<String, CacheVal>>>
+    key: Vec<u8>,
+    value: Vec<u8>,
+    options: SetOptions,
+    cache: Arc<Mutex<Keyspace>>,
+    write_commands: Option<ReplicationLog>
 }
 
 impl SetCommand {
-    pub fn new(key: String, value: String, expiration: Option<u128>, cache: Arc<Mutex<HashMap<String, CacheVal>>>) -> Self {
+    pub fn new(key: Vec<u8>, value: Vec<u8>, options: SetOptions, cache: Arc<Mutex<Keyspace>>, write_commands: Option<ReplicationLog>) -> Self {
         SetCommand {
             key: key,
             value: value,
-            expiration: expiration,
+            options,
             cache: cache,
+            write_commands
         }
     }
 }
 
 impl RedisCommand for SetCommand {
-    fn execute(&self, _: &mut Iter<'_, RespType>) -> Vec<String> {
+    fn execute(&self, _: &mut Iter<'_, RespType>) -> Vec<Vec<u8>> {
         let mut cache_guard = self.cache.lock().unwrap();
-        if let Some(exp_milis) = self.expiration {
-            let expiry = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).unwrap().as_millis() + exp_milis;
-            cache_guard.insert(self.key.clone(), CacheVal::String(StringCacheVal { val: self.value.clone(), expiry_time: Some(expiry) }));
-            return vec![create_simple_string_resp("OK".to_string())]
+        let (exists, old_val) = match cache_guard.get(&self.key) {
+            Some(CacheVal::String(val)) => (true, Some(val.val.clone())),
+            Some(_) if self.options.get => return vec![create_wrong_type_err_resp()],
+            Some(_) => (true, None),
+            None => (false, None)
+        };
+        let allowed = match self.options.condition {
+            SetCondition::Always => true,
+            SetCondition::NotExists => !exists,
+            SetCondition::Exists => exists
+        };
+        let reply = if self.options.get {
+            old_val.map_or_else(create_null_bulk_string_resp, create_bulk_string_resp)
+        } else if allowed {
+            create_simple_string_resp("OK".to_string())
         } else {
-            cache_guard.insert(self.key.clone(), CacheVal::String(StringCacheVal { val: self.value.clone(), expiry_time: None }));
-            return vec![create_simple_string_resp("OK".to_string())];
+            create_null_bulk_string_resp()
+        };
+        if !allowed {
+            return vec![reply];
         }
+
+        let expiry_time = match self.options.expiry {
+            SetExpiry::Clear => None,
+            SetExpiry::Keep => cache_guard.expiry(&self.key),
+            SetExpiry::At(at) => Some(at)
+        };
+        cache_guard.insert(self.key.clone(), CacheVal::String(StringCacheVal::new(self.value.clone())));
+        cache_guard.set_expiry(&self.key, expiry_time);
+
+        // replicas get the absolute expiry, relative ones would be counted from when they receive it
+        if let Some(write_commands) = &self.write_commands {
+            let mut command = vec![b"SET".to_vec(), self.key.clone(), self.value.clone()];
+            match self.options.expiry {
+                SetExpiry::Clear => {},
+                SetExpiry::Keep => command.push(b"KEEPTTL".to_vec()),
+                SetExpiry::At(at) => command.extend([b"PXAT".to_vec(), at.to_string().into_bytes()])
+            }
+            write_commands.push(create_array_resp(command.into_iter().map(create_bulk_string_resp).collect()));
+        }
+        vec![reply]
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,61 @@
+use std::collections::HashSet;
+
+use crate::{redis::{client::{CacheVal, SetCacheVal}, keyspace::Keyspace}, resp::{create_array_resp, create_bulk_string_resp, create_int_resp}};
+
+#[derive(Clone, Copy)]
+pub enum SetOp {
+    Inter,
+    Union,
+    Diff
+}
+
+/// Looks up every key as a set, a missing key counts as an empty set. Fails if any key holds another type.
+pub fn lookup_sets<'a>(cache: &'a Keyspace, keys: &[Vec<u8>]) -> Option<Vec<Option<&'a HashSet<Vec<u8>>>>> {
+    let mut sets = vec![];
+    for key in keys {
+        match cache.get(key) {
+            Some(CacheVal::Set(set_cache_val)) => sets.push(Some(&set_cache_val.set)),
+            Some(_) => return None,
+            None => sets.push(None)
+        }
+    }
+    Some(sets)
+}
+
+pub fn apply(op: SetOp, sets: &[Option<&HashSet<Vec<u8>>>]) -> HashSet<Vec<u8>> {
+    match op {
+        SetOp::Inter => {
+            if sets.iter().any(|set| set.is_none()) {
+                return HashSet::new();
+            }
+            // walk the smallest set and probe the others
+            let mut sets: Vec<&HashSet<Vec<u8>>> = sets.iter().map(|set| set.unwrap()).collect();
+            sets.sort_by_key(|set| set.len());
+            sets[0].iter().filter(|member| sets[1..].iter().all(|set| set.contains(*member))).cloned().collect()
+        },
+        SetOp::Union => sets.iter().flatten().flat_map(|set| set.iter().cloned()).collect(),
+        SetOp::Diff => {
+            let first = match sets.first() {
+                Some(Some(first)) => first,
+                _ => return HashSet::new()
+            };
+            first.iter().filter(|member| !sets[1..].iter().flatten().any(|set| set.contains(*member))).cloned().collect()
+        }
+    }
+}
+
+/// Replies with the members of the result, or stores them at the destination and replies with the cardinality.
+pub fn reply_or_store(cache: &mut Keyspace, destination: &Option<Vec<u8>>, result: HashSet<Vec<u8>>) -> Vec<Vec<u8>> {
+    match destination {
+        Some(destination) => {
+            let len = result.len();
+            if result.is_empty() {
+                cache.remove(destination);
+            } else {
+                cache.insert(destination.clone(), CacheVal::Set(SetCacheVal { set: result }));
+            }
+            vec![create_int_resp(len)]
+        },
+        None => vec![create_array_resp(result.into_iter().map(create_bulk_string_resp).collect())]
+    }
+}
@@ -0,0 +1,37 @@
+use std::{slice::Iter, sync::{Arc, Mutex}};
+
+use crate::{commands::RedisCommand, redis::{bitmap::{get_bit, set_bit}, client::{CacheVal, StringCacheVal}, keyspace::Keyspace, string::live_string}, resp::{create_int_resp, types::RespType}};
+
+/// SETBIT key offset value, replying the bit that was there before.
+pub struct SetbitCommand {
+    key: Vec<u8>,
+    offset: usize,
+    bit: u8,
+    cache: Arc<Mutex<Keyspace>>
+}
+
+impl SetbitCommand {
+    pub fn new(key: Vec<u8>, offset: usize, bit: u8, cache: Arc<Mutex<Keyspace>>) -> Self {
+        SetbitCommand { key, offset, bit, cache }
+    }
+}
+
+impl RedisCommand for SetbitCommand {
+    fn execute(&self, _: &mut Iter<'_, RespType>) -> Vec<Vec<u8>> {
+        let mut cache_guard = self.cache.lock().unwrap();
+        match live_string(&mut cache_guard, &self.key) {
+            Ok(Some(string_cache_val)) => {
+                let old_bit = get_bit(&string_cache_val.val, self.offset);
+                set_bit(&mut string_cache_val.val, self.offset, self.bit);
+                vec![create_int_resp(old_bit)]
+            },
+            Ok(None) => {
+                let mut bytes = vec![];
+                set_bit(&mut bytes, self.offset, self.bit);
+                cache_guard.insert(self.key.clone(), CacheVal::String(StringCacheVal::new(bytes)));
+                vec![create_int_resp(0)]
+            },
+            Err(err) => vec![err]
+        }
+    }
+}
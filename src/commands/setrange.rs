@@ -0,0 +1,45 @@
+use std::{slice::Iter, sync::{Arc, Mutex}};
+
+use crate::{commands::RedisCommand, redis::{client::{CacheVal, StringCacheVal}, keyspace::Keyspace, string::{live_string, update_string}}, resp::{create_basic_err_resp, create_int_resp, types::RespType}};
+
+/// Strings are capped at 512MB like the default proto-max-bulk-len.
+const MAX_STRING_LEN: usize = 512 * 1024 * 1024;
+
+/// SETRANGE key offset value, overwriting from `offset` and padding with zero bytes when the
+/// string is shorter than that.
+pub struct SetrangeCommand {
+    key: Vec<u8>,
+    offset: usize,
+    value: Vec<u8>,
+    cache: Arc<Mutex<Keyspace>>
+}
+
+impl SetrangeCommand {
+    pub fn new(key: Vec<u8>, offset: usize, value: Vec<u8>, cache: Arc<Mutex<Keyspace>>) -> Self {
+        SetrangeCommand { key, offset, value, cache }
+    }
+}
+
+impl RedisCommand for SetrangeCommand {
+    fn execute(&self, _: &mut Iter<'_, RespType>) -> Vec<Vec<u8>> {
+        if self.offset + self.value.len() > MAX_STRING_LEN {
+            return vec![create_basic_err_resp("ERR string exceeds maximum allowed size (proto-max-bulk-len)".to_string())];
+        }
+        let mut cache_guard = self.cache.lock().unwrap();
+        let mut bytes = match live_string(&mut cache_guard, &self.key) {
+            Ok(Some(string_cache_val)) if self.value.is_empty() => return vec![create_int_resp(string_cache_val.val.len())],
+            Ok(Some(string_cache_val)) => std::mem::take(&mut string_cache_val.val),
+            Ok(None) if self.value.is_empty() => return vec![create_int_resp(0)],
+            Ok(None) => vec![],
+            Err(err) => return vec![err]
+        };
+
+        if bytes.len() < self.offset + self.value.len() {
+            bytes.resize(self.offset + self.value.len(), 0);
+        }
+        bytes[self.offset..self.offset + self.value.len()].copy_from_slice(&self.value);
+        let len = bytes.len();
+        update_string(&mut cache_guard, &self.key, bytes);
+        vec![create_int_resp(len)]
+    }
+}
@@ -0,0 +1,34 @@
+use std::{slice::Iter, sync::{Arc, Mutex}};
+
+use crate::{commands::{set_ops::{self, SetOp}, RedisCommand}, redis::{client::CacheVal, keyspace::Keyspace}, resp::{create_wrong_args_err_resp, create_wrong_type_err_resp, types::RespType}};
+
+/// Serves both SINTER and SINTERSTORE, the latter when a destination is given.
+pub struct SinterCommand {
+    destination: Option<Vec<u8>>,
+    cache: Arc<Mutex<Keyspace>>
+}
+
+impl SinterCommand {
+    pub fn new(destination: Option<Vec<u8>>, cache: Arc<Mutex<Keyspace>>) -> Self {
+        SinterCommand { destination, cache }
+    }
+}
+
+impl RedisCommand for SinterCommand {
+    fn execute(&self, iter: &mut Iter<'_, RespType>) -> Vec<Vec<u8>> {
+        let mut keys = vec![];
+        while let Some(RespType::String(key)) = iter.next() {
+            keys.push(key.clone());
+        }
+        if keys.is_empty() {
+            return vec![create_wrong_args_err_resp(if self.destination.is_some() { "sinterstore" } else { "sinter" })];
+        }
+
+        let mut cache_guard = self.cache.lock().unwrap();
+        let result = match set_ops::lookup_sets(&cache_guard, &keys) {
+            Some(sets) => set_ops::apply(SetOp::Inter, &sets),
+            None => return vec![create_wrong_type_err_resp()]
+        };
+        set_ops::reply_or_store(&mut cache_guard, &self.destination, result)
+    }
+}
@@ -0,0 +1,30 @@
+use std::{slice::Iter, sync::{Arc, Mutex}};
+
+use crate::{commands::{set_ops::{self, SetOp}, RedisCommand}, redis::{client::CacheVal, keyspace::Keyspace}, resp::{create_int_resp, create_wrong_type_err_resp, types::RespType}};
+
+pub struct SintercardCommand {
+    keys: Vec<Vec<u8>>,
+    limit: usize,
+    cache: Arc<Mutex<Keyspace>>
+}
+
+impl SintercardCommand {
+    pub fn new(keys: Vec<Vec<u8>>, limit: usize, cache: Arc<Mutex<Keyspace>>) -> Self {
+        SintercardCommand { keys, limit, cache }
+    }
+}
+
+impl RedisCommand for SintercardCommand {
+    fn execute(&self, _: &mut Iter<'_, RespType>) -> Vec<Vec<u8>> {
+        let cache_guard = self.cache.lock().unwrap();
+        let cardinality = match set_ops::lookup_sets(&cache_guard, &self.keys) {
+            Some(sets) => set_ops::apply(SetOp::Inter, &sets).len(),
+            None => return vec![create_wrong_type_err_resp()]
+        };
+        // a limit of 0 means unlimited
+        if self.limit > 0 {
+            return vec![create_int_resp(cardinality.min(self.limit))];
+        }
+        vec![create_int_resp(cardinality)]
+    }
+}
@@ -0,0 +1,26 @@
+use std::{slice::Iter, sync::{Arc, Mutex}};
+
+use crate::{commands::RedisCommand, redis::{client::CacheVal, keyspace::Keyspace}, resp::{create_int_resp, create_wrong_type_err_resp, types::RespType}};
+
+pub struct SismemberCommand {
+    key: Vec<u8>,
+    member: Vec<u8>,
+    cache: Arc<Mutex<Keyspace>>
+}
+
+impl SismemberCommand {
+    pub fn new(key: Vec<u8>, member: Vec<u8>, cache: Arc<Mutex<Keyspace>>) -> Self {
+        SismemberCommand { key, member, cache }
+    }
+}
+
+impl RedisCommand for SismemberCommand {
+    fn execute(&self, _: &mut Iter<'_, RespType>) -> Vec<Vec<u8>> {
+        let cache_guard = self.cache.lock().unwrap();
+        match cache_guard.get(&self.key) {
+            Some(CacheVal::Set(set_cache_val)) => vec![create_int_resp(set_cache_val.set.contains(&self.member) as i64)],
+            Some(_) => vec![create_wrong_type_err_resp()],
+            None => vec![create_int_resp(0)]
+        }
+    }
+}
@@ -0,0 +1,35 @@
+use std::{slice::Iter, sync::{Arc, Mutex}};
+
+use crate::{commands::RedisCommand, redis::{client::CacheVal, keyspace::Keyspace}, resp::{create_array_resp, create_int_resp, create_wrong_args_err_resp, create_wrong_type_err_resp, types::RespType}};
+
+pub struct SmismemberCommand {
+    key: Vec<u8>,
+    cache: Arc<Mutex<Keyspace>>
+}
+
+impl SmismemberCommand {
+    pub fn new(key: Vec<u8>, cache: Arc<Mutex<Keyspace>>) -> Self {
+        SmismemberCommand { key, cache }
+    }
+}
+
+impl RedisCommand for SmismemberCommand {
+    fn execute(&self, iter: &mut Iter<'_, RespType>) -> Vec<Vec<u8>> {
+        let mut members = vec![];
+        while let Some(RespType::String(member)) = iter.next() {
+            members.push(member.clone());
+        }
+        if members.is_empty() {
+            return vec![create_wrong_args_err_resp("smismember")];
+        }
+
+        let cache_guard = self.cache.lock().unwrap();
+        let set = match cache_guard.get(&self.key) {
+            Some(CacheVal::Set(set_cache_val)) => Some(&set_cache_val.set),
+            Some(_) => return vec![create_wrong_type_err_resp()],
+            None => None
+        };
+        let flags = members.iter().map(|member| create_int_resp(set.is_some_and(|set| set.contains(member)) as i64)).collect();
+        vec![create_array_resp(flags)]
+    }
+}
@@ -0,0 +1,43 @@
+use std::{collections::HashSet, slice::Iter, sync::{Arc, Mutex}};
+
+use crate::{commands::RedisCommand, redis::{client::{CacheVal, SetCacheVal}, keyspace::Keyspace}, resp::{create_int_resp, create_wrong_type_err_resp, types::RespType}};
+
+pub struct SmoveCommand {
+    source: Vec<u8>,
+    destination: Vec<u8>,
+    member: Vec<u8>,
+    cache: Arc<Mutex<Keyspace>>
+}
+
+impl SmoveCommand {
+    pub fn new(source: Vec<u8>, destination: Vec<u8>, member: Vec<u8>, cache: Arc<Mutex<Keyspace>>) -> Self {
+        SmoveCommand { source, destination, member, cache }
+    }
+}
+
+impl RedisCommand for SmoveCommand {
+    fn execute(&self, _: &mut Iter<'_, RespType>) -> Vec<Vec<u8>> {
+        let mut cache_guard = self.cache.lock().unwrap();
+        match cache_guard.get(&self.destination) {
+            Some(CacheVal::Set(_)) | None => {},
+            Some(_) => return vec![create_wrong_type_err_resp()]
+        }
+        let source_cache_val = match cache_guard.get_mut(&self.source) {
+            Some(CacheVal::Set(set_cache_val)) => set_cache_val,
+            Some(_) => return vec![create_wrong_type_err_resp()],
+            None => return vec![create_int_resp(0)]
+        };
+
+        if !source_cache_val.set.remove(&self.member) {
+            return vec![create_int_resp(0)];
+        }
+        if source_cache_val.set.is_empty() {
+            cache_guard.remove(&self.source);
+        }
+
+        if let CacheVal::Set(destination_cache_val) = cache_guard.get_or_insert_with(self.destination.clone(), || CacheVal::Set(SetCacheVal { set: HashSet::new() })) {
+            destination_cache_val.set.insert(self.member.clone());
+        }
+        vec![create_int_resp(1)]
+    }
+}
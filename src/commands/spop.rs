@@ -0,0 +1,56 @@
+use std::{slice::Iter, sync::{Arc, Mutex}};
+
+use rand::{rngs::SmallRng, seq::IteratorRandom, SeedableRng};
+
+use crate::{commands::RedisCommand, redis::{client::CacheVal, keyspace::Keyspace, replication::ReplicationLog}, resp::{create_array_resp, create_bulk_string_resp, create_null_bulk_string_resp, create_wrong_type_err_resp, types::RespType}};
+
+pub struct SpopCommand {
+    key: Vec<u8>,
+    count: Option<usize>,
+    cache: Arc<Mutex<Keyspace>>,
+    write_commands: Option<ReplicationLog>
+}
+
+impl SpopCommand {
+    pub fn new(key: Vec<u8>, count: Option<usize>, cache: Arc<Mutex<Keyspace>>, write_commands: Option<ReplicationLog>) -> Self {
+        SpopCommand { key, count, cache, write_commands }
+    }
+}
+
+impl RedisCommand for SpopCommand {
+    fn execute(&self, _: &mut Iter<'_, RespType>) -> Vec<Vec<u8>> {
+        let mut cache_guard = self.cache.lock().unwrap();
+        let set_cache_val = match cache_guard.get_mut(&self.key) {
+            Some(CacheVal::Set(set_cache_val)) => set_cache_val,
+            Some(_) => return vec![create_wrong_type_err_resp()],
+            None if self.count.is_some() => return vec![create_array_resp(vec![])],
+            None => return vec![create_null_bulk_string_resp()]
+        };
+
+        let mut rng = SmallRng::from_entropy();
+        // choose_multiple allocates room for as many as asked for, a count beyond the set pops all of it
+        let count = self.count.unwrap_or(1).min(set_cache_val.set.len());
+        // only the chosen members are cloned, not the whole set
+        let popped: Vec<Vec<u8>> = set_cache_val.set.iter().choose_multiple(&mut rng, count).into_iter().cloned().collect();
+        for member in popped.iter() {
+            set_cache_val.set.remove(member);
+        }
+        if set_cache_val.set.is_empty() {
+            cache_guard.remove(&self.key);
+        }
+
+        // the members are picked at random, so replicas are told exactly which ones went away
+        if let Some(write_commands) = &self.write_commands {
+            if !popped.is_empty() {
+                let mut srem = vec![create_bulk_string_resp("SREM"), create_bulk_string_resp(&self.key)];
+                srem.extend(popped.iter().map(create_bulk_string_resp));
+                write_commands.push(create_array_resp(srem));
+            }
+        }
+
+        match self.count {
+            Some(_) => vec![create_array_resp(popped.into_iter().map(create_bulk_string_resp).collect())],
+            None => vec![create_bulk_string_resp(popped[0].clone())]
+        }
+    }
+}
@@ -0,0 +1,46 @@
+use std::{slice::Iter, sync::{Arc, Mutex}};
+
+use rand::{rngs::SmallRng, seq::{IteratorRandom, SliceRandom}, SeedableRng};
+
+use crate::{commands::RedisCommand, redis::{client::CacheVal, keyspace::Keyspace}, resp::{create_array_resp, create_bulk_string_resp, create_null_bulk_string_resp, create_wrong_type_err_resp, types::RespType}};
+
+pub struct SrandmemberCommand {
+    key: Vec<u8>,
+    /// At least `-MAX_RANDOM_REPEATS`, as `parse_random_count` makes sure.
+    count: Option<i64>,
+    cache: Arc<Mutex<Keyspace>>
+}
+
+impl SrandmemberCommand {
+    pub fn new(key: Vec<u8>, count: Option<i64>, cache: Arc<Mutex<Keyspace>>) -> Self {
+        SrandmemberCommand { key, count, cache }
+    }
+}
+
+impl RedisCommand for SrandmemberCommand {
+    fn execute(&self, _: &mut Iter<'_, RespType>) -> Vec<Vec<u8>> {
+        let cache_guard = self.cache.lock().unwrap();
+        let set = match cache_guard.get(&self.key) {
+            Some(CacheVal::Set(set_cache_val)) => &set_cache_val.set,
+            Some(_) => return vec![create_wrong_type_err_resp()],
+            None if self.count.is_some() => return vec![create_array_resp(vec![])],
+            None => return vec![create_null_bulk_string_resp()]
+        };
+
+        let mut rng = SmallRng::from_entropy();
+        let count = match self.count {
+            Some(count) => count,
+            None => return vec![create_bulk_string_resp(set.iter().choose(&mut rng).expect("stored sets are never empty").clone())]
+        };
+
+        let members: Vec<&Vec<u8>> = set.iter().collect();
+        let picked: Vec<&Vec<u8>> = if count >= 0 {
+            // positive counts return distinct members
+            members.choose_multiple(&mut rng, count as usize).cloned().collect()
+        } else {
+            // negative counts may return the same member several times
+            (0..count.unsigned_abs()).map(|_| *members.choose(&mut rng).expect("stored sets are never empty")).collect()
+        };
+        vec![create_array_resp(picked.into_iter().map(|member| create_bulk_string_resp(member.clone())).collect())]
+    }
+}
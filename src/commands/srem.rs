@@ -0,0 +1,39 @@
+use std::{slice::Iter, sync::{Arc, Mutex}};
+
+use crate::{commands::RedisCommand, redis::{client::CacheVal, keyspace::Keyspace}, resp::{create_int_resp, create_wrong_args_err_resp, create_wrong_type_err_resp, types::RespType}};
+
+pub struct SremCommand {
+    key: Vec<u8>,
+    cache: Arc<Mutex<Keyspace>>
+}
+
+impl SremCommand {
+    pub fn new(key: Vec<u8>, cache: Arc<Mutex<Keyspace>>) -> Self {
+        SremCommand { key, cache }
+    }
+}
+
+impl RedisCommand for SremCommand {
+    fn execute(&self, iter: &mut Iter<'_, RespType>) -> Vec<Vec<u8>> {
+        let mut members = vec![];
+        while let Some(RespType::String(member)) = iter.next() {
+            members.push(member.clone());
+        }
+        if members.is_empty() {
+            return vec![create_wrong_args_err_resp("srem")];
+        }
+
+        let mut cache_guard = self.cache.lock().unwrap();
+        let set_cache_val = match cache_guard.get_mut(&self.key) {
+            Some(CacheVal::Set(set_cache_val)) => set_cache_val,
+            Some(_) => return vec![create_wrong_type_err_resp()],
+            None => return vec![create_int_resp(0)]
+        };
+
+        let removed = members.iter().filter(|member| set_cache_val.set.remove(*member)).count();
+        if set_cache_val.set.is_empty() {
+            cache_guard.remove(&self.key);
+        }
+        vec![create_int_resp(removed)]
+    }
+}
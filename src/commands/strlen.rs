@@ -0,0 +1,24 @@
+use std::{slice::Iter, sync::{Arc, Mutex}};
+
+use crate::{commands::RedisCommand, redis::{client::CacheVal, keyspace::Keyspace, string::live_string}, resp::{create_int_resp, types::RespType}};
+
+pub struct StrlenCommand {
+    key: Vec<u8>,
+    cache: Arc<Mutex<Keyspace>>
+}
+
+impl StrlenCommand {
+    pub fn new(key: Vec<u8>, cache: Arc<Mutex<Keyspace>>) -> Self {
+        StrlenCommand { key, cache }
+    }
+}
+
+impl RedisCommand for StrlenCommand {
+    fn execute(&self, _: &mut Iter<'_, RespType>) -> Vec<Vec<u8>> {
+        let mut cache_guard = self.cache.lock().unwrap();
+        match live_string(&mut cache_guard, &self.key) {
+            Ok(string_cache_val) => vec![create_int_resp(string_cache_val.map_or(0, |val| val.val.len()))],
+            Err(err) => vec![err]
+        }
+    }
+}
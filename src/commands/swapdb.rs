@@ -0,0 +1,32 @@
+use std::{slice::Iter, sync::Arc};
+
+use crate::{commands::RedisCommand, redis::{blocking::Notifier, keyspace::{lock_pair, Databases}}, resp::{create_simple_string_resp, types::RespType}};
+
+/// Exchanges the keys of two dbs, connections on either one seeing the other's keys right away.
+pub struct SwapdbCommand {
+    databases: Databases,
+    first: usize,
+    second: usize,
+    notifier: Arc<Notifier>
+}
+
+impl SwapdbCommand {
+    pub fn new(databases: Databases, first: usize, second: usize, notifier: Arc<Notifier>) -> Self {
+        SwapdbCommand { databases, first, second, notifier }
+    }
+}
+
+impl RedisCommand for SwapdbCommand {
+    fn execute(&self, _: &mut Iter<'_, RespType>) -> Vec<Vec<u8>> {
+        if self.first != self.second {
+            let (mut first, mut second) = lock_pair(&self.databases, self.first, self.second);
+            first.swap(&mut second);
+            drop((first, second));
+            // the keys blocked connections wait on may exist now
+            for key in self.notifier.blocked_keys(self.first).into_iter().chain(self.notifier.blocked_keys(self.second)) {
+                self.notifier.notify_key(&key);
+            }
+        }
+        vec![create_simple_string_resp("OK".to_string())]
+    }
+}
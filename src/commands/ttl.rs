@@ -0,0 +1,52 @@
+use std::{slice::Iter, sync::{Arc, Mutex}};
+
+use crate::{commands::RedisCommand, redis::{keyspace::Keyspace, stream::now_ms}, resp::{create_int_resp, types::RespType}};
+
+/// What TTL, PTTL, EXPIRETIME and PEXPIRETIME reply with.
+#[derive(Clone, Copy, PartialEq)]
+pub enum TtlReply {
+    /// Seconds left, TTL.
+    Remaining,
+    /// Milliseconds left, PTTL.
+    RemainingMs,
+    /// Unix time in seconds, EXPIRETIME.
+    At,
+    /// Unix time in milliseconds, PEXPIRETIME.
+    AtMs
+}
+
+/// Replies -2 when the key doesn't exist and -1 when it has no expiry.
+pub struct TtlCommand {
+    key: Vec<u8>,
+    reply: TtlReply,
+    cache: Arc<Mutex<Keyspace>>
+}
+
+impl TtlCommand {
+    pub fn new(key: Vec<u8>, reply: TtlReply, cache: Arc<Mutex<Keyspace>>) -> Self {
+        TtlCommand { key, reply, cache }
+    }
+}
+
+impl RedisCommand for TtlCommand {
+    fn execute(&self, _: &mut Iter<'_, RespType>) -> Vec<Vec<u8>> {
+        let cache_guard = self.cache.lock().unwrap();
+        if !cache_guard.contains_key(&self.key) {
+            return vec![create_int_resp(-2)];
+        }
+        let expiry_time = match cache_guard.expiry(&self.key) {
+            Some(expiry_time) => expiry_time,
+            None => return vec![create_int_resp(-1)]
+        };
+
+        let ms = match self.reply {
+            TtlReply::Remaining | TtlReply::RemainingMs => expiry_time.saturating_sub(now_ms()),
+            TtlReply::At | TtlReply::AtMs => expiry_time
+        };
+        match self.reply {
+            // seconds are rounded to the nearest one like redis does
+            TtlReply::Remaining | TtlReply::At => vec![create_int_resp((ms + 500) / 1000)],
+            TtlReply::RemainingMs | TtlReply::AtMs => vec![create_int_resp(ms)]
+        }
+    }
+}
@@ -1,14 +1,14 @@
-use std::{collections::HashMap, slice::Iter, sync::{Arc, Mutex}};
+use std::{slice::Iter, sync::{Arc, Mutex}};
 
-use crate::{commands::RedisCommand, redis::{client::CacheVal}, resp::{create_simple_string_resp, types::RespType}};
+use crate::{commands::RedisCommand, redis::keyspace::Keyspace, resp::{create_simple_string_resp, types::RespType}};
 
 pub struct TypeCommand {
-    key: String,
-    cache: Arc<Mutex<HashMap<String, CacheVal>>>
+    key: Vec<u8>,
+    cache: Arc<Mutex<Keyspace>>
 }
 
 impl TypeCommand {
-    pub fn new(key: String, cache: Arc<Mutex<HashMap<String, CacheVal>>>) -> Self {
+    pub fn new(key: Vec<u8>, cache: Arc<Mutex<Keyspace>>) -> Self {
         TypeCommand {
             key: key,
             cache: cache
@@ -17,12 +17,10 @@ impl TypeCommand {
 }
 
 impl RedisCommand for TypeCommand {
-    fn execute(&self, _: &mut Iter<'_, RespType>) -> Vec<String> {
+    fn execute(&self, _: &mut Iter<'_, RespType>) -> Vec<Vec<u8>> {
         let cache_guard = self.cache.lock().unwrap();
         match cache_guard.get(&self.key) {
-            Some(CacheVal::String(_)) => vec![create_simple_string_resp("string".to_string())],
-            Some(CacheVal::List(_)) => vec![create_simple_string_resp("list".to_string())],
-            Some(CacheVal::Stream(_)) => vec![create_simple_string_resp("stream".to_string())],
+            Some(val) => vec![create_simple_string_resp(val.type_name().to_string())],
             None => vec![create_simple_string_resp("none".to_string())]
         }
     }
@@ -4,26 +4,26 @@ use crate::{commands::RedisCommand, resp::{create_array_resp, create_bulk_string
 
 pub struct UnsubscribeCommand {
     id: String,
-    channel: String,
-    channel_to_subscribers: Arc<Mutex<HashMap<String, Vec<String>>>>,
+    channel: Vec<u8>,
+    channel_to_subscribers: Arc<Mutex<HashMap<Vec<u8>, Vec<String>>>>,
     num_subscribed_channels: i64
 }
 
 impl UnsubscribeCommand {
-    pub fn new(id: String, channel: String, channel_to_subscribers: Arc<Mutex<HashMap<String, Vec<String>>>>, num_subscribed_channels: i64) -> Self {
+    pub fn new(id: String, channel: Vec<u8>, channel_to_subscribers: Arc<Mutex<HashMap<Vec<u8>, Vec<String>>>>, num_subscribed_channels: i64) -> Self {
         UnsubscribeCommand { id, channel, channel_to_subscribers, num_subscribed_channels }
     }
 }
 
 impl RedisCommand for UnsubscribeCommand {
-    fn execute(&self, _: &mut Iter<'_, RespType>) -> Vec<String> {
+    fn execute(&self, _: &mut Iter<'_, RespType>) -> Vec<Vec<u8>> {
         let mut channel_to_subscribers_gaurd = self.channel_to_subscribers.lock().unwrap();
-        match channel_to_subscribers_gaurd.get_mut(self.channel.as_str()) {
+        match channel_to_subscribers_gaurd.get_mut(&self.channel) {
             Some(subs) => {
                 subs.retain(|x| x != &self.id);
             },
             _ => {}
         }
-        return vec![create_array_resp(vec![create_bulk_string_resp("unsubscribe".into()), create_bulk_string_resp(self.channel.to_string().into()), create_int_resp(self.num_subscribed_channels)])];
+        return vec![create_array_resp(vec![create_bulk_string_resp("unsubscribe"), create_bulk_string_resp(&self.channel), create_int_resp(self.num_subscribed_channels)])];
     }
 }
\ No newline at end of file
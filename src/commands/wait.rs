@@ -1,39 +1,44 @@
 use std::slice::Iter;
 use std::sync::{Arc, Mutex};
 
-use crate::{commands::RedisCommand, resp::{create_int_resp}, resp::types::RespType};
+use crate::{commands::RedisCommand, redis::blocking::Notifier, resp::{create_int_resp}, resp::types::RespType};
 
 pub struct WaitCommand {
     num_replicas: usize,
     timeout_ms: u128,
-    ack_replicas: Arc<Mutex<usize>>
+    ack_replicas: Arc<Mutex<usize>>,
+    notifier: Arc<Notifier>
 }
 
 impl WaitCommand {
-    pub fn new(num_replicas: usize, timeout_ms: u128, ack_replicas: Arc<Mutex<usize>>) -> Self {
-        WaitCommand { num_replicas, timeout_ms, ack_replicas }
+    pub fn new(num_replicas: usize, timeout_ms: u128, ack_replicas: Arc<Mutex<usize>>, notifier: Arc<Notifier>) -> Self {
+        WaitCommand { num_replicas, timeout_ms, ack_replicas, notifier }
     }
 }
 
 impl RedisCommand for WaitCommand {
-    fn execute(&self, _: &mut Iter<'_, RespType>) -> Vec<String> {
+    fn execute(&self, _: &mut Iter<'_, RespType>) -> Vec<Vec<u8>> {
         let now = std::time::SystemTime::now()
             .duration_since(std::time::UNIX_EPOCH)
             .unwrap()
             .as_millis();
         let expiration = now + self.timeout_ms;
 
+        let watch = self.notifier.watch_replica_acks();
         loop {
             let now = std::time::SystemTime::now()
                 .duration_since(std::time::UNIX_EPOCH)
                 .unwrap()
                 .as_millis();
             let ack_replica_gaurd = self.ack_replicas.lock().unwrap();
+            watch.reset();
 
             if now > expiration || *ack_replica_gaurd >= self.num_replicas {
                 println!("SENDING WAIT {}",ack_replica_gaurd);
                 return vec![create_int_resp(ack_replica_gaurd)];
             }
+            drop(ack_replica_gaurd);
+            watch.wait(expiration);
         }
     }
 }
\ No newline at end of file
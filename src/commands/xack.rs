@@ -0,0 +1,41 @@
+use std::{slice::Iter, sync::{Arc, Mutex}};
+
+use crate::{commands::RedisCommand, redis::{client::CacheVal, keyspace::Keyspace, stream::{create_invalid_id_err_resp, StreamId}}, resp::{create_int_resp, create_wrong_args_err_resp, create_wrong_type_err_resp, types::RespType}};
+
+pub struct XackCommand {
+    key: Vec<u8>,
+    group: Vec<u8>,
+    cache: Arc<Mutex<Keyspace>>
+}
+
+impl XackCommand {
+    pub fn new(key: Vec<u8>, group: Vec<u8>, cache: Arc<Mutex<Keyspace>>) -> Self {
+        XackCommand { key, group, cache }
+    }
+}
+
+impl RedisCommand for XackCommand {
+    fn execute(&self, iter: &mut Iter<'_, RespType>) -> Vec<Vec<u8>> {
+        let mut ids = vec![];
+        while let Some(RespType::String(id)) = iter.next() {
+            match StreamId::parse(id, 0) {
+                Some(id) => ids.push(id),
+                None => return vec![create_invalid_id_err_resp()]
+            }
+        }
+        if ids.is_empty() {
+            return vec![create_wrong_args_err_resp("xack")];
+        }
+
+        let mut cache_guard = self.cache.lock().unwrap();
+        let group = match cache_guard.get_mut(&self.key) {
+            Some(CacheVal::Stream(stream)) => match stream.groups.get_mut(&self.group) {
+                Some(group) => group,
+                None => return vec![create_int_resp(0)]
+            },
+            Some(_) => return vec![create_wrong_type_err_resp()],
+            None => return vec![create_int_resp(0)]
+        };
+        vec![create_int_resp(ids.into_iter().filter(|id| group.ack(*id)).count())]
+    }
+}
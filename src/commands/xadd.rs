@@ -1,89 +1,113 @@
-use std::{collections::HashMap, slice::Iter, sync::{Arc, Mutex}};
+use std::{slice::Iter, sync::{Arc, Mutex}};
 
-use crate::{commands::RedisCommand, redis::{client::{CacheVal, KeyVal, StreamCacheVal, StreamItem, StringCacheVal}}, resp::{create_basic_err_resp, create_bulk_string_resp, create_null_bulk_string_resp}, resp::types::RespType};
+use crate::{commands::{parse_arg, RedisCommand}, redis::{client::{CacheVal, KeyVal, StreamCacheVal, StreamItem, StringCacheVal}, keyspace::Keyspace, replication::ReplicationLog, stream::{create_invalid_id_err_resp, now_ms, StreamId, TrimArgs}}, resp::{create_array_resp, create_basic_err_resp, create_bulk_string_resp, create_null_bulk_string_resp, create_wrong_type_err_resp}, resp::types::RespType};
 
 pub struct XaddCommand {
-    stream_key: String,
-    entry_id: String,
-    cache: Arc<Mutex<HashMap<String, CacheVal>>>
+    stream_key: Vec<u8>,
+    entry_id: Vec<u8>,
+    no_mkstream: bool,
+    trim: Option<TrimArgs>,
+    cache: Arc<Mutex<Keyspace>>,
+    write_commands: Option<ReplicationLog>
 }
 
 impl XaddCommand {
-    pub fn new(stream_key: String, entry_id: String, cache: Arc<Mutex<HashMap<String, CacheVal>>>) -> Self {
+    pub fn new(stream_key: Vec<u8>, entry_id: Vec<u8>, no_mkstream: bool, trim: Option<TrimArgs>, cache: Arc<Mutex<Keyspace>>, write_commands: Option<ReplicationLog>) -> Self {
         XaddCommand {
             stream_key: stream_key,
             entry_id: entry_id,
+            no_mkstream,
+            trim,
             cache: cache,
+            write_commands
         }
     }
+
+    /// Works out the id of the new entry from the `*`, `ms-*`, `ms-seq` or `ms` argument,
+    /// which has to end up bigger than the last id the stream generated.
+    fn resolve_id(arg: &[u8], last_id: StreamId) -> Result<StreamId, Vec<u8>> {
+        let too_small_err = || create_basic_err_resp("ERR The ID specified in XADD is equal or smaller than the target stream top item".to_string());
+        let entry_id = match arg {
+            b"*" => {
+                let now = now_ms() as u64;
+                if now > last_id.ms {
+                    StreamId::new(now, 0)
+                } else {
+                    // the clock went backwards, keep counting in the last millisecond
+                    last_id.next().ok_or_else(|| create_basic_err_resp("ERR The stream has exhausted the last possible ID, unable to add more items".to_string()))?
+                }
+            },
+            _ => match arg.strip_suffix(b"-*") {
+                Some(ms) => {
+                    let ms = parse_arg::<u64>(ms).ok_or_else(create_invalid_id_err_resp)?;
+                    match ms.cmp(&last_id.ms) {
+                        std::cmp::Ordering::Less => return Err(too_small_err()),
+                        std::cmp::Ordering::Equal => StreamId::new(ms, last_id.seq.checked_add(1).ok_or_else(too_small_err)?),
+                        std::cmp::Ordering::Greater => StreamId::new(ms, 0)
+                    }
+                },
+                None => StreamId::parse(arg, 0).ok_or_else(create_invalid_id_err_resp)?
+            }
+        };
+        if entry_id == StreamId::MIN {
+            return Err(create_basic_err_resp("ERR The ID specified in XADD must be greater than 0-0".to_string()));
+        }
+        if entry_id <= last_id {
+            return Err(too_small_err());
+        }
+        Ok(entry_id)
+    }
 }
 
 impl RedisCommand for XaddCommand {
-    fn execute(&self, iter: &mut Iter<'_, RespType>) -> Vec<String> {
+    fn execute(&self, iter: &mut Iter<'_, RespType>) -> Vec<Vec<u8>> {
         let mut cache_guard = self.cache.lock().unwrap();
-        if !cache_guard.contains_key(&self.stream_key) {
-            cache_guard.insert(self.stream_key.clone(), CacheVal::Stream(StreamCacheVal { stream: vec![] }));
-        }
-
-        match cache_guard.get_mut(&self.stream_key) {
-            Some(CacheVal::Stream(cache_stream)) => {
-                let mut entry_id = self.entry_id.clone();
-                if entry_id.eq("*") {
-                    let now = std::time::SystemTime::now()
-                        .duration_since(std::time::UNIX_EPOCH)
-                        .unwrap()
-                        .as_millis();
-                    entry_id = format!("{}-*", now);
-                }
-                let parts: Vec<&str> = entry_id.split('-').collect();
-                if parts.len() != 2 {
-                    return vec![create_basic_err_resp("ERR Invalid stream ID format".to_string())];
-                }
-
-                if entry_id == "0-0" {
-                    return vec![create_basic_err_resp("ERR The ID specified in XADD must be greater than 0-0".to_string())];
-                }
-                
-                let stream_id = parts[1];
-                let final_id_sequence = match (stream_id, cache_stream.stream.is_empty()) {
-                    ("*", true) => if parts[0] == "0" { 1 } else { 0 },
-                    ("*", false) => {
-                        let last_id = &cache_stream.stream.last().unwrap().id;
-                        let last_id_parts: Vec<&str> = last_id.split('-').collect();
-                        if last_id_parts[0] == parts[0] {
-                            let last_id_sequence = last_id_parts[1].parse::<i64>().unwrap_or(0);
-                            last_id_sequence + 1    
-                        } else {
-                            if parts[0] == "0" { 1 } else { 0 }
-                        }
-                        
-                    },
-                    (val, _) => val.parse::<i64>().unwrap_or(0)
-                };
-
-                let entry_id = format!("{}-{}", parts[0], final_id_sequence);
-
-                if !cache_stream.stream.is_empty() {
-                    let last_id = &cache_stream.stream.last().unwrap().id;
-                    if entry_id <= last_id.clone() {
-                        return vec![create_basic_err_resp("ERR The ID specified in XADD is equal or smaller than the target stream top item".to_string())];
-                    }
-                }
+        // the id is checked before the key is created, so a rejected XADD leaves no empty stream behind
+        let last_id = match cache_guard.get(&self.stream_key) {
+            Some(CacheVal::Stream(cache_stream)) => cache_stream.last_id,
+            Some(_) => return vec![create_wrong_type_err_resp()],
+            None if self.no_mkstream => return vec![create_null_bulk_string_resp()],
+            None => StreamId::MIN
+        };
+        let entry_id = match Self::resolve_id(&self.entry_id, last_id) {
+            Ok(entry_id) => entry_id,
+            Err(err) => return vec![err]
+        };
 
+        match cache_guard.get_or_insert_with(self.stream_key.clone(), || CacheVal::Stream(StreamCacheVal::new(vec![]))) {
+            CacheVal::Stream(cache_stream) => {
                 let mut kvs: Vec<KeyVal> = vec![];
                 loop {
                     match (iter.next(), iter.next()) {
                         (Some(RespType::String(entry_key)), Some(RespType::String(entry_val))) => {
-                            kvs.push(KeyVal { key: entry_key.to_string(), val: entry_val.to_string() });
+                            kvs.push(KeyVal { key: entry_key.clone(), val: entry_val.clone() });
                         },
                         _ => break
                     }
                 }
                 
-                cache_stream.stream.push(StreamItem { id: entry_id.clone().into(), key_vals: kvs });
+                // replicas get the generated id and an exact trim so they end up with the same entries
+                let mut propagated = vec![b"XADD".to_vec(), self.stream_key.clone()];
+                if self.no_mkstream {
+                    propagated.push(b"NOMKSTREAM".to_vec());
+                }
+
+                cache_stream.stream.push(StreamItem { id: entry_id, key_vals: kvs.clone() });
+                cache_stream.last_id = entry_id;
+                cache_stream.entries_added += 1;
+                if let Some(trim) = &self.trim {
+                    cache_stream.trim(trim);
+                    propagated.extend(trim.to_exact_args(cache_stream));
+                }
+
+                if let Some(write_commands) = &self.write_commands {
+                    propagated.push(entry_id.to_string().into_bytes());
+                    propagated.extend(kvs.into_iter().flat_map(|kv| [kv.key, kv.val]));
+                    write_commands.push(create_array_resp(propagated.into_iter().map(create_bulk_string_resp).collect()));
+                }
                 return vec![create_bulk_string_resp(entry_id.to_string())];
             },
-            _ => return vec![create_null_bulk_string_resp()]
+            _ => unreachable!("the key was just checked to hold a stream")
         }
     }
 }
\ No newline at end of file
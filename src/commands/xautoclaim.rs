@@ -0,0 +1,106 @@
+use std::{slice::Iter, sync::{Arc, Mutex}};
+
+use crate::{commands::{parse_arg, xclaim::{claim_entry, ClaimOptions, ClaimOutcome}, RedisCommand}, redis::{client::CacheVal, keyspace::Keyspace, replication::ReplicationLog, stream::{create_invalid_id_err_resp, create_no_group_err_resp, create_stream_item_resp, find_entry, now_ms, IdBound, StreamId}}, resp::{create_array_resp, create_basic_err_resp, create_bulk_string_resp, create_wrong_type_err_resp, types::RespType}};
+
+/// XAUTOCLAIM key group consumer min-idle-time start [COUNT count] [JUSTID]
+/// Walks the pending entries list from `start` and replies with the cursor to continue from,
+/// the claimed entries and the ids that were dropped because they no longer exist.
+pub struct XautoclaimCommand {
+    key: Vec<u8>,
+    group: Vec<u8>,
+    consumer: Vec<u8>,
+    min_idle: u128,
+    cache: Arc<Mutex<Keyspace>>,
+    write_commands: Option<ReplicationLog>
+}
+
+impl XautoclaimCommand {
+    pub fn new(key: Vec<u8>, group: Vec<u8>, consumer: Vec<u8>, min_idle: u128, cache: Arc<Mutex<Keyspace>>, write_commands: Option<ReplicationLog>) -> Self {
+        XautoclaimCommand { key, group, consumer, min_idle, cache, write_commands }
+    }
+}
+
+impl RedisCommand for XautoclaimCommand {
+    fn execute(&self, iter: &mut Iter<'_, RespType>) -> Vec<Vec<u8>> {
+        let mut args = vec![];
+        while let Some(RespType::String(arg)) = iter.next() {
+            args.push(arg.clone());
+        }
+        let start = match args.first().and_then(|arg| IdBound::parse(arg, true)) {
+            Some(start) => start,
+            None => return vec![create_invalid_id_err_resp()]
+        };
+
+        let mut count = 100;
+        let mut just_id = false;
+        let mut pos = 1;
+        while pos < args.len() {
+            match args[pos].to_ascii_lowercase().as_slice() {
+                b"count" => {
+                    count = match args.get(pos + 1).map(|count| parse_arg::<i64>(count)) {
+                        Some(Some(count)) if count > 0 && count <= i64::MAX / 10 => count as usize,
+                        _ => return vec![create_basic_err_resp("ERR COUNT must be > 0".to_string())]
+                    };
+                    pos += 2;
+                },
+                b"justid" => {
+                    just_id = true;
+                    pos += 1;
+                },
+                _ => return vec![create_basic_err_resp("ERR syntax error".to_string())]
+            }
+        }
+
+        let now = now_ms();
+        let mut cache_guard = self.cache.lock().unwrap();
+        let stream = match cache_guard.get_mut(&self.key) {
+            Some(CacheVal::Stream(stream)) => stream,
+            Some(_) => return vec![create_wrong_type_err_resp()],
+            None => return vec![create_no_group_err_resp(&self.key, &self.group)]
+        };
+        let group = match stream.groups.get_mut(&self.group) {
+            Some(group) => group,
+            None => return vec![create_no_group_err_resp(&self.key, &self.group)]
+        };
+        group.touch_consumer(&self.consumer, now);
+
+        // like redis, look at no more than ten times COUNT entries per call
+        let candidates: Vec<StreamId> = group.pending.keys().filter(|id| start.admits_from_below(**id)).take(count * 10 + 1).cloned().collect();
+        let options = ClaimOptions { min_idle: self.min_idle, delivery_time: now, retry_count: None, force: false, just_id };
+        let (mut claimed, mut deleted) = (vec![], vec![]);
+        let mut cursor = StreamId::MIN;
+        for (scanned, id) in candidates.into_iter().enumerate() {
+            if claimed.len() == count || scanned == count * 10 {
+                cursor = id;
+                break;
+            }
+            match claim_entry(&stream.stream, group, id, &self.consumer, &options, now) {
+                ClaimOutcome::Claimed => claimed.push(id),
+                ClaimOutcome::Deleted => deleted.push(id),
+                ClaimOutcome::Skipped => {}
+            }
+        }
+
+        // which entries were idle long enough depends on the clock, so replicas get an explicit XCLAIM
+        if let Some(write_commands) = &self.write_commands {
+            if !claimed.is_empty() || !deleted.is_empty() {
+                let mut command = vec![b"XCLAIM".to_vec(), self.key.clone(), self.group.clone(), self.consumer.clone(), b"0".to_vec()];
+                command.extend(claimed.iter().chain(deleted.iter()).map(|id| id.to_string().into_bytes()));
+                if just_id {
+                    command.push(b"JUSTID".to_vec());
+                }
+                write_commands.push(create_array_resp(command.into_iter().map(create_bulk_string_resp).collect()));
+            }
+        }
+
+        let entries = claimed.iter().map(|id| match just_id {
+            true => create_bulk_string_resp(id.to_string()),
+            false => create_stream_item_resp(find_entry(&stream.stream, *id).unwrap())
+        }).collect();
+        vec![create_array_resp(vec![
+            create_bulk_string_resp(cursor.to_string()),
+            create_array_resp(entries),
+            create_array_resp(deleted.into_iter().map(|id| create_bulk_string_resp(id.to_string())).collect())
+        ])]
+    }
+}
@@ -0,0 +1,166 @@
+use std::{slice::Iter, sync::{Arc, Mutex}};
+
+use crate::{commands::{parse_arg, RedisCommand}, redis::{client::{CacheVal, StreamItem}, keyspace::Keyspace, replication::ReplicationLog, stream::{create_invalid_id_err_resp, create_no_group_err_resp, create_stream_item_resp, find_entry, now_ms, ConsumerGroup, StreamId}}, resp::{create_array_resp, create_basic_err_resp, create_bulk_string_resp, create_wrong_type_err_resp, types::RespType}};
+
+/// XCLAIM key group consumer min-idle-time id [id ...] [IDLE ms] [TIME ms] [RETRYCOUNT count] [FORCE] [JUSTID] [LASTID id]
+pub struct XclaimCommand {
+    key: Vec<u8>,
+    group: Vec<u8>,
+    consumer: Vec<u8>,
+    min_idle: u128,
+    cache: Arc<Mutex<Keyspace>>,
+    write_commands: Option<ReplicationLog>
+}
+
+pub enum ClaimOutcome {
+    Claimed,
+    Deleted,
+    Skipped
+}
+
+/// Delivery state to give every claimed entry.
+pub struct ClaimOptions {
+    pub(crate) min_idle: u128,
+    pub(crate) delivery_time: u128,
+    pub(crate) retry_count: Option<u64>,
+    pub(crate) force: bool,
+    pub(crate) just_id: bool
+}
+
+/// Transfers one pending entry to `consumer` if it has been idle long enough. Entries that were
+/// deleted from the stream meanwhile are dropped from the pending entries list instead.
+pub fn claim_entry(items: &[StreamItem], group: &mut ConsumerGroup, id: StreamId, consumer: &[u8], options: &ClaimOptions, now: u128) -> ClaimOutcome {
+    let exists = find_entry(items, id).is_some();
+    let delivery_count = match group.pending.get(&id) {
+        Some(entry) => {
+            if now.saturating_sub(entry.delivery_time) < options.min_idle {
+                return ClaimOutcome::Skipped;
+            }
+            entry.delivery_count
+        },
+        // forced entries have no previous owner, so there is no idle time to respect
+        None if options.force && exists => 1,
+        None => return ClaimOutcome::Skipped
+    };
+    if !exists {
+        group.ack(id);
+        return ClaimOutcome::Deleted;
+    }
+
+    let delivery_count = match options.retry_count {
+        Some(retry_count) => retry_count,
+        None if !options.just_id => delivery_count + 1,
+        None => delivery_count
+    };
+    group.assign(id, consumer, options.delivery_time, delivery_count, now);
+    ClaimOutcome::Claimed
+}
+
+impl XclaimCommand {
+    pub fn new(key: Vec<u8>, group: Vec<u8>, consumer: Vec<u8>, min_idle: u128, cache: Arc<Mutex<Keyspace>>, write_commands: Option<ReplicationLog>) -> Self {
+        XclaimCommand { key, group, consumer, min_idle, cache, write_commands }
+    }
+}
+
+impl RedisCommand for XclaimCommand {
+    fn execute(&self, iter: &mut Iter<'_, RespType>) -> Vec<Vec<u8>> {
+        let mut args = vec![];
+        while let Some(RespType::String(arg)) = iter.next() {
+            args.push(arg.clone());
+        }
+
+        let now = now_ms();
+        let mut ids = vec![];
+        let mut pos = 0;
+        while pos < args.len() {
+            match StreamId::parse(&args[pos], 0) {
+                Some(id) => ids.push(id),
+                None => break
+            }
+            pos += 1;
+        }
+        if ids.is_empty() {
+            return vec![create_invalid_id_err_resp()];
+        }
+
+        let mut options = ClaimOptions { min_idle: self.min_idle, delivery_time: now, retry_count: None, force: false, just_id: false };
+        let mut last_id = None;
+        let propagated_options = args[pos..].to_vec();
+        while pos < args.len() {
+            let value = args.get(pos + 1);
+            let parse_value = || value.and_then(|value| parse_arg::<i64>(value));
+            match args[pos].to_ascii_lowercase().as_slice() {
+                b"force" => options.force = true,
+                b"justid" => options.just_id = true,
+                b"idle" | b"time" | b"retrycount" | b"lastid" if value.is_none() => return vec![create_basic_err_resp("ERR syntax error".to_string())],
+                b"idle" => match parse_value() {
+                    Some(idle) => options.delivery_time = now.saturating_sub(idle.max(0) as u128),
+                    None => return vec![create_basic_err_resp("ERR Invalid IDLE option argument for XCLAIM".to_string())]
+                },
+                b"time" => match parse_value() {
+                    Some(time) => options.delivery_time = time.max(0) as u128,
+                    None => return vec![create_basic_err_resp("ERR Invalid TIME option argument for XCLAIM".to_string())]
+                },
+                b"retrycount" => match parse_value() {
+                    Some(retry_count) if retry_count >= 0 => options.retry_count = Some(retry_count as u64),
+                    _ => return vec![create_basic_err_resp("ERR Invalid RETRYCOUNT option argument for XCLAIM".to_string())]
+                },
+                b"lastid" => match StreamId::parse(value.unwrap(), 0) {
+                    Some(id) => last_id = Some(id),
+                    None => return vec![create_invalid_id_err_resp()]
+                },
+                _ => return vec![create_basic_err_resp(format!("ERR Unrecognized XCLAIM option '{}'", String::from_utf8_lossy(&args[pos])))]
+            }
+            pos += if matches!(args[pos].to_ascii_lowercase().as_slice(), b"force" | b"justid") { 1 } else { 2 };
+        }
+        // the delivery time is never in the future
+        options.delivery_time = options.delivery_time.min(now);
+
+        let mut cache_guard = self.cache.lock().unwrap();
+        let stream = match cache_guard.get_mut(&self.key) {
+            Some(CacheVal::Stream(stream)) => stream,
+            Some(_) => return vec![create_wrong_type_err_resp()],
+            None => return vec![create_no_group_err_resp(&self.key, &self.group)]
+        };
+        let group = match stream.groups.get_mut(&self.group) {
+            Some(group) => group,
+            None => return vec![create_no_group_err_resp(&self.key, &self.group)]
+        };
+
+        if let Some(last_id) = last_id {
+            if last_id > group.last_delivered_id {
+                group.last_delivered_id = last_id;
+            }
+        }
+        group.touch_consumer(&self.consumer, now);
+
+        let mut claimed = vec![];
+        let mut effective = vec![];
+        for id in ids {
+            match claim_entry(&stream.stream, group, id, &self.consumer, &options, now) {
+                ClaimOutcome::Claimed => {
+                    claimed.push(id);
+                    effective.push(id);
+                },
+                ClaimOutcome::Deleted => effective.push(id),
+                ClaimOutcome::Skipped => {}
+            }
+        }
+
+        // the idle check depends on the clock, so replicas are told exactly which entries moved
+        if let Some(write_commands) = &self.write_commands {
+            if !effective.is_empty() {
+                let mut command = vec![b"XCLAIM".to_vec(), self.key.clone(), self.group.clone(), self.consumer.clone(), b"0".to_vec()];
+                command.extend(effective.iter().map(|id| id.to_string().into_bytes()));
+                command.extend(propagated_options);
+                write_commands.push(create_array_resp(command.into_iter().map(create_bulk_string_resp).collect()));
+            }
+        }
+
+        let reply = claimed.iter().map(|id| match options.just_id {
+            true => create_bulk_string_resp(id.to_string()),
+            false => create_stream_item_resp(find_entry(&stream.stream, *id).unwrap())
+        }).collect();
+        vec![create_array_resp(reply)]
+    }
+}
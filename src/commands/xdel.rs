@@ -0,0 +1,45 @@
+use std::{slice::Iter, sync::{Arc, Mutex}};
+
+use crate::{commands::RedisCommand, redis::{client::CacheVal, keyspace::Keyspace, stream::{create_invalid_id_err_resp, find_entry, StreamId}}, resp::{create_int_resp, create_wrong_args_err_resp, create_wrong_type_err_resp, types::RespType}};
+
+/// Deleting entries never moves the stream's last id back, so later XADDs keep generating bigger ids.
+pub struct XdelCommand {
+    key: Vec<u8>,
+    cache: Arc<Mutex<Keyspace>>
+}
+
+impl XdelCommand {
+    pub fn new(key: Vec<u8>, cache: Arc<Mutex<Keyspace>>) -> Self {
+        XdelCommand { key, cache }
+    }
+}
+
+impl RedisCommand for XdelCommand {
+    fn execute(&self, iter: &mut Iter<'_, RespType>) -> Vec<Vec<u8>> {
+        let mut ids = vec![];
+        while let Some(RespType::String(id)) = iter.next() {
+            match StreamId::parse(id, 0) {
+                Some(id) => ids.push(id),
+                None => return vec![create_invalid_id_err_resp()]
+            }
+        }
+        if ids.is_empty() {
+            return vec![create_wrong_args_err_resp("xdel")];
+        }
+
+        let mut cache_guard = self.cache.lock().unwrap();
+        let stream = match cache_guard.get_mut(&self.key) {
+            Some(CacheVal::Stream(stream)) => stream,
+            Some(_) => return vec![create_wrong_type_err_resp()],
+            None => return vec![create_int_resp(0)]
+        };
+        ids.sort();
+        ids.dedup();
+        ids.retain(|id| find_entry(&stream.stream, *id).is_some());
+        stream.stream.retain(|item| ids.binary_search(&item.id).is_err());
+        for id in &ids {
+            stream.note_deleted(*id);
+        }
+        vec![create_int_resp(ids.len())]
+    }
+}
@@ -0,0 +1,108 @@
+use std::{slice::Iter, sync::{Arc, Mutex}};
+
+use crate::{commands::RedisCommand, redis::{client::{CacheVal, StreamCacheVal}, keyspace::Keyspace, stream::{create_invalid_id_err_resp, now_ms, ConsumerGroup, StreamId}}, resp::{create_basic_err_resp, create_int_resp, create_simple_string_resp, create_wrong_args_err_resp, create_wrong_type_err_resp, types::RespType}};
+
+/// XGROUP CREATE/DESTROY/SETID/CREATECONSUMER/DELCONSUMER.
+pub struct XgroupCommand {
+    cache: Arc<Mutex<Keyspace>>
+}
+
+impl XgroupCommand {
+    pub fn new(cache: Arc<Mutex<Keyspace>>) -> Self {
+        XgroupCommand { cache }
+    }
+}
+
+fn create_missing_key_err_resp() -> Vec<u8> {
+    create_basic_err_resp("ERR The XGROUP subcommand requires the key to exist. Note that for CREATE you may want to use the MKSTREAM option to create an empty stream automatically.".to_string())
+}
+
+fn create_missing_group_err_resp(key: &[u8], group: &[u8]) -> Vec<u8> {
+    create_basic_err_resp(format!("NOGROUP No such consumer group '{}' for key name '{}'", String::from_utf8_lossy(group), String::from_utf8_lossy(key)))
+}
+
+/// Resolves the id a group starts from, `$` meaning the current end of the stream.
+fn resolve_group_id(arg: &[u8], stream: &StreamCacheVal) -> Option<StreamId> {
+    match arg {
+        b"$" => Some(stream.last_id),
+        _ => StreamId::parse(arg, 0)
+    }
+}
+
+impl RedisCommand for XgroupCommand {
+    fn execute(&self, iter: &mut Iter<'_, RespType>) -> Vec<Vec<u8>> {
+        let mut args = vec![];
+        while let Some(RespType::String(arg)) = iter.next() {
+            args.push(arg.clone());
+        }
+        let subcommand = args.first().map(|arg| arg.to_ascii_lowercase()).unwrap_or_default();
+        let arity_ok = match subcommand.as_slice() {
+            b"create" => (4..=5).contains(&args.len()),
+            b"destroy" => args.len() == 3,
+            b"setid" | b"createconsumer" | b"delconsumer" => args.len() == 4,
+            _ => return vec![create_basic_err_resp(format!("ERR unknown subcommand '{}'. Try XGROUP HELP.", String::from_utf8_lossy(args.first().map(Vec::as_slice).unwrap_or_default())))]
+        };
+        if !arity_ok {
+            return vec![create_wrong_args_err_resp(&format!("xgroup|{}", String::from_utf8_lossy(&subcommand)))];
+        }
+        let (key, group) = (&args[1], &args[2]);
+
+        let mut cache_guard = self.cache.lock().unwrap();
+        if subcommand.eq(b"create") && !cache_guard.contains_key(key) {
+            match args.get(4) {
+                Some(option) if option.eq_ignore_ascii_case(b"mkstream") => {
+                    cache_guard.insert(key.clone(), CacheVal::Stream(StreamCacheVal::new(vec![])));
+                },
+                Some(_) => return vec![create_basic_err_resp("ERR syntax error".to_string())],
+                None => return vec![create_missing_key_err_resp()]
+            }
+        }
+        let stream = match cache_guard.get_mut(key) {
+            Some(CacheVal::Stream(stream)) => stream,
+            Some(_) => return vec![create_wrong_type_err_resp()],
+            None => return vec![create_missing_key_err_resp()]
+        };
+
+        match subcommand.as_slice() {
+            b"create" => {
+                if args.get(4).is_some_and(|option| !option.eq_ignore_ascii_case(b"mkstream")) {
+                    return vec![create_basic_err_resp("ERR syntax error".to_string())];
+                }
+                let last_delivered_id = match resolve_group_id(&args[3], stream) {
+                    Some(id) => id,
+                    None => return vec![create_invalid_id_err_resp()]
+                };
+                if stream.groups.contains_key(group) {
+                    return vec![create_basic_err_resp("BUSYGROUP Consumer Group name already exists".to_string())];
+                }
+                stream.groups.insert(group.clone(), ConsumerGroup::new(last_delivered_id));
+                vec![create_simple_string_resp("OK".to_string())]
+            },
+            b"destroy" => vec![create_int_resp(stream.groups.remove(group).map_or(0, |_| 1))],
+            _ => {
+                let resolved = resolve_group_id(&args[3], stream);
+                let consumer_group = match stream.groups.get_mut(group) {
+                    Some(consumer_group) => consumer_group,
+                    None => return vec![create_missing_group_err_resp(key, group)]
+                };
+                match subcommand.as_slice() {
+                    b"setid" => match resolved {
+                        Some(id) => {
+                            consumer_group.last_delivered_id = id;
+                            vec![create_simple_string_resp("OK".to_string())]
+                        },
+                        None => vec![create_invalid_id_err_resp()]
+                    },
+                    b"createconsumer" => {
+                        if consumer_group.consumers.contains_key(&args[3]) {
+                            return vec![create_int_resp(0)];
+                        }
+                        consumer_group.touch_consumer(&args[3], now_ms());
+                        vec![create_int_resp(1)]
+                    },
+                    _ => vec![create_int_resp(consumer_group.remove_consumer(&args[3]).unwrap_or(0))]
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,135 @@
+use std::{slice::Iter, sync::{Arc, Mutex}};
+
+use crate::{commands::{parse_arg, RedisCommand}, redis::{client::{CacheVal, StreamCacheVal}, keyspace::Keyspace, stream::{create_stream_item_resp, now_ms, ConsumerGroup, StreamId}}, resp::{create_array_resp, create_basic_err_resp, create_bulk_string_resp, create_int_resp, create_null_bulk_string_resp, create_wrong_args_err_resp, create_wrong_type_err_resp, types::RespType}};
+
+/// XINFO STREAM key [FULL [COUNT count]], XINFO GROUPS key and XINFO CONSUMERS key group.
+/// Replies are maps, sent as flat `field value ...` arrays.
+pub struct XinfoCommand {
+    cache: Arc<Mutex<Keyspace>>
+}
+
+impl XinfoCommand {
+    pub fn new(cache: Arc<Mutex<Keyspace>>) -> Self {
+        XinfoCommand { cache }
+    }
+}
+
+fn create_map_resp(fields: Vec<(&str, Vec<u8>)>) -> Vec<u8> {
+    create_array_resp(fields.into_iter().flat_map(|(field, val)| [create_bulk_string_resp(field), val]).collect())
+}
+
+fn stream_header(stream: &StreamCacheVal) -> Vec<(&'static str, Vec<u8>)> {
+    vec![
+        ("length", create_int_resp(stream.stream.len())),
+        ("last-generated-id", create_bulk_string_resp(stream.last_id.to_string())),
+        ("max-deleted-entry-id", create_bulk_string_resp(stream.max_deleted_id.to_string())),
+        ("entries-added", create_int_resp(stream.entries_added)),
+        ("recorded-first-entry-id", create_bulk_string_resp(stream.stream.first().map_or(StreamId::MIN, |item| item.id).to_string()))
+    ]
+}
+
+fn group_full(name: &[u8], group: &ConsumerGroup, count: usize) -> Vec<u8> {
+    let pending = group.pending.iter().take(count).map(|(id, entry)| create_array_resp(vec![
+        create_bulk_string_resp(id.to_string()),
+        create_bulk_string_resp(entry.consumer.clone()),
+        create_int_resp(entry.delivery_time),
+        create_int_resp(entry.delivery_count)
+    ])).collect();
+    let consumers = group.consumers.iter().map(|(consumer_name, consumer)| {
+        let pending = consumer.pending.iter().take(count).map(|id| {
+            let entry = &group.pending[id];
+            create_array_resp(vec![create_bulk_string_resp(id.to_string()), create_int_resp(entry.delivery_time), create_int_resp(entry.delivery_count)])
+        }).collect();
+        create_map_resp(vec![
+            ("name", create_bulk_string_resp(consumer_name.clone())),
+            ("seen-time", create_int_resp(consumer.seen_time)),
+            ("active-time", consumer.active_time.map_or(create_int_resp(-1), create_int_resp)),
+            ("pel-count", create_int_resp(consumer.pending.len())),
+            ("pending", create_array_resp(pending))
+        ])
+    }).collect();
+    create_map_resp(vec![
+        ("name", create_bulk_string_resp(name)),
+        ("last-delivered-id", create_bulk_string_resp(group.last_delivered_id.to_string())),
+        ("pel-count", create_int_resp(group.pending.len())),
+        ("pending", create_array_resp(pending)),
+        ("consumers", create_array_resp(consumers))
+    ])
+}
+
+impl RedisCommand for XinfoCommand {
+    fn execute(&self, iter: &mut Iter<'_, RespType>) -> Vec<Vec<u8>> {
+        let mut args = vec![];
+        while let Some(RespType::String(arg)) = iter.next() {
+            args.push(arg.clone());
+        }
+        let subcommand = args.first().map(|arg| arg.to_ascii_lowercase()).unwrap_or_default();
+        let arity_ok = match subcommand.as_slice() {
+            b"stream" => args.len() >= 2,
+            b"groups" => args.len() == 2,
+            b"consumers" => args.len() == 3,
+            _ => return vec![create_basic_err_resp(format!("ERR unknown subcommand '{}'. Try XINFO HELP.", String::from_utf8_lossy(args.first().map(Vec::as_slice).unwrap_or_default())))]
+        };
+        if !arity_ok {
+            return vec![create_wrong_args_err_resp(&format!("xinfo|{}", String::from_utf8_lossy(&subcommand)))];
+        }
+
+        let cache_guard = self.cache.lock().unwrap();
+        let stream = match cache_guard.get(&args[1]) {
+            Some(CacheVal::Stream(stream)) => stream,
+            Some(_) => return vec![create_wrong_type_err_resp()],
+            None => return vec![create_basic_err_resp("ERR no such key".to_string())]
+        };
+
+        match subcommand.as_slice() {
+            b"stream" => {
+                let full = match &args[2..] {
+                    [] => None,
+                    [full] if full.eq_ignore_ascii_case(b"full") => Some(10),
+                    [full, option, count] if full.eq_ignore_ascii_case(b"full") && option.eq_ignore_ascii_case(b"count") => match parse_arg::<i64>(count) {
+                        Some(count) if count <= 0 => Some(usize::MAX),
+                        Some(count) => Some(count as usize),
+                        None => return vec![create_basic_err_resp("ERR value is not an integer or out of range".to_string())]
+                    },
+                    _ => return vec![create_basic_err_resp("ERR syntax error".to_string())]
+                };
+                let mut fields = stream_header(stream);
+                match full {
+                    Some(count) => {
+                        fields.push(("entries", create_array_resp(stream.stream.iter().take(count).map(create_stream_item_resp).collect())));
+                        fields.push(("groups", create_array_resp(stream.groups.iter().map(|(name, group)| group_full(name, group, count)).collect())));
+                    },
+                    None => {
+                        fields.push(("groups", create_int_resp(stream.groups.len())));
+                        fields.push(("first-entry", stream.stream.first().map_or(create_null_bulk_string_resp(), create_stream_item_resp)));
+                        fields.push(("last-entry", stream.stream.last().map_or(create_null_bulk_string_resp(), create_stream_item_resp)));
+                    }
+                }
+                vec![create_map_resp(fields)]
+            },
+            b"groups" => {
+                let groups = stream.groups.iter().map(|(name, group)| create_map_resp(vec![
+                    ("name", create_bulk_string_resp(name.clone())),
+                    ("consumers", create_int_resp(group.consumers.len())),
+                    ("pending", create_int_resp(group.pending.len())),
+                    ("last-delivered-id", create_bulk_string_resp(group.last_delivered_id.to_string()))
+                ])).collect();
+                vec![create_array_resp(groups)]
+            },
+            _ => {
+                let group = match stream.groups.get(&args[2]) {
+                    Some(group) => group,
+                    None => return vec![create_basic_err_resp(format!("NOGROUP No such consumer group '{}' for key name '{}'", String::from_utf8_lossy(&args[2]), String::from_utf8_lossy(&args[1])))]
+                };
+                let now = now_ms();
+                let consumers = group.consumers.iter().map(|(name, consumer)| create_map_resp(vec![
+                    ("name", create_bulk_string_resp(name.clone())),
+                    ("pending", create_int_resp(consumer.pending.len())),
+                    ("idle", create_int_resp(now.saturating_sub(consumer.seen_time))),
+                    ("inactive", consumer.active_time.map_or(create_int_resp(-1), |active_time| create_int_resp(now.saturating_sub(active_time))))
+                ])).collect();
+                vec![create_array_resp(consumers)]
+            }
+        }
+    }
+}
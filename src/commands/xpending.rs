@@ -0,0 +1,90 @@
+use std::{slice::Iter, sync::{Arc, Mutex}};
+
+use crate::{commands::{parse_arg, RedisCommand}, redis::{client::CacheVal, keyspace::Keyspace, stream::{create_invalid_id_err_resp, create_no_group_err_resp, now_ms, IdBound}}, resp::{create_array_resp, create_basic_err_resp, create_bulk_string_resp, create_int_resp, create_null_array_resp, create_null_bulk_string_resp, create_wrong_type_err_resp, types::RespType}};
+
+/// XPENDING key group, or the extended form XPENDING key group [IDLE min-idle-time] start end count [consumer].
+pub struct XpendingCommand {
+    key: Vec<u8>,
+    group: Vec<u8>,
+    cache: Arc<Mutex<Keyspace>>
+}
+
+impl XpendingCommand {
+    pub fn new(key: Vec<u8>, group: Vec<u8>, cache: Arc<Mutex<Keyspace>>) -> Self {
+        XpendingCommand { key, group, cache }
+    }
+}
+
+impl RedisCommand for XpendingCommand {
+    fn execute(&self, iter: &mut Iter<'_, RespType>) -> Vec<Vec<u8>> {
+        let mut args = vec![];
+        while let Some(RespType::String(arg)) = iter.next() {
+            args.push(arg.clone());
+        }
+        let mut min_idle = 0;
+        if args.first().is_some_and(|arg| arg.eq_ignore_ascii_case(b"idle")) {
+            min_idle = match args.get(1).map(|arg| parse_arg::<u128>(arg)) {
+                Some(Some(min_idle)) => min_idle,
+                _ => return vec![create_basic_err_resp("ERR value is not an integer or out of range".to_string())]
+            };
+            args.drain(..2);
+            if args.is_empty() {
+                return vec![create_basic_err_resp("ERR syntax error".to_string())];
+            }
+        }
+        if !args.is_empty() && !(3..=4).contains(&args.len()) {
+            return vec![create_basic_err_resp("ERR syntax error".to_string())];
+        }
+
+        let cache_guard = self.cache.lock().unwrap();
+        let group = match cache_guard.get(&self.key) {
+            Some(CacheVal::Stream(stream)) => match stream.groups.get(&self.group) {
+                Some(group) => group,
+                None => return vec![create_no_group_err_resp(&self.key, &self.group)]
+            },
+            Some(_) => return vec![create_wrong_type_err_resp()],
+            None => return vec![create_no_group_err_resp(&self.key, &self.group)]
+        };
+
+        if args.is_empty() {
+            if group.pending.is_empty() {
+                return vec![create_array_resp(vec![create_int_resp(0), create_null_bulk_string_resp(), create_null_bulk_string_resp(), create_null_array_resp()])];
+            }
+            let consumers: Vec<Vec<u8>> = group.consumers.iter()
+                .filter(|(_, consumer)| !consumer.pending.is_empty())
+                .map(|(name, consumer)| create_array_resp(vec![create_bulk_string_resp(name.clone()), create_bulk_string_resp(consumer.pending.len().to_string())]))
+                .collect();
+            return vec![create_array_resp(vec![
+                create_int_resp(group.pending.len()),
+                create_bulk_string_resp(group.pending.keys().next().unwrap().to_string()),
+                create_bulk_string_resp(group.pending.keys().next_back().unwrap().to_string()),
+                create_array_resp(consumers)
+            ])];
+        }
+
+        let (start, end) = match (IdBound::parse(&args[0], true), IdBound::parse(&args[1], false)) {
+            (Some(start), Some(end)) => (start, end),
+            _ => return vec![create_invalid_id_err_resp()]
+        };
+        let count = match parse_arg::<i64>(&args[2]) {
+            Some(count) => count.max(0) as usize,
+            None => return vec![create_basic_err_resp("ERR value is not an integer or out of range".to_string())]
+        };
+        let consumer = args.get(3);
+
+        let now = now_ms();
+        let entries: Vec<Vec<u8>> = group.pending.iter()
+            .filter(|(id, _)| start.admits_from_below(**id) && end.admits_from_above(**id))
+            .filter(|(_, entry)| consumer.is_none_or(|consumer| entry.consumer.eq(consumer)))
+            .filter(|(_, entry)| now.saturating_sub(entry.delivery_time) >= min_idle)
+            .take(count)
+            .map(|(id, entry)| create_array_resp(vec![
+                create_bulk_string_resp(id.to_string()),
+                create_bulk_string_resp(entry.consumer.clone()),
+                create_int_resp(now.saturating_sub(entry.delivery_time) as usize),
+                create_int_resp(entry.delivery_count as usize)
+            ]))
+            .collect();
+        vec![create_array_resp(entries)]
+    }
+}
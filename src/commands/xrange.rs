@@ -1,51 +1,56 @@
-use std::{collections::HashMap, slice::Iter, sync::{Arc, Mutex}};
+use std::{slice::Iter, sync::{Arc, Mutex}};
 
-use crate::{commands::RedisCommand, redis::{client::{CacheVal, KeyVal, StreamCacheVal, StreamItem, StringCacheVal}}, resp::{create_array_resp, create_bulk_string_resp}, resp::types::RespType};
+use crate::{commands::{parse_arg, RedisCommand}, redis::{client::CacheVal, keyspace::Keyspace, stream::{create_stream_item_resp, entries_in, IdBound}}, resp::{create_array_resp, create_basic_err_resp, create_wrong_type_err_resp}, resp::types::RespType};
 
+/// Serves XRANGE and XREVRANGE (`reverse`), both optionally limited by COUNT.
 pub struct XrangeCommand {
-    stream_key: String,
-    start_id: String,
-    end_id: String,
-    cache: Arc<Mutex<HashMap<String, CacheVal>>>
+    stream_key: Vec<u8>,
+    start_id: Vec<u8>,
+    end_id: Vec<u8>,
+    reverse: bool,
+    cache: Arc<Mutex<Keyspace>>
 }
 
 impl XrangeCommand {
-    pub fn new(stream_key: String, start_id: String, end_id: String, cache: Arc<Mutex<HashMap<String, CacheVal>>>) -> Self {
+    pub fn new(stream_key: Vec<u8>, start_id: Vec<u8>, end_id: Vec<u8>, reverse: bool, cache: Arc<Mutex<Keyspace>>) -> Self {
         XrangeCommand {
             stream_key: stream_key,
             start_id: start_id,
             end_id: end_id,
+            reverse,
             cache: cache,
         }
     }
 }
 
 impl RedisCommand for XrangeCommand {
-    fn execute(&self, _: &mut Iter<'_, RespType>) -> Vec<String> {
+    fn execute(&self, iter: &mut Iter<'_, RespType>) -> Vec<Vec<u8>> {
+        let count = match (iter.next(), iter.next()) {
+            (None, _) => usize::MAX,
+            (Some(RespType::String(option)), Some(RespType::String(count))) if option.eq_ignore_ascii_case(b"count") => match parse_arg::<i64>(count) {
+                Some(count) => count.max(0) as usize,
+                None => return vec![create_basic_err_resp("ERR value is not an integer or out of range".to_string())]
+            },
+            _ => return vec![create_basic_err_resp("ERR syntax error".to_string())]
+        };
+        let (start, end) = match (IdBound::parse(&self.start_id, true), IdBound::parse(&self.end_id, false)) {
+            (Some(start), Some(end)) => (start, end),
+            _ => return vec![create_basic_err_resp("ERR Invalid stream ID specified as stream command argument".to_string())]
+        };
+
         let cache_guard = self.cache.lock().unwrap();
         match cache_guard.get(&self.stream_key) {
             Some(CacheVal::Stream(cache_stream)) => {
-                let start_index = match self.start_id.as_str() {
-                    "-" => 0,
-                    _ => cache_stream.stream.iter().position(|item| item.id > self.start_id).unwrap_or(1) - 1
+                let items = entries_in(&cache_stream.stream, &start, &end);
+                let stream_items: Vec<Vec<u8>> = if self.reverse {
+                    items.iter().rev().take(count).map(create_stream_item_resp).collect()
+                } else {
+                    items.iter().take(count).map(create_stream_item_resp).collect()
                 };
-                let end_index = match self.end_id.as_str() {
-                    "+" => cache_stream.stream.len(),
-                    _ => cache_stream.stream.iter().position(|item| item.id > self.end_id).unwrap_or(cache_stream.stream.len())
-                };
-                let entries_to_return = cache_stream.stream[start_index..end_index].to_vec();
-
-                let stream_items: Vec<String> = entries_to_return.iter().map(|item| {
-                    let data: Vec<String> = item.key_vals.iter().flat_map(|kv_item| {
-                        vec![create_bulk_string_resp(kv_item.key.clone()), create_bulk_string_resp(kv_item.val.clone())]
-                    }).collect();
-
-                    create_array_resp(vec![create_bulk_string_resp(item.id.clone()), create_array_resp(data)])
-                }).collect();
-
                 vec![create_array_resp(stream_items)]
             },
-            _ => vec![create_array_resp(vec![])]
+            Some(_) => vec![create_wrong_type_err_resp()],
+            None => vec![create_array_resp(vec![])]
         }
     }
-}
\ No newline at end of file
+}
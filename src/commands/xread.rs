@@ -1,84 +1,131 @@
-use std::{collections::HashMap, slice::Iter, sync::{Arc, Mutex}};
+use std::{slice::Iter, sync::{Arc, Mutex}};
 
-use crate::{commands::RedisCommand, redis::{client::{CacheVal, KeyVal, StreamCacheVal, StreamItem, StringCacheVal}}, resp::{create_array_resp, create_bulk_string_resp, create_null_bulk_string_resp}, resp::types::RespType};
+use crate::{commands::{parse_arg, RedisCommand}, redis::{blocking::Notifier, client::CacheVal, keyspace::Keyspace, stream::{create_invalid_id_err_resp, create_stream_item_resp, entries_in, now_ms, IdBound, StreamId}}, resp::{create_array_resp, create_basic_err_resp, create_bulk_string_resp, create_null_bulk_string_resp, create_wrong_type_err_resp}, resp::types::RespType};
 
+/// XREAD [COUNT count] [BLOCK ms] STREAMS key [key ...] id [id ...]
+/// A single wait covers every listed stream and ends as soon as any of them gets new entries.
 pub struct XreadCommand {
-    stream_key: String,
-    start_id_exclusive: String,
+    cache: Arc<Mutex<Keyspace>>,
+    notifier: Arc<Notifier>
+}
+
+struct ReadArgs {
+    count: Option<usize>,
     timeout_ms: Option<u128>,
-    cache: Arc<Mutex<HashMap<String, CacheVal>>>
+    streams: Vec<(Vec<u8>, Option<StreamId>)>
 }
 
 impl XreadCommand {
-    pub fn new(stream_key: String, timeout_ms: Option<u128>, start_id_exclusive: String, cache: Arc<Mutex<HashMap<String, CacheVal>>>) -> Self {
-        XreadCommand {
-            stream_key: stream_key,
-            timeout_ms: timeout_ms,
-            start_id_exclusive: start_id_exclusive,
-            cache: cache,
+    pub fn new(cache: Arc<Mutex<Keyspace>>, notifier: Arc<Notifier>) -> Self {
+        XreadCommand { cache, notifier }
+    }
+
+    fn parse(args: &[Vec<u8>]) -> Result<ReadArgs, Vec<u8>> {
+        let mut read_args = ReadArgs { count: None, timeout_ms: None, streams: vec![] };
+        let mut pos = 0;
+        while pos < args.len() {
+            match args[pos].to_ascii_lowercase().as_slice() {
+                b"count" if pos + 1 < args.len() => {
+                    read_args.count = match parse_arg::<i64>(&args[pos + 1]) {
+                        Some(count) if count > 0 => Some(count as usize),
+                        Some(_) => None,
+                        None => return Err(create_basic_err_resp("ERR value is not an integer or out of range".to_string()))
+                    };
+                    pos += 2;
+                },
+                b"block" if pos + 1 < args.len() => {
+                    read_args.timeout_ms = match parse_arg::<u128>(&args[pos + 1]) {
+                        Some(timeout_ms) => Some(timeout_ms),
+                        None => return Err(create_basic_err_resp("ERR timeout is not an integer or out of range".to_string()))
+                    };
+                    pos += 2;
+                },
+                b"streams" => {
+                    let rest = &args[pos + 1..];
+                    if rest.is_empty() || !rest.len().is_multiple_of(2) {
+                        return Err(create_basic_err_resp("ERR Unbalanced 'xread' list of streams: for each stream key an ID or '$' must be specified.".to_string()));
+                    }
+                    let (keys, ids) = rest.split_at(rest.len() / 2);
+                    for (key, id) in keys.iter().zip(ids) {
+                        // `$` is resolved once the cache is locked
+                        let id = match id.as_slice() {
+                            b"$" => None,
+                            _ => match StreamId::parse(id, 0) {
+                                Some(id) => Some(id),
+                                None => return Err(create_invalid_id_err_resp())
+                            }
+                        };
+                        read_args.streams.push((key.clone(), id));
+                    }
+                    return Ok(read_args);
+                },
+                _ => return Err(create_basic_err_resp("ERR syntax error".to_string()))
+            }
         }
+        Err(create_basic_err_resp("ERR syntax error".to_string()))
+    }
+
+    /// One pass over every stream, returning the replies of the streams that have entries after their id.
+    fn read(&self, args: &ReadArgs, start_ids: &[StreamId], cache: &Keyspace) -> Result<Vec<Vec<u8>>, Vec<u8>> {
+        let mut replies = vec![];
+        for ((key, _), start_id) in args.streams.iter().zip(start_ids) {
+            let stream = match cache.get(key) {
+                Some(CacheVal::Stream(stream)) => stream,
+                Some(_) => return Err(create_wrong_type_err_resp()),
+                None => continue
+            };
+            let items: Vec<Vec<u8>> = entries_in(&stream.stream, &IdBound::Exclusive(*start_id), &IdBound::Unbounded).iter()
+                .take(args.count.unwrap_or(usize::MAX))
+                .map(create_stream_item_resp)
+                .collect();
+            if !items.is_empty() {
+                replies.push(create_array_resp(vec![create_bulk_string_resp(key.clone()), create_array_resp(items)]));
+            }
+        }
+        Ok(replies)
     }
 }
 
 impl RedisCommand for XreadCommand {
-    fn execute(&self, _: &mut Iter<'_, RespType>) -> Vec<String> {
-        let expiration = match  self.timeout_ms {
-            Some(ms) if ms == 0 => {
-                Some(u128::MAX)
-            },
-            Some(ms) => {
-                let now = std::time::SystemTime::now()
-                    .duration_since(std::time::UNIX_EPOCH)
-                    .unwrap()
-                    .as_millis();
-                Some(now + ms)
-            }
-            None => None
+    fn execute(&self, iter: &mut Iter<'_, RespType>) -> Vec<Vec<u8>> {
+        let mut args = vec![];
+        while let Some(RespType::String(arg)) = iter.next() {
+            args.push(arg.clone());
+        }
+        let args = match Self::parse(&args) {
+            Ok(args) => args,
+            Err(err) => return vec![err]
         };
 
-        let mut start_id_exclusive = "0-0".to_string();
-
-        if self.start_id_exclusive.eq("$") {
+        let expiration = args.timeout_ms.map(|ms| if ms == 0 { u128::MAX } else { now_ms() + ms });
+        let start_ids: Vec<StreamId> = {
             let cache_guard = self.cache.lock().unwrap();
-            if let Some(CacheVal::Stream(cache_stream)) = cache_guard.get(&self.stream_key) {
-                if let Some(stream_item) = cache_stream.stream.last() {
-                    start_id_exclusive = stream_item.id.clone();
-                }
-            }
-        } else {
-            start_id_exclusive = self.start_id_exclusive.clone();
-        }
+            args.streams.iter().map(|(key, id)| match (id, cache_guard.get(key)) {
+                (Some(id), _) => *id,
+                (None, Some(CacheVal::Stream(stream))) => stream.last_id,
+                (None, _) => StreamId::MIN
+            }).collect()
+        };
 
+        let keys: Vec<Vec<u8>> = args.streams.iter().map(|(key, _)| key.clone()).collect();
+        let watch = self.notifier.watch_keys(&keys);
         loop {
             let cache_guard = self.cache.lock().unwrap();
-            if let Some(CacheVal::Stream(cache_stream)) = cache_guard.get(&self.stream_key) {
-                let start_index = cache_stream.stream.iter().position(|item| item.id > start_id_exclusive);
-                if start_index.is_some() {
-                    let entries_to_return = cache_stream.stream[start_index.unwrap()..].to_vec();
-
-                    let stream_items: Vec<String> = entries_to_return.iter().map(|item| {
-                        let data: Vec<String> = item.key_vals.iter().flat_map(|kv_item| {
-                            vec![create_bulk_string_resp(kv_item.key.clone()), create_bulk_string_resp(kv_item.val.clone())]
-                        }).collect();
-
-                        create_array_resp(vec![create_bulk_string_resp(item.id.clone()), create_array_resp(data)])
-                    }).collect();
-
-                    if stream_items.len() > 0 {
-                        return vec![create_array_resp(vec![create_bulk_string_resp(self.stream_key.clone()), create_array_resp(stream_items)])];
-                    }
-                }
-            }
-
-            let now = std::time::SystemTime::now()
-                .duration_since(std::time::UNIX_EPOCH)
-                .unwrap()
-                .as_millis();
-            if expiration.is_none() || now > expiration.unwrap() {
-                break;
+            watch.reset();
+            match self.read(&args, &start_ids, &cache_guard) {
+                Ok(replies) if !replies.is_empty() => return vec![create_array_resp(replies)],
+                Ok(_) => {},
+                Err(err) => return vec![err]
             }
+            let expiration = match expiration {
+                Some(expiration) if now_ms() <= expiration => expiration,
+                _ => break
+            };
+            drop(cache_guard);
+            watch.wait(expiration);
         }
-        
+
+        // this is pretty bad spec design by redis to expect a null bulk string if timeout but an array if success, I would have just had it return an empty array or the null bulk string in an array
         vec![create_null_bulk_string_resp()]
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,196 @@
+use std::{ops::Bound, slice::Iter, sync::{Arc, Mutex}};
+
+use crate::{commands::{parse_arg, RedisCommand}, redis::{blocking::Notifier, client::CacheVal, keyspace::Keyspace, replication::ReplicationLog, stream::{create_invalid_id_err_resp, create_stream_item_resp, entries_in, find_entry, now_ms, IdBound, StreamId}}, resp::{create_array_resp, create_basic_err_resp, create_bulk_string_resp, create_null_array_resp, create_wrong_type_err_resp, types::RespType}};
+
+/// XREADGROUP GROUP group consumer [COUNT count] [BLOCK ms] [NOACK] STREAMS key [key ...] id [id ...]
+/// `>` reads entries never delivered to the group, any other id reads back the consumer's own pending entries.
+pub struct XreadgroupCommand {
+    cache: Arc<Mutex<Keyspace>>,
+    notifier: Arc<Notifier>,
+    write_commands: Option<ReplicationLog>
+}
+
+struct ReadArgs {
+    group: Vec<u8>,
+    consumer: Vec<u8>,
+    count: Option<usize>,
+    timeout_ms: Option<u128>,
+    no_ack: bool,
+    streams: Vec<(Vec<u8>, Option<StreamId>)>
+}
+
+impl XreadgroupCommand {
+    pub fn new(cache: Arc<Mutex<Keyspace>>, notifier: Arc<Notifier>, write_commands: Option<ReplicationLog>) -> Self {
+        XreadgroupCommand { cache, notifier, write_commands }
+    }
+
+    fn parse(args: &[Vec<u8>]) -> Result<ReadArgs, Vec<u8>> {
+        let syntax_err = || create_basic_err_resp("ERR syntax error".to_string());
+        let mut read_args = ReadArgs { group: vec![], consumer: vec![], count: None, timeout_ms: None, no_ack: false, streams: vec![] };
+        let mut has_group = false;
+        let mut pos = 0;
+        while pos < args.len() {
+            match args[pos].to_ascii_lowercase().as_slice() {
+                b"group" if pos + 2 < args.len() => {
+                    read_args.group = args[pos + 1].clone();
+                    read_args.consumer = args[pos + 2].clone();
+                    has_group = true;
+                    pos += 3;
+                },
+                b"count" if pos + 1 < args.len() => {
+                    read_args.count = match parse_arg::<i64>(&args[pos + 1]) {
+                        Some(count) if count > 0 => Some(count as usize),
+                        Some(_) => None,
+                        None => return Err(create_basic_err_resp("ERR value is not an integer or out of range".to_string()))
+                    };
+                    pos += 2;
+                },
+                b"block" if pos + 1 < args.len() => {
+                    read_args.timeout_ms = match parse_arg::<u128>(&args[pos + 1]) {
+                        Some(timeout_ms) => Some(timeout_ms),
+                        None => return Err(create_basic_err_resp("ERR timeout is not an integer or out of range".to_string()))
+                    };
+                    pos += 2;
+                },
+                b"noack" => {
+                    read_args.no_ack = true;
+                    pos += 1;
+                },
+                b"streams" => {
+                    let rest = &args[pos + 1..];
+                    if rest.is_empty() || !rest.len().is_multiple_of(2) {
+                        return Err(create_basic_err_resp("ERR Unbalanced 'xreadgroup' list of streams: for each stream key an ID or '>' must be specified.".to_string()));
+                    }
+                    let (keys, ids) = rest.split_at(rest.len() / 2);
+                    for (key, id) in keys.iter().zip(ids) {
+                        let id = match id.as_slice() {
+                            b">" => None,
+                            b"$" => return Err(create_basic_err_resp("ERR The $ ID is meaningful only for XREAD, not XREADGROUP".to_string())),
+                            _ => match StreamId::parse(id, 0) {
+                                Some(id) => Some(id),
+                                None => return Err(create_invalid_id_err_resp())
+                            }
+                        };
+                        read_args.streams.push((key.clone(), id));
+                    }
+                    break;
+                },
+                _ => return Err(syntax_err())
+            }
+        }
+        if !has_group || read_args.streams.is_empty() {
+            return Err(create_basic_err_resp("ERR Missing GROUP or STREAMS option in 'xreadgroup' command".to_string()));
+        }
+        Ok(read_args)
+    }
+
+    /// One pass over every stream, returning the per stream replies (possibly none) or an error reply.
+    fn read(&self, args: &ReadArgs, cache: &mut Keyspace) -> Result<Vec<Vec<u8>>, Vec<u8>> {
+        let now = now_ms();
+        let mut replies = vec![];
+        let mut propagate = vec![];
+        for (key, id) in &args.streams {
+            let stream = match cache.get_mut(key) {
+                Some(CacheVal::Stream(stream)) => stream,
+                Some(_) => return Err(create_wrong_type_err_resp()),
+                None => return Err(Self::no_group_err(key, &args.group))
+            };
+            let group = match stream.groups.get_mut(&args.group) {
+                Some(group) => group,
+                None => return Err(Self::no_group_err(key, &args.group))
+            };
+            if !group.consumers.contains_key(&args.consumer) {
+                propagate.push(vec![b"XGROUP".to_vec(), b"CREATECONSUMER".to_vec(), key.clone(), args.group.clone(), args.consumer.clone()]);
+            }
+            group.touch_consumer(&args.consumer, now);
+
+            match id {
+                None => {
+                    let items: Vec<_> = entries_in(&stream.stream, &IdBound::Exclusive(group.last_delivered_id), &IdBound::Unbounded).iter()
+                        .take(args.count.unwrap_or(usize::MAX))
+                        .collect();
+                    if items.is_empty() {
+                        continue;
+                    }
+                    let ids: Vec<StreamId> = items.iter().map(|item| item.id).collect();
+                    group.last_delivered_id = *ids.last().unwrap();
+                    if !args.no_ack {
+                        for id in &ids {
+                            group.deliver(*id, &args.consumer, now, true);
+                        }
+                    }
+                    replies.push(create_array_resp(vec![create_bulk_string_resp(key.clone()), create_array_resp(items.into_iter().map(create_stream_item_resp).collect())]));
+
+                    // replicas get the resulting group state rather than a read they would have to repeat
+                    let mut command = if args.no_ack {
+                        vec![b"XGROUP".to_vec(), b"SETID".to_vec(), key.clone(), args.group.clone()]
+                    } else {
+                        let mut command = vec![b"XCLAIM".to_vec(), key.clone(), args.group.clone(), args.consumer.clone(), b"0".to_vec()];
+                        command.extend(ids.iter().map(|id| id.to_string().into_bytes()));
+                        command.extend([b"FORCE".to_vec(), b"JUSTID".to_vec(), b"LASTID".to_vec()]);
+                        command
+                    };
+                    command.push(group.last_delivered_id.to_string().into_bytes());
+                    propagate.push(command);
+                },
+                Some(start_id) => {
+                    let consumer = &group.consumers[&args.consumer];
+                    let entries: Vec<Vec<u8>> = consumer.pending.range((Bound::Excluded(*start_id), Bound::Unbounded))
+                        .take(args.count.unwrap_or(usize::MAX))
+                        .map(|id| match find_entry(&stream.stream, *id) {
+                            Some(item) => create_stream_item_resp(item),
+                            // the entry was deleted while pending
+                            None => create_array_resp(vec![create_bulk_string_resp(id.to_string()), create_null_array_resp()])
+                        })
+                        .collect();
+                    replies.push(create_array_resp(vec![create_bulk_string_resp(key.clone()), create_array_resp(entries)]));
+                }
+            }
+        }
+
+        if let Some(write_commands) = &self.write_commands {
+            for command in propagate {
+                write_commands.push(create_array_resp(command.into_iter().map(create_bulk_string_resp).collect()));
+            }
+        }
+        Ok(replies)
+    }
+
+    fn no_group_err(key: &[u8], group: &[u8]) -> Vec<u8> {
+        create_basic_err_resp(format!("NOGROUP No such key '{}' or consumer group '{}' in XREADGROUP with GROUP option", String::from_utf8_lossy(key), String::from_utf8_lossy(group)))
+    }
+}
+
+impl RedisCommand for XreadgroupCommand {
+    fn execute(&self, iter: &mut Iter<'_, RespType>) -> Vec<Vec<u8>> {
+        let mut args = vec![];
+        while let Some(RespType::String(arg)) = iter.next() {
+            args.push(arg.clone());
+        }
+        let args = match Self::parse(&args) {
+            Ok(args) => args,
+            Err(err) => return vec![err]
+        };
+
+        // same waiting strategy as XREAD, a timeout of 0 blocks forever
+        let expiration = args.timeout_ms.map(|ms| if ms == 0 { u128::MAX } else { now_ms() + ms });
+        let keys: Vec<Vec<u8>> = args.streams.iter().map(|(key, _)| key.clone()).collect();
+        let watch = self.notifier.watch_keys(&keys);
+        loop {
+            let mut cache_guard = self.cache.lock().unwrap();
+            watch.reset();
+            match self.read(&args, &mut cache_guard) {
+                Ok(replies) if !replies.is_empty() => return vec![create_array_resp(replies)],
+                Ok(_) => {},
+                Err(err) => return vec![err]
+            }
+            let expiration = match expiration {
+                Some(expiration) if now_ms() <= expiration => expiration,
+                _ => break
+            };
+            drop(cache_guard);
+            watch.wait(expiration);
+        }
+        vec![create_null_array_resp()]
+    }
+}
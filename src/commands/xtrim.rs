@@ -0,0 +1,55 @@
+use std::{slice::Iter, sync::{Arc, Mutex}};
+
+use crate::{commands::RedisCommand, redis::{client::CacheVal, keyspace::Keyspace, replication::ReplicationLog, stream::TrimArgsParser}, resp::{create_array_resp, create_basic_err_resp, create_bulk_string_resp, create_int_resp, create_wrong_type_err_resp, types::RespType}};
+
+/// XTRIM key MAXLEN|MINID [=|~] threshold [LIMIT count]
+pub struct XtrimCommand {
+    key: Vec<u8>,
+    cache: Arc<Mutex<Keyspace>>,
+    write_commands: Option<ReplicationLog>
+}
+
+impl XtrimCommand {
+    pub fn new(key: Vec<u8>, cache: Arc<Mutex<Keyspace>>, write_commands: Option<ReplicationLog>) -> Self {
+        XtrimCommand { key, cache, write_commands }
+    }
+}
+
+impl RedisCommand for XtrimCommand {
+    fn execute(&self, iter: &mut Iter<'_, RespType>) -> Vec<Vec<u8>> {
+        let mut args = vec![];
+        while let Some(RespType::String(arg)) = iter.next() {
+            args.push(arg.clone());
+        }
+        let mut trim_parser = TrimArgsParser::default();
+        let mut pos = 0;
+        while pos < args.len() {
+            match trim_parser.parse(&args, pos) {
+                Ok(0) => return vec![create_basic_err_resp("ERR syntax error".to_string())],
+                Ok(used) => pos += used,
+                Err(err) => return vec![err]
+            }
+        }
+        let trim = match trim_parser.finish() {
+            Ok(Some(trim)) => trim,
+            Ok(None) => return vec![create_basic_err_resp("ERR syntax error".to_string())],
+            Err(err) => return vec![err]
+        };
+
+        let mut cache_guard = self.cache.lock().unwrap();
+        let stream = match cache_guard.get_mut(&self.key) {
+            Some(CacheVal::Stream(stream)) => stream,
+            Some(_) => return vec![create_wrong_type_err_resp()],
+            None => return vec![create_int_resp(0)]
+        };
+        let removed = stream.trim(&trim);
+
+        // approximate trims depend on node boundaries, replicas are told exactly where to cut
+        if let Some(write_commands) = &self.write_commands {
+            let mut command = vec![b"XTRIM".to_vec(), self.key.clone()];
+            command.extend(trim.to_exact_args(stream));
+            write_commands.push(create_array_resp(command.into_iter().map(create_bulk_string_resp).collect()));
+        }
+        vec![create_int_resp(removed)]
+    }
+}
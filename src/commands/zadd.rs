@@ -0,0 +1,123 @@
+use std::{slice::Iter, sync::{Arc, Mutex}};
+
+use crate::{commands::{format_float, RedisCommand}, redis::{client::CacheVal, keyspace::Keyspace, sorted_set::{parse_score, SortedSetCacheVal}}, resp::{create_basic_err_resp, create_bulk_string_resp, create_int_resp, create_null_bulk_string_resp, create_wrong_args_err_resp, create_wrong_type_err_resp, types::RespType}};
+
+pub struct ZaddCommand {
+    key: Vec<u8>,
+    cache: Arc<Mutex<Keyspace>>
+}
+
+impl ZaddCommand {
+    pub fn new(key: Vec<u8>, cache: Arc<Mutex<Keyspace>>) -> Self {
+        ZaddCommand { key, cache }
+    }
+}
+
+impl RedisCommand for ZaddCommand {
+    fn execute(&self, iter: &mut Iter<'_, RespType>) -> Vec<Vec<u8>> {
+        let mut args = vec![];
+        while let Some(RespType::String(arg)) = iter.next() {
+            args.push(arg.clone());
+        }
+
+        let (mut nx, mut xx, mut gt, mut lt, mut ch, mut incr) = (false, false, false, false, false, false);
+        let mut pos = 0;
+        while pos < args.len() {
+            match args[pos].to_ascii_lowercase().as_slice() {
+                b"nx" => nx = true,
+                b"xx" => xx = true,
+                b"gt" => gt = true,
+                b"lt" => lt = true,
+                b"ch" => ch = true,
+                b"incr" => incr = true,
+                _ => break
+            }
+            pos += 1;
+        }
+
+        let pairs = &args[pos..];
+        if pairs.is_empty() || pairs.len() % 2 != 0 {
+            if pairs.is_empty() && pos == 0 {
+                return vec![create_wrong_args_err_resp("zadd")];
+            }
+            return vec![create_basic_err_resp("ERR syntax error".to_string())];
+        }
+        if nx && xx {
+            return vec![create_basic_err_resp("ERR XX and NX options at the same time are not compatible".to_string())];
+        }
+        if (gt && lt) || (nx && (gt || lt)) {
+            return vec![create_basic_err_resp("ERR GT, LT, and/or NX options at the same time are not compatible".to_string())];
+        }
+        if incr && pairs.len() > 2 {
+            return vec![create_basic_err_resp("ERR INCR option supports a single increment-element pair".to_string())];
+        }
+
+        let mut score_members = vec![];
+        for pair in pairs.chunks(2) {
+            match parse_score(&pair[0]) {
+                Some(score) => score_members.push((score, pair[1].clone())),
+                None => return vec![create_basic_err_resp("ERR value is not a valid float".to_string())]
+            }
+        }
+
+        let mut cache_guard = self.cache.lock().unwrap();
+        let zset = match cache_guard.get_mut(&self.key) {
+            Some(CacheVal::SortedSet(zset)) => zset,
+            Some(_) => return vec![create_wrong_type_err_resp()],
+            None if xx => {
+                return if incr { vec![create_null_bulk_string_resp()] } else { vec![create_int_resp(0)] };
+            },
+            None => {
+                cache_guard.insert(self.key.clone(), CacheVal::SortedSet(SortedSetCacheVal::new()));
+                match cache_guard.get_mut(&self.key) {
+                    Some(CacheVal::SortedSet(zset)) => zset,
+                    _ => unreachable!()
+                }
+            }
+        };
+
+        let mut added = 0;
+        let mut changed = 0;
+        let mut incr_result = None;
+        for (score, member) in score_members {
+            match zset.score(&member) {
+                Some(current) => {
+                    if nx {
+                        continue;
+                    }
+                    let new_score = if incr { current + score } else { score };
+                    if new_score.is_nan() {
+                        return vec![create_basic_err_resp("ERR resulting score is not a number (NaN)".to_string())];
+                    }
+                    if (gt && new_score <= current) || (lt && new_score >= current) {
+                        continue;
+                    }
+                    if new_score != current {
+                        zset.insert(member, new_score);
+                        changed += 1;
+                    }
+                    incr_result = Some(new_score);
+                },
+                None => {
+                    if xx {
+                        continue;
+                    }
+                    zset.insert(member, score);
+                    added += 1;
+                    incr_result = Some(score);
+                }
+            }
+        }
+        if zset.is_empty() {
+            cache_guard.remove(&self.key);
+        }
+
+        if incr {
+            return match incr_result {
+                Some(score) => vec![create_bulk_string_resp(format_float(score))],
+                None => vec![create_null_bulk_string_resp()]
+            };
+        }
+        vec![create_int_resp(if ch { added + changed } else { added })]
+    }
+}
@@ -0,0 +1,30 @@
+use std::{slice::Iter, sync::{Arc, Mutex}};
+
+use crate::{commands::RedisCommand, redis::{client::CacheVal, keyspace::Keyspace, sorted_set::ScoreBound}, resp::{create_int_resp, create_wrong_type_err_resp, types::RespType}};
+
+pub struct ZcountCommand {
+    key: Vec<u8>,
+    min: ScoreBound,
+    max: ScoreBound,
+    cache: Arc<Mutex<Keyspace>>
+}
+
+impl ZcountCommand {
+    pub fn new(key: Vec<u8>, min: ScoreBound, max: ScoreBound, cache: Arc<Mutex<Keyspace>>) -> Self {
+        ZcountCommand { key, min, max, cache }
+    }
+}
+
+impl RedisCommand for ZcountCommand {
+    fn execute(&self, _: &mut Iter<'_, RespType>) -> Vec<Vec<u8>> {
+        let cache_guard = self.cache.lock().unwrap();
+        match cache_guard.get(&self.key) {
+            Some(CacheVal::SortedSet(zset)) => {
+                let (start, end) = zset.score_range(&self.min, &self.max);
+                vec![create_int_resp(end - start)]
+            },
+            Some(_) => vec![create_wrong_type_err_resp()],
+            None => vec![create_int_resp(0)]
+        }
+    }
+}
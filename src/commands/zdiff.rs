@@ -0,0 +1,22 @@
+use std::{slice::Iter, sync::{Arc, Mutex}};
+
+use crate::{commands::{zset_ops::{self, ZsetOp}, RedisCommand}, redis::{client::CacheVal, keyspace::Keyspace}, resp::types::RespType};
+
+/// Serves both ZDIFF and ZDIFFSTORE, the latter when a destination is given.
+pub struct ZdiffCommand {
+    destination: Option<Vec<u8>>,
+    cache: Arc<Mutex<Keyspace>>
+}
+
+impl ZdiffCommand {
+    pub fn new(destination: Option<Vec<u8>>, cache: Arc<Mutex<Keyspace>>) -> Self {
+        ZdiffCommand { destination, cache }
+    }
+}
+
+impl RedisCommand for ZdiffCommand {
+    fn execute(&self, iter: &mut Iter<'_, RespType>) -> Vec<Vec<u8>> {
+        let command = if self.destination.is_some() { "zdiffstore" } else { "zdiff" };
+        zset_ops::execute(ZsetOp::Diff, command, &self.destination, &self.cache, iter)
+    }
+}
@@ -0,0 +1,36 @@
+use std::{slice::Iter, sync::{Arc, Mutex}};
+
+use crate::{commands::{format_float, RedisCommand}, redis::{client::CacheVal, keyspace::Keyspace, sorted_set::SortedSetCacheVal}, resp::{create_basic_err_resp, create_bulk_string_resp, create_wrong_type_err_resp, types::RespType}};
+
+pub struct ZincrbyCommand {
+    key: Vec<u8>,
+    increment: f64,
+    member: Vec<u8>,
+    cache: Arc<Mutex<Keyspace>>
+}
+
+impl ZincrbyCommand {
+    pub fn new(key: Vec<u8>, increment: f64, member: Vec<u8>, cache: Arc<Mutex<Keyspace>>) -> Self {
+        ZincrbyCommand { key, increment, member, cache }
+    }
+}
+
+impl RedisCommand for ZincrbyCommand {
+    fn execute(&self, _: &mut Iter<'_, RespType>) -> Vec<Vec<u8>> {
+        let mut cache_guard = self.cache.lock().unwrap();
+        let zset = match cache_guard.get_or_insert_with(self.key.clone(), || CacheVal::SortedSet(SortedSetCacheVal::new())) {
+            CacheVal::SortedSet(zset) => zset,
+            _ => return vec![create_wrong_type_err_resp()]
+        };
+
+        let new_score = zset.score(&self.member).unwrap_or(0.0) + self.increment;
+        if new_score.is_nan() {
+            if zset.is_empty() {
+                cache_guard.remove(&self.key);
+            }
+            return vec![create_basic_err_resp("ERR resulting score is not a number (NaN)".to_string())];
+        }
+        zset.insert(self.member.clone(), new_score);
+        vec![create_bulk_string_resp(format_float(new_score))]
+    }
+}
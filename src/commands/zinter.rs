@@ -0,0 +1,22 @@
+use std::{slice::Iter, sync::{Arc, Mutex}};
+
+use crate::{commands::{zset_ops::{self, ZsetOp}, RedisCommand}, redis::{client::CacheVal, keyspace::Keyspace}, resp::types::RespType};
+
+/// Serves both ZINTER and ZINTERSTORE, the latter when a destination is given.
+pub struct ZinterCommand {
+    destination: Option<Vec<u8>>,
+    cache: Arc<Mutex<Keyspace>>
+}
+
+impl ZinterCommand {
+    pub fn new(destination: Option<Vec<u8>>, cache: Arc<Mutex<Keyspace>>) -> Self {
+        ZinterCommand { destination, cache }
+    }
+}
+
+impl RedisCommand for ZinterCommand {
+    fn execute(&self, iter: &mut Iter<'_, RespType>) -> Vec<Vec<u8>> {
+        let command = if self.destination.is_some() { "zinterstore" } else { "zinter" };
+        zset_ops::execute(ZsetOp::Inter, command, &self.destination, &self.cache, iter)
+    }
+}
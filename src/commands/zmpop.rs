@@ -0,0 +1,64 @@
+use std::{slice::Iter, sync::{Arc, Mutex}};
+
+use crate::{commands::{parse_arg, zpop::popped_resp, RedisCommand}, redis::{client::CacheVal, keyspace::Keyspace}, resp::{create_array_resp, create_basic_err_resp, create_bulk_string_resp, create_null_array_resp, create_wrong_args_err_resp, create_wrong_type_err_resp, types::RespType}};
+
+/// ZMPOP numkeys key [key ...] MIN|MAX [COUNT count], pops from the first non empty key.
+pub struct ZmpopCommand {
+    cache: Arc<Mutex<Keyspace>>
+}
+
+impl ZmpopCommand {
+    pub fn new(cache: Arc<Mutex<Keyspace>>) -> Self {
+        ZmpopCommand { cache }
+    }
+}
+
+impl RedisCommand for ZmpopCommand {
+    fn execute(&self, iter: &mut Iter<'_, RespType>) -> Vec<Vec<u8>> {
+        let mut args = vec![];
+        while let Some(RespType::String(arg)) = iter.next() {
+            args.push(arg.clone());
+        }
+        if args.len() < 3 {
+            return vec![create_wrong_args_err_resp("zmpop")];
+        }
+
+        let numkeys = match parse_arg::<usize>(&args[0]) {
+            Some(numkeys) if numkeys > 0 => numkeys,
+            _ => return vec![create_basic_err_resp("ERR numkeys should be greater than 0".to_string())]
+        };
+        if args.len() < numkeys + 2 {
+            return vec![create_basic_err_resp("ERR syntax error".to_string())];
+        }
+        let keys = &args[1..=numkeys];
+        let max = match args[numkeys + 1].to_ascii_lowercase().as_slice() {
+            b"min" => false,
+            b"max" => true,
+            _ => return vec![create_basic_err_resp("ERR syntax error".to_string())]
+        };
+        let count = match &args[numkeys + 2..] {
+            [] => 1,
+            [option, count] if option.eq_ignore_ascii_case(b"count") => match parse_arg::<usize>(count) {
+                Some(count) if count > 0 => count,
+                _ => return vec![create_basic_err_resp("ERR count should be greater than 0".to_string())]
+            },
+            _ => return vec![create_basic_err_resp("ERR syntax error".to_string())]
+        };
+
+        let mut cache_guard = self.cache.lock().unwrap();
+        for key in keys {
+            let zset = match cache_guard.get_mut(key) {
+                Some(CacheVal::SortedSet(zset)) if !zset.is_empty() => zset,
+                Some(CacheVal::SortedSet(_)) | None => continue,
+                Some(_) => return vec![create_wrong_type_err_resp()]
+            };
+            let popped: Vec<(Vec<u8>, f64)> = (0..count).map_while(|_| zset.pop(max)).collect();
+            if zset.is_empty() {
+                cache_guard.remove(key);
+            }
+            let pairs = popped_resp(popped).chunks(2).map(|pair| create_array_resp(pair.to_vec())).collect();
+            return vec![create_array_resp(vec![create_bulk_string_resp(key.clone()), create_array_resp(pairs)])];
+        }
+        vec![create_null_array_resp()]
+    }
+}
@@ -0,0 +1,38 @@
+use std::{slice::Iter, sync::{Arc, Mutex}};
+
+use crate::{commands::{format_float, RedisCommand}, redis::{client::CacheVal, keyspace::Keyspace}, resp::{create_array_resp, create_bulk_string_resp, create_null_bulk_string_resp, create_wrong_args_err_resp, create_wrong_type_err_resp, types::RespType}};
+
+pub struct ZmscoreCommand {
+    key: Vec<u8>,
+    cache: Arc<Mutex<Keyspace>>
+}
+
+impl ZmscoreCommand {
+    pub fn new(key: Vec<u8>, cache: Arc<Mutex<Keyspace>>) -> Self {
+        ZmscoreCommand { key, cache }
+    }
+}
+
+impl RedisCommand for ZmscoreCommand {
+    fn execute(&self, iter: &mut Iter<'_, RespType>) -> Vec<Vec<u8>> {
+        let mut members = vec![];
+        while let Some(RespType::String(member)) = iter.next() {
+            members.push(member.clone());
+        }
+        if members.is_empty() {
+            return vec![create_wrong_args_err_resp("zmscore")];
+        }
+
+        let cache_guard = self.cache.lock().unwrap();
+        let zset = match cache_guard.get(&self.key) {
+            Some(CacheVal::SortedSet(zset)) => Some(zset),
+            Some(_) => return vec![create_wrong_type_err_resp()],
+            None => None
+        };
+        let scores = members.iter().map(|member| match zset.and_then(|zset| zset.score(member)) {
+            Some(score) => create_bulk_string_resp(format_float(score)),
+            None => create_null_bulk_string_resp()
+        }).collect();
+        vec![create_array_resp(scores)]
+    }
+}
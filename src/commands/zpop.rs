@@ -0,0 +1,39 @@
+use std::{slice::Iter, sync::{Arc, Mutex}};
+
+use crate::{commands::{format_float, RedisCommand}, redis::{client::CacheVal, keyspace::Keyspace}, resp::{create_array_resp, create_bulk_string_resp, create_wrong_type_err_resp, types::RespType}};
+
+/// Serves both ZPOPMIN and ZPOPMAX.
+pub struct ZpopCommand {
+    key: Vec<u8>,
+    max: bool,
+    count: Option<usize>,
+    cache: Arc<Mutex<Keyspace>>
+}
+
+impl ZpopCommand {
+    pub fn new(key: Vec<u8>, max: bool, count: Option<usize>, cache: Arc<Mutex<Keyspace>>) -> Self {
+        ZpopCommand { key, max, count, cache }
+    }
+}
+
+/// Flattens popped pairs into the `member score member score ...` reply shape.
+pub fn popped_resp(popped: Vec<(Vec<u8>, f64)>) -> Vec<Vec<u8>> {
+    popped.into_iter().flat_map(|(member, score)| [create_bulk_string_resp(member), create_bulk_string_resp(format_float(score))]).collect()
+}
+
+impl RedisCommand for ZpopCommand {
+    fn execute(&self, _: &mut Iter<'_, RespType>) -> Vec<Vec<u8>> {
+        let mut cache_guard = self.cache.lock().unwrap();
+        let zset = match cache_guard.get_mut(&self.key) {
+            Some(CacheVal::SortedSet(zset)) => zset,
+            Some(_) => return vec![create_wrong_type_err_resp()],
+            None => return vec![create_array_resp(vec![])]
+        };
+
+        let popped: Vec<(Vec<u8>, f64)> = (0..self.count.unwrap_or(1)).map_while(|_| zset.pop(self.max)).collect();
+        if zset.is_empty() {
+            cache_guard.remove(&self.key);
+        }
+        vec![create_array_resp(popped_resp(popped))]
+    }
+}
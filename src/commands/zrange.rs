@@ -0,0 +1,129 @@
+use std::{slice::Iter, sync::{Arc, Mutex}};
+
+use crate::{commands::{parse_arg, format_float, RedisCommand}, redis::{client::CacheVal, keyspace::Keyspace, sorted_set::{LexBound, ScoreBound}}, resp::{create_array_resp, create_basic_err_resp, create_bulk_string_resp, create_wrong_args_err_resp, create_wrong_type_err_resp, types::RespType}};
+
+enum RangeBy {
+    Index(i64, i64),
+    Score(ScoreBound, ScoreBound),
+    Lex(LexBound, LexBound)
+}
+
+/// Unified ZRANGE: by index (default), BYSCORE or BYLEX, optionally REV, LIMIT and WITHSCORES.
+pub struct ZrangeCommand {
+    key: Vec<u8>,
+    cache: Arc<Mutex<Keyspace>>
+}
+
+impl ZrangeCommand {
+    pub fn new(key: Vec<u8>, cache: Arc<Mutex<Keyspace>>) -> Self {
+        ZrangeCommand { key, cache }
+    }
+}
+
+impl RedisCommand for ZrangeCommand {
+    fn execute(&self, iter: &mut Iter<'_, RespType>) -> Vec<Vec<u8>> {
+        let (start, stop) = match (iter.next(), iter.next()) {
+            (Some(RespType::String(start)), Some(RespType::String(stop))) => (start.clone(), stop.clone()),
+            _ => return vec![create_wrong_args_err_resp("zrange")]
+        };
+
+        let (mut by_score, mut by_lex, mut rev, mut with_scores) = (false, false, false, false);
+        let mut limit: Option<(usize, i64)> = None;
+        while let Some(RespType::String(arg)) = iter.next() {
+            match arg.to_ascii_lowercase().as_slice() {
+                b"byscore" => by_score = true,
+                b"bylex" => by_lex = true,
+                b"rev" => rev = true,
+                b"withscores" => with_scores = true,
+                b"limit" => {
+                    let offset = iter.next().and_then(|arg| if let RespType::String(arg) = arg { parse_arg::<i64>(arg) } else { None });
+                    let count = iter.next().and_then(|arg| if let RespType::String(arg) = arg { parse_arg::<i64>(arg) } else { None });
+                    match (offset, count) {
+                        // a negative offset always yields an empty range
+                        (Some(offset), Some(count)) => limit = Some((if offset < 0 { usize::MAX } else { offset as usize }, count)),
+                        _ => return vec![create_basic_err_resp("ERR value is not an integer or out of range".to_string())]
+                    }
+                },
+                _ => return vec![create_basic_err_resp("ERR syntax error".to_string())]
+            }
+        }
+
+        if by_score && by_lex {
+            return vec![create_basic_err_resp("ERR syntax error".to_string())];
+        }
+        if limit.is_some() && !by_score && !by_lex {
+            return vec![create_basic_err_resp("ERR syntax error, LIMIT is only supported in combination with either BYSCORE or BYLEX".to_string())];
+        }
+        if with_scores && by_lex {
+            return vec![create_basic_err_resp("ERR syntax error, WITHSCORES not supported in combination with BYLEX".to_string())];
+        }
+
+        // with REV the range is given from the high end to the low end
+        let (low, high) = if rev && (by_score || by_lex) { (stop, start) } else { (start, stop) };
+        let range_by = if by_score {
+            match (ScoreBound::parse(&low), ScoreBound::parse(&high)) {
+                (Some(min), Some(max)) => RangeBy::Score(min, max),
+                _ => return vec![create_basic_err_resp("ERR min or max is not a float".to_string())]
+            }
+        } else if by_lex {
+            match (LexBound::parse(&low), LexBound::parse(&high)) {
+                (Some(min), Some(max)) => RangeBy::Lex(min, max),
+                _ => return vec![create_basic_err_resp("ERR min or max not valid string range item".to_string())]
+            }
+        } else {
+            match (parse_arg::<i64>(&low), parse_arg::<i64>(&high)) {
+                (Some(start), Some(stop)) => RangeBy::Index(start, stop),
+                _ => return vec![create_basic_err_resp("ERR value is not an integer or out of range".to_string())]
+            }
+        };
+
+        let cache_guard = self.cache.lock().unwrap();
+        let zset = match cache_guard.get(&self.key) {
+            Some(CacheVal::SortedSet(zset)) => zset,
+            Some(_) => return vec![create_wrong_type_err_resp()],
+            None => return vec![create_array_resp(vec![])]
+        };
+
+        // work out the ascending ranks [first, last) to return before applying REV and LIMIT
+        let len = zset.len() as i64;
+        let (first, last) = match &range_by {
+            RangeBy::Index(start, stop) => {
+                let mut start = if *start < 0 { start + len } else { *start };
+                let mut stop = if *stop < 0 { stop + len } else { *stop };
+                start = start.max(0);
+                stop = stop.min(len - 1);
+                if start > stop || start >= len {
+                    return vec![create_array_resp(vec![])];
+                }
+                // the index range counts from the top when reversed
+                if rev {
+                    ((len - 1 - stop) as usize, (len - start) as usize)
+                } else {
+                    (start as usize, (stop + 1) as usize)
+                }
+            },
+            RangeBy::Score(min, max) => zset.score_range(min, max),
+            RangeBy::Lex(min, max) => zset.lex_range(min, max)
+        };
+
+        let (offset, count) = limit.unwrap_or((0, -1));
+        let available = (last - first).saturating_sub(offset);
+        let take = if count < 0 { available } else { available.min(count as usize) };
+        let items: Vec<(f64, &Vec<u8>)> = if take == 0 {
+            vec![]
+        } else if rev {
+            zset.iter_rev_from(last - 1 - offset).take(take).collect()
+        } else {
+            zset.iter_from(first + offset).take(take).collect()
+        };
+
+        let reply = items.into_iter().flat_map(|(score, member)| {
+            if with_scores {
+                vec![create_bulk_string_resp(member.clone()), create_bulk_string_resp(format_float(score))]
+            } else {
+                vec![create_bulk_string_resp(member.clone())]
+            }
+        }).collect();
+        vec![create_array_resp(reply)]
+    }
+}
@@ -0,0 +1,48 @@
+use std::{slice::Iter, sync::{Arc, Mutex}};
+
+use crate::{commands::{format_float, RedisCommand}, redis::{client::CacheVal, keyspace::Keyspace}, resp::{create_array_resp, create_bulk_string_resp, create_int_resp, create_null_array_resp, create_null_bulk_string_resp, create_wrong_type_err_resp, types::RespType}};
+
+/// Serves ZRANK and, with `reverse` set, ZREVRANK.
+pub struct ZrankCommand {
+    key: Vec<u8>,
+    member: Vec<u8>,
+    reverse: bool,
+    with_score: bool,
+    cache: Arc<Mutex<Keyspace>>
+}
+
+impl ZrankCommand {
+    pub fn new(key: Vec<u8>, member: Vec<u8>, reverse: bool, with_score: bool, cache: Arc<Mutex<Keyspace>>) -> Self {
+        ZrankCommand { key, member, reverse, with_score, cache }
+    }
+
+    fn missing(&self) -> Vec<Vec<u8>> {
+        if self.with_score {
+            vec![create_null_array_resp()]
+        } else {
+            vec![create_null_bulk_string_resp()]
+        }
+    }
+}
+
+impl RedisCommand for ZrankCommand {
+    fn execute(&self, _: &mut Iter<'_, RespType>) -> Vec<Vec<u8>> {
+        let cache_guard = self.cache.lock().unwrap();
+        let zset = match cache_guard.get(&self.key) {
+            Some(CacheVal::SortedSet(zset)) => zset,
+            Some(_) => return vec![create_wrong_type_err_resp()],
+            None => return self.missing()
+        };
+        let rank = match zset.rank(&self.member) {
+            Some(rank) if self.reverse => zset.len() - 1 - rank,
+            Some(rank) => rank,
+            None => return self.missing()
+        };
+
+        if self.with_score {
+            let score = zset.score(&self.member).expect("ranked members have a score");
+            return vec![create_array_resp(vec![create_int_resp(rank), create_bulk_string_resp(format_float(score))])];
+        }
+        vec![create_int_resp(rank)]
+    }
+}
@@ -0,0 +1,29 @@
+use std::{slice::Iter, sync::{Arc, Mutex}};
+
+use crate::{commands::{format_float, RedisCommand}, redis::{client::CacheVal, keyspace::Keyspace}, resp::{create_bulk_string_resp, create_null_bulk_string_resp, create_wrong_type_err_resp, types::RespType}};
+
+pub struct ZscoreCommand {
+    key: Vec<u8>,
+    member: Vec<u8>,
+    cache: Arc<Mutex<Keyspace>>
+}
+
+impl ZscoreCommand {
+    pub fn new(key: Vec<u8>, member: Vec<u8>, cache: Arc<Mutex<Keyspace>>) -> Self {
+        ZscoreCommand { key, member, cache }
+    }
+}
+
+impl RedisCommand for ZscoreCommand {
+    fn execute(&self, _: &mut Iter<'_, RespType>) -> Vec<Vec<u8>> {
+        let cache_guard = self.cache.lock().unwrap();
+        match cache_guard.get(&self.key) {
+            Some(CacheVal::SortedSet(zset)) => match zset.score(&self.member) {
+                Some(score) => vec![create_bulk_string_resp(format_float(score))],
+                None => vec![create_null_bulk_string_resp()]
+            },
+            Some(_) => vec![create_wrong_type_err_resp()],
+            None => vec![create_null_bulk_string_resp()]
+        }
+    }
+}
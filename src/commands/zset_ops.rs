@@ -0,0 +1,227 @@
+use std::{collections::{HashMap, HashSet}, slice::Iter, sync::{Arc, Mutex}};
+
+use crate::{commands::{format_float, parse_arg}, redis::{client::CacheVal, keyspace::Keyspace, sorted_set::{parse_score, SortedSetCacheVal}}, resp::{create_array_resp, create_basic_err_resp, create_bulk_string_resp, create_int_resp, create_wrong_args_err_resp, create_wrong_type_err_resp, types::RespType}};
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum ZsetOp {
+    Union,
+    Inter,
+    Diff
+}
+
+#[derive(Clone, Copy)]
+pub enum Aggregate {
+    Sum,
+    Min,
+    Max
+}
+
+/// The parsed `numkeys key [key ...] [WEIGHTS ...] [AGGREGATE ...] [WITHSCORES]` tail shared by the commands.
+pub struct ZsetOpArgs {
+    pub keys: Vec<Vec<u8>>,
+    pub weights: Vec<f64>,
+    pub aggregate: Aggregate,
+    pub with_scores: bool
+}
+
+/// An input key, plain sets take part with every member scored 1 like in redis.
+enum Input<'a> {
+    Zset(&'a SortedSetCacheVal),
+    Set(&'a HashSet<Vec<u8>>)
+}
+
+impl Input<'_> {
+    fn len(&self) -> usize {
+        match self {
+            Input::Zset(zset) => zset.len(),
+            Input::Set(set) => set.len()
+        }
+    }
+
+    fn score(&self, member: &[u8]) -> Option<f64> {
+        match self {
+            Input::Zset(zset) => zset.score(member),
+            Input::Set(set) => if set.contains(member) { Some(1.0) } else { None }
+        }
+    }
+
+    fn members(&self) -> Box<dyn Iterator<Item = (&Vec<u8>, f64)> + '_> {
+        match self {
+            Input::Zset(zset) => Box::new(zset.dict.iter().map(|(member, score)| (member, *score))),
+            Input::Set(set) => Box::new(set.iter().map(|member| (member, 1.0)))
+        }
+    }
+}
+
+impl Aggregate {
+    fn apply(&self, acc: f64, score: f64) -> f64 {
+        match self {
+            // inf + -inf is NaN, which redis turns into 0
+            Aggregate::Sum => {
+                let sum = acc + score;
+                if sum.is_nan() { 0.0 } else { sum }
+            },
+            Aggregate::Min => acc.min(score),
+            Aggregate::Max => acc.max(score)
+        }
+    }
+}
+
+/// Parses the arguments following the destination (if any). WEIGHTS and AGGREGATE are only accepted for
+/// unions and intersections, WITHSCORES only when the result is replied instead of stored.
+pub fn parse_args(op: ZsetOp, command: &str, args: &[Vec<u8>], store: bool) -> Result<ZsetOpArgs, Vec<u8>> {
+    let numkeys = match args.first().map(|arg| parse_arg::<i64>(arg)) {
+        Some(Some(numkeys)) => numkeys,
+        _ => return Err(create_basic_err_resp("ERR value is not an integer or out of range".to_string()))
+    };
+    if numkeys < 1 {
+        return Err(create_basic_err_resp(format!("ERR at least 1 input key is needed for '{}' command", command)));
+    }
+    let numkeys = numkeys as usize;
+    if args.len() < numkeys + 1 {
+        return Err(create_basic_err_resp("ERR syntax error".to_string()));
+    }
+
+    let mut parsed = ZsetOpArgs { keys: args[1..=numkeys].to_vec(), weights: vec![1.0; numkeys], aggregate: Aggregate::Sum, with_scores: false };
+    let mut pos = numkeys + 1;
+    while pos < args.len() {
+        match args[pos].to_ascii_lowercase().as_slice() {
+            b"weights" if op != ZsetOp::Diff && args.len() > pos + numkeys => {
+                for (i, weight) in args[pos + 1..=pos + numkeys].iter().enumerate() {
+                    parsed.weights[i] = match parse_score(weight) {
+                        Some(weight) => weight,
+                        None => return Err(create_basic_err_resp("ERR weight value is not a float".to_string()))
+                    };
+                }
+                pos += numkeys + 1;
+            },
+            b"aggregate" if op != ZsetOp::Diff && args.len() > pos + 1 => {
+                parsed.aggregate = match args[pos + 1].to_ascii_lowercase().as_slice() {
+                    b"sum" => Aggregate::Sum,
+                    b"min" => Aggregate::Min,
+                    b"max" => Aggregate::Max,
+                    _ => return Err(create_basic_err_resp("ERR syntax error".to_string()))
+                };
+                pos += 2;
+            },
+            b"withscores" if !store => {
+                parsed.with_scores = true;
+                pos += 1;
+            },
+            _ => return Err(create_basic_err_resp("ERR syntax error".to_string()))
+        }
+    }
+    Ok(parsed)
+}
+
+/// Computes the result ordered by score then member, `None` if a key holds something other than a set or sorted set.
+pub fn apply(op: ZsetOp, cache: &Keyspace, args: &ZsetOpArgs) -> Option<Vec<(Vec<u8>, f64)>> {
+    let mut inputs = vec![];
+    for key in &args.keys {
+        match cache.get(key) {
+            Some(CacheVal::SortedSet(zset)) => inputs.push(Some(Input::Zset(zset))),
+            Some(CacheVal::Set(set_cache_val)) => inputs.push(Some(Input::Set(&set_cache_val.set))),
+            Some(_) => return None,
+            None => inputs.push(None)
+        }
+    }
+
+    let weighted = |score: f64, weight: f64| {
+        // 0 * inf is NaN, which redis also treats as 0
+        let score = score * weight;
+        if score.is_nan() { 0.0 } else { score }
+    };
+
+    let mut result: HashMap<Vec<u8>, f64> = HashMap::new();
+    match op {
+        ZsetOp::Union => {
+            for (input, weight) in inputs.iter().zip(&args.weights) {
+                let Some(input) = input else { continue };
+                for (member, score) in input.members() {
+                    let score = weighted(score, *weight);
+                    result.entry(member.clone()).and_modify(|acc| *acc = args.aggregate.apply(*acc, score)).or_insert(score);
+                }
+            }
+        },
+        ZsetOp::Inter => {
+            if inputs.iter().any(|input| input.is_none()) {
+                return Some(vec![]);
+            }
+            // walk the smallest input and probe the others
+            let mut order: Vec<usize> = (0..inputs.len()).collect();
+            order.sort_by_key(|i| inputs[*i].as_ref().map_or(0, |input| input.len()));
+            let smallest = inputs[order[0]].as_ref().unwrap();
+            'members: for (member, score) in smallest.members() {
+                let mut acc = weighted(score, args.weights[order[0]]);
+                for i in &order[1..] {
+                    match inputs[*i].as_ref().and_then(|input| input.score(member)) {
+                        Some(score) => acc = args.aggregate.apply(acc, weighted(score, args.weights[*i])),
+                        None => continue 'members
+                    }
+                }
+                result.insert(member.clone(), acc);
+            }
+        },
+        ZsetOp::Diff => {
+            let Some(Some(first)) = inputs.first() else { return Some(vec![]) };
+            for (member, score) in first.members() {
+                if !inputs[1..].iter().flatten().any(|input| input.score(member).is_some()) {
+                    result.insert(member.clone(), score);
+                }
+            }
+        }
+    }
+
+    let mut result: Vec<(Vec<u8>, f64)> = result.into_iter().collect();
+    result.sort_by(|(m1, s1), (m2, s2)| s1.total_cmp(s2).then_with(|| m1.cmp(m2)));
+    Some(result)
+}
+
+/// Replies with the result, or stores it at the destination and replies with its cardinality.
+pub fn reply_or_store(cache: &mut Keyspace, destination: &Option<Vec<u8>>, result: Vec<(Vec<u8>, f64)>, with_scores: bool) -> Vec<Vec<u8>> {
+    match destination {
+        Some(destination) => {
+            let mut zset = SortedSetCacheVal::new();
+            cache.remove(destination);
+            for (member, score) in result {
+                zset.insert(member, score);
+            }
+            let len = zset.len();
+            if !zset.is_empty() {
+                cache.insert(destination.clone(), CacheVal::SortedSet(zset));
+            }
+            vec![create_int_resp(len)]
+        },
+        None => {
+            let mut items = vec![];
+            for (member, score) in result {
+                items.push(create_bulk_string_resp(member));
+                if with_scores {
+                    items.push(create_bulk_string_resp(format_float(score)));
+                }
+            }
+            vec![create_array_resp(items)]
+        }
+    }
+}
+
+/// Runs one of the ZUNION/ZINTER/ZDIFF family end to end from the remaining arguments.
+pub fn execute(op: ZsetOp, command: &str, destination: &Option<Vec<u8>>, cache: &Arc<Mutex<Keyspace>>, iter: &mut Iter<'_, RespType>) -> Vec<Vec<u8>> {
+    let mut args = vec![];
+    while let Some(RespType::String(arg)) = iter.next() {
+        args.push(arg.clone());
+    }
+    if args.is_empty() {
+        return vec![create_wrong_args_err_resp(command)];
+    }
+    let args = match parse_args(op, command, &args, destination.is_some()) {
+        Ok(args) => args,
+        Err(err) => return vec![err]
+    };
+
+    let mut cache_guard = cache.lock().unwrap();
+    match apply(op, &cache_guard, &args) {
+        Some(result) => reply_or_store(&mut cache_guard, destination, result, args.with_scores),
+        None => vec![create_wrong_type_err_resp()]
+    }
+}
@@ -0,0 +1,22 @@
+use std::{slice::Iter, sync::{Arc, Mutex}};
+
+use crate::{commands::{zset_ops::{self, ZsetOp}, RedisCommand}, redis::{client::CacheVal, keyspace::Keyspace}, resp::types::RespType};
+
+/// Serves both ZUNION and ZUNIONSTORE, the latter when a destination is given.
+pub struct ZunionCommand {
+    destination: Option<Vec<u8>>,
+    cache: Arc<Mutex<Keyspace>>
+}
+
+impl ZunionCommand {
+    pub fn new(destination: Option<Vec<u8>>, cache: Arc<Mutex<Keyspace>>) -> Self {
+        ZunionCommand { destination, cache }
+    }
+}
+
+impl RedisCommand for ZunionCommand {
+    fn execute(&self, iter: &mut Iter<'_, RespType>) -> Vec<Vec<u8>> {
+        let command = if self.destination.is_some() { "zunionstore" } else { "zunion" };
+        zset_ops::execute(ZsetOp::Union, command, &self.destination, &self.cache, iter)
+    }
+}
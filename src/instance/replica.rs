@@ -2,36 +2,38 @@ use std::{collections::HashMap, io::{Read, Write}, net::{TcpListener, TcpStream}
 
 use bytes::BytesMut;
 
-use crate::{instance::Instance, redis::client::{CacheVal, Client}, rdb::rdb::Rdb, resp::{create_array_resp, create_bulk_string_resp, types::RespType}};
+use crate::{instance::Instance, redis::{blocking::Notifier, client::{CacheVal, Client}, keyspace::{Databases, ExpireMode, Keyspace}, replication::WriteCommands}, rdb::rdb::Rdb, resp::{create_array_resp, create_bulk_string_resp, types::RespType, RespError}};
 
 pub struct ReplicaInstance {
     port: String,
     rdb_dir: String,
     rdb_file: String,
     replica_of: Option<String>,
-    cache: Arc<Mutex<HashMap<String, CacheVal>>>,
-    channel_to_subscribers: Arc<Mutex<HashMap<String, Vec<String>>>>,
+    databases: Databases,
+    channel_to_subscribers: Arc<Mutex<HashMap<Vec<u8>, Vec<String>>>>,
     client_to_stream: Arc<Mutex<HashMap<String, TcpStream>>>,
-    write_commands: Arc<Mutex<Vec<String>>>
+    write_commands: Arc<Mutex<WriteCommands>>,
+    notifier: Arc<Notifier>
 }
 
 impl ReplicaInstance {
-    pub fn new(port: String, rdb_dir: String, rdb_file: String, replica_of: Option<String>) -> Self {
-        let cache = Arc::new(Mutex::new(HashMap::new()));
+    pub fn new(port: String, rdb_dir: String, rdb_file: String, databases: usize, replica_of: Option<String>) -> Self {
+        let databases: Databases = Arc::new((0..databases).map(|_| Arc::new(Mutex::new(Keyspace::with_expire_mode(ExpireMode::Replica)))).collect());
         let rdb_data = std::fs::read(format!("{}/{}", rdb_dir, rdb_file));
         match rdb_data {
             Ok(data) => {
                 let rdb = Rdb::new(BytesMut::from(&data[..]));
-                rdb.apply_to_db(cache.clone());
+                rdb.apply_to_db(&databases);
             }
             Err(e) => println!("Error reading RDB file: {} treat as empty", e)
         }
 
         ReplicaInstance { 
-            port, rdb_dir, rdb_file, replica_of, cache,  
+            port, rdb_dir, rdb_file, replica_of, databases,  
             channel_to_subscribers: Arc::new(Mutex::new(HashMap::new())), 
             client_to_stream: Arc::new(Mutex::new(HashMap::new())), 
-            write_commands: Arc::new(Mutex::new(vec![])) 
+            write_commands: Arc::new(Mutex::new(WriteCommands::default())),
+            notifier: Arc::new(Notifier::default())
         }
     }
 
@@ -39,17 +41,17 @@ impl ReplicaInstance {
         let master_instance_parts: Vec<String> = self.replica_of.clone().expect("should have master").clone().split(" ").map(String::from).collect();
         let mut master_stream = TcpStream::connect(format!("{}:{}", master_instance_parts[0].clone(), master_instance_parts[1].clone())).unwrap();
 
-        master_stream = Self::call_and_wait_for_response(master_stream, create_array_resp(vec![create_bulk_string_resp("PING".into())]));
-        master_stream = Self::call_and_wait_for_response(master_stream, create_array_resp(vec![create_bulk_string_resp("REPLCONF".into()), create_bulk_string_resp("listening-port".into()), create_bulk_string_resp(port.to_string())]));
-        master_stream = Self::call_and_wait_for_response(master_stream, create_array_resp(vec![create_bulk_string_resp("REPLCONF".into()), create_bulk_string_resp("capa".into()), create_bulk_string_resp("psync2".into())]));
+        master_stream = Self::call_and_wait_for_response(master_stream, create_array_resp(vec![create_bulk_string_resp("PING")]));
+        master_stream = Self::call_and_wait_for_response(master_stream, create_array_resp(vec![create_bulk_string_resp("REPLCONF"), create_bulk_string_resp("listening-port"), create_bulk_string_resp(port)]));
+        master_stream = Self::call_and_wait_for_response(master_stream, create_array_resp(vec![create_bulk_string_resp("REPLCONF"), create_bulk_string_resp("capa"), create_bulk_string_resp("psync2")]));
 
-        let psync_message = create_array_resp(vec![create_bulk_string_resp("PSYNC".into()), create_bulk_string_resp("?".into()), create_bulk_string_resp("-1".into())]);
-        master_stream.write_all(psync_message.as_bytes()).unwrap();
+        let psync_message = create_array_resp(vec![create_bulk_string_resp("PSYNC"), create_bulk_string_resp("?"), create_bulk_string_resp("-1")]);
+        master_stream.write_all(&psync_message).unwrap();
         master_stream
     }
 
-    fn call_and_wait_for_response(mut stream: TcpStream, message: String) -> TcpStream {
-        stream.write_all(message.as_bytes()).unwrap();
+    fn call_and_wait_for_response(mut stream: TcpStream, message: Vec<u8>) -> TcpStream {
+        stream.write_all(&message).unwrap();
         let mut response_buf = [0; 512];
         stream.read(&mut response_buf).unwrap();
         stream
@@ -57,31 +59,37 @@ impl ReplicaInstance {
 
     fn handle_master_connection(mut stream: TcpStream, mut client: Client) {
         let mut master_bytes_consumed = 0;
+        let mut rdb_received = false;
+        let mut buffer = BytesMut::new();
         loop {
             let mut buf = [0; 512];
             let read_count = stream.read(&mut buf).unwrap();
             if read_count == 0 {
                 break;
             }
-            let buffer = bytes::BytesMut::from(&buf[..read_count]);
+            buffer.extend_from_slice(&buf[..read_count]);
             let mut cur = 0;
             while cur < buffer.len() {
-                let resp_res = RespType::parse(&buffer, cur);
+                // the RDB file right after FULLRESYNC is the only bulk string the master sends
+                let is_rdb = !rdb_received && buffer[cur] == b'$';
+                let resp_res = if is_rdb { RespType::rdb_file(&buffer, cur) } else { RespType::parse(&buffer, cur) };
                 match resp_res {
-                    Ok(res) => {
-                        let commands = client.handle_command(res.0.clone());
-                        cur = res.1;
+                    Ok((resp, end)) => {
+                        rdb_received |= is_rdb;
+                        let commands = client.handle_command(resp.clone());
                         for command in commands.iter() {
-                            if command.eq("SEND_REPLCONF_ACK") {
-                                stream.write_all(create_array_resp(vec![create_bulk_string_resp("REPLCONF".into()), create_bulk_string_resp("ACK".into()), create_bulk_string_resp((master_bytes_consumed).to_string())]).as_bytes()).unwrap();
+                            if command.eq(b"SEND_REPLCONF_ACK") {
+                                stream.write_all(&create_array_resp(vec![create_bulk_string_resp("REPLCONF"), create_bulk_string_resp("ACK"), create_bulk_string_resp(master_bytes_consumed.to_string())])).unwrap();
                             }
                         }
-                        if let RespType::Array(arr) = &res.0 {
-                            let num_bytes = RespType::Array(arr.clone()).to_string().as_bytes().len();
-                            master_bytes_consumed += num_bytes;
+                        if let RespType::Array(_) = &resp {
+                            master_bytes_consumed += end - cur;
                             println!("Replica has consumed {} bytes from master", master_bytes_consumed);
                         }
+                        cur = end;
                     },
+                    // wait for the rest of the value
+                    Err(RespError::Incomplete) => break,
                     Err(e) => {
                         println!("ERR: {:?}", e);
                         cur += 1;
@@ -89,28 +97,33 @@ impl ReplicaInstance {
                 };
                 println!("{} CONSUMED OUT OF {}", cur, buffer.len());
             }
+            let _ = buffer.split_to(cur);
         }
     }
 
     fn handle_client_connection(mut stream: TcpStream, mut client: Client) {
+        let mut buffer = BytesMut::new();
         loop {
             let mut buf = [0; 512];
             let read_count = stream.read(&mut buf).unwrap();
             if read_count == 0 {
                 break;
             }
-            let buffer = bytes::BytesMut::from(&buf[..read_count]);
+            buffer.extend_from_slice(&buf[..read_count]);
             println!("received: {}", String::from_utf8_lossy(&buffer));
-            let resp_res = RespType::parse(&buffer, 0);
-            match resp_res {
-                Ok(res) => {
-                    let commands = client.handle_command(res.0);
-                    for command in commands {
-                        stream.write_all(command.as_bytes()).unwrap();
-                    }
-                },
-                Err(e) => panic!("ERROR {:?}", e),
-            };
+            // a command may span several reads and a read may hold several commands
+            loop {
+                let (cmd, end) = match RespType::parse(&buffer, 0) {
+                    Ok(res) => res,
+                    Err(RespError::Incomplete) => break,
+                    Err(e) => panic!("ERROR {:?}", e),
+                };
+                let _ = buffer.split_to(end);
+                let commands = client.handle_command(cmd);
+                for command in commands {
+                    stream.write_all(&command).unwrap();
+                }
+            }
         }
     }
 }
@@ -123,7 +136,7 @@ impl Instance for ReplicaInstance {
 
         // Create special stream with master
         let master_stream = self.handle_replica_handshake(self.port.clone());
-        let client = Client::new(self.cache.clone(), self.write_commands.clone(), Arc::new(Mutex::new(0)), self.replica_of.clone(), self.channel_to_subscribers.clone(), self.client_to_stream.clone(), self.rdb_dir.clone(), self.rdb_file.clone());
+        let client = Client::new(self.databases.clone(), self.write_commands.clone(), Arc::new(Mutex::new(0)), self.notifier.clone(), self.replica_of.clone(), self.channel_to_subscribers.clone(), self.client_to_stream.clone(), self.rdb_dir.clone(), self.rdb_file.clone());
         thread::spawn(move || {
             Self::handle_master_connection(master_stream, client);
         });
@@ -134,8 +147,8 @@ impl Instance for ReplicaInstance {
                 Ok(stream) => {
                     println!("accepted new connection");
                     let client = Client::new(
-                        self.cache.clone(), self.write_commands.clone(), 
-                        Arc::new(Mutex::new(0)),  self.replica_of.clone(), self.channel_to_subscribers.clone(), 
+                        self.databases.clone(), self.write_commands.clone(), 
+                        Arc::new(Mutex::new(0)), self.notifier.clone(), self.replica_of.clone(), self.channel_to_subscribers.clone(), 
                         self.client_to_stream.clone(), self.rdb_dir.clone(), self.rdb_file.clone()
                     );
                     self.client_to_stream.lock().unwrap().insert(client.id.clone(), stream.try_clone().unwrap());
@@ -26,16 +26,19 @@ struct Args {
     // RDB file
     #[arg(long, default_value = "dump.rdb")]
     dbfilename: String,
+    /// Number of logical databases, selected with SELECT
+    #[arg(long, default_value = "16", value_parser = clap::value_parser!(u32).range(1..))]
+    databases: u32,
 }
 
 fn main() {
     // Parse command line arguments
     let args = Args::parse();
     if args.replicaof.is_some() {
-        let instance = ReplicaInstance::new(args.port.to_string(), args.dir.clone(), args.dbfilename.clone(), args.replicaof.clone());
+        let instance = ReplicaInstance::new(args.port.to_string(), args.dir.clone(), args.dbfilename.clone(), args.databases as usize, args.replicaof.clone());
         instance.start();
     } else {
-        let instance = MasterInstance::new(args.port.to_string(), args.dir.clone(), args.dbfilename.clone());
+        let instance = MasterInstance::new(args.port.to_string(), args.dir.clone(), args.dbfilename.clone(), args.databases as usize);
         instance.start();
     }
 }
\ No newline at end of file
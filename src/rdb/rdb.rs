@@ -1,8 +1,8 @@
-use std::{collections::HashMap, sync::{Arc, Mutex}};
+use std::collections::HashMap;
 
 use bytes::BytesMut;
 
-use crate::redis::client::{CacheVal, StringCacheVal};
+use crate::redis::{client::{CacheVal, StringCacheVal}, keyspace::Databases};
 
 pub struct Rdb {
     version: String,
@@ -11,29 +11,105 @@ pub struct Rdb {
 }
 
 pub struct KeyValue {
-    key: String,
-    value: String,
+    db: usize,
+    key: Vec<u8>,
+    value: Vec<u8>,
     expiry_time: Option<u128>,
 }
 
 impl Rdb {
     pub fn new(rdb_data: BytesMut) -> Self {
         let (version, pos) = Self::extract_version(&rdb_data, 0);
-        let (metadata, pos) = Self::extarct_metadata_section(&rdb_data, pos);
+        let (metadata, mut pos) = Self::extarct_metadata_section(&rdb_data, pos);
 
-        if rdb_data[pos] == 0xFF {
-            println!("No keys");
-            return Self { version: version, metadata: metadata, key_values: Vec::new() };
+        // a section per db holding keys, up to the 0xFF end of file
+        let mut key_values = Vec::new();
+        while rdb_data[pos] == 0xFE {
+            let (db_key_values, new_pos) = Self::extract_key_values(&rdb_data, pos + 1);
+            key_values.extend(db_key_values);
+            pos = new_pos;
         }
-
-        let (key_values, pos) = Self::extract_key_values(&rdb_data, pos + 1);
         Self { version: version, metadata: metadata, key_values: key_values }
     }
 
-    pub fn apply_to_db(&self, cache: Arc<Mutex<HashMap<String, CacheVal>>>) {
-        let mut cache = cache.lock().unwrap();
+    pub fn apply_to_db(&self, databases: &Databases) {
         for key_value in self.key_values.iter() {
-            cache.insert(key_value.key.clone(), CacheVal::String(StringCacheVal { val: key_value.value.clone(), expiry_time: key_value.expiry_time.clone() }));
+            let mut cache = match databases.get(key_value.db) {
+                Some(cache) => cache.lock().unwrap(),
+                None => {
+                    println!("Skipping key of db {}, only {} databases are configured", key_value.db, databases.len());
+                    continue;
+                }
+            };
+            cache.insert(key_value.key.clone(), CacheVal::String(StringCacheVal::new(key_value.value.clone())));
+            cache.set_expiry(&key_value.key, key_value.expiry_time);
+        }
+    }
+
+    /// Serializes the string keys of every db, each non-empty db in its own 0xFE section, in the
+    /// format `new` reads back. Other types are left out as the loader only knows strings.
+    pub fn dump(databases: &Databases) -> Vec<u8> {
+        let mut rdb_data = b"REDIS0011".to_vec();
+        rdb_data.push(0xFA);
+        Self::write_string(&mut rdb_data, b"redis-ver");
+        Self::write_string(&mut rdb_data, b"7.2.0");
+
+        for (db, cache) in databases.iter().enumerate() {
+            // one db at a time, so connections on the others aren't held up
+            let cache = cache.lock().unwrap();
+            let strings: Vec<_> = cache.iter().filter_map(|(key, val)| match val {
+                CacheVal::String(string_cache_val) => Some((key, &string_cache_val.val, cache.expiry(key))),
+                _ => None
+            }).collect();
+            if strings.is_empty() {
+                continue;
+            }
+            rdb_data.push(0xFE);
+            Self::write_length(&mut rdb_data, db);
+            rdb_data.push(0xFB);
+            Self::write_length(&mut rdb_data, strings.len());
+            Self::write_length(&mut rdb_data, strings.iter().filter(|(_, _, expiry_time)| expiry_time.is_some()).count());
+            for (key, val, expiry_time) in strings {
+                if let Some(expiry_time) = expiry_time {
+                    rdb_data.push(0xFC);
+                    rdb_data.extend_from_slice(&(expiry_time as u64).to_le_bytes());
+                }
+                // string value type
+                rdb_data.push(0x00);
+                Self::write_string(&mut rdb_data, key);
+                Self::write_string(&mut rdb_data, val);
+            }
+        }
+
+        // no checksum, which readers take as a disabled one
+        rdb_data.push(0xFF);
+        rdb_data.extend_from_slice(&[0; 8]);
+        rdb_data
+    }
+
+    fn write_length(rdb_data: &mut Vec<u8>, len: usize) {
+        match len {
+            0..=0x3F => rdb_data.push(len as u8),
+            0x40..=0x3FFF => rdb_data.extend_from_slice(&(0x4000 | len as u16).to_be_bytes()),
+            _ => {
+                rdb_data.push(0x80);
+                rdb_data.extend_from_slice(&(len as u32).to_be_bytes());
+            }
+        }
+    }
+
+    fn write_string(rdb_data: &mut Vec<u8>, val: &[u8]) {
+        Self::write_length(rdb_data, val.len());
+        rdb_data.extend_from_slice(val);
+    }
+
+    /// Reads a length in the 6, 14 or 32 bit encoding picked by its first two bits.
+    fn extract_length(rdb_data: &BytesMut, pos: usize) -> (usize, usize) {
+        match rdb_data[pos] >> 6 {
+            0 => ((rdb_data[pos] & 0x3F) as usize, pos + 1),
+            1 => ((((rdb_data[pos] & 0x3F) as usize) << 8) | rdb_data[pos + 1] as usize, pos + 2),
+            2 => (u32::from_be_bytes(rdb_data[pos + 1..pos + 5].try_into().unwrap()) as usize, pos + 5),
+            _ => panic!("Invalid length byte: {}", rdb_data[pos])
         }
     }
 
@@ -41,7 +117,7 @@ impl Rdb {
         String::from_utf8_lossy(&rdb_data[pos..pos+bytes]).to_string()
     }
 
-    fn extract_string(rdb_data: &BytesMut, pos: usize) -> (String, usize) {
+    fn extract_string(rdb_data: &BytesMut, pos: usize) -> (Vec<u8>, usize) {
         let mut cur_pos = pos;
         let type_byte = rdb_data[cur_pos];
         if type_byte & 0xc0 == 0xc0 {
@@ -50,25 +126,25 @@ impl Rdb {
                 0 => {
                     // 1 byte
                     let byte = i8::from_le_bytes(rdb_data[cur_pos + 1..cur_pos + 2].try_into().unwrap());
-                    return (byte.to_string(), cur_pos + 2);
+                    return (byte.to_string().into_bytes(), cur_pos + 2);
                 }
                 1 => {
                     // 2 bytes
                     let byte = i16::from_le_bytes(rdb_data[cur_pos + 1..cur_pos + 3].try_into().unwrap());
-                    return (byte.to_string(), cur_pos + 3);
+                    return (byte.to_string().into_bytes(), cur_pos + 3);
                 }
                 2 => {
                     // 4 bytes
                     let byte = i32::from_le_bytes(rdb_data[cur_pos + 1..cur_pos + 5].try_into().unwrap());
-                    return (byte.to_string(), cur_pos + 5);
+                    return (byte.to_string().into_bytes(), cur_pos + 5);
                 },
                 _ => panic!("Invalid string type byte: {}", type_byte)
             }
         } else {
             // length-prefixed string
-            let str_len = rdb_data[cur_pos] as usize;
-            cur_pos += 1;
-            return (Self::read_string(rdb_data, cur_pos, str_len), cur_pos + str_len);
+            let (str_len, new_pos) = Self::extract_length(rdb_data, cur_pos);
+            cur_pos = new_pos;
+            return (rdb_data[cur_pos..cur_pos + str_len].to_vec(), cur_pos + str_len);
         }
     }
 
@@ -111,7 +187,7 @@ impl Rdb {
                     cur_pos = new_pos;
                     let (val, new_pos) = Self::extract_string(rdb_data, cur_pos);
                     cur_pos = new_pos;
-                    metadata.insert(key, val);
+                    metadata.insert(String::from_utf8_lossy(&key).into_owned(), String::from_utf8_lossy(&val).into_owned());
                 }
             }
         }
@@ -120,21 +196,19 @@ impl Rdb {
     }
 
     fn extract_key_values(rdb_data: &BytesMut, pos: usize) -> (Vec<KeyValue>, usize) {
-        let mut cur_pos = pos;
-        let db_index = rdb_data[cur_pos] as usize;
+        let (db_index, mut cur_pos) = Self::extract_length(rdb_data, pos);
         println!("DB index: {}", db_index);
-        cur_pos += 1;
 
         assert_eq!(rdb_data[cur_pos], 0xFB);
         cur_pos += 1;
 
-        let total_keys = rdb_data[cur_pos] as usize;
+        let (total_keys, new_pos) = Self::extract_length(rdb_data, cur_pos);
         println!("Total keys: {}", total_keys);
-        cur_pos += 1;
+        cur_pos = new_pos;
 
-        let expiring_keys = rdb_data[cur_pos] as usize;
+        let (expiring_keys, new_pos) = Self::extract_length(rdb_data, cur_pos);
         println!("Expiring keys: {}", expiring_keys);
-        cur_pos += 1;
+        cur_pos = new_pos;
 
 
         let mut key_values = Vec::new();
@@ -169,7 +243,7 @@ impl Rdb {
             cur_pos = new_pos;
             let (val, new_pos) = Self::extract_string(rdb_data, cur_pos);
             cur_pos = new_pos;
-            key_values.push(KeyValue { key: key, value: val, expiry_time: key_expiry_time });
+            key_values.push(KeyValue { db: db_index, key, value: val, expiry_time: key_expiry_time });
         }
 
         return (key_values, cur_pos);
@@ -185,4 +259,46 @@ pub mod tests {
         let rdb = Rdb::new(BytesMut::from(&rdb_data[..]));
         assert_eq!(rdb.version, "0011");
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_multiple_dbs() {
+        let mut rdb_data = b"REDIS0011\xFA\x09redis-ver\x057.2.0".to_vec();
+        rdb_data.extend_from_slice(b"\xFE\x00\xFB\x01\x00\x00\x01a\x011");
+        rdb_data.extend_from_slice(b"\xFE\x03\xFB\x02\x00\x00\x01b\x012\x00\x01c\x013");
+        rdb_data.extend_from_slice(b"\xFE\x09\xFB\x01\x00\x00\x01d\x014");
+        rdb_data.extend_from_slice(b"\xFF\x00\x00\x00\x00\x00\x00\x00\x00");
+        let rdb = Rdb::new(BytesMut::from(&rdb_data[..]));
+        let dbs: Vec<usize> = rdb.key_values.iter().map(|key_value| key_value.db).collect();
+        assert_eq!(dbs, vec![0, 3, 3, 9]);
+
+        // keys of dbs beyond the configured ones are left out
+        let databases: Databases = std::sync::Arc::new((0..4).map(|_| Default::default()).collect());
+        rdb.apply_to_db(&databases);
+        assert!(databases[0].lock().unwrap().contains_key(b"a"));
+        assert_eq!(databases[3].lock().unwrap().iter().count(), 2);
+        assert!(databases[1].lock().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_dump_round_trip() {
+        let databases: Databases = std::sync::Arc::new((0..4).map(|_| Default::default()).collect());
+        let long_val = vec![b'x'; 300];
+        {
+            let mut cache = databases[0].lock().unwrap();
+            cache.insert(b"a".to_vec(), CacheVal::String(StringCacheVal::new(b"1".to_vec())));
+            cache.insert(b"long".to_vec(), CacheVal::String(StringCacheVal::new(long_val.clone())));
+            cache.set_expiry(b"long", Some(u64::MAX as u128 / 2));
+        }
+        databases[2].lock().unwrap().insert(b"b".to_vec(), CacheVal::String(StringCacheVal::new(b"2".to_vec())));
+
+        let rdb = Rdb::new(BytesMut::from(&Rdb::dump(&databases)[..]));
+        let loaded: Databases = std::sync::Arc::new((0..4).map(|_| Default::default()).collect());
+        rdb.apply_to_db(&loaded);
+        let cache = loaded[0].lock().unwrap();
+        assert!(matches!(cache.get(b"long"), Some(CacheVal::String(val)) if val.val == long_val));
+        assert_eq!(cache.expiry(b"long"), Some(u64::MAX as u128 / 2));
+        assert_eq!(cache.expiry(b"a"), None);
+        assert!(loaded[1].lock().unwrap().is_empty());
+        assert!(matches!(loaded[2].lock().unwrap().get(b"b"), Some(CacheVal::String(val)) if val.val == b"2"));
+    }
+}
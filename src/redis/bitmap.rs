@@ -0,0 +1,197 @@
+use crate::{commands::parse_arg, redis::string::clamp_offsets, resp::create_basic_err_resp};
+
+/// Strings are capped at 512MB, so bit offsets have to stay below 2^32.
+pub const MAX_BIT_OFFSET: u64 = 512 * 1024 * 1024 * 8;
+
+/// Bits are numbered from the most significant bit of the first byte, so bit 0 is `0x80` of byte 0.
+pub fn get_bit(bytes: &[u8], offset: usize) -> u8 {
+    match bytes.get(offset / 8) {
+        Some(byte) => (byte >> (7 - offset % 8)) & 1,
+        None => 0
+    }
+}
+
+/// Sets a bit, growing `bytes` with zeros when it is too short.
+pub fn set_bit(bytes: &mut Vec<u8>, offset: usize, bit: u8) {
+    if bytes.len() <= offset / 8 {
+        bytes.resize(offset / 8 + 1, 0);
+    }
+    let mask = 1 << (7 - offset % 8);
+    if bit == 1 {
+        bytes[offset / 8] |= mask;
+    } else {
+        bytes[offset / 8] &= !mask;
+    }
+}
+
+/// Counts the set bits between `first` and `last`, both inclusive bit offsets within `bytes`.
+pub fn count_bits(bytes: &[u8], first: usize, last: usize) -> usize {
+    let (first_byte, last_byte) = (first / 8, last / 8);
+    let first_mask = 0xffu8 >> (first % 8);
+    let last_mask = 0xffu8 << (7 - last % 8);
+    if first_byte == last_byte {
+        return (bytes[first_byte] & first_mask & last_mask).count_ones() as usize;
+    }
+    let middle: usize = bytes[first_byte + 1..last_byte].iter().map(|byte| byte.count_ones() as usize).sum();
+    middle + (bytes[first_byte] & first_mask).count_ones() as usize + (bytes[last_byte] & last_mask).count_ones() as usize
+}
+
+/// The offset of the first bit equal to `bit` between `first` and `last`, both inclusive.
+pub fn find_bit(bytes: &[u8], bit: u8, first: usize, last: usize) -> Option<usize> {
+    // whole bytes without a match are skipped without looking at their bits
+    let skip = if bit == 1 { 0x00 } else { 0xff };
+    let mut offset = first;
+    while offset <= last {
+        if offset.is_multiple_of(8) && offset + 7 <= last && bytes[offset / 8] == skip {
+            offset += 8;
+            continue;
+        }
+        if get_bit(bytes, offset) == bit {
+            return Some(offset);
+        }
+        offset += 1;
+    }
+    None
+}
+
+/// An integer type of BITFIELD, `i1` to `i64` or `u1` to `u63`.
+#[derive(Clone, Copy)]
+pub struct BitfieldType {
+    pub signed: bool,
+    pub bits: u32
+}
+
+impl BitfieldType {
+    pub fn parse(arg: &[u8]) -> Result<Self, Vec<u8>> {
+        let invalid = || create_basic_err_resp("ERR Invalid bitfield type. Use something like i16 u8. Note that u64 is not supported but i64 is.".to_string());
+        let signed = match arg.first() {
+            Some(b'i') | Some(b'I') => true,
+            Some(b'u') | Some(b'U') => false,
+            _ => return Err(invalid())
+        };
+        match parse_arg::<u32>(&arg[1..]) {
+            Some(bits) if bits >= 1 && (bits <= 63 || (signed && bits == 64)) => Ok(BitfieldType { signed, bits }),
+            _ => Err(invalid())
+        }
+    }
+
+    fn range(&self) -> (i128, i128) {
+        if self.signed {
+            (-(1 << (self.bits - 1)), (1 << (self.bits - 1)) - 1)
+        } else {
+            (0, (1 << self.bits) - 1)
+        }
+    }
+
+    /// Reads the integer starting at bit `offset`, bits past the end of `bytes` being zero.
+    pub fn read(&self, bytes: &[u8], offset: usize) -> i64 {
+        let mut val: u64 = 0;
+        for i in 0..self.bits as usize {
+            val = (val << 1) | get_bit(bytes, offset + i) as u64;
+        }
+        if self.signed && self.bits < 64 && val >> (self.bits - 1) == 1 {
+            // sign extend
+            val |= u64::MAX << self.bits;
+        }
+        val as i64
+    }
+
+    pub fn write(&self, bytes: &mut Vec<u8>, offset: usize, val: i64) {
+        for i in 0..self.bits as usize {
+            set_bit(bytes, offset + i, ((val as u64 >> (self.bits as usize - 1 - i)) & 1) as u8);
+        }
+    }
+
+    /// Fits `val` into the type as OVERFLOW says, `None` when it does not fit and overflows FAIL.
+    pub fn fit(&self, val: i128, overflow: Overflow) -> Option<i64> {
+        let (min, max) = self.range();
+        if (min..=max).contains(&val) {
+            return Some(val as i64);
+        }
+        match overflow {
+            Overflow::Wrap => {
+                let modulus = 1i128 << self.bits;
+                let mut wrapped = val.rem_euclid(modulus);
+                if wrapped > max {
+                    wrapped -= modulus;
+                }
+                Some(wrapped as i64)
+            },
+            Overflow::Sat => Some(val.clamp(min, max) as i64),
+            Overflow::Fail => None
+        }
+    }
+}
+
+/// The OVERFLOW behaviour of BITFIELD for the SET and INCRBY operations that follow it.
+#[derive(Clone, Copy)]
+pub enum Overflow {
+    Wrap,
+    Sat,
+    Fail
+}
+
+impl Overflow {
+    pub fn parse(arg: &[u8]) -> Option<Self> {
+        match arg.to_ascii_lowercase().as_slice() {
+            b"wrap" => Some(Overflow::Wrap),
+            b"sat" => Some(Overflow::Sat),
+            b"fail" => Some(Overflow::Fail),
+            _ => None
+        }
+    }
+}
+
+/// Parses a bit offset, which BITFIELD also accepts as `#n` meaning the n-th field of `bits` bits.
+pub fn parse_bit_offset(arg: &[u8], bits: Option<u32>) -> Result<usize, Vec<u8>> {
+    let offset = match (arg.strip_prefix(b"#"), bits) {
+        (Some(index), Some(bits)) => parse_arg::<u64>(index).and_then(|index| index.checked_mul(bits as u64)),
+        _ => parse_arg::<u64>(arg)
+    };
+    match offset {
+        Some(offset) if offset < MAX_BIT_OFFSET => Ok(offset as usize),
+        _ => Err(create_basic_err_resp("ERR bit offset is not an integer or out of range".to_string()))
+    }
+}
+
+/// The `start [end [BYTE|BIT]]` range of BITCOUNT and BITPOS.
+pub struct BitRange {
+    start: i64,
+    end: Option<i64>,
+    bit_unit: bool
+}
+
+impl BitRange {
+    /// Parses the arguments after the key (and bit for BITPOS), `None` when there is no range.
+    pub fn parse(args: &[Vec<u8>], end_required: bool) -> Result<Option<Self>, Vec<u8>> {
+        let parse_offset = |arg: &Vec<u8>| parse_arg::<i64>(arg).ok_or_else(|| create_basic_err_resp("ERR value is not an integer or out of range".to_string()));
+        let (start, end, unit) = match args {
+            [] => return Ok(None),
+            [start] if !end_required => (start, None, None),
+            [start, end] => (start, Some(end), None),
+            [start, end, unit] => (start, Some(end), Some(unit)),
+            _ => return Err(create_basic_err_resp("ERR syntax error".to_string()))
+        };
+        let bit_unit = match unit {
+            None => false,
+            Some(unit) if unit.eq_ignore_ascii_case(b"byte") => false,
+            Some(unit) if unit.eq_ignore_ascii_case(b"bit") => true,
+            Some(_) => return Err(create_basic_err_resp("ERR syntax error".to_string()))
+        };
+        Ok(Some(BitRange { start: parse_offset(start)?, end: end.map(parse_offset).transpose()?, bit_unit }))
+    }
+
+    pub fn has_end(&self) -> bool {
+        self.end.is_some()
+    }
+
+    /// The first and last bit offsets the range covers in a value of `len` bytes.
+    pub fn resolve(&self, len: usize) -> Option<(usize, usize)> {
+        let end = self.end.unwrap_or(-1);
+        if self.bit_unit {
+            clamp_offsets(self.start, end, len * 8)
+        } else {
+            clamp_offsets(self.start, end, len).map(|(start, end)| (start * 8, end * 8 + 7))
+        }
+    }
+}
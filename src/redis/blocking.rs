@@ -0,0 +1,207 @@
+use std::{collections::{HashMap, VecDeque}, sync::{Arc, Condvar, Mutex, MutexGuard}, time::Duration};
+
+use crate::redis::{keyspace::Keyspace, stream::now_ms};
+
+/// A blocked connection. Its flag is raised when something it watches changes and it sleeps on
+/// the condvar until then, so a notification sent between a readiness check and the sleep is not lost.
+#[derive(Default)]
+struct Waiter {
+    ready: Mutex<bool>,
+    condvar: Condvar
+}
+
+impl Waiter {
+    fn notify(&self) {
+        *self.ready.lock().unwrap() = true;
+        self.condvar.notify_one();
+    }
+}
+
+/// The connections blocked on each key of each db, in the order they blocked.
+type BlockQueues = HashMap<(usize, Vec<u8>), VecDeque<String>>;
+
+/// Tells blocked connections when the keys they wait on are written to, or when replicas
+/// acknowledge the replication stream. Writers signal after changing the cache, waiters look at
+/// the cache again once woken up.
+#[derive(Default)]
+pub struct Notifier {
+    keys: Mutex<HashMap<Vec<u8>, Vec<Arc<Waiter>>>>,
+    /// Kept out of the keyspace so a key nobody wrote to yet stays absent while connections wait on it.
+    queues: Mutex<BlockQueues>,
+    replica_acks: Mutex<Vec<Arc<Waiter>>>
+}
+
+/// A registration with the notifier, removed again when dropped.
+pub struct Watch<'a> {
+    notifier: &'a Notifier,
+    keys: Vec<Vec<u8>>,
+    replica_acks: bool,
+    waiter: Arc<Waiter>
+}
+
+impl Notifier {
+    /// Starts watching `keys`. Anything written to them from now on wakes the watch up.
+    pub fn watch_keys(&self, keys: &[Vec<u8>]) -> Watch<'_> {
+        let waiter = Arc::new(Waiter::default());
+        let mut keys_guard = self.keys.lock().unwrap();
+        for key in keys {
+            keys_guard.entry(key.clone()).or_default().push(waiter.clone());
+        }
+        Watch { notifier: self, keys: keys.to_vec(), replica_acks: false, waiter }
+    }
+
+    pub fn watch_replica_acks(&self) -> Watch<'_> {
+        let waiter = Arc::new(Waiter::default());
+        self.replica_acks.lock().unwrap().push(waiter.clone());
+        Watch { notifier: self, keys: vec![], replica_acks: true, waiter }
+    }
+
+    /// Wakes every connection blocked on `key`.
+    pub fn notify_key(&self, key: &[u8]) {
+        if let Some(waiters) = self.keys.lock().unwrap().get(key) {
+            for waiter in waiters {
+                waiter.notify();
+            }
+        }
+    }
+
+    /// Puts `connection_id` in line for `key` of `db` unless it already is, returning whether it is
+    /// at the front, the only place from which it may take from the key.
+    pub fn enqueue(&self, db: usize, key: &[u8], connection_id: &str) -> bool {
+        let mut queues_guard = self.queues.lock().unwrap();
+        let queue = queues_guard.entry((db, key.to_vec())).or_default();
+        if !queue.iter().any(|id| id == connection_id) {
+            queue.push_back(connection_id.to_string());
+        }
+        queue.front().is_some_and(|id| id == connection_id)
+    }
+
+    /// Takes `connection_id` out of the line for `key` of `db`, waking whoever is next.
+    pub fn dequeue(&self, db: usize, key: &[u8], connection_id: &str) {
+        let mut queues_guard = self.queues.lock().unwrap();
+        let queue = match queues_guard.get_mut(&(db, key.to_vec())) {
+            Some(queue) => queue,
+            None => return
+        };
+        queue.retain(|id| id != connection_id);
+        if queue.is_empty() {
+            queues_guard.remove(&(db, key.to_vec()));
+            return;
+        }
+        drop(queues_guard);
+        self.notify_key(key);
+    }
+
+    /// The keys of `db` connections are blocked on.
+    pub fn blocked_keys(&self, db: usize) -> Vec<Vec<u8>> {
+        self.queues.lock().unwrap().keys().filter(|(key_db, _)| *key_db == db).map(|(_, key)| key.clone()).collect()
+    }
+
+    pub fn notify_replica_acks(&self) {
+        for waiter in self.replica_acks.lock().unwrap().iter() {
+            waiter.notify();
+        }
+    }
+}
+
+impl Watch<'_> {
+    /// Forgets earlier notifications. Call it before looking at what is watched, so only
+    /// changes made after that look wake the next `wait` up.
+    pub fn reset(&self) {
+        *self.waiter.ready.lock().unwrap() = false;
+    }
+
+    /// Sleeps until notified or until `expiration` (unix ms, `u128::MAX` meaning forever) passes.
+    pub fn wait(&self, expiration: u128) {
+        let ready = self.waiter.ready.lock().unwrap();
+        if expiration == u128::MAX {
+            let _ready = self.waiter.condvar.wait_while(ready, |ready| !*ready).unwrap();
+            return;
+        }
+        let now = now_ms();
+        if expiration > now {
+            let timeout = Duration::from_millis((expiration - now).min(u64::MAX as u128) as u64);
+            let _ready = self.waiter.condvar.wait_timeout_while(ready, timeout, |ready| !*ready).unwrap();
+        }
+    }
+}
+
+impl Drop for Watch<'_> {
+    fn drop(&mut self) {
+        let mut keys_guard = self.notifier.keys.lock().unwrap();
+        for key in &self.keys {
+            if let Some(waiters) = keys_guard.get_mut(key) {
+                waiters.retain(|waiter| !Arc::ptr_eq(waiter, &self.waiter));
+                if waiters.is_empty() {
+                    keys_guard.remove(key);
+                }
+            }
+        }
+        if self.replica_acks {
+            self.notifier.replica_acks.lock().unwrap().retain(|waiter| !Arc::ptr_eq(waiter, &self.waiter));
+        }
+    }
+}
+
+/// The connection a blocking command runs for, how long it may wait and where.
+pub struct BlockingArgs {
+    connection_id: String,
+    /// 0 waits forever.
+    timeout_seconds: f32,
+    db: usize,
+    notifier: Arc<Notifier>
+}
+
+impl BlockingArgs {
+    pub fn new(connection_id: String, timeout_seconds: f32, db: usize, notifier: Arc<Notifier>) -> Self {
+        BlockingArgs { connection_id, timeout_seconds, db, notifier }
+    }
+
+    /// Puts the connection in line for `key`, returning whether it is at the front.
+    pub fn enqueue(&self, key: &[u8]) -> bool {
+        self.notifier.enqueue(self.db, key, &self.connection_id)
+    }
+
+    /// Wakes every connection blocked on `key`.
+    pub fn notify_key(&self, key: &[u8]) {
+        self.notifier.notify_key(key);
+    }
+
+    /// Runs the wait of a blocking command. `take` is tried on the locked keyspace, told whether the
+    /// connection blocked already, and queues the connection on the keys it looks at. While it finds
+    /// nothing the connection sleeps until one of `keys` is written to or the timeout runs out. The
+    /// queues are left whatever the outcome. Gives what was taken with the keyspace still locked,
+    /// `None` on timeout.
+    pub fn block_on_keys<'a, T>(&self, cache: &'a Mutex<Keyspace>, keys: &[Vec<u8>],
+            mut take: impl FnMut(&mut Keyspace, bool) -> Result<Option<T>, Vec<u8>>) -> Result<Option<(MutexGuard<'a, Keyspace>, T)>, Vec<u8>> {
+        let expiration = if self.timeout_seconds != 0.0 {
+            Some(now_ms() + ((self.timeout_seconds * 1000.0) as u128))
+        } else {
+            None
+        };
+
+        // sleep until one of the keys is written to instead of spinning on the cache
+        let watch = self.notifier.watch_keys(keys);
+        let mut blocked = false;
+        let taken = loop {
+            let mut cache_guard = cache.lock().unwrap();
+            watch.reset();
+            match take(&mut cache_guard, blocked) {
+                Ok(Some(taken)) => break Ok(Some((cache_guard, taken))),
+                // give up if you have waited too long
+                Ok(None) if expiration.is_some_and(|expiration| now_ms() > expiration) => break Ok(None),
+                Ok(None) => {},
+                Err(err) => break Err(err)
+            }
+            drop(cache_guard);
+            blocked = true;
+            watch.wait(expiration.unwrap_or(u128::MAX));
+        };
+
+        // leave the line of every key, waking whoever is next
+        for key in keys {
+            self.notifier.dequeue(self.db, key, &self.connection_id);
+        }
+        taken
+    }
+}
@@ -1,38 +1,56 @@
 use core::num;
-use std::{collections::{HashMap, HashSet}, fmt::format, io::{Read, Write}, net::TcpStream, slice::Iter, str::FromStr, sync::{Arc, Mutex}, thread};
+use std::{collections::{BTreeMap, HashMap, HashSet, VecDeque}, fmt::format, io::{Read, Write}, net::TcpStream, slice::Iter, str::FromStr, sync::{Arc, Mutex}, thread};
 
 use bytes::BytesMut;
 
-use crate::{commands::{blpop::BlpopCommand, echo::EchoCommand, get::{self, GetCommand}, incr::IncrCommand, info::InfoCommand, keys::KeysCommand, llen::LlenCommand, lpop::LpopCommand, lpush::LpushCommand, lrange::LrangeCommand, ping::PingCommand, psync::PsyncCommand, publish::PublishCommand, replconf::ReplConfCommand, rpush::RpushCommand, set::SetCommand, subscribe::SubscribeCommand, type_command::TypeCommand, unsubscribe::UnsubscribeCommand, wait::WaitCommand, xadd::XaddCommand, xrange::XrangeCommand, xread::XreadCommand, RedisCommand}, resp::{create_array_resp, create_basic_err_resp, create_bulk_string_resp, create_int_resp, create_null_bulk_string_resp, create_simple_string_resp, types::RespType}};
+use crate::{commands::{blpop::{BlockingListOp, BlpopCommand}, echo::EchoCommand, get::{self, GetCommand}, incr::IncrCommand, incrbyfloat::IncrbyfloatCommand, setbit::SetbitCommand, getbit::GetbitCommand, bitcount::BitcountCommand, bitpos::BitposCommand, bitop::{BitOperation, BitopCommand}, bitfield::BitfieldCommand, pfadd::PfaddCommand, pfcount::PfcountCommand, pfmerge::PfmergeCommand, info::InfoCommand, keys::KeysCommand, del::DelCommand, exists::ExistsCommand, rename::RenameCommand, copy::CopyCommand, randomkey::RandomkeyCommand, dbsize::DbsizeCommand, expire::{parse_expire_time, ExpireCommand, ExpireFlags}, ttl::{TtlCommand, TtlReply}, persist::PersistCommand, scan::{ScanCommand, ScanTarget}, move_command::MoveCommand, swapdb::SwapdbCommand, flushdb::FlushCommand, save::SaveCommand, llen::LlenCommand, lpop::LpopCommand, lindex::LindexCommand, lset::LsetCommand, linsert::LinsertCommand, lrem::LremCommand, ltrim::LtrimCommand, lpos::LposCommand, lmove::LmoveCommand, lmpop::LmpopCommand, lpushx::LpushxCommand, lpush::LpushCommand, lrange::LrangeCommand, ping::PingCommand, psync::PsyncCommand, publish::PublishCommand, replconf::ReplConfCommand, rpush::RpushCommand, set::{SetCommand, SetCondition, SetExpiry, SetOptions}, append::AppendCommand, strlen::StrlenCommand, getrange::GetrangeCommand, setrange::SetrangeCommand, mset::MsetCommand, mget::MgetCommand, getdel::GetdelCommand, getex::GetexCommand, subscribe::SubscribeCommand, type_command::TypeCommand, unsubscribe::UnsubscribeCommand, wait::WaitCommand, xadd::XaddCommand, xrange::XrangeCommand, xread::XreadCommand, hset::HsetCommand, hsetnx::HsetnxCommand, hget::HgetCommand, hmget::HmgetCommand, hdel::HdelCommand, hexists::HexistsCommand, hlen::HlenCommand, hkeys::HkeysCommand, hvals::HvalsCommand, hgetall::HgetallCommand, hincrby::HincrbyCommand, hincrbyfloat::HincrbyfloatCommand, hstrlen::HstrlenCommand, hrandfield::HrandfieldCommand, sadd::SaddCommand, srem::SremCommand, sismember::SismemberCommand, smismember::SmismemberCommand, scard::ScardCommand, smembers::SmembersCommand, spop::SpopCommand, srandmember::SrandmemberCommand, smove::SmoveCommand, sinter::SinterCommand, sunion::SunionCommand, sdiff::SdiffCommand, sintercard::SintercardCommand, zadd::ZaddCommand, zrem::ZremCommand, zscore::ZscoreCommand, zmscore::ZmscoreCommand, zincrby::ZincrbyCommand, zcard::ZcardCommand, zcount::ZcountCommand, zrank::ZrankCommand, zrange::ZrangeCommand, zunion::ZunionCommand, zinter::ZinterCommand, zdiff::ZdiffCommand, zpop::ZpopCommand, bzpop::BzpopCommand, zmpop::ZmpopCommand, geoadd::GeoaddCommand, geodist::GeodistCommand, geopos::GeoposCommand, geohash::GeohashCommand, geosearch::GeosearchCommand, xgroup::XgroupCommand, xreadgroup::XreadgroupCommand, xack::XackCommand, xpending::XpendingCommand, xclaim::XclaimCommand, xautoclaim::XautoclaimCommand, xlen::XlenCommand, xdel::XdelCommand, xtrim::XtrimCommand, xinfo::XinfoCommand, parse_arg, parse_integer, parse_random_count, RedisCommand}, redis::{bitmap::parse_bit_offset, blocking::{BlockingArgs, Notifier}, geo::parse_unit, keyspace::{Databases, Keyspace}, list::{parse_mpop_args, ListEnd}, replication::{ReplicationLog, WriteCommands}, scan::ScanArgs, sorted_set::{parse_score, ScoreBound, SortedSetCacheVal}, stream::{now_ms, ConsumerGroup, StreamId, TrimArgsParser}}, resp::{create_array_resp, create_basic_err_resp, create_bulk_string_resp, create_int_resp, create_null_bulk_string_resp, create_simple_string_resp, create_wrong_args_err_resp, types::RespType}};
 
 pub enum CacheVal {
     String(StringCacheVal),
     List(ListCacheVal),
-    Stream(StreamCacheVal)
+    Stream(StreamCacheVal),
+    Hash(HashCacheVal),
+    Set(SetCacheVal),
+    SortedSet(SortedSetCacheVal)
 }
+#[derive(Clone)]
 pub struct StringCacheVal {
-    pub(crate) val: String,
-    pub(crate) expiry_time: Option<u128>
+    pub(crate) val: Vec<u8>
 }
 
+#[derive(Default, Clone)]
 pub struct ListCacheVal {
-    pub(crate) list: Vec<String>,
-    pub(crate) block_queue: Vec<String>
+    pub(crate) list: VecDeque<Vec<u8>>
 }
 
+#[derive(Clone)]
 pub struct StreamCacheVal {
-    pub(crate) stream: Vec<StreamItem>
+    pub(crate) stream: Vec<StreamItem>,
+    pub(crate) last_id: StreamId,
+    pub(crate) max_deleted_id: StreamId,
+    pub(crate) entries_added: u64,
+    pub(crate) groups: BTreeMap<Vec<u8>, ConsumerGroup>
+}
+
+#[derive(Clone)]
+pub struct HashCacheVal {
+    pub(crate) hash: HashMap<Vec<u8>, Vec<u8>>
+}
+
+#[derive(Clone)]
+pub struct SetCacheVal {
+    pub(crate) set: HashSet<Vec<u8>>
 }
 
 #[derive(Clone)]
 pub struct KeyVal {
-    pub(crate) key: String,
-    pub(crate) val: String
+    pub(crate) key: Vec<u8>,
+    pub(crate) val: Vec<u8>
 }
 
 #[derive(Clone)]
 pub struct StreamItem {
-    pub(crate) id: String,
+    pub(crate) id: StreamId,
     pub(crate) key_vals: Vec<KeyVal>
 }
 pub struct Client {
@@ -41,11 +59,15 @@ pub struct Client {
     replica_of: Option<String>,
     master_repl_id: Option<String>,
     master_repl_offset: Option<u128>,
-    cache: Arc<Mutex<HashMap<String, CacheVal>>>,
-    write_commands: Arc<Mutex<Vec<String>>>,
+    databases: Databases,
+    /// The db SELECT picked and its keyspace.
+    db: usize,
+    cache: Arc<Mutex<Keyspace>>,
+    write_commands: Arc<Mutex<WriteCommands>>,
     ack_replicas: Arc<Mutex<usize>>,
-    subscribed_channels: HashSet<String>,
-    channel_to_subscribers: Arc<Mutex<HashMap<String, Vec<String>>>>,
+    notifier: Arc<Notifier>,
+    subscribed_channels: HashSet<Vec<u8>>,
+    channel_to_subscribers: Arc<Mutex<HashMap<Vec<u8>, Vec<String>>>>,
     client_to_stream: Arc<Mutex<HashMap<String, TcpStream>>>,
     staged_commands: Vec<RespType>,
     staging_commands: bool,
@@ -54,8 +76,8 @@ pub struct Client {
 }
 
 impl Client {
-    pub fn new(cache: Arc<Mutex<HashMap<String, CacheVal>>>, write_commands: Arc<Mutex<Vec<String>>>,
-         ack_replicas: Arc<Mutex<usize>>, replica_of: Option<String>, channel_to_subscribers: Arc<Mutex<HashMap<String, Vec<String>>>>, client_to_stream: Arc<Mutex<HashMap<String, TcpStream>>>, rdb_dir: String, rdb_file: String) -> Self {
+    pub fn new(databases: Databases, write_commands: Arc<Mutex<WriteCommands>>,
+         ack_replicas: Arc<Mutex<usize>>, notifier: Arc<Notifier>, replica_of: Option<String>, channel_to_subscribers: Arc<Mutex<HashMap<Vec<u8>, Vec<String>>>>, client_to_stream: Arc<Mutex<HashMap<String, TcpStream>>>, rdb_dir: String, rdb_file: String) -> Self {
 
         let mut master_repl_id = None;
         let mut master_repl_offset = None;
@@ -77,20 +99,23 @@ impl Client {
             master_repl_offset: master_repl_offset,
             master_repl_id: master_repl_id,
             staging_commands: false,
-            cache: cache,
+            cache: databases[0].clone(),
+            databases,
+            db: 0,
             ack_replicas: ack_replicas,
+            notifier,
             rdb_dir: rdb_dir,
             rdb_file: rdb_file
         }
     }
 
-    pub fn handle_command(&mut self, cmd: RespType) -> Vec<String> {
+    pub fn handle_command(&mut self, cmd: RespType) -> Vec<Vec<u8>> {
         match cmd {
             RespType::Array(resp_types) => {
                 let mut iter = resp_types.iter();
 
                 if let RespType::String(s) = iter.next().unwrap() {
-                    let command = s.to_lowercase();
+                    let command = String::from_utf8_lossy(s).to_lowercase();
 
                     // SUBSCRIBE STATE
                     if self.subscribed_channels.len() > 0 {
@@ -116,9 +141,187 @@ impl Client {
                                 RespType::String(pattern) => pattern,
                                 _ => panic!("KEYS command expects a pattern")
                             };
-                            let redis_command = KeysCommand::new(pattern.to_string(), self.cache.clone());
+                            let redis_command = KeysCommand::new(pattern.clone(), self.cache.clone());
                             return redis_command.execute(&mut iter);
                         },
+                        "del" | "unlink" | "exists" | "touch" => {
+                            let mut keys = vec![];
+                            while let Some(key) = Self::extract_string(&mut iter) {
+                                keys.push(key);
+                            }
+                            if keys.is_empty() {
+                                return vec![create_wrong_args_err_resp(command.as_str())];
+                            }
+                            if matches!(command.as_str(), "exists" | "touch") {
+                                return ExistsCommand::new(keys, self.cache.clone()).execute(&mut iter);
+                            }
+                            let redis_command = DelCommand::new(keys, command.eq("unlink"), self.cache.clone());
+                            self.propagate_write(&resp_types);
+                            redis_command.execute(&mut iter)
+                        },
+                        "rename" | "renamenx" => {
+                            let (key, new_key) = match (Self::extract_string(&mut iter), Self::extract_string(&mut iter)) {
+                                (Some(key), Some(new_key)) => (key, new_key),
+                                _ => return vec![create_wrong_args_err_resp(command.as_str())]
+                            };
+                            let redis_command = RenameCommand::new(key, new_key.clone(), command.eq("renamenx"), self.cache.clone());
+                            self.propagate_write(&resp_types);
+                            let res = redis_command.execute(&mut iter);
+                            self.notifier.notify_key(&new_key);
+                            res
+                        },
+                        "copy" => {
+                            let (source, destination) = match (Self::extract_string(&mut iter), Self::extract_string(&mut iter)) {
+                                (Some(source), Some(destination)) => (source, destination),
+                                _ => return vec![create_wrong_args_err_resp("copy")]
+                            };
+                            let mut replace = false;
+                            let mut destination_db = self.db;
+                            while let Some(arg) = Self::extract_string(&mut iter) {
+                                if arg.eq_ignore_ascii_case(b"replace") {
+                                    replace = true;
+                                } else if let (true, Some(db)) = (arg.eq_ignore_ascii_case(b"db"), Self::extract_string(&mut iter)) {
+                                    destination_db = match self.parse_db_index(&db) {
+                                        Ok(db) => db,
+                                        Err(err) => return vec![err]
+                                    };
+                                } else {
+                                    return vec![create_basic_err_resp("ERR syntax error".to_string())];
+                                }
+                            }
+                            let redis_command = CopyCommand::new(source, destination.clone(), replace, self.databases.clone(), self.db, destination_db);
+                            self.propagate_write(&resp_types);
+                            let res = redis_command.execute(&mut iter);
+                            self.notifier.notify_key(&destination);
+                            res
+                        },
+                        "expire" | "pexpire" | "expireat" | "pexpireat" => {
+                            let mut args = vec![];
+                            while let Some(arg) = Self::extract_string(&mut iter) {
+                                args.push(arg);
+                            }
+                            if args.len() < 2 {
+                                return vec![create_wrong_args_err_resp(command.as_str())];
+                            }
+                            let (at, flags) = match (parse_expire_time(command.as_str(), &args[1], now_ms()), ExpireFlags::parse(&args[2..])) {
+                                (Ok(at), Ok(flags)) => (at, flags),
+                                (Err(err), _) | (_, Err(err)) => return vec![err]
+                            };
+                            let redis_command = ExpireCommand::new(args[0].clone(), at, flags, self.cache.clone(), self.replication_log());
+                            redis_command.execute(&mut iter)
+                        },
+                        "ttl" | "pttl" | "expiretime" | "pexpiretime" => {
+                            let key = match Self::extract_string(&mut iter) {
+                                Some(key) => key,
+                                None => return vec![create_wrong_args_err_resp(command.as_str())]
+                            };
+                            let reply = match command.as_str() {
+                                "ttl" => TtlReply::Remaining,
+                                "pttl" => TtlReply::RemainingMs,
+                                "expiretime" => TtlReply::At,
+                                _ => TtlReply::AtMs
+                            };
+                            let redis_command = TtlCommand::new(key, reply, self.cache.clone());
+                            redis_command.execute(&mut iter)
+                        },
+                        "persist" => {
+                            let key = match Self::extract_string(&mut iter) {
+                                Some(key) => key,
+                                None => return vec![create_wrong_args_err_resp("persist")]
+                            };
+                            let redis_command = PersistCommand::new(key, self.cache.clone());
+                            self.propagate_write(&resp_types);
+                            redis_command.execute(&mut iter)
+                        },
+                        "randomkey" => {
+                            let redis_command = RandomkeyCommand::new(self.cache.clone());
+                            redis_command.execute(&mut iter)
+                        },
+                        "dbsize" => {
+                            let redis_command = DbsizeCommand::new(self.cache.clone());
+                            redis_command.execute(&mut iter)
+                        },
+                        "select" => {
+                            let db = match Self::extract_string(&mut iter) {
+                                Some(db) => db,
+                                None => return vec![create_wrong_args_err_resp("select")]
+                            };
+                            match self.parse_db_index(&db) {
+                                Ok(db) => {
+                                    self.db = db;
+                                    self.cache = self.databases[db].clone();
+                                    vec![create_simple_string_resp("OK".to_string())]
+                                },
+                                Err(err) => vec![err]
+                            }
+                        },
+                        "move" => {
+                            let (key, db) = match (Self::extract_string(&mut iter), Self::extract_string(&mut iter)) {
+                                (Some(key), Some(db)) => (key, db),
+                                _ => return vec![create_wrong_args_err_resp("move")]
+                            };
+                            let destination_db = match self.parse_db_index(&db) {
+                                Ok(db) => db,
+                                Err(err) => return vec![err]
+                            };
+                            let redis_command = MoveCommand::new(key.clone(), self.databases.clone(), self.db, destination_db);
+                            self.propagate_write(&resp_types);
+                            let res = redis_command.execute(&mut iter);
+                            self.notifier.notify_key(&key);
+                            res
+                        },
+                        "swapdb" => {
+                            let (first, second) = match (Self::extract_string(&mut iter), Self::extract_string(&mut iter)) {
+                                (Some(first), Some(second)) => (first, second),
+                                _ => return vec![create_wrong_args_err_resp("swapdb")]
+                            };
+                            let (first, second) = match (self.parse_db_index(&first), self.parse_db_index(&second)) {
+                                (Ok(first), Ok(second)) => (first, second),
+                                (Err(err), _) | (_, Err(err)) => return vec![err]
+                            };
+                            let redis_command = SwapdbCommand::new(self.databases.clone(), first, second, self.notifier.clone());
+                            self.propagate_write(&resp_types);
+                            redis_command.execute(&mut iter)
+                        },
+                        "flushdb" | "flushall" => {
+                            let lazy = match Self::extract_string(&mut iter) {
+                                None => false,
+                                Some(mode) if mode.eq_ignore_ascii_case(b"async") => true,
+                                Some(mode) if mode.eq_ignore_ascii_case(b"sync") => false,
+                                Some(_) => return vec![create_basic_err_resp("ERR syntax error".to_string())]
+                            };
+                            let databases = if command.eq("flushall") { self.databases.to_vec() } else { vec![self.cache.clone()] };
+                            let redis_command = FlushCommand::new(databases, lazy);
+                            self.propagate_write(&resp_types);
+                            redis_command.execute(&mut iter)
+                        },
+                        "save" | "bgsave" => {
+                            let path = format!("{}/{}", self.rdb_dir, self.rdb_file);
+                            let redis_command = SaveCommand::new(self.databases.clone(), path, command.eq("bgsave"));
+                            redis_command.execute(&mut iter)
+                        },
+                        "scan" | "hscan" | "sscan" | "zscan" => {
+                            let mut args = vec![];
+                            while let Some(arg) = Self::extract_string(&mut iter) {
+                                args.push(arg);
+                            }
+                            let (target, args) = match (command.as_str(), args.split_first()) {
+                                ("scan", _) => (ScanTarget::Keys, args.as_slice()),
+                                ("hscan", Some((key, args))) => (ScanTarget::Hash(key.clone()), args),
+                                ("sscan", Some((key, args))) => (ScanTarget::Set(key.clone()), args),
+                                ("zscan", Some((key, args))) => (ScanTarget::SortedSet(key.clone()), args),
+                                _ => return vec![create_wrong_args_err_resp(command.as_str())]
+                            };
+                            if args.is_empty() {
+                                return vec![create_wrong_args_err_resp(command.as_str())];
+                            }
+                            let scan_args = match ScanArgs::parse(args, matches!(target, ScanTarget::Keys)) {
+                                Ok(scan_args) => scan_args,
+                                Err(err) => return vec![err]
+                            };
+                            let redis_command = ScanCommand::new(target, scan_args, self.cache.clone());
+                            redis_command.execute(&mut iter)
+                        },
                         "publish" => {
                             let channel = match iter.next().expect("Should have channel") {
                                 RespType::String(channel) => channel,
@@ -128,7 +331,7 @@ impl Client {
                                 RespType::String(message) => message,
                                 _ => panic!("PUBLISH command expects a message")
                             };
-                            let redis_command = PublishCommand::new(channel.to_string(), message.to_string(), self.channel_to_subscribers.clone(), self.client_to_stream.clone());
+                            let redis_command = PublishCommand::new(channel.clone(), message.clone(), self.channel_to_subscribers.clone(), self.client_to_stream.clone());
                             return redis_command.execute(&mut iter);
                         },
                         "unsubscribe" => {
@@ -136,8 +339,8 @@ impl Client {
                                 RespType::String(channel) => channel,
                                 _ => panic!("UNSUBSCRIBE command expects a channel")
                             };
-                            self.subscribed_channels.remove(channel.as_str());
-                            let redis_command = UnsubscribeCommand::new(self.id.clone(), channel.to_string(), self.channel_to_subscribers.clone(), self.subscribed_channels.len() as i64);
+                            self.subscribed_channels.remove(channel);
+                            let redis_command = UnsubscribeCommand::new(self.id.clone(), channel.clone(), self.channel_to_subscribers.clone(), self.subscribed_channels.len() as i64);
                             return redis_command.execute(&mut iter);
                         }
                         "subscribe" => {
@@ -145,8 +348,8 @@ impl Client {
                                 RespType::String(channel) => channel,
                                 _ => panic!("SUBSCRIBE command expects a channel")
                             };
-                            self.subscribed_channels.insert(channel.to_string());
-                            let redis_command = SubscribeCommand::new(self.id.clone(), channel.to_string(), self.channel_to_subscribers.clone(), self.subscribed_channels.len() as i64);
+                            self.subscribed_channels.insert(channel.clone());
+                            let redis_command = SubscribeCommand::new(self.id.clone(), channel.clone(), self.channel_to_subscribers.clone(), self.subscribed_channels.len() as i64);
                             return redis_command.execute(&mut iter);
                         },
                         "config" => {
@@ -158,9 +361,10 @@ impl Client {
                                 RespType::String(v) => v,
                                 _ => panic!("CONFIG command expects a value to get")
                             };
-                            match value.as_str() {
-                                "dir" => vec![create_array_resp(vec![create_bulk_string_resp("dir".into()), create_bulk_string_resp(self.rdb_dir.clone().into())])],
-                                "dbfilename" => vec![create_array_resp(vec![create_bulk_string_resp("dbfilename".into()), create_bulk_string_resp(self.rdb_file.clone().into())])],
+                            match value.to_ascii_lowercase().as_slice() {
+                                b"dir" => vec![create_array_resp(vec![create_bulk_string_resp("dir"), create_bulk_string_resp(&self.rdb_dir)])],
+                                b"dbfilename" => vec![create_array_resp(vec![create_bulk_string_resp("dbfilename"), create_bulk_string_resp(&self.rdb_file)])],
+                                b"databases" => vec![create_array_resp(vec![create_bulk_string_resp("databases"), create_bulk_string_resp(self.databases.len().to_string())])],
                                 _ => panic!("UNKOWN VALUE TO GET")
                             }
                         },
@@ -180,23 +384,24 @@ impl Client {
                                 RespType::String(keyword) => keyword,
                                 _ => panic!("REPLCONF command expects a keyword")
                             };
-                            if keyword.to_lowercase().eq("listening-port") || keyword.to_lowercase().eq("capa") {
+                            if keyword.eq_ignore_ascii_case(b"listening-port") || keyword.eq_ignore_ascii_case(b"capa") {
                                 return vec![create_simple_string_resp("OK".to_string())]
                             }
 
-                            if keyword.to_lowercase().eq("ack") {
+                            if keyword.eq_ignore_ascii_case(b"ack") {
                                 let mut ack_replica_gaurd = self.ack_replicas.lock().unwrap();
                                 *ack_replica_gaurd += 1;
                                 println!("INCREMENTING ACKS NOW AT {}", ack_replica_gaurd);
+                                self.notifier.notify_replica_acks();
                                 return vec![];
                             }
                     
-                            assert!(keyword.to_lowercase().eq("getack"));
+                            assert!(keyword.eq_ignore_ascii_case(b"getack"));
                             let star = match iter.next().expect("Should have * key") {
                                 RespType::String(star) => star,
                                 _ => panic!("REPLCONF command expects a *")
                             };
-                            assert!(star.to_lowercase().eq("*"));
+                            assert!(star.eq_ignore_ascii_case(b"*"));
                             return vec!["SEND_REPLCONF_ACK".into()];
                         }
                         "multi" => {
@@ -213,7 +418,7 @@ impl Client {
                                 None => panic!("LRANGE could not parse end timeout"),
                             };
 
-                            let redis_command = WaitCommand::new(num_replicas, timeout_ms, self.ack_replicas.clone());
+                            let redis_command = WaitCommand::new(num_replicas, timeout_ms, self.ack_replicas.clone(), self.notifier.clone());
                             return redis_command.execute(&mut iter);
                         }
                         "discard" => {
@@ -248,7 +453,7 @@ impl Client {
                                 RespType::String(message) => message,
                                 _ => panic!("Echo command expects a string message")
                             };
-                            let redis_command = EchoCommand::new(message.to_string());
+                            let redis_command = EchoCommand::new(message.clone());
                             return redis_command.execute(&mut iter);
                         },
                         "get" => {
@@ -256,59 +461,257 @@ impl Client {
                                 RespType::String(key) => key,
                                 _ => panic!("Get command expects a string key")
                             };
-                            let redis_command = GetCommand::new(key.to_string(), self.cache.clone());
+                            let redis_command = GetCommand::new(key.clone(), self.cache.clone());
                             return redis_command.execute(&mut iter);
                         },
                         "set" => {
-                            let (key, value) = match (iter.next().expect("Should have key"), iter.next().expect("Should have value")) {
-                                (RespType::String(key), RespType::String(value)) => (key,value),
-                                _ => panic!("Set command expects a string key and value")
-                            };
-                            let expire: Option<u128> = match (iter.next(), iter.next()) {
-                                (Some(RespType::String(px)), Some(RespType::String(exp))) if px.to_lowercase().eq("px") => {
-                                    if let Ok(exp_millis) = exp.parse::<u128>() {
-                                        Some(exp_millis)
-                                    } else {
-                                        panic!("Invalid expiry time")
-                                    }
-                                },
-                                _ => None
+                            let mut args = vec![];
+                            while let Some(arg) = Self::extract_string(&mut iter) {
+                                args.push(arg);
+                            }
+                            if args.len() < 2 {
+                                return vec![create_wrong_args_err_resp("set")];
+                            }
+                            let options = match SetOptions::parse(&args[2..], now_ms()) {
+                                Ok(options) => options,
+                                Err(err) => return vec![err]
                             };
-                            let redis_command = SetCommand::new(key.to_string(), value.to_string(), expire, self.cache.clone());
-                            if self.replica_of.is_none() {
-                                let mut write_command_gaurd = self.write_commands.lock().unwrap();
-                                let cmd_clone = RespType::Array(resp_types.clone());
-                                write_command_gaurd.push(cmd_clone.to_string());
-                                println!("WRITING TO COMMANDS LIST LEN: {}", write_command_gaurd.len());
+                            let redis_command = SetCommand::new(args[0].clone(), args[1].clone(), options, self.cache.clone(), self.replication_log());
+                            redis_command.execute(&mut iter)
+                        },
+                        "getset" => {
+                            let (key, value) = match (Self::extract_string(&mut iter), Self::extract_string(&mut iter)) {
+                                (Some(key), Some(value)) => (key, value),
+                                _ => return vec![create_wrong_args_err_resp("getset")]
+                            };
+                            let options = SetOptions { condition: SetCondition::Always, expiry: SetExpiry::Clear, get: true };
+                            let redis_command = SetCommand::new(key, value, options, self.cache.clone(), self.replication_log());
+                            redis_command.execute(&mut iter)
+                        },
+                        "mset" | "msetnx" | "setnx" => {
+                            let mut args = vec![];
+                            while let Some(arg) = Self::extract_string(&mut iter) {
+                                args.push(arg);
                             }
-                            println!("EXECUTING SET {} {}", key.to_string(), value.to_string());
-                            return redis_command.execute(&mut iter);
+                            if args.is_empty() || args.len() % 2 != 0 || (command.eq("setnx") && args.len() != 2) {
+                                return vec![create_wrong_args_err_resp(command.as_str())];
+                            }
+                            let pairs = args.chunks(2).map(|pair| (pair[0].clone(), pair[1].clone())).collect();
+                            let redis_command = MsetCommand::new(pairs, !command.eq("mset"), self.cache.clone());
+                            self.propagate_write(&resp_types);
+                            redis_command.execute(&mut iter)
+                        },
+                        "mget" => {
+                            let mut keys = vec![];
+                            while let Some(key) = Self::extract_string(&mut iter) {
+                                keys.push(key);
+                            }
+                            if keys.is_empty() {
+                                return vec![create_wrong_args_err_resp("mget")];
+                            }
+                            let redis_command = MgetCommand::new(keys, self.cache.clone());
+                            redis_command.execute(&mut iter)
+                        },
+                        "append" => {
+                            let (key, value) = match (Self::extract_string(&mut iter), Self::extract_string(&mut iter)) {
+                                (Some(key), Some(value)) => (key, value),
+                                _ => return vec![create_wrong_args_err_resp("append")]
+                            };
+                            let redis_command = AppendCommand::new(key, value, self.cache.clone());
+                            self.propagate_write(&resp_types);
+                            redis_command.execute(&mut iter)
+                        },
+                        "strlen" => {
+                            let key = match Self::extract_string(&mut iter) {
+                                Some(key) => key,
+                                None => return vec![create_wrong_args_err_resp("strlen")]
+                            };
+                            let redis_command = StrlenCommand::new(key, self.cache.clone());
+                            redis_command.execute(&mut iter)
+                        },
+                        "getrange" => {
+                            let (key, start, end) = match (Self::extract_string(&mut iter), Self::extract_string(&mut iter), Self::extract_string(&mut iter)) {
+                                (Some(key), Some(start), Some(end)) => (key, start, end),
+                                _ => return vec![create_wrong_args_err_resp("getrange")]
+                            };
+                            let (start, end) = match (parse_arg::<i64>(&start), parse_arg::<i64>(&end)) {
+                                (Some(start), Some(end)) => (start, end),
+                                _ => return vec![create_basic_err_resp("ERR value is not an integer or out of range".to_string())]
+                            };
+                            let redis_command = GetrangeCommand::new(key, start, end, self.cache.clone());
+                            redis_command.execute(&mut iter)
+                        },
+                        "setrange" => {
+                            let (key, offset, value) = match (Self::extract_string(&mut iter), Self::extract_string(&mut iter), Self::extract_string(&mut iter)) {
+                                (Some(key), Some(offset), Some(value)) => (key, offset, value),
+                                _ => return vec![create_wrong_args_err_resp("setrange")]
+                            };
+                            let offset = match parse_arg::<i64>(&offset) {
+                                Some(offset) if offset < 0 => return vec![create_basic_err_resp("ERR offset is out of range".to_string())],
+                                Some(offset) => offset as usize,
+                                None => return vec![create_basic_err_resp("ERR value is not an integer or out of range".to_string())]
+                            };
+                            let redis_command = SetrangeCommand::new(key, offset, value, self.cache.clone());
+                            self.propagate_write(&resp_types);
+                            redis_command.execute(&mut iter)
+                        },
+                        "getdel" => {
+                            let key = match Self::extract_string(&mut iter) {
+                                Some(key) => key,
+                                None => return vec![create_wrong_args_err_resp("getdel")]
+                            };
+                            let redis_command = GetdelCommand::new(key, self.cache.clone());
+                            self.propagate_write(&resp_types);
+                            redis_command.execute(&mut iter)
+                        },
+                        "getex" => {
+                            let key = match Self::extract_string(&mut iter) {
+                                Some(key) => key,
+                                None => return vec![create_wrong_args_err_resp("getex")]
+                            };
+                            let redis_command = GetexCommand::new(key, self.cache.clone(), self.replication_log());
+                            redis_command.execute(&mut iter)
+                        },
+                        "setbit" => {
+                            let (key, offset, bit) = match (Self::extract_string(&mut iter), Self::extract_string(&mut iter), Self::extract_string(&mut iter)) {
+                                (Some(key), Some(offset), Some(bit)) => (key, offset, bit),
+                                _ => return vec![create_wrong_args_err_resp("setbit")]
+                            };
+                            let offset = match parse_bit_offset(&offset, None) {
+                                Ok(offset) => offset,
+                                Err(err) => return vec![err]
+                            };
+                            let bit = match bit.as_slice() {
+                                b"0" => 0,
+                                b"1" => 1,
+                                _ => return vec![create_basic_err_resp("ERR bit is not an integer or out of range".to_string())]
+                            };
+                            let redis_command = SetbitCommand::new(key, offset, bit, self.cache.clone());
+                            self.propagate_write(&resp_types);
+                            redis_command.execute(&mut iter)
+                        },
+                        "getbit" => {
+                            let (key, offset) = match (Self::extract_string(&mut iter), Self::extract_string(&mut iter)) {
+                                (Some(key), Some(offset)) => (key, offset),
+                                _ => return vec![create_wrong_args_err_resp("getbit")]
+                            };
+                            let offset = match parse_bit_offset(&offset, None) {
+                                Ok(offset) => offset,
+                                Err(err) => return vec![err]
+                            };
+                            let redis_command = GetbitCommand::new(key, offset, self.cache.clone());
+                            redis_command.execute(&mut iter)
+                        },
+                        "bitcount" => {
+                            let key = match Self::extract_string(&mut iter) {
+                                Some(key) => key,
+                                None => return vec![create_wrong_args_err_resp("bitcount")]
+                            };
+                            let redis_command = BitcountCommand::new(key, self.cache.clone());
+                            redis_command.execute(&mut iter)
+                        },
+                        "bitpos" => {
+                            let (key, bit) = match (Self::extract_string(&mut iter), Self::extract_string(&mut iter)) {
+                                (Some(key), Some(bit)) => (key, bit),
+                                _ => return vec![create_wrong_args_err_resp("bitpos")]
+                            };
+                            let bit = match parse_arg::<i64>(&bit) {
+                                Some(bit) if bit == 0 || bit == 1 => bit as u8,
+                                Some(_) => return vec![create_basic_err_resp("ERR The bit argument must be 1 or 0.".to_string())],
+                                None => return vec![create_basic_err_resp("ERR value is not an integer or out of range".to_string())]
+                            };
+                            let redis_command = BitposCommand::new(key, bit, self.cache.clone());
+                            redis_command.execute(&mut iter)
+                        },
+                        "bitop" => {
+                            let mut args = vec![];
+                            while let Some(arg) = Self::extract_string(&mut iter) {
+                                args.push(arg);
+                            }
+                            if args.len() < 3 {
+                                return vec![create_wrong_args_err_resp("bitop")];
+                            }
+                            let operation = match BitOperation::parse(&args[0]) {
+                                Some(operation) => operation,
+                                None => return vec![create_basic_err_resp("ERR syntax error".to_string())]
+                            };
+                            if operation == BitOperation::Not && args.len() != 3 {
+                                return vec![create_basic_err_resp("ERR BITOP NOT must be called with a single source key.".to_string())];
+                            }
+                            let redis_command = BitopCommand::new(operation, args[1].clone(), args[2..].to_vec(), self.cache.clone());
+                            self.propagate_write(&resp_types);
+                            redis_command.execute(&mut iter)
+                        },
+                        "bitfield" | "bitfield_ro" => {
+                            let key = match Self::extract_string(&mut iter) {
+                                Some(key) => key,
+                                None => return vec![create_wrong_args_err_resp(command.as_str())]
+                            };
+                            let read_only = command.eq("bitfield_ro");
+                            let redis_command = BitfieldCommand::new(key, read_only, self.cache.clone());
+                            if !read_only {
+                                self.propagate_write(&resp_types);
+                            }
+                            redis_command.execute(&mut iter)
+                        },
+                        "pfadd" => {
+                            let key = match Self::extract_string(&mut iter) {
+                                Some(key) => key,
+                                None => return vec![create_wrong_args_err_resp("pfadd")]
+                            };
+                            let mut elements = vec![];
+                            while let Some(element) = Self::extract_string(&mut iter) {
+                                elements.push(element);
+                            }
+                            let redis_command = PfaddCommand::new(key, elements, self.cache.clone());
+                            self.propagate_write(&resp_types);
+                            redis_command.execute(&mut iter)
+                        },
+                        "pfcount" => {
+                            let mut keys = vec![];
+                            while let Some(key) = Self::extract_string(&mut iter) {
+                                keys.push(key);
+                            }
+                            if keys.is_empty() {
+                                return vec![create_wrong_args_err_resp("pfcount")];
+                            }
+                            let redis_command = PfcountCommand::new(keys, self.cache.clone());
+                            redis_command.execute(&mut iter)
+                        },
+                        "pfmerge" => {
+                            let destination = match Self::extract_string(&mut iter) {
+                                Some(destination) => destination,
+                                None => return vec![create_wrong_args_err_resp("pfmerge")]
+                            };
+                            let mut sources = vec![];
+                            while let Some(source) = Self::extract_string(&mut iter) {
+                                sources.push(source);
+                            }
+                            let redis_command = PfmergeCommand::new(destination, sources, self.cache.clone());
+                            self.propagate_write(&resp_types);
+                            redis_command.execute(&mut iter)
                         },
                         "rpush" => {
                             let list_key = match iter.next().expect("Should have list key") {
                                 RespType::String(list_key) => list_key,
                                 _ => panic!("RPUSH command expects a list key")
                             };
-                            let redis_command = RpushCommand::new(list_key.to_string(), self.cache.clone());
-                            if self.replica_of.is_none() {
-                                let mut write_command_gaurd = self.write_commands.lock().unwrap();
-                                let cmd_clone = RespType::Array(resp_types.clone());
-                                write_command_gaurd.push(cmd_clone.to_string());
-                            }
-                            return redis_command.execute(&mut iter);
+                            let redis_command = RpushCommand::new(list_key.clone(), self.cache.clone());
+                            self.propagate_write(&resp_types);
+                            let res = redis_command.execute(&mut iter);
+                            self.notifier.notify_key(list_key);
+                            res
                         },
                         "lpush" => {
                             let list_key = match iter.next().expect("Should have list key") {
                                 RespType::String(list_key) => list_key,
                                 _ => panic!("LPUSH command expects a list key")
                             };
-                            let redis_command = LpushCommand::new(list_key.to_string(), self.cache.clone());
-                            if self.replica_of.is_none() {
-                                let mut write_command_gaurd = self.write_commands.lock().unwrap();
-                                let cmd_clone = RespType::Array(resp_types.clone());
-                                write_command_gaurd.push(cmd_clone.to_string());
-                            }
-                            return redis_command.execute(&mut iter);
+                            let redis_command = LpushCommand::new(list_key.clone(), self.cache.clone());
+                            self.propagate_write(&resp_types);
+                            let res = redis_command.execute(&mut iter);
+                            self.notifier.notify_key(list_key);
+                            res
                         },
                         "lrange" => {
                             let list_key = match iter.next().expect("Should have list key") {
@@ -324,7 +727,7 @@ impl Client {
                                 None => panic!("LRANGE could not parse end int"),
                             };
 
-                            let redis_command = LrangeCommand::new(list_key.to_string(), start, end, self.cache.clone());
+                            let redis_command = LrangeCommand::new(list_key.clone(), start, end, self.cache.clone());
                             return redis_command.execute(&mut iter);
                         },
                         "llen" => {
@@ -332,49 +735,204 @@ impl Client {
                                 RespType::String(list_key) => list_key,
                                 _ => panic!("LLEN command expects a list key")
                             };
-                            let redis_command = LlenCommand::new(list_key.to_string(), self.cache.clone());
+                            let redis_command = LlenCommand::new(list_key.clone(), self.cache.clone());
                             return redis_command.execute(&mut iter);
                         },
-                        "lpop" => {
+                        "lpop" | "rpop" => {
                             let list_key = match iter.next().expect("Should have list key") {
                                 RespType::String(list_key) => list_key,
                                 _ => panic!("LPOP command expects a list key")
                             };
-                            let count = match Self::extract_num(&mut iter) {
-                                Some(val) => Some(val),
-                                None => None
+                            let count = match iter.next() {
+                                Some(RespType::String(count)) => match parse_arg::<usize>(count) {
+                                    Some(count) => Some(count),
+                                    None => return vec![create_basic_err_resp("ERR value is out of range, must be positive".to_string())]
+                                },
+                                _ => None
                             };
-                            let redis_command = LpopCommand::new(list_key.to_string(), count, self.cache.clone());
-                            if self.replica_of.is_none() {
-                                let mut write_command_gaurd = self.write_commands.lock().unwrap();
-                                let cmd_clone = RespType::Array(resp_types.clone());
-                                write_command_gaurd.push(cmd_clone.to_string());
-                            }
+                            let redis_command = LpopCommand::new(list_key.clone(), if command.eq("rpop") { ListEnd::Right } else { ListEnd::Left }, count, self.cache.clone());
+                            self.propagate_write(&resp_types);
                             return redis_command.execute(&mut iter);
                         }
-                        "blpop" => {
-                            let list_key = match iter.next().expect("Should have list key") {
-                                RespType::String(list_key) => list_key,
-                                _ => panic!("BLPOP command expects a list key")
+                        "blpop" | "brpop" => {
+                            let mut keys = vec![];
+                            while let Some(arg) = Self::extract_string(&mut iter) {
+                                keys.push(arg);
+                            }
+                            if keys.len() < 2 {
+                                return vec![create_wrong_args_err_resp(command.as_str())];
+                            }
+                            let timeout = match Self::parse_block_timeout(&keys.pop().unwrap()) {
+                                Ok(timeout) => timeout,
+                                Err(err) => return vec![err]
                             };
-                            let timeout = match Self::extract_num(&mut iter) {
-                                Some(val) => val,
-                                None => panic!("BLPOP could not parse timeout float"),
+                            let end = if command.eq("brpop") { ListEnd::Right } else { ListEnd::Left };
+                            let redis_command = BlpopCommand::new(keys, BlockingListOp::Pop(end), BlockingArgs::new(self.id.clone(), timeout, self.db, self.notifier.clone()), self.cache.clone(), self.replication_log());
+                            redis_command.execute(&mut iter)
+                        },
+                        "blmpop" => {
+                            let mut args = vec![];
+                            while let Some(arg) = Self::extract_string(&mut iter) {
+                                args.push(arg);
+                            }
+                            if args.len() < 4 {
+                                return vec![create_wrong_args_err_resp("blmpop")];
+                            }
+                            let timeout = match Self::parse_block_timeout(&args[0]) {
+                                Ok(timeout) => timeout,
+                                Err(err) => return vec![err]
                             };
-                            let redis_command = BlpopCommand::new(list_key.to_string(), self.id.clone(), timeout, self.cache.clone());
-                            if self.replica_of.is_none() {
-                                let mut write_command_gaurd = self.write_commands.lock().unwrap();
-                                let cmd_clone = RespType::Array(resp_types.clone());
-                                write_command_gaurd.push(cmd_clone.to_string());
+                            let (keys, end, count) = match parse_mpop_args(&args[1..]) {
+                                Ok(parsed) => parsed,
+                                Err(err) => return vec![err]
+                            };
+                            let redis_command = BlpopCommand::new(keys, BlockingListOp::Mpop(end, count), BlockingArgs::new(self.id.clone(), timeout, self.db, self.notifier.clone()), self.cache.clone(), self.replication_log());
+                            redis_command.execute(&mut iter)
+                        },
+                        "blmove" | "brpoplpush" => {
+                            let mut args = vec![];
+                            while let Some(arg) = Self::extract_string(&mut iter) {
+                                args.push(arg);
                             }
-                            return redis_command.execute(&mut iter);
+                            // BRPOPLPUSH source destination timeout is BLMOVE source destination RIGHT LEFT timeout
+                            let (source, destination, from, to, timeout) = match args.as_slice() {
+                                [source, destination, from, to, timeout] if command.eq("blmove") => match (ListEnd::parse(from), ListEnd::parse(to)) {
+                                    (Some(from), Some(to)) => (source, destination, from, to, timeout),
+                                    _ => return vec![create_basic_err_resp("ERR syntax error".to_string())]
+                                },
+                                [source, destination, timeout] if command.eq("brpoplpush") => (source, destination, ListEnd::Right, ListEnd::Left, timeout),
+                                _ => return vec![create_wrong_args_err_resp(command.as_str())]
+                            };
+                            let timeout = match Self::parse_block_timeout(timeout) {
+                                Ok(timeout) => timeout,
+                                Err(err) => return vec![err]
+                            };
+                            let op = BlockingListOp::Move { destination: destination.clone(), from, to };
+                            let redis_command = BlpopCommand::new(vec![source.clone()], op, BlockingArgs::new(self.id.clone(), timeout, self.db, self.notifier.clone()), self.cache.clone(), self.replication_log());
+                            redis_command.execute(&mut iter)
+                        },
+                        "lindex" => {
+                            let (key, index) = match (Self::extract_string(&mut iter), Self::extract_string(&mut iter)) {
+                                (Some(key), Some(index)) => (key, index),
+                                _ => return vec![create_wrong_args_err_resp("lindex")]
+                            };
+                            let index = match parse_arg::<i64>(&index) {
+                                Some(index) => index,
+                                None => return vec![create_basic_err_resp("ERR value is not an integer or out of range".to_string())]
+                            };
+                            let redis_command = LindexCommand::new(key, index, self.cache.clone());
+                            redis_command.execute(&mut iter)
+                        },
+                        "lset" => {
+                            let (key, index, element) = match (Self::extract_string(&mut iter), Self::extract_string(&mut iter), Self::extract_string(&mut iter)) {
+                                (Some(key), Some(index), Some(element)) => (key, index, element),
+                                _ => return vec![create_wrong_args_err_resp("lset")]
+                            };
+                            let index = match parse_arg::<i64>(&index) {
+                                Some(index) => index,
+                                None => return vec![create_basic_err_resp("ERR value is not an integer or out of range".to_string())]
+                            };
+                            let redis_command = LsetCommand::new(key, index, element, self.cache.clone());
+                            self.propagate_write(&resp_types);
+                            redis_command.execute(&mut iter)
+                        },
+                        "linsert" => {
+                            let (key, position, pivot, element) = match (Self::extract_string(&mut iter), Self::extract_string(&mut iter), Self::extract_string(&mut iter), Self::extract_string(&mut iter)) {
+                                (Some(key), Some(position), Some(pivot), Some(element)) => (key, position, pivot, element),
+                                _ => return vec![create_wrong_args_err_resp("linsert")]
+                            };
+                            let after = match position.to_ascii_lowercase().as_slice() {
+                                b"before" => false,
+                                b"after" => true,
+                                _ => return vec![create_basic_err_resp("ERR syntax error".to_string())]
+                            };
+                            let redis_command = LinsertCommand::new(key, after, pivot, element, self.cache.clone());
+                            self.propagate_write(&resp_types);
+                            redis_command.execute(&mut iter)
+                        },
+                        "lrem" => {
+                            let (key, count, element) = match (Self::extract_string(&mut iter), Self::extract_string(&mut iter), Self::extract_string(&mut iter)) {
+                                (Some(key), Some(count), Some(element)) => (key, count, element),
+                                _ => return vec![create_wrong_args_err_resp("lrem")]
+                            };
+                            let count = match parse_arg::<i64>(&count) {
+                                Some(count) => count,
+                                None => return vec![create_basic_err_resp("ERR value is not an integer or out of range".to_string())]
+                            };
+                            let redis_command = LremCommand::new(key, count, element, self.cache.clone());
+                            self.propagate_write(&resp_types);
+                            redis_command.execute(&mut iter)
+                        },
+                        "ltrim" => {
+                            let (key, start, end) = match (Self::extract_string(&mut iter), Self::extract_string(&mut iter), Self::extract_string(&mut iter)) {
+                                (Some(key), Some(start), Some(end)) => (key, start, end),
+                                _ => return vec![create_wrong_args_err_resp("ltrim")]
+                            };
+                            let (start, end) = match (parse_arg::<i64>(&start), parse_arg::<i64>(&end)) {
+                                (Some(start), Some(end)) => (start, end),
+                                _ => return vec![create_basic_err_resp("ERR value is not an integer or out of range".to_string())]
+                            };
+                            let redis_command = LtrimCommand::new(key, start, end, self.cache.clone());
+                            self.propagate_write(&resp_types);
+                            redis_command.execute(&mut iter)
+                        },
+                        "lpos" => {
+                            let (key, element) = match (Self::extract_string(&mut iter), Self::extract_string(&mut iter)) {
+                                (Some(key), Some(element)) => (key, element),
+                                _ => return vec![create_wrong_args_err_resp("lpos")]
+                            };
+                            let redis_command = LposCommand::new(key, element, self.cache.clone());
+                            redis_command.execute(&mut iter)
+                        },
+                        "lmove" | "rpoplpush" => {
+                            let mut args = vec![];
+                            while let Some(arg) = Self::extract_string(&mut iter) {
+                                args.push(arg);
+                            }
+                            let (source, destination, from, to) = match args.as_slice() {
+                                [source, destination, from, to] if command.eq("lmove") => match (ListEnd::parse(from), ListEnd::parse(to)) {
+                                    (Some(from), Some(to)) => (source, destination, from, to),
+                                    _ => return vec![create_basic_err_resp("ERR syntax error".to_string())]
+                                },
+                                [source, destination] if command.eq("rpoplpush") => (source, destination, ListEnd::Right, ListEnd::Left),
+                                _ => return vec![create_wrong_args_err_resp(command.as_str())]
+                            };
+                            let redis_command = LmoveCommand::new(source.clone(), destination.clone(), from, to, self.cache.clone());
+                            self.propagate_write(&resp_types);
+                            let res = redis_command.execute(&mut iter);
+                            self.notifier.notify_key(destination);
+                            res
+                        },
+                        "lmpop" => {
+                            let redis_command = LmpopCommand::new(self.cache.clone());
+                            self.propagate_write(&resp_types);
+                            redis_command.execute(&mut iter)
+                        },
+                        "lpushx" | "rpushx" => {
+                            let key = match Self::extract_string(&mut iter) {
+                                Some(key) => key,
+                                None => return vec![create_wrong_args_err_resp(command.as_str())]
+                            };
+                            let mut elements = vec![];
+                            while let Some(element) = Self::extract_string(&mut iter) {
+                                elements.push(element);
+                            }
+                            if elements.is_empty() {
+                                return vec![create_wrong_args_err_resp(command.as_str())];
+                            }
+                            let end = if command.eq("rpushx") { ListEnd::Right } else { ListEnd::Left };
+                            let redis_command = LpushxCommand::new(key.clone(), end, elements, self.cache.clone());
+                            self.propagate_write(&resp_types);
+                            let res = redis_command.execute(&mut iter);
+                            self.notifier.notify_key(&key);
+                            res
                         },
                         "type" => {
                             let key = match iter.next().expect("Should have key") {
                                 RespType::String(key) => key,
                                 _ => panic!("TYPE command expects a key")
                             };
-                            let redis_command = TypeCommand::new(key.to_string(), self.cache.clone());
+                            let redis_command = TypeCommand::new(key.clone(), self.cache.clone());
                             return redis_command.execute(&mut iter);
                         },
                         "xadd" => {
@@ -382,122 +940,771 @@ impl Client {
                                 RespType::String(stream_key) => stream_key,
                                 _ => panic!("XADD command expects a stream_key")
                             };
-                            let entry_id = match iter.next().expect("Should have stream entry_id") {
-                                RespType::String(stream_key) => stream_key,
-                                _ => panic!("XADD command expects a entry_id")
+                            // XADD key [NOMKSTREAM] [MAXLEN|MINID [=|~] threshold [LIMIT count]] id field value [field value ...]
+                            let args: Vec<Vec<u8>> = resp_types[2..].iter().map(|arg| match arg {
+                                RespType::String(arg) => arg.clone(),
+                                _ => vec![]
+                            }).collect();
+                            let mut no_mkstream = false;
+                            let mut trim_parser = TrimArgsParser::default();
+                            let mut pos = 0;
+                            while pos < args.len() {
+                                if args[pos].eq_ignore_ascii_case(b"nomkstream") {
+                                    no_mkstream = true;
+                                    pos += 1;
+                                    continue;
+                                }
+                                match trim_parser.parse(&args, pos) {
+                                    Ok(0) => break,
+                                    Ok(used) => pos += used,
+                                    Err(err) => return vec![err]
+                                }
+                            }
+                            let trim = match trim_parser.finish() {
+                                Ok(trim) => trim,
+                                Err(err) => return vec![err]
                             };
-                            let redis_command = XaddCommand::new(stream_key.to_string(), entry_id.to_string(), self.cache.clone());
-                            if self.replica_of.is_none() {
-                                let mut write_command_gaurd = self.write_commands.lock().unwrap();
-                                let cmd_clone = RespType::Array(resp_types.clone());
-                                write_command_gaurd.push(cmd_clone.to_string());
+                            let field_values = args.len().saturating_sub(pos + 1);
+                            if field_values == 0 || !field_values.is_multiple_of(2) {
+                                return vec![create_wrong_args_err_resp("xadd")];
                             }
-                            return redis_command.execute(&mut iter);
+                            let redis_command = XaddCommand::new(stream_key.clone(), args[pos].clone(), no_mkstream, trim, self.cache.clone(), self.replication_log());
+                            let mut field_values = resp_types[pos + 3..].iter();
+                            let res = redis_command.execute(&mut field_values);
+                            self.notifier.notify_key(stream_key);
+                            res
                         },
-                        "xrange" => {
-                            let stream_key = match iter.next().expect("Should have stream key") {
-                                RespType::String(stream_key) => stream_key,
-                                _ => panic!("XRANGE command expects a stream_key")
-                            };
-                            let start_id = match iter.next().expect("Should have stream start_id") {
-                                RespType::String(stream_key) => stream_key,
-                                _ => panic!("XRANGE command expects a start_id")
+                        "xrange" | "xrevrange" => {
+                            let (stream_key, first_id, second_id) = match (Self::extract_string(&mut iter), Self::extract_string(&mut iter), Self::extract_string(&mut iter)) {
+                                (Some(stream_key), Some(first_id), Some(second_id)) => (stream_key, first_id, second_id),
+                                _ => return vec![create_wrong_args_err_resp(command.as_str())]
                             };
-                            let end_id = match iter.next().expect("Should have stream end_id") {
-                                RespType::String(stream_key) => stream_key,
-                                _ => panic!("XRANGE command expects a end_id")
+                            // XREVRANGE takes the end of the range first
+                            let redis_command = if command.eq("xrevrange") {
+                                XrangeCommand::new(stream_key, second_id, first_id, true, self.cache.clone())
+                            } else {
+                                XrangeCommand::new(stream_key, first_id, second_id, false, self.cache.clone())
                             };
-                            let redis_command = XrangeCommand::new(stream_key.to_string(), start_id.to_string(), end_id.to_string(), self.cache.clone());
-                            return redis_command.execute(&mut iter);
+                            redis_command.execute(&mut iter)
                         },
                         "xread" => {
-                            let mut timeout_ms = None;
-                            let mut keywords_consumed = 1;
-                            loop {
-                                let keyword = match iter.next().expect("Should have a keyword") {
-                                    RespType::String(keyword) => keyword,
-                                    _ => panic!("XREAD command expects a keyword first")
-                                };
-                                keywords_consumed += 1;
-
-                                match keyword.as_str() {
-                                    "streams" => break,
-                                    "block" => {
-                                        timeout_ms = match Self::extract_num(&mut iter) {
-                                            Some(val) => Some(val),
-                                            None => panic!("expected timeout after block keyword")
-                                        };
-                                        keywords_consumed += 1
-                                    }
-                                    _ => panic!("Unrecognized XREAD keyword")
+                            let redis_command = XreadCommand::new(self.cache.clone(), self.notifier.clone());
+                            redis_command.execute(&mut iter)
+                        },
+                        "incr" | "decr" | "incrby" | "decrby" => {
+                            let key = match Self::extract_string(&mut iter) {
+                                Some(key) => key,
+                                None => return vec![create_wrong_args_err_resp(command.as_str())]
+                            };
+                            let increment = match command.as_str() {
+                                "incr" => 1,
+                                "decr" => -1,
+                                _ => match Self::extract_string(&mut iter).map(|increment| parse_integer(&increment)) {
+                                    Some(Some(increment)) if command.eq("incrby") => increment,
+                                    Some(Some(decrement)) => match decrement.checked_neg() {
+                                        Some(increment) => increment,
+                                        None => return vec![create_basic_err_resp("ERR decrement would overflow".to_string())]
+                                    },
+                                    Some(None) => return vec![create_basic_err_resp("ERR value is not an integer or out of range".to_string())],
+                                    None => return vec![create_wrong_args_err_resp(command.as_str())]
                                 }
-                            }
-                            
-
-                            let num_streams = (resp_types.len() - keywords_consumed)/ 2;
-                            let mut stream_keys = vec![];
-                            let mut start_ids = vec![];
-                            for _ in 0..num_streams {
-                                let stream_key = match iter.next().expect("Should have stream key") {
-                                    RespType::String(stream_key) => stream_key,
-                                    _ => panic!("XREAD command expects a stream_key")
-                                };
-                                stream_keys.push(stream_key);
-                            }
-                            
-                            for _ in 0..num_streams { 
-                                let start_id = match iter.next().expect("Should have stream start_id") {
-                                    RespType::String(stream_key) => stream_key,
-                                    _ => panic!("XREAD command expects a start_id")
-                                };
-                                start_ids.push(start_id);
-                            } 
-                            
-                            let mut stream_responses = vec![];
-
-                            for i in 0..num_streams {
-                                let redis_command = XreadCommand::new(stream_keys[i].to_string(), timeout_ms, start_ids[i].to_string(), self.cache.clone());
-                                stream_responses.push(redis_command.execute(&mut iter));
-                            }
-                            
-                            if stream_responses.len() == 1 && stream_responses[0].clone()[0].eq(&create_null_bulk_string_resp()) {
-                                // this is pretty bad spec design by redis to expect a null bulk string if timeout but an array if success, I would have just had it return an empty array or the null bulk string in an array
-                                return vec![create_null_bulk_string_resp()];
-                            } else {
-                                return vec![create_array_resp(stream_responses.into_iter().flatten().collect())];
-                            }
+                            };
+                            let redis_command = IncrCommand::new(key, increment, self.cache.clone());
+                            self.propagate_write(&resp_types);
+                            redis_command.execute(&mut iter)
+                        },
+                        "incrbyfloat" => {
+                            let (key, increment) = match (Self::extract_string(&mut iter), Self::extract_string(&mut iter)) {
+                                (Some(key), Some(increment)) => (key, increment),
+                                _ => return vec![create_wrong_args_err_resp("incrbyfloat")]
+                            };
+                            let increment = match parse_arg::<f64>(&increment) {
+                                Some(increment) if increment.is_finite() => increment,
+                                _ => return vec![create_basic_err_resp("ERR value is not a valid float".to_string())]
+                            };
+                            let redis_command = IncrbyfloatCommand::new(key, increment, self.cache.clone(), self.replication_log());
+                            redis_command.execute(&mut iter)
+                        },
+                        "hset" => {
+                            let key = match Self::extract_string(&mut iter) {
+                                Some(key) => key,
+                                None => return vec![create_wrong_args_err_resp("hset")]
+                            };
+                            let redis_command = HsetCommand::new(key, self.cache.clone());
+                            self.propagate_write(&resp_types);
+                            redis_command.execute(&mut iter)
+                        },
+                        "hsetnx" => {
+                            let (key, field, value) = match (Self::extract_string(&mut iter), Self::extract_string(&mut iter), Self::extract_string(&mut iter)) {
+                                (Some(key), Some(field), Some(value)) => (key, field, value),
+                                _ => return vec![create_wrong_args_err_resp("hsetnx")]
+                            };
+                            let redis_command = HsetnxCommand::new(key, field, value, self.cache.clone());
+                            self.propagate_write(&resp_types);
+                            redis_command.execute(&mut iter)
+                        },
+                        "hget" => {
+                            let (key, field) = match (Self::extract_string(&mut iter), Self::extract_string(&mut iter)) {
+                                (Some(key), Some(field)) => (key, field),
+                                _ => return vec![create_wrong_args_err_resp("hget")]
+                            };
+                            let redis_command = HgetCommand::new(key, field, self.cache.clone());
+                            redis_command.execute(&mut iter)
+                        },
+                        "hmget" => {
+                            let key = match Self::extract_string(&mut iter) {
+                                Some(key) => key,
+                                None => return vec![create_wrong_args_err_resp("hmget")]
+                            };
+                            let redis_command = HmgetCommand::new(key, self.cache.clone());
+                            redis_command.execute(&mut iter)
+                        },
+                        "hdel" => {
+                            let key = match Self::extract_string(&mut iter) {
+                                Some(key) => key,
+                                None => return vec![create_wrong_args_err_resp("hdel")]
+                            };
+                            let redis_command = HdelCommand::new(key, self.cache.clone());
+                            self.propagate_write(&resp_types);
+                            redis_command.execute(&mut iter)
+                        },
+                        "hexists" => {
+                            let (key, field) = match (Self::extract_string(&mut iter), Self::extract_string(&mut iter)) {
+                                (Some(key), Some(field)) => (key, field),
+                                _ => return vec![create_wrong_args_err_resp("hexists")]
+                            };
+                            let redis_command = HexistsCommand::new(key, field, self.cache.clone());
+                            redis_command.execute(&mut iter)
+                        },
+                        "hlen" => {
+                            let key = match Self::extract_string(&mut iter) {
+                                Some(key) => key,
+                                None => return vec![create_wrong_args_err_resp("hlen")]
+                            };
+                            let redis_command = HlenCommand::new(key, self.cache.clone());
+                            redis_command.execute(&mut iter)
+                        },
+                        "hkeys" => {
+                            let key = match Self::extract_string(&mut iter) {
+                                Some(key) => key,
+                                None => return vec![create_wrong_args_err_resp("hkeys")]
+                            };
+                            let redis_command = HkeysCommand::new(key, self.cache.clone());
+                            redis_command.execute(&mut iter)
+                        },
+                        "hvals" => {
+                            let key = match Self::extract_string(&mut iter) {
+                                Some(key) => key,
+                                None => return vec![create_wrong_args_err_resp("hvals")]
+                            };
+                            let redis_command = HvalsCommand::new(key, self.cache.clone());
+                            redis_command.execute(&mut iter)
+                        },
+                        "hgetall" => {
+                            let key = match Self::extract_string(&mut iter) {
+                                Some(key) => key,
+                                None => return vec![create_wrong_args_err_resp("hgetall")]
+                            };
+                            let redis_command = HgetallCommand::new(key, self.cache.clone());
+                            redis_command.execute(&mut iter)
+                        },
+                        "hstrlen" => {
+                            let (key, field) = match (Self::extract_string(&mut iter), Self::extract_string(&mut iter)) {
+                                (Some(key), Some(field)) => (key, field),
+                                _ => return vec![create_wrong_args_err_resp("hstrlen")]
+                            };
+                            let redis_command = HstrlenCommand::new(key, field, self.cache.clone());
+                            redis_command.execute(&mut iter)
+                        },
+                        "hincrby" => {
+                            let (key, field) = match (Self::extract_string(&mut iter), Self::extract_string(&mut iter)) {
+                                (Some(key), Some(field)) => (key, field),
+                                _ => return vec![create_wrong_args_err_resp("hincrby")]
+                            };
+                            let increment: i64 = match Self::extract_num(&mut iter) {
+                                Some(val) => val,
+                                None => return vec![create_basic_err_resp("ERR value is not an integer or out of range".to_string())]
+                            };
+                            let redis_command = HincrbyCommand::new(key, field, increment, self.cache.clone());
+                            self.propagate_write(&resp_types);
+                            redis_command.execute(&mut iter)
+                        },
+                        "hincrbyfloat" => {
+                            let (key, field) = match (Self::extract_string(&mut iter), Self::extract_string(&mut iter)) {
+                                (Some(key), Some(field)) => (key, field),
+                                _ => return vec![create_wrong_args_err_resp("hincrbyfloat")]
+                            };
+                            let increment: f64 = match Self::extract_num(&mut iter) {
+                                Some(val) if f64::is_finite(val) => val,
+                                _ => return vec![create_basic_err_resp("ERR value is not a valid float".to_string())]
+                            };
+                            let redis_command = HincrbyfloatCommand::new(key, field, increment, self.cache.clone(), self.replication_log());
+                            redis_command.execute(&mut iter)
+                        },
+                        "hrandfield" => {
+                            let key = match Self::extract_string(&mut iter) {
+                                Some(key) => key,
+                                None => return vec![create_wrong_args_err_resp("hrandfield")]
+                            };
+                            let count = match iter.next() {
+                                Some(RespType::String(count)) => match parse_random_count(count) {
+                                    Ok(count) => Some(count),
+                                    Err(err) => return vec![err]
+                                },
+                                _ => None
+                            };
+                            let with_values = match Self::extract_string(&mut iter) {
+                                Some(arg) if arg.eq_ignore_ascii_case(b"withvalues") && count.is_some() => true,
+                                Some(_) => return vec![create_basic_err_resp("ERR syntax error".to_string())],
+                                None => false
+                            };
+                            let redis_command = HrandfieldCommand::new(key, count, with_values, self.cache.clone());
+                            redis_command.execute(&mut iter)
                         }
-                        "incr" => {
-                            let key = match iter.next().expect("Should have key") {
-                                RespType::String(key) => key,
-                                _ => panic!("INCR command expects a string key")
+                        "sadd" => {
+                            let key = match Self::extract_string(&mut iter) {
+                                Some(key) => key,
+                                None => return vec![create_wrong_args_err_resp("sadd")]
+                            };
+                            let redis_command = SaddCommand::new(key, self.cache.clone());
+                            self.propagate_write(&resp_types);
+                            redis_command.execute(&mut iter)
+                        },
+                        "srem" => {
+                            let key = match Self::extract_string(&mut iter) {
+                                Some(key) => key,
+                                None => return vec![create_wrong_args_err_resp("srem")]
+                            };
+                            let redis_command = SremCommand::new(key, self.cache.clone());
+                            self.propagate_write(&resp_types);
+                            redis_command.execute(&mut iter)
+                        },
+                        "sismember" => {
+                            let (key, member) = match (Self::extract_string(&mut iter), Self::extract_string(&mut iter)) {
+                                (Some(key), Some(member)) => (key, member),
+                                _ => return vec![create_wrong_args_err_resp("sismember")]
+                            };
+                            let redis_command = SismemberCommand::new(key, member, self.cache.clone());
+                            redis_command.execute(&mut iter)
+                        },
+                        "smismember" => {
+                            let key = match Self::extract_string(&mut iter) {
+                                Some(key) => key,
+                                None => return vec![create_wrong_args_err_resp("smismember")]
+                            };
+                            let redis_command = SmismemberCommand::new(key, self.cache.clone());
+                            redis_command.execute(&mut iter)
+                        },
+                        "scard" => {
+                            let key = match Self::extract_string(&mut iter) {
+                                Some(key) => key,
+                                None => return vec![create_wrong_args_err_resp("scard")]
+                            };
+                            let redis_command = ScardCommand::new(key, self.cache.clone());
+                            redis_command.execute(&mut iter)
+                        },
+                        "smembers" => {
+                            let key = match Self::extract_string(&mut iter) {
+                                Some(key) => key,
+                                None => return vec![create_wrong_args_err_resp("smembers")]
+                            };
+                            let redis_command = SmembersCommand::new(key, self.cache.clone());
+                            redis_command.execute(&mut iter)
+                        },
+                        "spop" => {
+                            let key = match Self::extract_string(&mut iter) {
+                                Some(key) => key,
+                                None => return vec![create_wrong_args_err_resp("spop")]
+                            };
+                            let count = match iter.next() {
+                                Some(RespType::String(count)) => match parse_arg::<usize>(count) {
+                                    Some(count) => Some(count),
+                                    None => return vec![create_basic_err_resp("ERR value is out of range, must be positive".to_string())]
+                                },
+                                _ => None
+                            };
+                            let redis_command = SpopCommand::new(key, count, self.cache.clone(), self.replication_log());
+                            redis_command.execute(&mut iter)
+                        },
+                        "srandmember" => {
+                            let key = match Self::extract_string(&mut iter) {
+                                Some(key) => key,
+                                None => return vec![create_wrong_args_err_resp("srandmember")]
+                            };
+                            let count = match iter.next() {
+                                Some(RespType::String(count)) => match parse_random_count(count) {
+                                    Ok(count) => Some(count),
+                                    Err(err) => return vec![err]
+                                },
+                                _ => None
+                            };
+                            let redis_command = SrandmemberCommand::new(key, count, self.cache.clone());
+                            redis_command.execute(&mut iter)
+                        },
+                        "smove" => {
+                            let (source, destination, member) = match (Self::extract_string(&mut iter), Self::extract_string(&mut iter), Self::extract_string(&mut iter)) {
+                                (Some(source), Some(destination), Some(member)) => (source, destination, member),
+                                _ => return vec![create_wrong_args_err_resp("smove")]
+                            };
+                            let redis_command = SmoveCommand::new(source, destination, member, self.cache.clone());
+                            self.propagate_write(&resp_types);
+                            redis_command.execute(&mut iter)
+                        },
+                        "sinter" => {
+                            let redis_command = SinterCommand::new(None, self.cache.clone());
+                            redis_command.execute(&mut iter)
+                        },
+                        "sinterstore" => {
+                            let destination = match Self::extract_string(&mut iter) {
+                                Some(destination) => destination,
+                                None => return vec![create_wrong_args_err_resp("sinterstore")]
+                            };
+                            let redis_command = SinterCommand::new(Some(destination), self.cache.clone());
+                            self.propagate_write(&resp_types);
+                            redis_command.execute(&mut iter)
+                        },
+                        "sunion" => {
+                            let redis_command = SunionCommand::new(None, self.cache.clone());
+                            redis_command.execute(&mut iter)
+                        },
+                        "sunionstore" => {
+                            let destination = match Self::extract_string(&mut iter) {
+                                Some(destination) => destination,
+                                None => return vec![create_wrong_args_err_resp("sunionstore")]
+                            };
+                            let redis_command = SunionCommand::new(Some(destination), self.cache.clone());
+                            self.propagate_write(&resp_types);
+                            redis_command.execute(&mut iter)
+                        },
+                        "sdiff" => {
+                            let redis_command = SdiffCommand::new(None, self.cache.clone());
+                            redis_command.execute(&mut iter)
+                        },
+                        "sdiffstore" => {
+                            let destination = match Self::extract_string(&mut iter) {
+                                Some(destination) => destination,
+                                None => return vec![create_wrong_args_err_resp("sdiffstore")]
+                            };
+                            let redis_command = SdiffCommand::new(Some(destination), self.cache.clone());
+                            self.propagate_write(&resp_types);
+                            redis_command.execute(&mut iter)
+                        },
+                        "sintercard" => {
+                            let num_keys: usize = match Self::extract_num(&mut iter) {
+                                Some(num_keys) if num_keys > 0 => num_keys,
+                                _ => return vec![create_basic_err_resp("ERR numkeys should be greater than 0".to_string())]
                             };
-                            let redis_command = IncrCommand::new(key.to_string(), self.cache.clone());
-                            if self.replica_of.is_none() {
-                                let mut write_command_gaurd = self.write_commands.lock().unwrap();
-                                let cmd_clone = RespType::Array(resp_types.clone());
-                                write_command_gaurd.push(cmd_clone.to_string());
+                            let mut keys = vec![];
+                            for _ in 0..num_keys {
+                                match Self::extract_string(&mut iter) {
+                                    Some(key) => keys.push(key),
+                                    None => return vec![create_basic_err_resp("ERR Number of keys can't be greater than number of args".to_string())]
+                                }
                             }
-                            return redis_command.execute(&mut iter);
-                        }
-                        _ => panic!("UNEXPECTED COMMAND")
-                    }
-                } else {
-                    panic!("UNEXPECTED ARRAY ENTRY EXPECTING ONLY STRINGS")
-                } 
-            },
-            _ => return vec![]
-        }
-    }
-
-    fn extract_num<T>(iter: &mut Iter<'_, RespType>) -> Option<T> where T: FromStr {
+                            let limit = match Self::extract_string(&mut iter) {
+                                Some(arg) if arg.eq_ignore_ascii_case(b"limit") => match Self::extract_num(&mut iter) {
+                                    Some(limit) => limit,
+                                    None => return vec![create_basic_err_resp("ERR LIMIT can't be negative".to_string())]
+                                },
+                                Some(_) => return vec![create_basic_err_resp("ERR syntax error".to_string())],
+                                None => 0
+                            };
+                            let redis_command = SintercardCommand::new(keys, limit, self.cache.clone());
+                            redis_command.execute(&mut iter)
+                        },
+                        "zadd" => {
+                            let key = match Self::extract_string(&mut iter) {
+                                Some(key) => key,
+                                None => return vec![create_wrong_args_err_resp("zadd")]
+                            };
+                            let redis_command = ZaddCommand::new(key.clone(), self.cache.clone());
+                            self.propagate_write(&resp_types);
+                            let res = redis_command.execute(&mut iter);
+                            self.notifier.notify_key(&key);
+                            res
+                        },
+                        "zrem" => {
+                            let key = match Self::extract_string(&mut iter) {
+                                Some(key) => key,
+                                None => return vec![create_wrong_args_err_resp("zrem")]
+                            };
+                            let redis_command = ZremCommand::new(key, self.cache.clone());
+                            self.propagate_write(&resp_types);
+                            redis_command.execute(&mut iter)
+                        },
+                        "zscore" => {
+                            let (key, member) = match (Self::extract_string(&mut iter), Self::extract_string(&mut iter)) {
+                                (Some(key), Some(member)) => (key, member),
+                                _ => return vec![create_wrong_args_err_resp("zscore")]
+                            };
+                            let redis_command = ZscoreCommand::new(key, member, self.cache.clone());
+                            redis_command.execute(&mut iter)
+                        },
+                        "zmscore" => {
+                            let key = match Self::extract_string(&mut iter) {
+                                Some(key) => key,
+                                None => return vec![create_wrong_args_err_resp("zmscore")]
+                            };
+                            let redis_command = ZmscoreCommand::new(key, self.cache.clone());
+                            redis_command.execute(&mut iter)
+                        },
+                        "zincrby" => {
+                            let (key, increment, member) = match (Self::extract_string(&mut iter), Self::extract_string(&mut iter), Self::extract_string(&mut iter)) {
+                                (Some(key), Some(increment), Some(member)) => (key, increment, member),
+                                _ => return vec![create_wrong_args_err_resp("zincrby")]
+                            };
+                            let increment = match parse_score(&increment) {
+                                Some(increment) => increment,
+                                None => return vec![create_basic_err_resp("ERR value is not a valid float".to_string())]
+                            };
+                            let redis_command = ZincrbyCommand::new(key.clone(), increment, member, self.cache.clone());
+                            self.propagate_write(&resp_types);
+                            let res = redis_command.execute(&mut iter);
+                            self.notifier.notify_key(&key);
+                            res
+                        },
+                        "zcard" => {
+                            let key = match Self::extract_string(&mut iter) {
+                                Some(key) => key,
+                                None => return vec![create_wrong_args_err_resp("zcard")]
+                            };
+                            let redis_command = ZcardCommand::new(key, self.cache.clone());
+                            redis_command.execute(&mut iter)
+                        },
+                        "zcount" => {
+                            let (key, min, max) = match (Self::extract_string(&mut iter), Self::extract_string(&mut iter), Self::extract_string(&mut iter)) {
+                                (Some(key), Some(min), Some(max)) => (key, min, max),
+                                _ => return vec![create_wrong_args_err_resp("zcount")]
+                            };
+                            let (min, max) = match (ScoreBound::parse(&min), ScoreBound::parse(&max)) {
+                                (Some(min), Some(max)) => (min, max),
+                                _ => return vec![create_basic_err_resp("ERR min or max is not a float".to_string())]
+                            };
+                            let redis_command = ZcountCommand::new(key, min, max, self.cache.clone());
+                            redis_command.execute(&mut iter)
+                        },
+                        "zrank" | "zrevrank" => {
+                            let (key, member) = match (Self::extract_string(&mut iter), Self::extract_string(&mut iter)) {
+                                (Some(key), Some(member)) => (key, member),
+                                _ => return vec![create_wrong_args_err_resp(command.as_str())]
+                            };
+                            let with_score = match Self::extract_string(&mut iter) {
+                                Some(arg) if arg.eq_ignore_ascii_case(b"withscore") => true,
+                                Some(_) => return vec![create_basic_err_resp("ERR syntax error".to_string())],
+                                None => false
+                            };
+                            let redis_command = ZrankCommand::new(key, member, command.eq("zrevrank"), with_score, self.cache.clone());
+                            redis_command.execute(&mut iter)
+                        },
+                        "zrange" => {
+                            let key = match Self::extract_string(&mut iter) {
+                                Some(key) => key,
+                                None => return vec![create_wrong_args_err_resp("zrange")]
+                            };
+                            let redis_command = ZrangeCommand::new(key, self.cache.clone());
+                            redis_command.execute(&mut iter)
+                        },
+                        "zunion" => {
+                            let redis_command = ZunionCommand::new(None, self.cache.clone());
+                            redis_command.execute(&mut iter)
+                        },
+                        "zunionstore" => {
+                            let destination = match Self::extract_string(&mut iter) {
+                                Some(destination) => destination,
+                                None => return vec![create_wrong_args_err_resp("zunionstore")]
+                            };
+                            let redis_command = ZunionCommand::new(Some(destination.clone()), self.cache.clone());
+                            self.propagate_write(&resp_types);
+                            let res = redis_command.execute(&mut iter);
+                            self.notifier.notify_key(&destination);
+                            res
+                        },
+                        "zinter" => {
+                            let redis_command = ZinterCommand::new(None, self.cache.clone());
+                            redis_command.execute(&mut iter)
+                        },
+                        "zinterstore" => {
+                            let destination = match Self::extract_string(&mut iter) {
+                                Some(destination) => destination,
+                                None => return vec![create_wrong_args_err_resp("zinterstore")]
+                            };
+                            let redis_command = ZinterCommand::new(Some(destination.clone()), self.cache.clone());
+                            self.propagate_write(&resp_types);
+                            let res = redis_command.execute(&mut iter);
+                            self.notifier.notify_key(&destination);
+                            res
+                        },
+                        "zdiff" => {
+                            let redis_command = ZdiffCommand::new(None, self.cache.clone());
+                            redis_command.execute(&mut iter)
+                        },
+                        "zdiffstore" => {
+                            let destination = match Self::extract_string(&mut iter) {
+                                Some(destination) => destination,
+                                None => return vec![create_wrong_args_err_resp("zdiffstore")]
+                            };
+                            let redis_command = ZdiffCommand::new(Some(destination.clone()), self.cache.clone());
+                            self.propagate_write(&resp_types);
+                            let res = redis_command.execute(&mut iter);
+                            self.notifier.notify_key(&destination);
+                            res
+                        },
+                        "zpopmin" | "zpopmax" => {
+                            let key = match Self::extract_string(&mut iter) {
+                                Some(key) => key,
+                                None => return vec![create_wrong_args_err_resp(command.as_str())]
+                            };
+                            let count = match iter.next() {
+                                Some(RespType::String(count)) => match parse_arg::<usize>(count) {
+                                    Some(count) => Some(count),
+                                    None => return vec![create_basic_err_resp("ERR value is out of range, must be positive".to_string())]
+                                },
+                                _ => None
+                            };
+                            let redis_command = ZpopCommand::new(key, command.eq("zpopmax"), count, self.cache.clone());
+                            self.propagate_write(&resp_types);
+                            redis_command.execute(&mut iter)
+                        },
+                        "bzpopmin" | "bzpopmax" => {
+                            let mut args = vec![];
+                            while let Some(arg) = Self::extract_string(&mut iter) {
+                                args.push(arg);
+                            }
+                            if args.len() < 2 {
+                                return vec![create_wrong_args_err_resp(command.as_str())];
+                            }
+                            let timeout = match Self::parse_block_timeout(&args.pop().unwrap()) {
+                                Ok(timeout) => timeout,
+                                Err(err) => return vec![err]
+                            };
+                            let redis_command = BzpopCommand::new(args, command.eq("bzpopmax"), BlockingArgs::new(self.id.clone(), timeout, self.db, self.notifier.clone()), self.cache.clone(), self.replication_log());
+                            redis_command.execute(&mut iter)
+                        },
+                        "zmpop" => {
+                            let redis_command = ZmpopCommand::new(self.cache.clone());
+                            self.propagate_write(&resp_types);
+                            redis_command.execute(&mut iter)
+                        },
+                        "geoadd" => {
+                            let key = match Self::extract_string(&mut iter) {
+                                Some(key) => key,
+                                None => return vec![create_wrong_args_err_resp("geoadd")]
+                            };
+                            let redis_command = GeoaddCommand::new(key.clone(), self.cache.clone());
+                            self.propagate_write(&resp_types);
+                            let res = redis_command.execute(&mut iter);
+                            self.notifier.notify_key(&key);
+                            res
+                        },
+                        "geodist" => {
+                            let (key, member1, member2) = match (Self::extract_string(&mut iter), Self::extract_string(&mut iter), Self::extract_string(&mut iter)) {
+                                (Some(key), Some(member1), Some(member2)) => (key, member1, member2),
+                                _ => return vec![create_wrong_args_err_resp("geodist")]
+                            };
+                            let unit = match (Self::extract_string(&mut iter), iter.next()) {
+                                (None, _) => 1.0,
+                                (Some(unit), None) => match parse_unit(&unit) {
+                                    Some(unit) => unit,
+                                    None => return vec![create_basic_err_resp("ERR unsupported unit provided. please use M, KM, FT, MI".to_string())]
+                                },
+                                _ => return vec![create_basic_err_resp("ERR syntax error".to_string())]
+                            };
+                            let redis_command = GeodistCommand::new(key, member1, member2, unit, self.cache.clone());
+                            redis_command.execute(&mut iter)
+                        },
+                        "geopos" => {
+                            let key = match Self::extract_string(&mut iter) {
+                                Some(key) => key,
+                                None => return vec![create_wrong_args_err_resp("geopos")]
+                            };
+                            let redis_command = GeoposCommand::new(key, self.cache.clone());
+                            redis_command.execute(&mut iter)
+                        },
+                        "geohash" => {
+                            let key = match Self::extract_string(&mut iter) {
+                                Some(key) => key,
+                                None => return vec![create_wrong_args_err_resp("geohash")]
+                            };
+                            let redis_command = GeohashCommand::new(key, self.cache.clone());
+                            redis_command.execute(&mut iter)
+                        },
+                        "geosearch" => {
+                            let redis_command = GeosearchCommand::new(None, self.cache.clone());
+                            redis_command.execute(&mut iter)
+                        },
+                        "geosearchstore" => {
+                            let destination = match Self::extract_string(&mut iter) {
+                                Some(destination) => destination,
+                                None => return vec![create_wrong_args_err_resp("geosearchstore")]
+                            };
+                            let redis_command = GeosearchCommand::new(Some(destination.clone()), self.cache.clone());
+                            self.propagate_write(&resp_types);
+                            let res = redis_command.execute(&mut iter);
+                            self.notifier.notify_key(&destination);
+                            res
+                        },
+                        "xlen" => {
+                            let key = match Self::extract_string(&mut iter) {
+                                Some(key) => key,
+                                None => return vec![create_wrong_args_err_resp("xlen")]
+                            };
+                            let redis_command = XlenCommand::new(key, self.cache.clone());
+                            redis_command.execute(&mut iter)
+                        },
+                        "xdel" => {
+                            let key = match Self::extract_string(&mut iter) {
+                                Some(key) => key,
+                                None => return vec![create_wrong_args_err_resp("xdel")]
+                            };
+                            let redis_command = XdelCommand::new(key, self.cache.clone());
+                            self.propagate_write(&resp_types);
+                            redis_command.execute(&mut iter)
+                        },
+                        "xtrim" => {
+                            let key = match Self::extract_string(&mut iter) {
+                                Some(key) => key,
+                                None => return vec![create_wrong_args_err_resp("xtrim")]
+                            };
+                            let redis_command = XtrimCommand::new(key, self.cache.clone(), self.replication_log());
+                            redis_command.execute(&mut iter)
+                        },
+                        "xinfo" => {
+                            let redis_command = XinfoCommand::new(self.cache.clone());
+                            redis_command.execute(&mut iter)
+                        },
+                        "xgroup" => {
+                            let redis_command = XgroupCommand::new(self.cache.clone());
+                            self.propagate_write(&resp_types);
+                            redis_command.execute(&mut iter)
+                        },
+                        "xreadgroup" => {
+                            let redis_command = XreadgroupCommand::new(self.cache.clone(), self.notifier.clone(), self.replication_log());
+                            redis_command.execute(&mut iter)
+                        },
+                        "xack" => {
+                            let (key, group) = match (Self::extract_string(&mut iter), Self::extract_string(&mut iter)) {
+                                (Some(key), Some(group)) => (key, group),
+                                _ => return vec![create_wrong_args_err_resp("xack")]
+                            };
+                            let redis_command = XackCommand::new(key, group, self.cache.clone());
+                            self.propagate_write(&resp_types);
+                            redis_command.execute(&mut iter)
+                        },
+                        "xpending" => {
+                            let (key, group) = match (Self::extract_string(&mut iter), Self::extract_string(&mut iter)) {
+                                (Some(key), Some(group)) => (key, group),
+                                _ => return vec![create_wrong_args_err_resp("xpending")]
+                            };
+                            let redis_command = XpendingCommand::new(key, group, self.cache.clone());
+                            redis_command.execute(&mut iter)
+                        },
+                        "xclaim" | "xautoclaim" => {
+                            let (key, group, consumer) = match (Self::extract_string(&mut iter), Self::extract_string(&mut iter), Self::extract_string(&mut iter)) {
+                                (Some(key), Some(group), Some(consumer)) => (key, group, consumer),
+                                _ => return vec![create_wrong_args_err_resp(command.as_str())]
+                            };
+                            let min_idle = match Self::extract_string(&mut iter).map(|min_idle| parse_arg::<i64>(&min_idle)) {
+                                Some(Some(min_idle)) => min_idle.max(0) as u128,
+                                Some(None) => return vec![create_basic_err_resp("ERR Invalid min-idle-time argument for XCLAIM".to_string())],
+                                None => return vec![create_wrong_args_err_resp(command.as_str())]
+                            };
+                            if command.eq("xclaim") {
+                                XclaimCommand::new(key, group, consumer, min_idle, self.cache.clone(), self.replication_log()).execute(&mut iter)
+                            } else {
+                                XautoclaimCommand::new(key, group, consumer, min_idle, self.cache.clone(), self.replication_log()).execute(&mut iter)
+                            }
+                        },
+                        _ => panic!("UNEXPECTED COMMAND")
+                    }
+                } else {
+                    panic!("UNEXPECTED ARRAY ENTRY EXPECTING ONLY STRINGS")
+                } 
+            },
+            _ => return vec![]
+        }
+    }
+
+    fn extract_string(iter: &mut Iter<'_, RespType>) -> Option<Vec<u8>> {
         match iter.next() {
-            Some(RespType::String(int_str)) => {
-                match int_str.parse::<T>() {
-                    Ok(val) => Some(val),
-                    Err(_) => None
-                }
+            Some(RespType::String(s)) => Some(s.clone()),
+            _ => None
+        }
+    }
+
+    /// Queues the command as received so it is forwarded verbatim to the replicas.
+    fn propagate_write(&self, resp_types: &[RespType]) {
+        if self.replica_of.is_none() {
+            // the command is replicated before it runs, so the keys it would find expired have to be
+            // deleted first for their DEL to reach the replicas ahead of it
+            let args: Vec<&[u8]> = resp_types.iter().filter_map(|arg| match arg {
+                RespType::String(arg) => Some(arg.as_slice()),
+                _ => None
+            }).collect();
+            let mut cache_guard = self.cache.lock().unwrap();
+            for key in Self::write_command_keys(&args) {
+                cache_guard.expire_if_needed(key);
             }
+            let mut write_command_gaurd = self.write_commands.lock().unwrap();
+            write_command_gaurd.push(self.db, RespType::Array(resp_types.to_vec()).to_bytes());
+        }
+    }
+
+    /// The keys among the arguments of a write forwarded verbatim, the command name first. Values,
+    /// fields and members are left out so they are never mistaken for keys.
+    fn write_command_keys<'a>(args: &[&'a [u8]]) -> Vec<&'a [u8]> {
+        let command = match args.first() {
+            Some(command) => command.to_ascii_lowercase(),
+            None => return vec![]
+        };
+        let args = &args[1..];
+        // the keys that follow a count, as in ZUNIONSTORE destination numkeys key ... or LMPOP numkeys key ...
+        let counted = |count_at: usize| {
+            let count = args.get(count_at).and_then(|count| parse_arg::<usize>(count)).unwrap_or(0);
+            args.iter().skip(count_at + 1).take(count).copied().collect::<Vec<_>>()
+        };
+        match command.as_slice() {
+            b"del" | b"unlink" | b"sinterstore" | b"sunionstore" | b"sdiffstore" | b"pfmerge" => args.to_vec(),
+            b"rename" | b"renamenx" | b"copy" | b"smove" | b"lmove" | b"rpoplpush" | b"geosearchstore" => args.iter().take(2).copied().collect(),
+            b"mset" | b"msetnx" => args.iter().step_by(2).copied().collect(),
+            b"bitop" => args.iter().skip(1).copied().collect(),
+            b"zunionstore" | b"zinterstore" | b"zdiffstore" => args.iter().take(1).copied().chain(counted(1)).collect(),
+            b"lmpop" | b"zmpop" => counted(0),
+            b"xgroup" => args.iter().skip(1).take(1).copied().collect(),
+            b"swapdb" | b"flushdb" | b"flushall" => vec![],
+            _ => args.iter().take(1).copied().collect()
+        }
+    }
+
+    /// The replication queue for commands that need to rewrite what gets propagated, `None` on replicas.
+    fn replication_log(&self) -> Option<ReplicationLog> {
+        if self.replica_of.is_none() {
+            Some(ReplicationLog::new(self.write_commands.clone(), self.db))
+        } else {
+            None
+        }
+    }
+
+    /// The db a SELECT, MOVE, SWAPDB or COPY argument names.
+    fn parse_db_index(&self, arg: &[u8]) -> Result<usize, Vec<u8>> {
+        match parse_arg::<i64>(arg) {
+            Some(db) if db >= 0 && (db as usize) < self.databases.len() => Ok(db as usize),
+            Some(_) => Err(create_basic_err_resp("ERR DB index is out of range".to_string())),
+            None => Err(create_basic_err_resp("ERR value is not an integer or out of range".to_string()))
+        }
+    }
+
+    /// Parses the timeout in seconds of a blocking command, zero meaning forever.
+    fn parse_block_timeout(arg: &[u8]) -> Result<f32, Vec<u8>> {
+        match parse_arg::<f32>(arg) {
+            Some(timeout) if timeout < 0.0 => Err(create_basic_err_resp("ERR timeout is negative".to_string())),
+            Some(timeout) if timeout.is_finite() => Ok(timeout),
+            _ => Err(create_basic_err_resp("ERR timeout is not a float or out of range".to_string()))
+        }
+    }
+
+    fn extract_num<T>(iter: &mut Iter<'_, RespType>) -> Option<T> where T: FromStr {
+        match iter.next() {
+            Some(RespType::String(int_str)) => parse_arg::<T>(int_str),
             _ => None
         }
     }
@@ -508,48 +1715,55 @@ mod tests {
     use crate::redis::client;
 
     use super::*;
+    use crate::commands::MAX_RANDOM_REPEATS;
+    use crate::redis::keyspace::ExpireMode;
 
-    fn instantiate_client() -> (Client, Arc<Mutex<HashMap<String, CacheVal>>>, Arc<Mutex<Vec<String>>>, Arc<Mutex<HashMap<String, Vec<String>>>>) {
-        let cache: Arc<Mutex<HashMap<String, CacheVal>>> = Arc::new(Mutex::new(HashMap::new()));
-        let write_commands = Arc::new(Mutex::new(vec![]));
+    fn instantiate_client() -> (Client, Arc<Mutex<Keyspace>>, Arc<Mutex<WriteCommands>>, Arc<Mutex<HashMap<Vec<u8>, Vec<String>>>>) {
+        let databases: Databases = Arc::new((0..16).map(|_| Arc::new(Mutex::new(Keyspace::new()))).collect());
+        let cache = databases[0].clone();
+        let write_commands = Arc::new(Mutex::new(WriteCommands::default()));
         let ack_replicas = Arc::new(Mutex::new(0));
         let channel_to_subscribers = Arc::new(Mutex::new(HashMap::new()));
         let client_to_stream = Arc::new(Mutex::new(HashMap::new()));
-        let client = Client::new(cache.clone(), write_commands.clone(), ack_replicas.clone(),None, channel_to_subscribers.clone(), client_to_stream.clone(), "test_rdb_dir".to_string(), "test_rdb_file".to_string());
+        let client = Client::new(databases, write_commands.clone(), ack_replicas.clone(), Arc::new(Notifier::default()), None, channel_to_subscribers.clone(), client_to_stream.clone(), "test_rdb_dir".to_string(), "test_rdb_file".to_string());
         (client, cache, write_commands, channel_to_subscribers)
     }
 
+    fn build_command(args: &[&str]) -> RespType {
+        RespType::Array(args.iter().map(|arg| RespType::String(arg.as_bytes().to_vec())).collect())
+    }
+
     #[test]
     fn test_publish_command() {
         let (mut client, cache, write_commands, channel_to_subscribers) = instantiate_client();
         let cmds = vec![
-            RespType::String("PUBLISH".to_string()),
-            RespType::String("channel1".to_string()),
-            RespType::String("message".to_string())
+            RespType::String(b"PUBLISH".to_vec()),
+            RespType::String(b"channel1".to_vec()),
+            RespType::String(b"message".to_vec())
         ];
         let cmd = RespType::Array(cmds);
         let res = client.handle_command(cmd);
-        assert!(res[0].eq(":0\r\n"));
+        assert!(res[0].eq(b":0\r\n"));
 
         let cmds = vec![
-            RespType::String("SUBSCRIBE".to_string()),
-            RespType::String("channel1".to_string())
+            RespType::String(b"SUBSCRIBE".to_vec()),
+            RespType::String(b"channel1".to_vec())
         ];
         let cmd = RespType::Array(cmds);
         let res = client.handle_command(cmd);
-        assert!(res[0].eq("*3\r\n$9\r\nsubscribe\r\n$8\r\nchannel1\r\n:1\r\n"));
+        assert!(res[0].eq(b"*3\r\n$9\r\nsubscribe\r\n$8\r\nchannel1\r\n:1\r\n"));
 
 
-        let mut client_two = Client::new(cache.clone(), write_commands.clone(), Arc::new(Mutex::new(0)).clone(),None, channel_to_subscribers.clone(), Arc::new(Mutex::new(HashMap::new())), "test_rdb_dir".to_string(), "test_rdb_file".to_string());
+        let mut client_two = Client::new(client.databases.clone(), write_commands.clone(), Arc::new(Mutex::new(0)).clone(), client.notifier.clone(), None, channel_to_subscribers.clone(), Arc::new(Mutex::new(HashMap::new())), "test_rdb_dir".to_string(), "test_rdb_file".to_string());
 
         let cmds = vec![
-            RespType::String("PUBLISH".to_string()),
-            RespType::String("channel1".to_string()),
-            RespType::String("message".to_string())
+            RespType::String(b"PUBLISH".to_vec()),
+            RespType::String(b"channel1".to_vec()),
+            RespType::String(b"message".to_vec())
         ];
         let cmd = RespType::Array(cmds);
         let res = client_two.handle_command(cmd);
-        assert!(res[0].eq(":1\r\n"));
+        assert!(res[0].eq(b":1\r\n"));
     }
 
     #[test]
@@ -557,41 +1771,41 @@ mod tests {
         let (mut client, _ ,_ , _) = instantiate_client();
 
         let cmds = vec![
-            RespType::String("SUBSCRIBE".to_string()),
-            RespType::String("channel1".to_string())
+            RespType::String(b"SUBSCRIBE".to_vec()),
+            RespType::String(b"channel1".to_vec())
         ];
         let cmd = RespType::Array(cmds);
         let res = client.handle_command(cmd);
-        assert!(res[0].eq("*3\r\n$9\r\nsubscribe\r\n$8\r\nchannel1\r\n:1\r\n"));
-        assert!(client.subscribed_channels.contains(&"channel1".to_string()));
+        assert!(res[0].eq(b"*3\r\n$9\r\nsubscribe\r\n$8\r\nchannel1\r\n:1\r\n"));
+        assert!(client.subscribed_channels.contains(b"channel1".as_slice()));
 
         let cmds = vec![
-            RespType::String("SUBSCRIBE".to_string()),
-            RespType::String("channel2".to_string())
+            RespType::String(b"SUBSCRIBE".to_vec()),
+            RespType::String(b"channel2".to_vec())
         ];
         let cmd = RespType::Array(cmds);
         let res = client.handle_command(cmd);
-        assert!(res[0].eq("*3\r\n$9\r\nsubscribe\r\n$8\r\nchannel2\r\n:2\r\n"));
-        assert!(client.subscribed_channels.contains(&"channel1".to_string()));
+        assert!(res[0].eq(b"*3\r\n$9\r\nsubscribe\r\n$8\r\nchannel2\r\n:2\r\n"));
+        assert!(client.subscribed_channels.contains(b"channel1".as_slice()));
 
         let cmds = vec![
-            RespType::String("SUBSCRIBE".to_string()),
-            RespType::String("channel2".to_string())
+            RespType::String(b"SUBSCRIBE".to_vec()),
+            RespType::String(b"channel2".to_vec())
         ];
         let cmd = RespType::Array(cmds);
         let res = client.handle_command(cmd);
-        assert!(res[0].eq("*3\r\n$9\r\nsubscribe\r\n$8\r\nchannel2\r\n:2\r\n"));
-        assert!(client.subscribed_channels.contains(&"channel1".to_string()));
+        assert!(res[0].eq(b"*3\r\n$9\r\nsubscribe\r\n$8\r\nchannel2\r\n:2\r\n"));
+        assert!(client.subscribed_channels.contains(b"channel1".as_slice()));
 
         let cmds = vec![
-            RespType::String("SET".to_string()),
-            RespType::String("key1".to_string()),
-            RespType::String("value1".to_string())
+            RespType::String(b"SET".to_vec()),
+            RespType::String(b"key1".to_vec()),
+            RespType::String(b"value1".to_vec())
         ];
         let cmd = RespType::Array(cmds);
         let res = client.handle_command(cmd);
-        println!("RES: {}", res[0]);
-        assert!(res[0].eq("-ERR Can't execute 'set': only (P|S)SUBSCRIBE / (P|S)UNSUBSCRIBE / PING / QUIT / RESET are allowed in this context\r\n"));
+        println!("RES: {}", String::from_utf8_lossy(&res[0]));
+        assert!(res[0].eq(b"-ERR Can't execute 'set': only (P|S)SUBSCRIBE / (P|S)UNSUBSCRIBE / PING / QUIT / RESET are allowed in this context\r\n"));
         
     }
 
@@ -600,38 +1814,38 @@ mod tests {
         let (mut client,_ ,_ , _) = instantiate_client();
         {
             let mut cache_guard = client.cache.lock().unwrap();
-            cache_guard.insert("key1".to_string(), CacheVal::String(StringCacheVal { val: "value1".to_string(), expiry_time: None }));
-            cache_guard.insert("key2".to_string(), CacheVal::String(StringCacheVal { val: "value2".to_string(), expiry_time: None }));
+            cache_guard.insert(b"key1".to_vec(), CacheVal::String(StringCacheVal::new("value1".into())));
+            cache_guard.insert(b"key2".to_vec(), CacheVal::String(StringCacheVal::new("value2".into())));
         }
         let cmds = vec![
-            RespType::String("KEYS".to_string()),
-            RespType::String("*".to_string())
+            RespType::String(b"KEYS".to_vec()),
+            RespType::String(b"*".to_vec())
         ];
         let cmd = RespType::Array(cmds);
         let res = client.handle_command(cmd);
-        assert!(res[0].contains("key1"));
-        assert!(res[0].contains("key2"));
+        assert!(String::from_utf8_lossy(&res[0]).contains("key1"));
+        assert!(String::from_utf8_lossy(&res[0]).contains("key2"));
     }
 
     #[test]
     fn test_config_command() {
         let (mut client,_ ,_ , _) = instantiate_client();
         let cmds = vec![
-            RespType::String("CONFIG".to_string()),
-            RespType::String("GET".to_string()),
-            RespType::String("dir".to_string())
+            RespType::String(b"CONFIG".to_vec()),
+            RespType::String(b"GET".to_vec()),
+            RespType::String(b"dir".to_vec())
         ];
         let cmd = RespType::Array(cmds);
         let res = client.handle_command(cmd);
-        assert!(res[0].eq("*2\r\n$3\r\ndir\r\n$12\r\ntest_rdb_dir\r\n"));
+        assert!(res[0].eq(b"*2\r\n$3\r\ndir\r\n$12\r\ntest_rdb_dir\r\n"));
         let cmds = vec![
-            RespType::String("CONFIG".to_string()),
-            RespType::String("GET".to_string()),
-            RespType::String("dbfilename".to_string())
+            RespType::String(b"CONFIG".to_vec()),
+            RespType::String(b"GET".to_vec()),
+            RespType::String(b"dbfilename".to_vec())
         ];
         let cmd = RespType::Array(cmds);
         let res = client.handle_command(cmd);
-        assert!(res[0].eq("*2\r\n$10\r\ndbfilename\r\n$13\r\ntest_rdb_file\r\n"));
+        assert!(res[0].eq(b"*2\r\n$10\r\ndbfilename\r\n$13\r\ntest_rdb_file\r\n"));
     }
 
     #[test]
@@ -639,36 +1853,36 @@ mod tests {
         let (mut client,_ ,_ , _) = instantiate_client();
 
         let cmds = vec![
-            RespType::String("MULTI".to_string())
+            RespType::String(b"MULTI".to_vec())
         ];
         let cmd = RespType::Array(cmds);
 
         let res = client.handle_command(cmd);
-        assert!(res[0].eq("+OK\r\n"));
+        assert!(res[0].eq(b"+OK\r\n"));
 
         let cmds = vec![
-            RespType::String("SET".to_string()),
-            RespType::String("foo".to_string()),
-            RespType::String("41".to_string()),
+            RespType::String(b"SET".to_vec()),
+            RespType::String(b"foo".to_vec()),
+            RespType::String(b"41".to_vec()),
         ];
         let cmd = RespType::Array(cmds);
         let res = client.handle_command(cmd);
-        assert!(res[0].eq("+QUEUED\r\n"));
+        assert!(res[0].eq(b"+QUEUED\r\n"));
 
         let cmds = vec![
-            RespType::String("INCR".to_string()),
-            RespType::String("foo".to_string()),
+            RespType::String(b"INCR".to_vec()),
+            RespType::String(b"foo".to_vec()),
         ];
         let cmd = RespType::Array(cmds);
         let res = client.handle_command(cmd);
-        assert!(res[0].eq("+QUEUED\r\n"));
+        assert!(res[0].eq(b"+QUEUED\r\n"));
 
         let cmds = vec![
-            RespType::String("EXEC".to_string())
+            RespType::String(b"EXEC".to_vec())
         ];
         let cmd = RespType::Array(cmds);
         let res = client.handle_command(cmd);
-        assert!(res[0].eq("*2\r\n+OK\r\n:42\r\n"));
+        assert!(res[0].eq(b"*2\r\n+OK\r\n:42\r\n"));
     }
 
     #[test]
@@ -676,21 +1890,21 @@ mod tests {
         let (mut client,_ ,_ , _) = instantiate_client();
 
         let cmds = vec![
-            RespType::String("INCR".to_string()),
-            RespType::String("key".to_string())
+            RespType::String(b"INCR".to_vec()),
+            RespType::String(b"key".to_vec())
         ];
         let cmd = RespType::Array(cmds);
 
         let res = client.handle_command(cmd);
-        assert!(res[0].eq(":1\r\n"));
+        assert!(res[0].eq(b":1\r\n"));
 
         let cmds = vec![
-            RespType::String("INCR".to_string()),
-            RespType::String("key".to_string())
+            RespType::String(b"INCR".to_vec()),
+            RespType::String(b"key".to_vec())
         ];
         let cmd = RespType::Array(cmds);
         let res = client.handle_command(cmd);
-        assert!(res[0].eq(":2\r\n"));
+        assert!(res[0].eq(b":2\r\n"));
     }
 
     #[test]
@@ -699,25 +1913,25 @@ mod tests {
 
         {
             let mut cache_guard = cache.lock().unwrap();
-            let stream_item_one = StreamItem {id: "0-1".into(), key_vals: vec![KeyVal {key: "foo".to_string(), val: "bar".to_string()} ]};
-            let stream_item_two = StreamItem {id: "0-2".into(), key_vals: vec![KeyVal {key: "bar".to_string(), val: "baz".to_string()} ]};
-            let stream_item_three = StreamItem {id: "0-3".into(), key_vals: vec![KeyVal {key: "baz".to_string(), val: "foo".to_string()} ]};
-            cache_guard.insert("stream_key".to_string(), CacheVal::Stream(StreamCacheVal { stream: vec![stream_item_one.clone(), stream_item_two.clone(), stream_item_three.clone()] }));
-            cache_guard.insert("other_stream_key".to_string(), CacheVal::Stream(StreamCacheVal { stream: vec![stream_item_one, stream_item_two, stream_item_three] }));
+            let stream_item_one = StreamItem {id: StreamId::new(0, 1), key_vals: vec![KeyVal {key: b"foo".to_vec(), val: b"bar".to_vec()} ]};
+            let stream_item_two = StreamItem {id: StreamId::new(0, 2), key_vals: vec![KeyVal {key: b"bar".to_vec(), val: b"baz".to_vec()} ]};
+            let stream_item_three = StreamItem {id: StreamId::new(0, 3), key_vals: vec![KeyVal {key: b"baz".to_vec(), val: b"foo".to_vec()} ]};
+            cache_guard.insert(b"stream_key".to_vec(), CacheVal::Stream(StreamCacheVal::new(vec![stream_item_one.clone(), stream_item_two.clone(), stream_item_three.clone()])));
+            cache_guard.insert(b"other_stream_key".to_vec(), CacheVal::Stream(StreamCacheVal::new(vec![stream_item_one, stream_item_two, stream_item_three])));
         }
         
         let cmds = vec![
-            RespType::String("XREAD".to_string()),
-            RespType::String("streams".to_string()),
-            RespType::String("stream_key".to_string()),
-            RespType::String("other_stream_key".to_string()),
-            RespType::String("0-2".to_string()),
-            RespType::String("0-1".to_string())
+            RespType::String(b"XREAD".to_vec()),
+            RespType::String(b"streams".to_vec()),
+            RespType::String(b"stream_key".to_vec()),
+            RespType::String(b"other_stream_key".to_vec()),
+            RespType::String(b"0-2".to_vec()),
+            RespType::String(b"0-1".to_vec())
         ];
         let cmd = RespType::Array(cmds);
 
         let res = client.handle_command(cmd);
-        assert!(res[0].eq("*2\r\n*2\r\n$10\r\nstream_key\r\n*1\r\n*2\r\n$3\r\n0-3\r\n*2\r\n$3\r\nbaz\r\n$3\r\nfoo\r\n*2\r\n$16\r\nother_stream_key\r\n*2\r\n*2\r\n$3\r\n0-2\r\n*2\r\n$3\r\nbar\r\n$3\r\nbaz\r\n*2\r\n$3\r\n0-3\r\n*2\r\n$3\r\nbaz\r\n$3\r\nfoo\r\n"));
+        assert!(res[0].eq(b"*2\r\n*2\r\n$10\r\nstream_key\r\n*1\r\n*2\r\n$3\r\n0-3\r\n*2\r\n$3\r\nbaz\r\n$3\r\nfoo\r\n*2\r\n$16\r\nother_stream_key\r\n*2\r\n*2\r\n$3\r\n0-2\r\n*2\r\n$3\r\nbar\r\n$3\r\nbaz\r\n*2\r\n$3\r\n0-3\r\n*2\r\n$3\r\nbaz\r\n$3\r\nfoo\r\n"));
     }
 
     #[test]
@@ -726,22 +1940,22 @@ mod tests {
 
         {
             let mut cache_guard = cache.lock().unwrap();
-            let stream_item_one = StreamItem {id: "0-1".into(), key_vals: vec![KeyVal {key: "foo".to_string(), val: "bar".to_string()} ]};
-            let stream_item_two = StreamItem {id: "0-2".into(), key_vals: vec![KeyVal {key: "bar".to_string(), val: "baz".to_string()} ]};
-            let stream_item_three = StreamItem {id: "0-3".into(), key_vals: vec![KeyVal {key: "baz".to_string(), val: "foo".to_string()} ]};
-            cache_guard.insert("stream_key".to_string(), CacheVal::Stream(StreamCacheVal { stream: vec![stream_item_one, stream_item_two, stream_item_three] }));
+            let stream_item_one = StreamItem {id: StreamId::new(0, 1), key_vals: vec![KeyVal {key: b"foo".to_vec(), val: b"bar".to_vec()} ]};
+            let stream_item_two = StreamItem {id: StreamId::new(0, 2), key_vals: vec![KeyVal {key: b"bar".to_vec(), val: b"baz".to_vec()} ]};
+            let stream_item_three = StreamItem {id: StreamId::new(0, 3), key_vals: vec![KeyVal {key: b"baz".to_vec(), val: b"foo".to_vec()} ]};
+            cache_guard.insert(b"stream_key".to_vec(), CacheVal::Stream(StreamCacheVal::new(vec![stream_item_one, stream_item_two, stream_item_three])));
         }
         
         let cmds = vec![
-            RespType::String("XREAD".to_string()),
-            RespType::String("streams".to_string()),
-            RespType::String("stream_key".to_string()),
-            RespType::String("0-2".to_string())
+            RespType::String(b"XREAD".to_vec()),
+            RespType::String(b"streams".to_vec()),
+            RespType::String(b"stream_key".to_vec()),
+            RespType::String(b"0-2".to_vec())
         ];
         let cmd = RespType::Array(cmds);
 
         let res = client.handle_command(cmd);
-        assert!(res[0].eq("*1\r\n*2\r\n$10\r\nstream_key\r\n*1\r\n*2\r\n$3\r\n0-3\r\n*2\r\n$3\r\nbaz\r\n$3\r\nfoo\r\n"));
+        assert!(res[0].eq(b"*1\r\n*2\r\n$10\r\nstream_key\r\n*1\r\n*2\r\n$3\r\n0-3\r\n*2\r\n$3\r\nbaz\r\n$3\r\nfoo\r\n"));
     }
 
     #[test]
@@ -750,69 +1964,69 @@ mod tests {
 
         {
             let mut cache_guard = cache.lock().unwrap();
-            let stream_item_one = StreamItem {id: "0-1".into(), key_vals: vec![KeyVal {key: "foo".to_string(), val: "bar".to_string()} ]};
-            let stream_item_two = StreamItem {id: "0-2".into(), key_vals: vec![KeyVal {key: "bar".to_string(), val: "baz".to_string()} ]};
-            let stream_item_three = StreamItem {id: "0-3".into(), key_vals: vec![KeyVal {key: "baz".to_string(), val: "foo".to_string()} ]};
-            cache_guard.insert("stream_key".to_string(), CacheVal::Stream(StreamCacheVal { stream: vec![stream_item_one, stream_item_two, stream_item_three] }));
+            let stream_item_one = StreamItem {id: StreamId::new(0, 1), key_vals: vec![KeyVal {key: b"foo".to_vec(), val: b"bar".to_vec()} ]};
+            let stream_item_two = StreamItem {id: StreamId::new(0, 2), key_vals: vec![KeyVal {key: b"bar".to_vec(), val: b"baz".to_vec()} ]};
+            let stream_item_three = StreamItem {id: StreamId::new(0, 3), key_vals: vec![KeyVal {key: b"baz".to_vec(), val: b"foo".to_vec()} ]};
+            cache_guard.insert(b"stream_key".to_vec(), CacheVal::Stream(StreamCacheVal::new(vec![stream_item_one, stream_item_two, stream_item_three])));
         }
         
         let cmds = vec![
-            RespType::String("XRANGE".to_string()),
-            RespType::String("stream_key".to_string()),
-            RespType::String("0-2".to_string()),
-            RespType::String("0-3".to_string())
+            RespType::String(b"XRANGE".to_vec()),
+            RespType::String(b"stream_key".to_vec()),
+            RespType::String(b"0-2".to_vec()),
+            RespType::String(b"0-3".to_vec())
         ];
         let cmd = RespType::Array(cmds);
 
         let res = client.handle_command(cmd);
-        assert!(res[0].eq("*2\r\n*2\r\n$3\r\n0-2\r\n*2\r\n$3\r\nbar\r\n$3\r\nbaz\r\n*2\r\n$3\r\n0-3\r\n*2\r\n$3\r\nbaz\r\n$3\r\nfoo\r\n"));
+        assert!(res[0].eq(b"*2\r\n*2\r\n$3\r\n0-2\r\n*2\r\n$3\r\nbar\r\n$3\r\nbaz\r\n*2\r\n$3\r\n0-3\r\n*2\r\n$3\r\nbaz\r\n$3\r\nfoo\r\n"));
 
         let cmds = vec![
-            RespType::String("XRANGE".to_string()),
-            RespType::String("stream_key".to_string()),
-            RespType::String("-".to_string()),
-            RespType::String("0-2".to_string())
+            RespType::String(b"XRANGE".to_vec()),
+            RespType::String(b"stream_key".to_vec()),
+            RespType::String(b"-".to_vec()),
+            RespType::String(b"0-2".to_vec())
         ];
         let cmd = RespType::Array(cmds);
 
         let res = client.handle_command(cmd);
-        assert!(res[0].eq("*2\r\n*2\r\n$3\r\n0-1\r\n*2\r\n$3\r\nfoo\r\n$3\r\nbar\r\n*2\r\n$3\r\n0-2\r\n*2\r\n$3\r\nbar\r\n$3\r\nbaz\r\n"));
+        assert!(res[0].eq(b"*2\r\n*2\r\n$3\r\n0-1\r\n*2\r\n$3\r\nfoo\r\n$3\r\nbar\r\n*2\r\n$3\r\n0-2\r\n*2\r\n$3\r\nbar\r\n$3\r\nbaz\r\n"));
 
         let cmds = vec![
-            RespType::String("XRANGE".to_string()),
-            RespType::String("stream_key".to_string()),
-            RespType::String("0-2".to_string()),
-            RespType::String("+".to_string())
+            RespType::String(b"XRANGE".to_vec()),
+            RespType::String(b"stream_key".to_vec()),
+            RespType::String(b"0-2".to_vec()),
+            RespType::String(b"+".to_vec())
         ];
         let cmd = RespType::Array(cmds);
 
         let res = client.handle_command(cmd);
-        assert!(res[0].eq("*2\r\n*2\r\n$3\r\n0-2\r\n*2\r\n$3\r\nbar\r\n$3\r\nbaz\r\n*2\r\n$3\r\n0-3\r\n*2\r\n$3\r\nbaz\r\n$3\r\nfoo\r\n"));
+        assert!(res[0].eq(b"*2\r\n*2\r\n$3\r\n0-2\r\n*2\r\n$3\r\nbar\r\n$3\r\nbaz\r\n*2\r\n$3\r\n0-3\r\n*2\r\n$3\r\nbaz\r\n$3\r\nfoo\r\n"));
     }
     #[test]
     fn test_xadd_command() {
         let (mut client, cache ,_ , _) = instantiate_client();
         
         let cmds = vec![
-            RespType::String("XADD".to_string()),
-            RespType::String("stream_key".to_string()),
-            RespType::String("1526919030474-0".to_string()),
-            RespType::String("temperature".to_string()),
-            RespType::String("36".to_string()),
-            RespType::String("humidity".to_string()),
-            RespType::String("92".to_string())
+            RespType::String(b"XADD".to_vec()),
+            RespType::String(b"stream_key".to_vec()),
+            RespType::String(b"1526919030474-0".to_vec()),
+            RespType::String(b"temperature".to_vec()),
+            RespType::String(b"36".to_vec()),
+            RespType::String(b"humidity".to_vec()),
+            RespType::String(b"92".to_vec())
         ];
         let cmd = RespType::Array(cmds);
         
         let res = client.handle_command(cmd);
-        assert!(res[0].eq("$15\r\n1526919030474-0\r\n"));
+        assert!(res[0].eq(b"$15\r\n1526919030474-0\r\n"));
         let cach_gaurd = cache.lock().unwrap();
-        assert!(cach_gaurd.contains_key("stream_key"));
-        let cache_val = cach_gaurd.get("stream_key").unwrap();
+        assert!(cach_gaurd.contains_key(b"stream_key".as_slice()));
+        let cache_val = cach_gaurd.get(b"stream_key".as_slice()).unwrap();
         match cache_val {
             CacheVal::Stream(val) => {
                 assert!(val.stream.len() == 1);
-                assert!(val.stream.get(0).unwrap().id.eq("1526919030474-0"));
+                assert!(val.stream.first().unwrap().id.eq(&StreamId::new(1526919030474, 0)));
             },
             _ => panic!("Incorrect cache type")
         }
@@ -823,44 +2037,44 @@ mod tests {
         let (mut client, cache ,_ , _) = instantiate_client();
         
         let cmds = vec![
-            RespType::String("XADD".to_string()),
-            RespType::String("stream_key".to_string()),
-            RespType::String("0-*".to_string()),
-            RespType::String("temperature".to_string()),
-            RespType::String("36".to_string())
+            RespType::String(b"XADD".to_vec()),
+            RespType::String(b"stream_key".to_vec()),
+            RespType::String(b"0-*".to_vec()),
+            RespType::String(b"temperature".to_vec()),
+            RespType::String(b"36".to_vec())
         ];
         let cmd = RespType::Array(cmds);
         let res = client.handle_command(cmd);
-        assert!(res[0].eq("$3\r\n0-1\r\n"));
+        assert!(res[0].eq(b"$3\r\n0-1\r\n"));
 
         let cmds = vec![
-            RespType::String("XADD".to_string()),
-            RespType::String("stream_key".to_string()),
-            RespType::String("1-*".to_string()),
-            RespType::String("temperature".to_string()),
-            RespType::String("36".to_string())
+            RespType::String(b"XADD".to_vec()),
+            RespType::String(b"stream_key".to_vec()),
+            RespType::String(b"1-*".to_vec()),
+            RespType::String(b"temperature".to_vec()),
+            RespType::String(b"36".to_vec())
         ];
         let cmd = RespType::Array(cmds);
         let res = client.handle_command(cmd);
-        assert!(res[0].eq("$3\r\n1-0\r\n"));
+        assert!(res[0].eq(b"$3\r\n1-0\r\n"));
         
         
         {
             let mut cache_guard = cache.lock().unwrap();
-            let stream_item = StreamItem {id: "2-1".into(), key_vals: vec![]};
-            cache_guard.insert("stream_key".to_string(), CacheVal::Stream(StreamCacheVal { stream: vec![stream_item] }));
+            let stream_item = StreamItem {id: StreamId::new(2, 1), key_vals: vec![]};
+            cache_guard.insert(b"stream_key".to_vec(), CacheVal::Stream(StreamCacheVal::new(vec![stream_item])));
         }
 
         let cmds = vec![
-            RespType::String("XADD".to_string()),
-            RespType::String("stream_key".to_string()),
-            RespType::String("2-*".to_string()),
-            RespType::String("temperature".to_string()),
-            RespType::String("36".to_string())
+            RespType::String(b"XADD".to_vec()),
+            RespType::String(b"stream_key".to_vec()),
+            RespType::String(b"2-*".to_vec()),
+            RespType::String(b"temperature".to_vec()),
+            RespType::String(b"36".to_vec())
         ];
         let cmd = RespType::Array(cmds);
         let res = client.handle_command(cmd);
-        assert!(res[0].eq("$3\r\n2-2\r\n"));
+        assert!(res[0].eq(b"$3\r\n2-2\r\n"));
     }
 
     #[test]
@@ -868,15 +2082,16 @@ mod tests {
         let (mut client, _ ,_ , _) = instantiate_client();
         
         let cmds = vec![
-            RespType::String("XADD".to_string()),
-            RespType::String("stream_key".to_string()),
-            RespType::String("*".to_string()),
-            RespType::String("temperature".to_string()),
-            RespType::String("36".to_string())
+            RespType::String(b"XADD".to_vec()),
+            RespType::String(b"stream_key".to_vec()),
+            RespType::String(b"*".to_vec()),
+            RespType::String(b"temperature".to_vec()),
+            RespType::String(b"36".to_vec())
         ];
         let cmd = RespType::Array(cmds);
         let res = client.handle_command(cmd);
-        let parts: Vec<&str>  = res[0].split("\r\n").collect();
+        let res = String::from_utf8_lossy(&res[0]);
+        let parts: Vec<&str>  = res.split("\r\n").collect();
         let id_parts: Vec<&str> = parts[1].split("-").collect();
         assert!(id_parts[1].eq("0"));
     }
@@ -886,43 +2101,47 @@ mod tests {
         let (mut client, cache ,_ , _) = instantiate_client();
         
         let cmds = vec![
-            RespType::String("XADD".to_string()),
-            RespType::String("stream_key".to_string()),
-            RespType::String("0-0".to_string()),
-            RespType::String("temperature".to_string()),
-            RespType::String("36".to_string())
+            RespType::String(b"XADD".to_vec()),
+            RespType::String(b"stream_key".to_vec()),
+            RespType::String(b"0-0".to_vec()),
+            RespType::String(b"temperature".to_vec()),
+            RespType::String(b"36".to_vec())
         ];
         let cmd = RespType::Array(cmds);
         let res = client.handle_command(cmd);
-        assert!(res[0].eq("-ERR The ID specified in XADD must be greater than 0-0\r\n"));
+        assert!(res[0].eq(b"-ERR The ID specified in XADD must be greater than 0-0\r\n"));
+        // a rejected id doesn't leave an empty stream behind
+        let res = client.handle_command(build_command(&["XADD", "stream_key", "bad-id", "temperature", "36"]));
+        assert!(res[0].starts_with(b"-ERR"));
+        assert!(!cache.lock().unwrap().contains_key(b"stream_key"));
         
         {
             let mut cache_guard = cache.lock().unwrap();
-            let stream_item = StreamItem {id: "1-1".into(), key_vals: vec![]};
-            cache_guard.insert("stream_key".to_string(), CacheVal::Stream(StreamCacheVal { stream: vec![stream_item] }));
+            let stream_item = StreamItem {id: StreamId::new(1, 1), key_vals: vec![]};
+            cache_guard.insert(b"stream_key".to_vec(), CacheVal::Stream(StreamCacheVal::new(vec![stream_item])));
         }
 
         let cmds = vec![
-            RespType::String("XADD".to_string()),
-            RespType::String("stream_key".to_string()),
-            RespType::String("1-1".to_string()),
-            RespType::String("temperature".to_string()),
-            RespType::String("36".to_string())
+            RespType::String(b"XADD".to_vec()),
+            RespType::String(b"stream_key".to_vec()),
+            RespType::String(b"1-1".to_vec()),
+            RespType::String(b"temperature".to_vec()),
+            RespType::String(b"36".to_vec())
         ];
         let cmd = RespType::Array(cmds);
         let res = client.handle_command(cmd);
-        assert!(res[0].eq("-ERR The ID specified in XADD is equal or smaller than the target stream top item\r\n"));
+        assert!(res[0].eq(b"-ERR The ID specified in XADD is equal or smaller than the target stream top item\r\n"));
 
         let cmds = vec![
-            RespType::String("XADD".to_string()),
-            RespType::String("stream_key".to_string()),
-            RespType::String("0-1".to_string()),
-            RespType::String("temperature".to_string()),
-            RespType::String("36".to_string())
+            RespType::String(b"XADD".to_vec()),
+            RespType::String(b"stream_key".to_vec()),
+            RespType::String(b"0-1".to_vec()),
+            RespType::String(b"temperature".to_vec()),
+            RespType::String(b"36".to_vec())
         ];
         let cmd = RespType::Array(cmds);
         let res = client.handle_command(cmd);
-        assert!(res[0].eq("-ERR The ID specified in XADD is equal or smaller than the target stream top item\r\n"));
+        assert!(res[0].eq(b"-ERR The ID specified in XADD is equal or smaller than the target stream top item\r\n"));
     }
 
     #[test]
@@ -931,42 +2150,52 @@ mod tests {
 
         {
             let mut cache_guard = cache.lock().unwrap();
-            cache_guard.insert("foo".to_string(), CacheVal::String(StringCacheVal { val: "bar".to_string(), expiry_time: None }));
-            cache_guard.insert("bar".to_string(), CacheVal::List(ListCacheVal {list: vec![], block_queue: vec![]}));
-            cache_guard.insert("faz".to_string(), CacheVal::Stream(StreamCacheVal { stream: vec![] }));
+            cache_guard.insert(b"foo".to_vec(), CacheVal::String(StringCacheVal::new("bar".into())));
+            cache_guard.insert(b"bar".to_vec(), CacheVal::List(ListCacheVal {list: VecDeque::new()}));
+            cache_guard.insert(b"faz".to_vec(), CacheVal::Stream(StreamCacheVal::new(vec![])));
         }
 
         let cmds = vec![
-            RespType::String("TYPE".to_string()),
-            RespType::String("foo".to_string())
+            RespType::String(b"TYPE".to_vec()),
+            RespType::String(b"foo".to_vec())
         ];
         let cmd = RespType::Array(cmds);
         let res = client.handle_command(cmd);
-        assert!(res[0].eq("+string\r\n"));
+        assert!(res[0].eq(b"+string\r\n"));
 
         let cmds = vec![
-            RespType::String("TYPE".to_string()),
-            RespType::String("bar".to_string())
+            RespType::String(b"TYPE".to_vec()),
+            RespType::String(b"bar".to_vec())
         ];
         let cmd = RespType::Array(cmds);
         let res = client.handle_command(cmd);
-        assert!(res[0].eq("+list\r\n"));
+        assert!(res[0].eq(b"+list\r\n"));
 
         let cmds = vec![
-            RespType::String("TYPE".to_string()),
-            RespType::String("faz".to_string())
+            RespType::String(b"TYPE".to_vec()),
+            RespType::String(b"faz".to_vec())
         ];
         let cmd = RespType::Array(cmds);
         let res = client.handle_command(cmd);
-        assert!(res[0].eq("+stream\r\n"));
+        assert!(res[0].eq(b"+stream\r\n"));
 
         let cmds = vec![
-            RespType::String("TYPE".to_string()),
-            RespType::String("other".to_string())
+            RespType::String(b"TYPE".to_vec()),
+            RespType::String(b"other".to_vec())
         ];
         let cmd = RespType::Array(cmds);
         let res = client.handle_command(cmd);
-        assert!(res[0].eq("+none\r\n"));
+        assert!(res[0].eq(b"+none\r\n"));
+
+        // emptied collections are gone rather than left behind for blocked connections
+        client.handle_command(build_command(&["RPUSH", "popped", "a"]));
+        client.handle_command(build_command(&["LPOP", "popped"]));
+        let res = client.handle_command(build_command(&["TYPE", "popped"]));
+        assert!(res[0].eq(b"+none\r\n"));
+        client.handle_command(build_command(&["ZADD", "zpopped", "1", "a"]));
+        client.handle_command(build_command(&["BZPOPMIN", "zpopped", "0.01"]));
+        let res = client.handle_command(build_command(&["TYPE", "zpopped"]));
+        assert!(res[0].eq(b"+none\r\n"));
     }
 
     #[test]
@@ -974,21 +2203,21 @@ mod tests {
         let (mut client, cache ,_ , _) = instantiate_client();
         
         let cmds = vec![
-            RespType::String("SET".to_string()),
-            RespType::String("foo".to_string()),
-            RespType::String("bar".to_string())
+            RespType::String(b"SET".to_vec()),
+            RespType::String(b"foo".to_vec()),
+            RespType::String(b"bar".to_vec())
         ];
         let cmd = RespType::Array(cmds);
 
         let res = client.handle_command(cmd);
-        assert!(res[0].eq("+OK\r\n"));
+        assert!(res[0].eq(b"+OK\r\n"));
         let cach_gaurd = cache.lock().unwrap();
-        assert!(cach_gaurd.contains_key("foo"));
-        let cache_val = cach_gaurd.get("foo").unwrap();
+        assert!(cach_gaurd.contains_key(b"foo".as_slice()));
+        let cache_val = cach_gaurd.get(b"foo".as_slice()).unwrap();
         match cache_val {
             CacheVal::String(val) => {
-                assert!(val.val.eq("bar"));
-                assert!(val.expiry_time.is_none());
+                assert!(val.val.eq(b"bar"));
+                assert!(cach_gaurd.expiry(b"foo").is_none());
             },
             _ => panic!("Incorrect cache type")
         }
@@ -998,24 +2227,24 @@ mod tests {
     fn test_set_with_expr_command() {
         let (mut client, cache ,_ , _) = instantiate_client();
         let cmds = vec![
-            RespType::String("SET".to_string()),
-            RespType::String("foo".to_string()),
-            RespType::String("bar".to_string()),
-            RespType::String("PX".to_string()),
-            RespType::String("100".to_string()),
+            RespType::String(b"SET".to_vec()),
+            RespType::String(b"foo".to_vec()),
+            RespType::String(b"bar".to_vec()),
+            RespType::String(b"PX".to_vec()),
+            RespType::String(b"100".to_vec()),
         ];
         let cmd = RespType::Array(cmds);
 
         let res = client.handle_command(cmd);
-        assert!(res[0].eq("+OK\r\n"));
+        assert!(res[0].eq(b"+OK\r\n"));
         let cache_guard = cache.lock().unwrap();
-        assert!(cache_guard.contains_key("foo"));
-        let cache_val = cache_guard.get("foo").unwrap();
+        assert!(cache_guard.contains_key(b"foo".as_slice()));
+        let cache_val = cache_guard.get(b"foo".as_slice()).unwrap();
         match cache_val {
             CacheVal::String(val) => {
-                assert!(val.val.eq("bar"));
-                assert!(val.expiry_time.is_some());
-                assert!(val.expiry_time.unwrap() > 100);
+                assert!(val.val.eq(b"bar"));
+                assert!(cache_guard.expiry(b"foo").is_some());
+                assert!(cache_guard.expiry(b"foo").unwrap() > 100);
             },
             _ => panic!("Incorrect cache type")
         }
@@ -1025,42 +2254,43 @@ mod tests {
     fn test_get_command() {
         let (mut client, cache ,_ , _) = instantiate_client();
         let cmds = vec![
-            RespType::String("GET".to_string()),
-            RespType::String("foo".to_string())
+            RespType::String(b"GET".to_vec()),
+            RespType::String(b"foo".to_vec())
         ];
         let cmd = RespType::Array(cmds);
 
         {
             let mut cache_guard = cache.lock().unwrap();
-            cache_guard.insert("foo".to_string(), CacheVal::String(StringCacheVal { val: "bar".to_string(), expiry_time: None }));
+            cache_guard.insert(b"foo".to_vec(), CacheVal::String(StringCacheVal::new("bar".into())));
         }
         let res = client.handle_command(cmd);
-        assert!(res[0].eq("+bar\r\n"));
+        assert!(res[0].eq(b"$3\r\nbar\r\n"));
     }
 
     #[test]
     fn test_get_expired_command() {
         let (mut client, cache ,_ , _) = instantiate_client();
         let cmds = vec![
-            RespType::String("GET".to_string()),
-            RespType::String("foo".to_string())
+            RespType::String(b"GET".to_vec()),
+            RespType::String(b"foo".to_vec())
         ];
         let cmd = RespType::Array(cmds);
 
         {
             let mut cache_guard = cache.lock().unwrap();
-            cache_guard.insert("foo".to_string(), CacheVal::String(StringCacheVal { val: "bar".to_string(), expiry_time: Some(500) }));
+            cache_guard.insert(b"foo".to_vec(), CacheVal::String(StringCacheVal::new("bar".into())));
+            cache_guard.set_expiry(b"foo", Some(500));
         }
         let res = client.handle_command(cmd);
-        assert!(res[0].eq("$-1\r\n"));
+        assert!(res[0].eq(b"$-1\r\n"));
     }
 
     #[test]
     fn test_get_non_expired_command() {
         let (mut client, cache ,_ , _) = instantiate_client();
         let cmds = vec![
-            RespType::String("GET".to_string()),
-            RespType::String("foo".to_string())
+            RespType::String(b"GET".to_vec()),
+            RespType::String(b"foo".to_vec())
         ];
         let cmd = RespType::Array(cmds);
 
@@ -1071,113 +2301,114 @@ mod tests {
 
         {
             let mut cache_guard = cache.lock().unwrap();
-            cache_guard.insert("foo".to_string(), CacheVal::String(StringCacheVal { val: "bar".to_string(), expiry_time: Some(now + 60000) }));
+            cache_guard.insert(b"foo".to_vec(), CacheVal::String(StringCacheVal::new("bar".into())));
+            cache_guard.set_expiry(b"foo", Some(now + 60000));
         }
         let res = client.handle_command(cmd);
-        assert!(res[0].eq("+bar\r\n"));
+        assert!(res[0].eq(b"$3\r\nbar\r\n"));
     }
 
     #[test]
     fn test_null_get_command() {
         let (mut client, _ ,_ , _) = instantiate_client();
         let cmds = vec![
-            RespType::String("GET".to_string()),
-            RespType::String("foo".to_string())
+            RespType::String(b"GET".to_vec()),
+            RespType::String(b"foo".to_vec())
         ];
         let cmd = RespType::Array(cmds);
 
         let res = client.handle_command(cmd);
-        assert!(res[0].eq("$-1\r\n"));
+        assert!(res[0].eq(b"$-1\r\n"));
     }
 
     #[test]
     fn test_ping_command() {
         let (mut client, _ ,_ , _) = instantiate_client();
-        let cmds = vec![RespType::String("PING".to_string())];
+        let cmds = vec![RespType::String(b"PING".to_vec())];
         let cmd = RespType::Array(cmds);
         let res = client.handle_command(cmd);
-        assert!(res[0].eq("+PONG\r\n"));
+        assert!(res[0].eq(b"+PONG\r\n"));
     }
 
     #[test]
     fn test_ping_sub_mode_command() {
         let (mut client, _ ,_ , _) = instantiate_client();
-        client.subscribed_channels.insert("channel1".to_string());
-        let cmds = vec![RespType::String("PING".to_string())];
+        client.subscribed_channels.insert(b"channel1".to_vec());
+        let cmds = vec![RespType::String(b"PING".to_vec())];
         let cmd = RespType::Array(cmds);
         let res = client.handle_command(cmd);
-        assert!(res[0].eq("*2\r\n$4\r\npong\r\n$0\r\n\r\n"));
+        assert!(res[0].eq(b"*2\r\n$4\r\npong\r\n$0\r\n\r\n"));
     }
 
     #[test]
     fn test_echo_command() {
         let (mut client, _ ,_ , _) = instantiate_client();
         let cmds = vec![
-            RespType::String("ECHO".to_string()),
-            RespType::String("hello".to_string())
+            RespType::String(b"ECHO".to_vec()),
+            RespType::String(b"hello".to_vec())
         ];
         let cmd = RespType::Array(cmds);
 
         let res = client.handle_command(cmd);
-        assert!(res[0].eq("+hello\r\n"));
+        assert!(res[0].eq(b"$5\r\nhello\r\n"));
     }
 
     #[test]
     fn test_llen_command() {
         let (mut client, cache ,_ , _) = instantiate_client();
         let cmds = vec![
-            RespType::String("LLEN".to_string()),
-            RespType::String("list_key".to_string())
+            RespType::String(b"LLEN".to_vec()),
+            RespType::String(b"list_key".to_vec())
         ];
         let cmd = RespType::Array(cmds);
 
         let res = client.handle_command(cmd);
-        assert!(res[0].eq(":0\r\n"));
+        assert!(res[0].eq(b":0\r\n"));
 
         {
             let mut cache_guard = cache.lock().unwrap();
-            cache_guard.insert("list_key".into(), CacheVal::List(ListCacheVal { list: vec!["a".into(), "b".into(), "c".into(), "d".into(), "e".into(), "f".into()], block_queue: vec![] }));
+            cache_guard.insert("list_key".into(), CacheVal::List(ListCacheVal { list: vec!["a".into(), "b".into(), "c".into(), "d".into(), "e".into(), "f".into()].into() }));
         }
         let cmds = vec![
-            RespType::String("LLEN".to_string()),
-            RespType::String("list_key".to_string())
+            RespType::String(b"LLEN".to_vec()),
+            RespType::String(b"list_key".to_vec())
         ];
         let cmd = RespType::Array(cmds);
 
         let res = client.handle_command(cmd);
-        assert!(res[0].eq(":6\r\n"));
+        assert!(res[0].eq(b":6\r\n"));
     }
 
     #[test]
     fn test_lpop_command() {
         let (mut client, cache ,_ , _) = instantiate_client();
         let cmds = vec![
-            RespType::String("LPOP".to_string()),
-            RespType::String("list_key".to_string())
+            RespType::String(b"LPOP".to_vec()),
+            RespType::String(b"list_key".to_vec())
         ];
         let cmd = RespType::Array(cmds);
 
         let res = client.handle_command(cmd);
-        assert!(res[0].eq("$-1\r\n"));
+        assert!(res[0].eq(b"$-1\r\n"));
 
         {
             let mut cache_guard = cache.lock().unwrap();
-            cache_guard.insert("list_key".into(), CacheVal::List(ListCacheVal { list: vec!["a".into(), "b".into(), "c".into(), "d".into(), "e".into(), "f".into()], block_queue: vec![] }));
+            cache_guard.insert("list_key".into(), CacheVal::List(ListCacheVal { list: vec!["a".into(), "b".into(), "c".into(), "d".into(), "e".into(), "f".into()].into() }));
         }
         let cmds = vec![
-            RespType::String("LPOP".to_string()),
-            RespType::String("list_key".to_string()),
-            RespType::String("2".to_string())
+            RespType::String(b"LPOP".to_vec()),
+            RespType::String(b"list_key".to_vec()),
+            RespType::String(b"2".to_vec())
         ];
         let cmd = RespType::Array(cmds);
 
         let res = client.handle_command(cmd);
-        assert!(res[0].eq("*2\r\n$1\r\na\r\n$1\r\nb\r\n"));
+        assert!(res[0].eq(b"*2\r\n$1\r\na\r\n$1\r\nb\r\n"));
         let cache_guard = cache.lock().unwrap();
-        match cache_guard.get("list_key".into()) {
+        match cache_guard.get(b"list_key".as_slice()) {
             Some(CacheVal::List(val)) => {
                 assert!(val.list.len() == 4);
-                assert!(val.list.get(0).unwrap().eq("c"));
+                assert!(val.list.get(0).unwrap().eq(b"c"));
             },
             _ => panic!("list value not found"),
         }
@@ -1187,38 +2418,38 @@ mod tests {
     fn test_rpush_command() {
         let (mut client, cache ,_ , _) = instantiate_client();
         let cmds = vec![
-            RespType::String("RPUSH".to_string()),
-            RespType::String("list_key".to_string()),
-            RespType::String("foo".to_string()),
+            RespType::String(b"RPUSH".to_vec()),
+            RespType::String(b"list_key".to_vec()),
+            RespType::String(b"foo".to_vec()),
         ];
         let cmd = RespType::Array(cmds);
 
         let res = client.handle_command(cmd);
-        assert!(res[0].eq(":1\r\n"));
+        assert!(res[0].eq(b":1\r\n"));
         let cache_guard = cache.lock().unwrap();
-        match cache_guard.get("list_key".into()) {
+        match cache_guard.get(b"list_key".as_slice()) {
             Some(CacheVal::List(val)) => {
                 assert!(val.list.len() == 1);
-                assert!(val.list.get(0).unwrap().eq("foo"));
+                assert!(val.list.get(0).unwrap().eq(b"foo"));
             }
             _ => panic!("not list key")
         }
         drop(cache_guard);
 
         let cmds = vec![
-            RespType::String("RPUSH".to_string()),
-            RespType::String("list_key".to_string()),
-            RespType::String("bar".to_string()),
+            RespType::String(b"RPUSH".to_vec()),
+            RespType::String(b"list_key".to_vec()),
+            RespType::String(b"bar".to_vec()),
         ];
         let cmd = RespType::Array(cmds);
 
         let res = client.handle_command(cmd);
-        assert!(res[0].eq(":2\r\n"));
+        assert!(res[0].eq(b":2\r\n"));
         let cache_guard = cache.lock().unwrap();
-        match cache_guard.get("list_key".into()) {
+        match cache_guard.get(b"list_key".as_slice()) {
             Some(CacheVal::List(val)) => {
                 assert!(val.list.len() == 2);
-                assert!(val.list.get(1).unwrap().eq("bar"));
+                assert!(val.list.get(1).unwrap().eq(b"bar"));
             }
             _ => panic!("not list key")
         }
@@ -1228,21 +2459,21 @@ mod tests {
     fn test_multi_rpush_command() {
         let (mut client, cache ,_ , _) = instantiate_client();
         let cmds = vec![
-            RespType::String("RPUSH".to_string()),
-            RespType::String("list_key".to_string()),
-            RespType::String("foo".to_string()),
-            RespType::String("bar".to_string()),
-            RespType::String("baz".to_string()),
+            RespType::String(b"RPUSH".to_vec()),
+            RespType::String(b"list_key".to_vec()),
+            RespType::String(b"foo".to_vec()),
+            RespType::String(b"bar".to_vec()),
+            RespType::String(b"baz".to_vec()),
         ];
         let cmd = RespType::Array(cmds);
 
         let res = client.handle_command(cmd);
-        assert!(res[0].eq(":3\r\n"));
+        assert!(res[0].eq(b":3\r\n"));
         let cache_gaurd = cache.lock().unwrap();
-        match cache_gaurd.get("list_key".into()) {
+        match cache_gaurd.get(b"list_key".as_slice()) {
             Some(CacheVal::List(val)) => {
                 assert!(val.list.len() == 3);
-                assert!(val.list.get(0).unwrap().eq("foo"));
+                assert!(val.list.get(0).unwrap().eq(b"foo"));
             }
             _ => panic!("Not list key")
         }
@@ -1253,40 +2484,40 @@ mod tests {
     fn test_multi_lpush_command() {
         let (mut client, cache ,_ , _) = instantiate_client();
         let cmds = vec![
-            RespType::String("LPUSH".to_string()),
-            RespType::String("list_key".to_string()),
-            RespType::String("foo".to_string()),
-            RespType::String("bar".to_string()),
-            RespType::String("baz".to_string()),
+            RespType::String(b"LPUSH".to_vec()),
+            RespType::String(b"list_key".to_vec()),
+            RespType::String(b"foo".to_vec()),
+            RespType::String(b"bar".to_vec()),
+            RespType::String(b"baz".to_vec()),
         ];
         let cmd = RespType::Array(cmds);
 
         let res = client.handle_command(cmd);
-        assert!(res[0].eq(":3\r\n"));
+        assert!(res[0].eq(b":3\r\n"));
         let cache_guard = cache.lock().unwrap();
-        match cache_guard.get("list_key".into()) {
+        match cache_guard.get(b"list_key".as_slice()) {
             Some(CacheVal::List(val)) => {
                 assert!(val.list.len() == 3);
-                assert!(val.list.get(0).unwrap().eq("baz"));
+                assert!(val.list.get(0).unwrap().eq(b"baz"));
             }
             _ => panic!("not list key")
         }
         drop(cache_guard);
 
         let cmds = vec![
-            RespType::String("LPUSH".to_string()),
-            RespType::String("list_key".to_string()),
-            RespType::String("first".to_string())
+            RespType::String(b"LPUSH".to_vec()),
+            RespType::String(b"list_key".to_vec()),
+            RespType::String(b"first".to_vec())
         ];
         let cmd = RespType::Array(cmds);
 
         let res = client.handle_command(cmd);
-        assert!(res[0].eq(":4\r\n"));
+        assert!(res[0].eq(b":4\r\n"));
         let cache_guard = cache.lock().unwrap();
-        match cache_guard.get("list_key".into()) {
+        match cache_guard.get(b"list_key".as_slice()) {
             Some(CacheVal::List(val)) => {
                 assert!(val.list.len() == 4);
-                assert!(val.list.get(0).unwrap().eq("first"));
+                assert!(val.list.get(0).unwrap().eq(b"first"));
             }
             _ => panic!("not list key")
         }
@@ -1296,52 +2527,1830 @@ mod tests {
     fn test_lrange_command() {
         let (mut client, cache ,_ , _) = instantiate_client();
         let cmds = vec![
-            RespType::String("LRANGE".to_string()),
-            RespType::String("list_key".to_string()),
-            RespType::String("2".to_string()),
-            RespType::String("4".to_string())
+            RespType::String(b"LRANGE".to_vec()),
+            RespType::String(b"list_key".to_vec()),
+            RespType::String(b"2".to_vec()),
+            RespType::String(b"4".to_vec())
         ];
         let cmd = RespType::Array(cmds);
 
         {
             let mut cache_gaurd = cache.lock().unwrap();
-            cache_gaurd.insert("list_key".into(), CacheVal::List(ListCacheVal {list: vec!["a".into(), "b".into(), "c".into(), "d".into(), "e".into(), "f".into()], block_queue: vec![]}));
+            cache_gaurd.insert("list_key".into(), CacheVal::List(ListCacheVal {list: vec!["a".into(), "b".into(), "c".into(), "d".into(), "e".into(), "f".into()].into() }));
         }
         let res = client.handle_command(cmd);
-        assert!(res[0].eq("*3\r\n$1\r\nc\r\n$1\r\nd\r\n$1\r\ne\r\n"));
+        assert!(res[0].eq(b"*3\r\n$1\r\nc\r\n$1\r\nd\r\n$1\r\ne\r\n"));
     }
 
     #[test]
     fn test_empty_lrange_command() {
         let (mut client, _ ,_ , _) = instantiate_client();
         let cmds = vec![
-            RespType::String("LRANGE".to_string()),
-            RespType::String("list_key".to_string()),
-            RespType::String("2".to_string()),
-            RespType::String("4".to_string())
+            RespType::String(b"LRANGE".to_vec()),
+            RespType::String(b"list_key".to_vec()),
+            RespType::String(b"2".to_vec()),
+            RespType::String(b"4".to_vec())
         ];
         let cmd = RespType::Array(cmds);
 
         let res = client.handle_command(cmd);
-        assert!(res[0].eq("*0\r\n"));
+        assert!(res[0].eq(b"*0\r\n"));
     }
 
     #[test]
     fn test_negative_lrange_command() {
         let (mut client, cache ,_ , _) = instantiate_client();
         let cmds = vec![
-            RespType::String("LRANGE".to_string()),
-            RespType::String("list_key".to_string()),
-            RespType::String("2".to_string()),
-            RespType::String("-2".to_string())
+            RespType::String(b"LRANGE".to_vec()),
+            RespType::String(b"list_key".to_vec()),
+            RespType::String(b"2".to_vec()),
+            RespType::String(b"-2".to_vec())
         ];
         let cmd = RespType::Array(cmds);
 
         {
             let mut cache_gaurd = cache.lock().unwrap();
-            cache_gaurd.insert("list_key".into(), CacheVal::List(ListCacheVal {list: vec!["a".into(), "b".into(), "c".into(), "d".into(), "e".into(), "f".into()], block_queue: vec![]}));
+            cache_gaurd.insert("list_key".into(), CacheVal::List(ListCacheVal {list: vec!["a".into(), "b".into(), "c".into(), "d".into(), "e".into(), "f".into()].into() }));
         }
         let res = client.handle_command(cmd);
-        assert!(res[0].eq("*3\r\n$1\r\nc\r\n$1\r\nd\r\n$1\r\ne\r\n"));
+        assert!(res[0].eq(b"*3\r\n$1\r\nc\r\n$1\r\nd\r\n$1\r\ne\r\n"));
+    }
+
+    #[test]
+    fn test_hset_hget_command() {
+        let (mut client, _ ,write_commands , _) = instantiate_client();
+
+        let res = client.handle_command(build_command(&["HSET", "user", "name", "ada", "lang", "rust"]));
+        assert!(res[0].eq(b":2\r\n"));
+        let res = client.handle_command(build_command(&["HSET", "user", "name", "grace", "age", "36"]));
+        assert!(res[0].eq(b":1\r\n"));
+        assert_eq!(write_commands.lock().unwrap().len(), 2);
+
+        let res = client.handle_command(build_command(&["HGET", "user", "name"]));
+        assert!(res[0].eq(b"$5\r\ngrace\r\n"));
+        let res = client.handle_command(build_command(&["HGET", "user", "missing"]));
+        assert!(res[0].eq(b"$-1\r\n"));
+        let res = client.handle_command(build_command(&["HMGET", "user", "lang", "missing", "age"]));
+        assert!(res[0].eq(b"*3\r\n$4\r\nrust\r\n$-1\r\n$2\r\n36\r\n"));
+        let res = client.handle_command(build_command(&["HLEN", "user"]));
+        assert!(res[0].eq(b":3\r\n"));
+        let res = client.handle_command(build_command(&["HEXISTS", "user", "lang"]));
+        assert!(res[0].eq(b":1\r\n"));
+        let res = client.handle_command(build_command(&["HSTRLEN", "user", "name"]));
+        assert!(res[0].eq(b":5\r\n"));
+        let res = client.handle_command(build_command(&["HSETNX", "user", "name", "linus"]));
+        assert!(res[0].eq(b":0\r\n"));
+
+        let res = client.handle_command(build_command(&["HGETALL", "user"]));
+        assert!(res[0].starts_with(b"*6\r\n"));
+        assert!(String::from_utf8_lossy(&res[0]).contains("$4\r\nlang\r\n$4\r\nrust\r\n"));
+
+        let res = client.handle_command(build_command(&["HSET", "user", "dangling"]));
+        assert!(res[0].eq(b"-ERR wrong number of arguments for 'hset' command\r\n"));
+
+        let res = client.handle_command(build_command(&["TYPE", "user"]));
+        assert!(res[0].eq(b"+hash\r\n"));
+    }
+
+    #[test]
+    fn test_hdel_command() {
+        let (mut client, cache ,_ , _) = instantiate_client();
+
+        client.handle_command(build_command(&["HSET", "user", "name", "ada", "lang", "rust"]));
+        let res = client.handle_command(build_command(&["HDEL", "user", "name", "missing"]));
+        assert!(res[0].eq(b":1\r\n"));
+        let res = client.handle_command(build_command(&["HKEYS", "user"]));
+        assert!(res[0].eq(b"*1\r\n$4\r\nlang\r\n"));
+        let res = client.handle_command(build_command(&["HVALS", "user"]));
+        assert!(res[0].eq(b"*1\r\n$4\r\nrust\r\n"));
+
+        let res = client.handle_command(build_command(&["HDEL", "user", "lang"]));
+        assert!(res[0].eq(b":1\r\n"));
+        assert!(!cache.lock().unwrap().contains_key(b"user".as_slice()));
+    }
+
+    #[test]
+    fn test_hincrby_command() {
+        let (mut client, _ ,write_commands , _) = instantiate_client();
+
+        let res = client.handle_command(build_command(&["HINCRBY", "counters", "visits", "5"]));
+        assert!(res[0].eq(b":5\r\n"));
+        let res = client.handle_command(build_command(&["HINCRBY", "counters", "visits", "-7"]));
+        assert!(res[0].eq(b":-2\r\n"));
+
+        client.handle_command(build_command(&["HSET", "counters", "big", &i64::MAX.to_string(), "name", "ada"]));
+        let res = client.handle_command(build_command(&["HINCRBY", "counters", "big", "1"]));
+        assert!(res[0].eq(b"-ERR increment or decrement would overflow\r\n"));
+        let res = client.handle_command(build_command(&["HINCRBY", "counters", "name", "1"]));
+        assert!(res[0].eq(b"-ERR hash value is not an integer\r\n"));
+
+        write_commands.lock().unwrap().clear();
+        let res = client.handle_command(build_command(&["HINCRBYFLOAT", "counters", "ratio", "10.5"]));
+        assert!(res[0].eq(b"$4\r\n10.5\r\n"));
+        let res = client.handle_command(build_command(&["HINCRBYFLOAT", "counters", "ratio", "0.5"]));
+        assert!(res[0].eq(b"$2\r\n11\r\n"));
+        let res = client.handle_command(build_command(&["HINCRBYFLOAT", "counters", "ratio", "abc"]));
+        assert!(res[0].eq(b"-ERR value is not a valid float\r\n"));
+
+        // float increments are replicated as the resulting value
+        let write_commands_guard = write_commands.lock().unwrap();
+        assert_eq!(write_commands_guard.len(), 2);
+        assert!(write_commands_guard[1].eq(b"*4\r\n$4\r\nHSET\r\n$8\r\ncounters\r\n$5\r\nratio\r\n$2\r\n11\r\n"));
+    }
+
+    #[test]
+    fn test_hrandfield_command() {
+        let (mut client, _ ,_ , _) = instantiate_client();
+
+        let res = client.handle_command(build_command(&["HRANDFIELD", "user"]));
+        assert!(res[0].eq(b"$-1\r\n"));
+
+        client.handle_command(build_command(&["HSET", "user", "name", "ada", "lang", "rust"]));
+        let res = client.handle_command(build_command(&["HRANDFIELD", "user"]));
+        assert!(res[0].eq(b"$4\r\nname\r\n") || res[0].eq(b"$4\r\nlang\r\n"));
+        let res = client.handle_command(build_command(&["HRANDFIELD", "user", "5"]));
+        assert!(res[0].starts_with(b"*2\r\n"));
+        let res = client.handle_command(build_command(&["HRANDFIELD", "user", "-5", "WITHVALUES"]));
+        assert!(res[0].starts_with(b"*10\r\n"));
+    }
+
+    #[test]
+    fn test_hash_wrong_type() {
+        let (mut client, _ ,_ , _) = instantiate_client();
+
+        client.handle_command(build_command(&["SET", "foo", "bar"]));
+        let res = client.handle_command(build_command(&["HSET", "foo", "field", "val"]));
+        assert!(res[0].eq(b"-WRONGTYPE Operation against a key holding the wrong kind of value\r\n"));
+        let res = client.handle_command(build_command(&["HGET", "foo", "field"]));
+        assert!(res[0].eq(b"-WRONGTYPE Operation against a key holding the wrong kind of value\r\n"));
+    }
+
+    fn sorted_members(cache: &Arc<Mutex<Keyspace>>, key: &str) -> Vec<Vec<u8>> {
+        match cache.lock().unwrap().get(key.as_bytes()) {
+            Some(CacheVal::Set(set_cache_val)) => {
+                let mut members: Vec<Vec<u8>> = set_cache_val.set.iter().cloned().collect();
+                members.sort();
+                members
+            },
+            _ => vec![]
+        }
+    }
+
+    #[test]
+    fn test_sadd_srem_command() {
+        let (mut client, cache ,write_commands , _) = instantiate_client();
+
+        let res = client.handle_command(build_command(&["SADD", "tags", "a", "b", "a"]));
+        assert!(res[0].eq(b":2\r\n"));
+        let res = client.handle_command(build_command(&["SADD", "tags", "b", "c"]));
+        assert!(res[0].eq(b":1\r\n"));
+        assert_eq!(sorted_members(&cache, "tags"), vec![b"a", b"b", b"c"]);
+
+        let res = client.handle_command(build_command(&["SISMEMBER", "tags", "b"]));
+        assert!(res[0].eq(b":1\r\n"));
+        let res = client.handle_command(build_command(&["SMISMEMBER", "tags", "b", "z"]));
+        assert!(res[0].eq(b"*2\r\n:1\r\n:0\r\n"));
+        let res = client.handle_command(build_command(&["SCARD", "tags"]));
+        assert!(res[0].eq(b":3\r\n"));
+        let res = client.handle_command(build_command(&["TYPE", "tags"]));
+        assert!(res[0].eq(b"+set\r\n"));
+
+        let res = client.handle_command(build_command(&["SREM", "tags", "a", "b", "c", "z"]));
+        assert!(res[0].eq(b":3\r\n"));
+        assert!(!cache.lock().unwrap().contains_key(b"tags".as_slice()));
+        assert_eq!(write_commands.lock().unwrap().len(), 3);
+
+        client.handle_command(build_command(&["SET", "str", "val"]));
+        let res = client.handle_command(build_command(&["SADD", "str", "a"]));
+        assert!(res[0].eq(b"-WRONGTYPE Operation against a key holding the wrong kind of value\r\n"));
+    }
+
+    #[test]
+    fn test_spop_srandmember_command() {
+        let (mut client, cache ,write_commands , _) = instantiate_client();
+
+        client.handle_command(build_command(&["SADD", "tags", "a", "b", "c"]));
+        write_commands.lock().unwrap().clear();
+
+        let res = client.handle_command(build_command(&["SRANDMEMBER", "tags", "-5"]));
+        assert!(res[0].starts_with(b"*5\r\n"));
+        let res = client.handle_command(build_command(&["SRANDMEMBER", "tags", "5"]));
+        assert!(res[0].starts_with(b"*3\r\n"));
+
+        let res = client.handle_command(build_command(&["SPOP", "tags", "2"]));
+        assert!(res[0].starts_with(b"*2\r\n"));
+        assert_eq!(sorted_members(&cache, "tags").len(), 1);
+
+        // the random pop is replicated as the removal of the chosen members
+        let write_commands_guard = write_commands.lock().unwrap();
+        assert_eq!(write_commands_guard.len(), 1);
+        assert!(write_commands_guard[0].starts_with(b"*4\r\n$4\r\nSREM\r\n$4\r\ntags\r\n"));
+        drop(write_commands_guard);
+
+        client.handle_command(build_command(&["SPOP", "tags"]));
+        assert!(!cache.lock().unwrap().contains_key(b"tags".as_slice()));
+        let res = client.handle_command(build_command(&["SPOP", "tags"]));
+        assert!(res[0].eq(b"$-1\r\n"));
+    }
+
+    #[test]
+    fn test_smove_command() {
+        let (mut client, cache ,_ , _) = instantiate_client();
+
+        client.handle_command(build_command(&["SADD", "src", "a", "b"]));
+        let res = client.handle_command(build_command(&["SMOVE", "src", "dst", "a"]));
+        assert!(res[0].eq(b":1\r\n"));
+        let res = client.handle_command(build_command(&["SMOVE", "src", "dst", "z"]));
+        assert!(res[0].eq(b":0\r\n"));
+        assert_eq!(sorted_members(&cache, "src"), vec![b"b"]);
+        assert_eq!(sorted_members(&cache, "dst"), vec![b"a"]);
+    }
+
+    #[test]
+    fn test_set_algebra_command() {
+        let (mut client, cache ,_ , _) = instantiate_client();
+
+        client.handle_command(build_command(&["SADD", "s1", "a", "b", "c", "d"]));
+        client.handle_command(build_command(&["SADD", "s2", "c"]));
+        client.handle_command(build_command(&["SADD", "s3", "a", "c", "e"]));
+
+        let res = client.handle_command(build_command(&["SINTER", "s1", "s2", "s3"]));
+        assert!(res[0].eq(b"*1\r\n$1\r\nc\r\n"));
+        let res = client.handle_command(build_command(&["SINTER", "s1", "missing"]));
+        assert!(res[0].eq(b"*0\r\n"));
+        let res = client.handle_command(build_command(&["SINTERCARD", "2", "s1", "s3"]));
+        assert!(res[0].eq(b":2\r\n"));
+        let res = client.handle_command(build_command(&["SINTERCARD", "2", "s1", "s3", "LIMIT", "1"]));
+        assert!(res[0].eq(b":1\r\n"));
+
+        let res = client.handle_command(build_command(&["SUNIONSTORE", "union", "s1", "s3", "missing"]));
+        assert!(res[0].eq(b":5\r\n"));
+        assert_eq!(sorted_members(&cache, "union"), vec![b"a", b"b", b"c", b"d", b"e"]);
+
+        let res = client.handle_command(build_command(&["SDIFFSTORE", "diff", "s1", "s2", "s3"]));
+        assert!(res[0].eq(b":2\r\n"));
+        assert_eq!(sorted_members(&cache, "diff"), vec![b"b", b"d"]);
+        let res = client.handle_command(build_command(&["SDIFF", "s2", "s1"]));
+        assert!(res[0].eq(b"*0\r\n"));
+
+        // an empty result removes the destination
+        let res = client.handle_command(build_command(&["SINTERSTORE", "diff", "s2", "missing"]));
+        assert!(res[0].eq(b":0\r\n"));
+        assert!(!cache.lock().unwrap().contains_key(b"diff".as_slice()));
+
+        client.handle_command(build_command(&["SET", "str", "val"]));
+        let res = client.handle_command(build_command(&["SUNION", "s1", "str"]));
+        assert!(res[0].eq(b"-WRONGTYPE Operation against a key holding the wrong kind of value\r\n"));
+    }
+
+    #[test]
+    fn test_zadd_command() {
+        let (mut client, _ ,write_commands , _) = instantiate_client();
+
+        let res = client.handle_command(build_command(&["ZADD", "board", "1", "a", "2", "b", "3", "c"]));
+        assert!(res[0].eq(b":3\r\n"));
+        let res = client.handle_command(build_command(&["ZADD", "board", "NX", "10", "a", "4", "d"]));
+        assert!(res[0].eq(b":1\r\n"));
+        let res = client.handle_command(build_command(&["ZADD", "board", "XX", "CH", "5", "a", "9", "missing"]));
+        assert!(res[0].eq(b":1\r\n"));
+        let res = client.handle_command(build_command(&["ZADD", "board", "GT", "CH", "1", "a", "6", "b"]));
+        assert!(res[0].eq(b":1\r\n"));
+        let res = client.handle_command(build_command(&["ZADD", "board", "INCR", "2.5", "a"]));
+        assert!(res[0].eq(b"$3\r\n7.5\r\n"));
+        let res = client.handle_command(build_command(&["ZADD", "board", "LT", "INCR", "1", "a"]));
+        assert!(res[0].eq(b"$-1\r\n"));
+        assert_eq!(write_commands.lock().unwrap().len(), 6);
+
+        let res = client.handle_command(build_command(&["ZADD", "board", "NX", "XX", "1", "a"]));
+        assert!(res[0].eq(b"-ERR XX and NX options at the same time are not compatible\r\n"));
+        let res = client.handle_command(build_command(&["ZADD", "board", "GT", "LT", "1", "a"]));
+        assert!(res[0].eq(b"-ERR GT, LT, and/or NX options at the same time are not compatible\r\n"));
+        let res = client.handle_command(build_command(&["ZADD", "board", "INCR", "1", "a", "2", "b"]));
+        assert!(res[0].eq(b"-ERR INCR option supports a single increment-element pair\r\n"));
+        let res = client.handle_command(build_command(&["ZADD", "board", "abc", "a"]));
+        assert!(res[0].eq(b"-ERR value is not a valid float\r\n"));
+
+        let res = client.handle_command(build_command(&["ZSCORE", "board", "a"]));
+        assert!(res[0].eq(b"$3\r\n7.5\r\n"));
+        let res = client.handle_command(build_command(&["ZMSCORE", "board", "b", "missing"]));
+        assert!(res[0].eq(b"*2\r\n$1\r\n6\r\n$-1\r\n"));
+        let res = client.handle_command(build_command(&["ZCARD", "board"]));
+        assert!(res[0].eq(b":4\r\n"));
+        let res = client.handle_command(build_command(&["TYPE", "board"]));
+        assert!(res[0].eq(b"+zset\r\n"));
+    }
+
+    #[test]
+    fn test_zincrby_zrem_command() {
+        let (mut client, cache ,_ , _) = instantiate_client();
+
+        let res = client.handle_command(build_command(&["ZINCRBY", "board", "2", "a"]));
+        assert!(res[0].eq(b"$1\r\n2\r\n"));
+        let res = client.handle_command(build_command(&["ZINCRBY", "board", "-0.5", "a"]));
+        assert!(res[0].eq(b"$3\r\n1.5\r\n"));
+        let res = client.handle_command(build_command(&["ZINCRBY", "board", "nope", "a"]));
+        assert!(res[0].eq(b"-ERR value is not a valid float\r\n"));
+
+        let res = client.handle_command(build_command(&["ZREM", "board", "a", "missing"]));
+        assert!(res[0].eq(b":1\r\n"));
+        assert!(!cache.lock().unwrap().contains_key(b"board".as_slice()));
+    }
+
+    #[test]
+    fn test_zrank_zcount_command() {
+        let (mut client, _ ,_ , _) = instantiate_client();
+
+        client.handle_command(build_command(&["ZADD", "board", "1", "a", "2", "b", "2", "c", "3", "d"]));
+        let res = client.handle_command(build_command(&["ZRANK", "board", "c"]));
+        assert!(res[0].eq(b":2\r\n"));
+        let res = client.handle_command(build_command(&["ZREVRANK", "board", "c", "WITHSCORE"]));
+        assert!(res[0].eq(b"*2\r\n:1\r\n$1\r\n2\r\n"));
+        let res = client.handle_command(build_command(&["ZRANK", "board", "missing"]));
+        assert!(res[0].eq(b"$-1\r\n"));
+
+        let res = client.handle_command(build_command(&["ZCOUNT", "board", "(1", "+inf"]));
+        assert!(res[0].eq(b":3\r\n"));
+        let res = client.handle_command(build_command(&["ZCOUNT", "board", "-inf", "(2"]));
+        assert!(res[0].eq(b":1\r\n"));
+        let res = client.handle_command(build_command(&["ZCOUNT", "board", "3", "1"]));
+        assert!(res[0].eq(b":0\r\n"));
+        let res = client.handle_command(build_command(&["ZCOUNT", "board", "x", "1"]));
+        assert!(res[0].eq(b"-ERR min or max is not a float\r\n"));
+    }
+
+    #[test]
+    fn test_zrange_command() {
+        let (mut client, _ ,_ , _) = instantiate_client();
+
+        client.handle_command(build_command(&["ZADD", "board", "1", "a", "2", "b", "3", "c", "4", "d"]));
+        let res = client.handle_command(build_command(&["ZRANGE", "board", "0", "-1"]));
+        assert!(res[0].eq(b"*4\r\n$1\r\na\r\n$1\r\nb\r\n$1\r\nc\r\n$1\r\nd\r\n"));
+        let res = client.handle_command(build_command(&["ZRANGE", "board", "0", "1", "REV", "WITHSCORES"]));
+        assert!(res[0].eq(b"*4\r\n$1\r\nd\r\n$1\r\n4\r\n$1\r\nc\r\n$1\r\n3\r\n"));
+        let res = client.handle_command(build_command(&["ZRANGE", "board", "(1", "3", "BYSCORE"]));
+        assert!(res[0].eq(b"*2\r\n$1\r\nb\r\n$1\r\nc\r\n"));
+        let res = client.handle_command(build_command(&["ZRANGE", "board", "+inf", "-inf", "BYSCORE", "REV", "LIMIT", "1", "2"]));
+        assert!(res[0].eq(b"*2\r\n$1\r\nc\r\n$1\r\nb\r\n"));
+        let res = client.handle_command(build_command(&["ZRANGE", "board", "0", "-1", "LIMIT", "0", "1"]));
+        assert!(res[0].eq(b"-ERR syntax error, LIMIT is only supported in combination with either BYSCORE or BYLEX\r\n"));
+
+        client.handle_command(build_command(&["ZADD", "names", "0", "alpha", "0", "bravo", "0", "charlie", "0", "delta"]));
+        let res = client.handle_command(build_command(&["ZRANGE", "names", "[b", "(d", "BYLEX"]));
+        assert!(res[0].eq(b"*2\r\n$5\r\nbravo\r\n$7\r\ncharlie\r\n"));
+        let res = client.handle_command(build_command(&["ZRANGE", "names", "+", "-", "BYLEX", "REV", "LIMIT", "0", "1"]));
+        assert!(res[0].eq(b"*1\r\n$5\r\ndelta\r\n"));
+        let res = client.handle_command(build_command(&["ZRANGE", "names", "b", "d", "BYLEX"]));
+        assert!(res[0].eq(b"-ERR min or max not valid string range item\r\n"));
+    }
+
+    #[test]
+    fn test_zunionstore_zinterstore_command() {
+        let (mut client, cache ,write_commands , _) = instantiate_client();
+
+        client.handle_command(build_command(&["ZADD", "z1", "1", "a", "2", "b"]));
+        client.handle_command(build_command(&["ZADD", "z2", "10", "b", "20", "c"]));
+        client.handle_command(build_command(&["SADD", "s", "c", "d"]));
+
+        let res = client.handle_command(build_command(&["ZUNIONSTORE", "out", "3", "z1", "z2", "s", "WEIGHTS", "2", "1", "1"]));
+        assert!(res[0].eq(b":4\r\n"));
+        let res = client.handle_command(build_command(&["ZRANGE", "out", "0", "-1", "WITHSCORES"]));
+        assert!(res[0].eq(b"*8\r\n$1\r\nd\r\n$1\r\n1\r\n$1\r\na\r\n$1\r\n2\r\n$1\r\nb\r\n$2\r\n14\r\n$1\r\nc\r\n$2\r\n21\r\n"));
+
+        let res = client.handle_command(build_command(&["ZINTERSTORE", "out", "2", "z1", "z2", "AGGREGATE", "MAX"]));
+        assert!(res[0].eq(b":1\r\n"));
+        let res = client.handle_command(build_command(&["ZSCORE", "out", "b"]));
+        assert!(res[0].eq(b"$2\r\n10\r\n"));
+        let res = client.handle_command(build_command(&["ZINTER", "2", "z1", "missing"]));
+        assert!(res[0].eq(b"*0\r\n"));
+
+        let res = client.handle_command(build_command(&["ZINTERSTORE", "out", "2", "z1", "missing"]));
+        assert!(res[0].eq(b":0\r\n"));
+        assert!(!cache.lock().unwrap().contains_key(b"out".as_slice()));
+        assert_eq!(write_commands.lock().unwrap().len(), 6);
+
+        let res = client.handle_command(build_command(&["ZUNIONSTORE", "out", "0", "z1"]));
+        assert!(res[0].eq(b"-ERR at least 1 input key is needed for 'zunionstore' command\r\n"));
+        let res = client.handle_command(build_command(&["ZUNION", "2", "z1", "z2", "AGGREGATE", "AVG"]));
+        assert!(res[0].eq(b"-ERR syntax error\r\n"));
+        client.handle_command(build_command(&["SET", "str", "val"]));
+        let res = client.handle_command(build_command(&["ZUNION", "2", "z1", "str"]));
+        assert!(res[0].eq(b"-WRONGTYPE Operation against a key holding the wrong kind of value\r\n"));
+    }
+
+    #[test]
+    fn test_zdiff_command() {
+        let (mut client, _ ,_ , _) = instantiate_client();
+
+        client.handle_command(build_command(&["ZADD", "z1", "1", "a", "2", "b", "3", "c"]));
+        client.handle_command(build_command(&["ZADD", "z2", "5", "b"]));
+        let res = client.handle_command(build_command(&["ZDIFF", "2", "z1", "z2", "WITHSCORES"]));
+        assert!(res[0].eq(b"*4\r\n$1\r\na\r\n$1\r\n1\r\n$1\r\nc\r\n$1\r\n3\r\n"));
+        let res = client.handle_command(build_command(&["ZDIFFSTORE", "out", "2", "z1", "z2"]));
+        assert!(res[0].eq(b":2\r\n"));
+        let res = client.handle_command(build_command(&["ZDIFF", "2", "z1", "z2", "WEIGHTS", "1", "1"]));
+        assert!(res[0].eq(b"-ERR syntax error\r\n"));
+    }
+
+    #[test]
+    fn test_zpop_zmpop_command() {
+        let (mut client, cache ,_ , _) = instantiate_client();
+
+        client.handle_command(build_command(&["ZADD", "board", "1", "a", "2", "b", "3", "c", "4", "d"]));
+        let res = client.handle_command(build_command(&["ZPOPMIN", "board"]));
+        assert!(res[0].eq(b"*2\r\n$1\r\na\r\n$1\r\n1\r\n"));
+        let res = client.handle_command(build_command(&["ZPOPMAX", "board", "2"]));
+        assert!(res[0].eq(b"*4\r\n$1\r\nd\r\n$1\r\n4\r\n$1\r\nc\r\n$1\r\n3\r\n"));
+        let res = client.handle_command(build_command(&["ZPOPMIN", "board", "-1"]));
+        assert!(res[0].eq(b"-ERR value is out of range, must be positive\r\n"));
+
+        let res = client.handle_command(build_command(&["ZMPOP", "2", "missing", "board", "MIN", "COUNT", "10"]));
+        assert!(res[0].eq(b"*2\r\n$5\r\nboard\r\n*1\r\n*2\r\n$1\r\nb\r\n$1\r\n2\r\n"));
+        assert!(!cache.lock().unwrap().contains_key(b"board".as_slice()));
+        let res = client.handle_command(build_command(&["ZMPOP", "1", "board", "MAX"]));
+        assert!(res[0].eq(b"*-1\r\n"));
+        let res = client.handle_command(build_command(&["ZPOPMIN", "board"]));
+        assert!(res[0].eq(b"*0\r\n"));
+    }
+
+    #[test]
+    fn test_bzpopmin_command() {
+        let (mut client, cache ,write_commands , channel_to_subscribers) = instantiate_client();
+
+        client.handle_command(build_command(&["ZADD", "board", "1", "a", "2", "b"]));
+        let res = client.handle_command(build_command(&["BZPOPMAX", "missing", "board", "0"]));
+        assert!(res[0].eq(b"*3\r\n$5\r\nboard\r\n$1\r\nb\r\n$1\r\n2\r\n"));
+        assert!(!cache.lock().unwrap().contains_key(b"missing".as_slice()));
+        assert!(write_commands.lock().unwrap().last().unwrap().eq(b"*2\r\n$7\r\nZPOPMAX\r\n$5\r\nboard\r\n"));
+
+        let res = client.handle_command(build_command(&["BZPOPMIN", "empty", "0.05"]));
+        assert!(res[0].eq(b"*-1\r\n"));
+        assert!(!cache.lock().unwrap().contains_key(b"empty".as_slice()));
+
+        let mut client_two = Client::new(client.databases.clone(), write_commands.clone(), Arc::new(Mutex::new(0)).clone(), client.notifier.clone(), None, channel_to_subscribers.clone(), Arc::new(Mutex::new(HashMap::new())), "test_rdb_dir".to_string(), "test_rdb_file".to_string());
+        let handle = thread::spawn(move || {
+            thread::sleep(std::time::Duration::from_millis(50));
+            client_two.handle_command(build_command(&["ZADD", "later", "7", "x"]));
+        });
+        let res = client.handle_command(build_command(&["BZPOPMIN", "later", "0"]));
+        handle.join().unwrap();
+        assert!(res[0].eq(b"*3\r\n$5\r\nlater\r\n$1\r\nx\r\n$1\r\n7\r\n"));
+        assert!(!cache.lock().unwrap().contains_key(b"later".as_slice()));
+
+        let res = client.handle_command(build_command(&["BZPOPMIN", "board", "-1"]));
+        assert!(res[0].eq(b"-ERR timeout is negative\r\n"));
+    }
+
+    fn seed_stream(client: &mut Client) {
+        client.handle_command(build_command(&["XADD", "jobs", "1-1", "task", "a"]));
+        client.handle_command(build_command(&["XADD", "jobs", "1-2", "task", "b"]));
+        client.handle_command(build_command(&["XADD", "jobs", "1-3", "task", "c"]));
+    }
+
+    #[test]
+    fn test_xgroup_command() {
+        let (mut client, cache ,_ , _) = instantiate_client();
+
+        let res = client.handle_command(build_command(&["XGROUP", "CREATE", "jobs", "workers", "$"]));
+        assert!(res[0].starts_with(b"-ERR The XGROUP subcommand requires the key to exist"));
+        let res = client.handle_command(build_command(&["XGROUP", "CREATE", "jobs", "workers", "$", "MKSTREAM"]));
+        assert!(res[0].eq(b"+OK\r\n"));
+        let res = client.handle_command(build_command(&["XGROUP", "CREATE", "jobs", "workers", "0"]));
+        assert!(res[0].eq(b"-BUSYGROUP Consumer Group name already exists\r\n"));
+
+        seed_stream(&mut client);
+        let res = client.handle_command(build_command(&["XGROUP", "SETID", "jobs", "workers", "$"]));
+        assert!(res[0].eq(b"+OK\r\n"));
+        if let Some(CacheVal::Stream(stream)) = cache.lock().unwrap().get(b"jobs".as_slice()) {
+            assert_eq!(stream.groups[b"workers".as_slice()].last_delivered_id, StreamId::new(1, 3));
+        }
+        let res = client.handle_command(build_command(&["XGROUP", "SETID", "jobs", "missing", "0"]));
+        assert!(res[0].eq(b"-NOGROUP No such consumer group 'missing' for key name 'jobs'\r\n"));
+
+        let res = client.handle_command(build_command(&["XGROUP", "CREATECONSUMER", "jobs", "workers", "alice"]));
+        assert!(res[0].eq(b":1\r\n"));
+        let res = client.handle_command(build_command(&["XGROUP", "CREATECONSUMER", "jobs", "workers", "alice"]));
+        assert!(res[0].eq(b":0\r\n"));
+        let res = client.handle_command(build_command(&["XGROUP", "DELCONSUMER", "jobs", "workers", "alice"]));
+        assert!(res[0].eq(b":0\r\n"));
+        let res = client.handle_command(build_command(&["XGROUP", "DESTROY", "jobs", "workers"]));
+        assert!(res[0].eq(b":1\r\n"));
+        let res = client.handle_command(build_command(&["XGROUP", "DESTROY", "jobs", "workers"]));
+        assert!(res[0].eq(b":0\r\n"));
+    }
+
+    #[test]
+    fn test_xreadgroup_xack_command() {
+        let (mut client, _ ,write_commands , _) = instantiate_client();
+        seed_stream(&mut client);
+        client.handle_command(build_command(&["XGROUP", "CREATE", "jobs", "workers", "0"]));
+
+        let res = client.handle_command(build_command(&["XREADGROUP", "GROUP", "workers", "alice", "COUNT", "2", "STREAMS", "jobs", ">"]));
+        assert!(res[0].eq(b"*1\r\n*2\r\n$4\r\njobs\r\n*2\r\n*2\r\n$3\r\n1-1\r\n*2\r\n$4\r\ntask\r\n$1\r\na\r\n*2\r\n$3\r\n1-2\r\n*2\r\n$4\r\ntask\r\n$1\r\nb\r\n"));
+        assert!(write_commands.lock().unwrap().last().unwrap().eq(b"*11\r\n$6\r\nXCLAIM\r\n$4\r\njobs\r\n$7\r\nworkers\r\n$5\r\nalice\r\n$1\r\n0\r\n$3\r\n1-1\r\n$3\r\n1-2\r\n$5\r\nFORCE\r\n$6\r\nJUSTID\r\n$6\r\nLASTID\r\n$3\r\n1-2\r\n"));
+
+        let res = client.handle_command(build_command(&["XREADGROUP", "GROUP", "workers", "bob", "STREAMS", "jobs", ">"]));
+        assert!(res[0].eq(b"*1\r\n*2\r\n$4\r\njobs\r\n*1\r\n*2\r\n$3\r\n1-3\r\n*2\r\n$4\r\ntask\r\n$1\r\nc\r\n"));
+        let res = client.handle_command(build_command(&["XREADGROUP", "GROUP", "workers", "bob", "STREAMS", "jobs", ">"]));
+        assert!(res[0].eq(b"*-1\r\n"));
+
+        // history reads only return the consumer's own pending entries
+        let res = client.handle_command(build_command(&["XREADGROUP", "GROUP", "workers", "alice", "STREAMS", "jobs", "0"]));
+        assert!(res[0].starts_with(b"*1\r\n*2\r\n$4\r\njobs\r\n*2\r\n*2\r\n$3\r\n1-1\r\n"));
+        let res = client.handle_command(build_command(&["XACK", "jobs", "workers", "1-1", "1-2", "9-9"]));
+        assert!(res[0].eq(b":2\r\n"));
+        let res = client.handle_command(build_command(&["XREADGROUP", "GROUP", "workers", "alice", "STREAMS", "jobs", "0"]));
+        assert!(res[0].eq(b"*1\r\n*2\r\n$4\r\njobs\r\n*0\r\n"));
+
+        let res = client.handle_command(build_command(&["XREADGROUP", "GROUP", "missing", "alice", "STREAMS", "jobs", ">"]));
+        assert!(res[0].eq(b"-NOGROUP No such key 'jobs' or consumer group 'missing' in XREADGROUP with GROUP option\r\n"));
+        let res = client.handle_command(build_command(&["XREADGROUP", "GROUP", "workers", "bob", "BLOCK", "50", "STREAMS", "jobs", ">"]));
+        assert!(res[0].eq(b"*-1\r\n"));
+    }
+
+    #[test]
+    fn test_xpending_command() {
+        let (mut client, _ ,_ , _) = instantiate_client();
+        seed_stream(&mut client);
+        client.handle_command(build_command(&["XGROUP", "CREATE", "jobs", "workers", "0"]));
+
+        let res = client.handle_command(build_command(&["XPENDING", "jobs", "workers"]));
+        assert!(res[0].eq(b"*4\r\n:0\r\n$-1\r\n$-1\r\n*-1\r\n"));
+
+        client.handle_command(build_command(&["XREADGROUP", "GROUP", "workers", "alice", "COUNT", "2", "STREAMS", "jobs", ">"]));
+        client.handle_command(build_command(&["XREADGROUP", "GROUP", "workers", "bob", "STREAMS", "jobs", ">"]));
+        let res = client.handle_command(build_command(&["XPENDING", "jobs", "workers"]));
+        assert!(res[0].eq(b"*4\r\n:3\r\n$3\r\n1-1\r\n$3\r\n1-3\r\n*2\r\n*2\r\n$5\r\nalice\r\n$1\r\n2\r\n*2\r\n$3\r\nbob\r\n$1\r\n1\r\n"));
+
+        let res = client.handle_command(build_command(&["XPENDING", "jobs", "workers", "(1-1", "+", "10", "alice"]));
+        assert!(res[0].starts_with(b"*1\r\n*4\r\n$3\r\n1-2\r\n$5\r\nalice\r\n:"));
+        assert!(res[0].ends_with(b":1\r\n"));
+        let res = client.handle_command(build_command(&["XPENDING", "jobs", "workers", "IDLE", "60000", "-", "+", "10"]));
+        assert!(res[0].eq(b"*0\r\n"));
+        let res = client.handle_command(build_command(&["XPENDING", "jobs", "missing"]));
+        assert!(res[0].eq(b"-NOGROUP No such key 'jobs' or consumer group 'missing'\r\n"));
+    }
+
+    #[test]
+    fn test_xclaim_xautoclaim_command() {
+        let (mut client, cache ,_ , _) = instantiate_client();
+        seed_stream(&mut client);
+        client.handle_command(build_command(&["XGROUP", "CREATE", "jobs", "workers", "0"]));
+        client.handle_command(build_command(&["XREADGROUP", "GROUP", "workers", "alice", "STREAMS", "jobs", ">"]));
+
+        let res = client.handle_command(build_command(&["XCLAIM", "jobs", "workers", "bob", "60000", "1-1"]));
+        assert!(res[0].eq(b"*0\r\n"));
+        let res = client.handle_command(build_command(&["XCLAIM", "jobs", "workers", "bob", "0", "1-1", "JUSTID"]));
+        assert!(res[0].eq(b"*1\r\n$3\r\n1-1\r\n"));
+        let res = client.handle_command(build_command(&["XCLAIM", "jobs", "workers", "bob", "0", "1-2", "RETRYCOUNT", "5"]));
+        assert!(res[0].eq(b"*1\r\n*2\r\n$3\r\n1-2\r\n*2\r\n$4\r\ntask\r\n$1\r\nb\r\n"));
+        if let Some(CacheVal::Stream(stream)) = cache.lock().unwrap().get(b"jobs".as_slice()) {
+            let group = &stream.groups[b"workers".as_slice()];
+            assert_eq!(group.pending[&StreamId::new(1, 1)].consumer, b"bob");
+            assert_eq!(group.pending[&StreamId::new(1, 1)].delivery_count, 1);
+            assert_eq!(group.pending[&StreamId::new(1, 2)].delivery_count, 5);
+            assert_eq!(group.consumers[b"alice".as_slice()].pending.len(), 1);
+        }
+
+        // an entry deleted from the stream is dropped from the pending list instead of being claimed
+        if let Some(CacheVal::Stream(stream)) = cache.lock().unwrap().get_mut(b"jobs".as_slice()) {
+            stream.stream.retain(|item| item.id != StreamId::new(1, 3));
+        }
+        let res = client.handle_command(build_command(&["XAUTOCLAIM", "jobs", "workers", "carol", "0", "0", "COUNT", "1"]));
+        assert!(res[0].eq(b"*3\r\n$3\r\n1-2\r\n*1\r\n*2\r\n$3\r\n1-1\r\n*2\r\n$4\r\ntask\r\n$1\r\na\r\n*0\r\n"));
+        let res = client.handle_command(build_command(&["XAUTOCLAIM", "jobs", "workers", "carol", "0", "1-2", "JUSTID"]));
+        assert!(res[0].eq(b"*3\r\n$3\r\n0-0\r\n*1\r\n$3\r\n1-2\r\n*1\r\n$3\r\n1-3\r\n"));
+        let res = client.handle_command(build_command(&["XPENDING", "jobs", "workers"]));
+        assert!(res[0].eq(b"*4\r\n:2\r\n$3\r\n1-1\r\n$3\r\n1-2\r\n*1\r\n*2\r\n$5\r\ncarol\r\n$1\r\n2\r\n"));
+    }
+
+    #[test]
+    fn test_xlen_xdel_command() {
+        let (mut client, _ ,_ , _) = instantiate_client();
+        seed_stream(&mut client);
+
+        let res = client.handle_command(build_command(&["XLEN", "jobs"]));
+        assert!(res[0].eq(b":3\r\n"));
+        let res = client.handle_command(build_command(&["XDEL", "jobs", "1-3", "1-3", "7-7"]));
+        assert!(res[0].eq(b":1\r\n"));
+        let res = client.handle_command(build_command(&["XLEN", "jobs"]));
+        assert!(res[0].eq(b":2\r\n"));
+
+        // the deleted top id is still the floor for new entries
+        let res = client.handle_command(build_command(&["XADD", "jobs", "1-3", "task", "d"]));
+        assert!(res[0].eq(b"-ERR The ID specified in XADD is equal or smaller than the target stream top item\r\n"));
+        let res = client.handle_command(build_command(&["XADD", "jobs", "1-*", "task", "d"]));
+        assert!(res[0].eq(b"$3\r\n1-4\r\n"));
+        let res = client.handle_command(build_command(&["XLEN", "missing"]));
+        assert!(res[0].eq(b":0\r\n"));
+    }
+
+    #[test]
+    fn test_xtrim_command() {
+        let (mut client, cache ,write_commands , _) = instantiate_client();
+        for i in 1001..=1250 {
+            client.handle_command(build_command(&["XADD", "events", &format!("{}-0", i), "n", &i.to_string()]));
+        }
+
+        // approximate trimming only drops whole nodes and is replicated as the exact result
+        let res = client.handle_command(build_command(&["XTRIM", "events", "MAXLEN", "~", "120"]));
+        assert!(res[0].eq(b":100\r\n"));
+        assert!(write_commands.lock().unwrap().last().unwrap().eq(b"*5\r\n$5\r\nXTRIM\r\n$6\r\nevents\r\n$6\r\nMAXLEN\r\n$1\r\n=\r\n$3\r\n150\r\n"));
+        let res = client.handle_command(build_command(&["XTRIM", "events", "MAXLEN", "120"]));
+        assert!(res[0].eq(b":30\r\n"));
+        let res = client.handle_command(build_command(&["XTRIM", "events", "MINID", "=", "1200"]));
+        assert!(res[0].eq(b":69\r\n"));
+        if let Some(CacheVal::Stream(stream)) = cache.lock().unwrap().get(b"events".as_slice()) {
+            assert_eq!(stream.stream.first().unwrap().id, StreamId::new(1200, 0));
+        }
+
+        let res = client.handle_command(build_command(&["XTRIM", "events", "MAXLEN", "10", "LIMIT", "5"]));
+        assert!(res[0].eq(b"-ERR syntax error, LIMIT cannot be used without the special ~ option\r\n"));
+        let res = client.handle_command(build_command(&["XTRIM", "events", "MAXLEN", "1", "MINID", "1"]));
+        assert!(res[0].eq(b"-ERR syntax error, MAXLEN and MINID options at the same time are not compatible\r\n"));
+    }
+
+    #[test]
+    fn test_xadd_trim_options() {
+        let (mut client, cache ,write_commands , _) = instantiate_client();
+
+        let res = client.handle_command(build_command(&["XADD", "events", "NOMKSTREAM", "*", "n", "1"]));
+        assert!(res[0].eq(b"$-1\r\n"));
+        assert!(!cache.lock().unwrap().contains_key(b"events".as_slice()));
+
+        for i in 1..=3 {
+            client.handle_command(build_command(&["XADD", "events", "MAXLEN", "2", &format!("1-{}", i), "n", &i.to_string()]));
+        }
+        let res = client.handle_command(build_command(&["XRANGE", "events", "-", "+"]));
+        assert!(res[0].starts_with(b"*2\r\n*2\r\n$3\r\n1-2\r\n"));
+
+        // generated ids are replicated as the concrete id
+        let res = client.handle_command(build_command(&["XADD", "events", "NOMKSTREAM", "MINID", "~", "2", "LIMIT", "10", "*", "n", "4"]));
+        let id = String::from_utf8_lossy(&res[0]).split("\r\n").nth(1).unwrap().to_string();
+        let propagated = write_commands.lock().unwrap().last().unwrap().clone();
+        assert!(propagated.starts_with(b"*9\r\n$4\r\nXADD\r\n$6\r\nevents\r\n$10\r\nNOMKSTREAM\r\n$5\r\nMINID\r\n$1\r\n=\r\n$3\r\n1-2\r\n"));
+        assert!(String::from_utf8_lossy(&propagated).contains(&id));
+
+        let res = client.handle_command(build_command(&["XADD", "events", "MAXLEN", "2", "*", "n"]));
+        assert!(res[0].eq(b"-ERR wrong number of arguments for 'xadd' command\r\n"));
+    }
+
+
+    #[test]
+    fn test_xrevrange_command() {
+        let (mut client, _ ,_ , _) = instantiate_client();
+        seed_stream(&mut client);
+
+        let res = client.handle_command(build_command(&["XREVRANGE", "jobs", "+", "-", "COUNT", "2"]));
+        assert!(res[0].eq(b"*2\r\n*2\r\n$3\r\n1-3\r\n*2\r\n$4\r\ntask\r\n$1\r\nc\r\n*2\r\n$3\r\n1-2\r\n*2\r\n$4\r\ntask\r\n$1\r\nb\r\n"));
+        let res = client.handle_command(build_command(&["XRANGE", "jobs", "(1-1", "+", "COUNT", "1"]));
+        assert!(res[0].eq(b"*1\r\n*2\r\n$3\r\n1-2\r\n*2\r\n$4\r\ntask\r\n$1\r\nb\r\n"));
+        let res = client.handle_command(build_command(&["XREVRANGE", "jobs", "(1-3", "(1-1"]));
+        assert!(res[0].eq(b"*1\r\n*2\r\n$3\r\n1-2\r\n*2\r\n$4\r\ntask\r\n$1\r\nb\r\n"));
+        let res = client.handle_command(build_command(&["XRANGE", "jobs", "-", "+", "COUNT", "0"]));
+        assert!(res[0].eq(b"*0\r\n"));
+        let res = client.handle_command(build_command(&["XRANGE", "jobs", "-", "+", "LIMIT", "1"]));
+        assert!(res[0].eq(b"-ERR syntax error\r\n"));
+        let res = client.handle_command(build_command(&["XRANGE", "jobs", "(x", "+"]));
+        assert!(res[0].eq(b"-ERR Invalid stream ID specified as stream command argument\r\n"));
+    }
+
+    #[test]
+    fn test_xinfo_command() {
+        let (mut client, _ ,_ , _) = instantiate_client();
+        let res = client.handle_command(build_command(&["XINFO", "STREAM", "jobs"]));
+        assert!(res[0].eq(b"-ERR no such key\r\n"));
+
+        seed_stream(&mut client);
+        client.handle_command(build_command(&["XDEL", "jobs", "1-1"]));
+        client.handle_command(build_command(&["XGROUP", "CREATE", "jobs", "workers", "0"]));
+        client.handle_command(build_command(&["XREADGROUP", "GROUP", "workers", "alice", "COUNT", "1", "STREAMS", "jobs", ">"]));
+
+        let res = client.handle_command(build_command(&["XINFO", "STREAM", "jobs"]));
+        assert!(res[0].starts_with(b"*16\r\n$6\r\nlength\r\n:2\r\n$17\r\nlast-generated-id\r\n$3\r\n1-3\r\n$20\r\nmax-deleted-entry-id\r\n$3\r\n1-1\r\n$13\r\nentries-added\r\n:3\r\n$23\r\nrecorded-first-entry-id\r\n$3\r\n1-2\r\n$6\r\ngroups\r\n:1\r\n"));
+        let res = client.handle_command(build_command(&["XINFO", "STREAM", "jobs", "FULL", "COUNT", "1"]));
+        assert!(String::from_utf8_lossy(&res[0]).contains("$7\r\nentries\r\n*1\r\n*2\r\n$3\r\n1-2\r\n"));
+        assert!(String::from_utf8_lossy(&res[0]).contains("$9\r\npel-count\r\n:1\r\n"));
+
+        let res = client.handle_command(build_command(&["XINFO", "GROUPS", "jobs"]));
+        assert!(res[0].eq(b"*1\r\n*8\r\n$4\r\nname\r\n$7\r\nworkers\r\n$9\r\nconsumers\r\n:1\r\n$7\r\npending\r\n:1\r\n$17\r\nlast-delivered-id\r\n$3\r\n1-2\r\n"));
+        let res = client.handle_command(build_command(&["XINFO", "CONSUMERS", "jobs", "workers"]));
+        assert!(res[0].starts_with(b"*1\r\n*8\r\n$4\r\nname\r\n$5\r\nalice\r\n$7\r\npending\r\n:1\r\n"));
+        let res = client.handle_command(build_command(&["XINFO", "CONSUMERS", "jobs", "missing"]));
+        assert!(res[0].eq(b"-NOGROUP No such consumer group 'missing' for key name 'jobs'\r\n"));
+    }
+
+
+    #[test]
+    fn test_stream_ids_order_numerically() {
+        let (mut client, _ ,_ , _) = instantiate_client();
+        client.handle_command(build_command(&["XADD", "events", "9-0", "n", "a"]));
+        let res = client.handle_command(build_command(&["XADD", "events", "10-0", "n", "b"]));
+        assert!(res[0].eq(b"$4\r\n10-0\r\n"));
+        let res = client.handle_command(build_command(&["XADD", "events", "9-5", "n", "c"]));
+        assert!(res[0].eq(b"-ERR The ID specified in XADD is equal or smaller than the target stream top item\r\n"));
+        let res = client.handle_command(build_command(&["XADD", "events", "10-*", "n", "c"]));
+        assert!(res[0].eq(b"$4\r\n10-1\r\n"));
+        let res = client.handle_command(build_command(&["XADD", "events", "11", "n", "d"]));
+        assert!(res[0].eq(b"$4\r\n11-0\r\n"));
+        let res = client.handle_command(build_command(&["XADD", "events", "x-1", "n", "d"]));
+        assert!(res[0].eq(b"-ERR Invalid stream ID specified as stream command argument\r\n"));
+
+        let res = client.handle_command(build_command(&["XRANGE", "events", "10", "10"]));
+        assert!(res[0].eq(b"*2\r\n*2\r\n$4\r\n10-0\r\n*2\r\n$1\r\nn\r\n$1\r\nb\r\n*2\r\n$4\r\n10-1\r\n*2\r\n$1\r\nn\r\n$1\r\nc\r\n"));
+        let res = client.handle_command(build_command(&["XREVRANGE", "events", "+", "-", "COUNT", "1"]));
+        assert!(res[0].starts_with(b"*1\r\n*2\r\n$4\r\n11-0\r\n"));
+        let res = client.handle_command(build_command(&["XREAD", "streams", "events", "10-1"]));
+        assert!(res[0].eq(b"*1\r\n*2\r\n$6\r\nevents\r\n*1\r\n*2\r\n$4\r\n11-0\r\n*2\r\n$1\r\nn\r\n$1\r\nd\r\n"));
+    }
+
+
+    #[test]
+    fn test_xread_block_multiple_streams() {
+        let (mut client, cache ,write_commands , channel_to_subscribers) = instantiate_client();
+        seed_stream(&mut client);
+
+        let res = client.handle_command(build_command(&["XREAD", "COUNT", "2", "STREAMS", "jobs", "missing", "0", "0"]));
+        assert!(res[0].eq(b"*1\r\n*2\r\n$4\r\njobs\r\n*2\r\n*2\r\n$3\r\n1-1\r\n*2\r\n$4\r\ntask\r\n$1\r\na\r\n*2\r\n$3\r\n1-2\r\n*2\r\n$4\r\ntask\r\n$1\r\nb\r\n"));
+        let res = client.handle_command(build_command(&["XREAD", "BLOCK", "50", "STREAMS", "jobs", "missing", "$", "$"]));
+        assert!(res[0].eq(b"$-1\r\n"));
+
+        // a single wait covers both streams and ends with the one that got data
+        let mut client_two = Client::new(client.databases.clone(), write_commands.clone(), Arc::new(Mutex::new(0)).clone(), client.notifier.clone(), None, channel_to_subscribers.clone(), Arc::new(Mutex::new(HashMap::new())), "test_rdb_dir".to_string(), "test_rdb_file".to_string());
+        let handle = thread::spawn(move || {
+            thread::sleep(std::time::Duration::from_millis(50));
+            client_two.handle_command(build_command(&["XADD", "other", "5-0", "n", "1"]));
+        });
+        let started = std::time::Instant::now();
+        let res = client.handle_command(build_command(&["XREAD", "BLOCK", "2000", "STREAMS", "jobs", "other", "$", "$"]));
+        handle.join().unwrap();
+        assert!(started.elapsed() < std::time::Duration::from_millis(1000));
+        assert!(res[0].eq(b"*1\r\n*2\r\n$5\r\nother\r\n*1\r\n*2\r\n$3\r\n5-0\r\n*2\r\n$1\r\nn\r\n$1\r\n1\r\n"));
+
+        let res = client.handle_command(build_command(&["XREAD", "STREAMS", "jobs", "other", "0"]));
+        assert!(res[0].starts_with(b"-ERR Unbalanced 'xread' list of streams"));
+    }
+
+
+    #[test]
+    fn test_blocked_clients_are_woken_up() {
+        let (other, _ ,write_commands , channel_to_subscribers) = instantiate_client();
+        let ack_replicas = Arc::new(Mutex::new(0));
+        let notifier = Arc::new(Notifier::default());
+        let mut client = Client::new(other.databases.clone(), write_commands.clone(), ack_replicas.clone(), notifier.clone(), None, channel_to_subscribers.clone(), Arc::new(Mutex::new(HashMap::new())), "test_rdb_dir".to_string(), "test_rdb_file".to_string());
+        let mut client_two = Client::new(other.databases.clone(), write_commands.clone(), ack_replicas.clone(), notifier.clone(), None, channel_to_subscribers.clone(), Arc::new(Mutex::new(HashMap::new())), "test_rdb_dir".to_string(), "test_rdb_file".to_string());
+
+        let handle = thread::spawn(move || {
+            thread::sleep(std::time::Duration::from_millis(50));
+            client_two.handle_command(build_command(&["RPUSH", "queue", "job"]));
+            thread::sleep(std::time::Duration::from_millis(50));
+            client_two.handle_command(build_command(&["REPLCONF", "ACK", "0"]));
+        });
+        let started = std::time::Instant::now();
+        let res = client.handle_command(build_command(&["BLPOP", "queue", "5"]));
+        assert!(res[0].eq(b"*2\r\n$5\r\nqueue\r\n$3\r\njob\r\n"));
+        let res = client.handle_command(build_command(&["WAIT", "1", "5000"]));
+        assert!(res[0].eq(b":1\r\n"));
+        handle.join().unwrap();
+        assert!(started.elapsed() < std::time::Duration::from_millis(2000));
+    }
+
+
+    #[test]
+    fn test_blpop_multiple_keys() {
+        let (mut client, cache ,write_commands , channel_to_subscribers) = instantiate_client();
+
+        client.handle_command(build_command(&["RPUSH", "second", "a", "b"]));
+        client.handle_command(build_command(&["RPUSH", "third", "c"]));
+        let res = client.handle_command(build_command(&["BLPOP", "first", "second", "third", "0"]));
+        assert!(res[0].eq(b"*2\r\n$6\r\nsecond\r\n$1\r\na\r\n"));
+        assert!(!cache.lock().unwrap().contains_key(b"first".as_slice()));
+        assert!(write_commands.lock().unwrap().last().unwrap().eq(b"*2\r\n$4\r\nLPOP\r\n$6\r\nsecond\r\n"));
+
+        let res = client.handle_command(build_command(&["BRPOP", "first", "third", "0"]));
+        assert!(res[0].eq(b"*2\r\n$5\r\nthird\r\n$1\r\nc\r\n"));
+        assert!(!cache.lock().unwrap().contains_key(b"third".as_slice()));
+
+        let res = client.handle_command(build_command(&["BLPOP", "first", "0.05"]));
+        assert!(res[0].eq(b"$-1\r\n"));
+        assert!(!cache.lock().unwrap().contains_key(b"first".as_slice()));
+
+        let mut client_two = Client::new(client.databases.clone(), write_commands.clone(), Arc::new(Mutex::new(0)).clone(), client.notifier.clone(), None, channel_to_subscribers.clone(), Arc::new(Mutex::new(HashMap::new())), "test_rdb_dir".to_string(), "test_rdb_file".to_string());
+        let handle = thread::spawn(move || {
+            thread::sleep(std::time::Duration::from_millis(50));
+            client_two.handle_command(build_command(&["RPUSH", "later", "x", "y"]));
+        });
+        let res = client.handle_command(build_command(&["BRPOP", "never", "later", "0"]));
+        handle.join().unwrap();
+        assert!(res[0].eq(b"*2\r\n$5\r\nlater\r\n$1\r\ny\r\n"));
+        assert!(!cache.lock().unwrap().contains_key(b"never".as_slice()));
+
+        let res = client.handle_command(build_command(&["BLPOP", "later", "-1"]));
+        assert!(res[0].eq(b"-ERR timeout is negative\r\n"));
+    }
+
+    #[test]
+    fn test_blmpop_and_blmove_commands() {
+        let (mut client, cache ,write_commands , _) = instantiate_client();
+
+        client.handle_command(build_command(&["RPUSH", "src", "a", "b", "c"]));
+        let res = client.handle_command(build_command(&["BLMPOP", "0", "2", "missing", "src", "RIGHT", "COUNT", "2"]));
+        assert!(res[0].eq(b"*2\r\n$3\r\nsrc\r\n*2\r\n$1\r\nc\r\n$1\r\nb\r\n"));
+        assert!(write_commands.lock().unwrap().last().unwrap().eq(b"*3\r\n$4\r\nRPOP\r\n$3\r\nsrc\r\n$1\r\n2\r\n"));
+        let res = client.handle_command(build_command(&["BLMPOP", "0.05", "1", "missing", "LEFT"]));
+        assert!(res[0].eq(b"*-1\r\n"));
+        let res = client.handle_command(build_command(&["BLMPOP", "0", "0", "src", "LEFT"]));
+        assert!(res[0].eq(b"-ERR numkeys should be greater than 0\r\n"));
+        let res = client.handle_command(build_command(&["BLMPOP", "0", "1", "src", "UP"]));
+        assert!(res[0].eq(b"-ERR syntax error\r\n"));
+
+        let res = client.handle_command(build_command(&["BLMOVE", "src", "dst", "LEFT", "RIGHT", "0"]));
+        assert!(res[0].eq(b"$1\r\na\r\n"));
+        assert!(!cache.lock().unwrap().contains_key(b"src".as_slice()));
+        assert!(write_commands.lock().unwrap().last().unwrap().eq(b"*5\r\n$5\r\nLMOVE\r\n$3\r\nsrc\r\n$3\r\ndst\r\n$4\r\nLEFT\r\n$5\r\nRIGHT\r\n"));
+
+        client.handle_command(build_command(&["RPUSH", "dst", "b"]));
+        let res = client.handle_command(build_command(&["BRPOPLPUSH", "dst", "dst", "0"]));
+        assert!(res[0].eq(b"$1\r\nb\r\n"));
+        let res = client.handle_command(build_command(&["LRANGE", "dst", "0", "-1"]));
+        assert!(res[0].eq(b"*2\r\n$1\r\nb\r\n$1\r\na\r\n"));
+
+        client.handle_command(build_command(&["SET", "str", "v"]));
+        let res = client.handle_command(build_command(&["BLMOVE", "dst", "str", "LEFT", "LEFT", "0"]));
+        assert!(res[0].starts_with(b"-WRONGTYPE"));
+        let res = client.handle_command(build_command(&["BRPOPLPUSH", "empty", "dst", "0.05"]));
+        assert!(res[0].eq(b"$-1\r\n"));
+        assert!(!cache.lock().unwrap().contains_key(b"empty".as_slice()));
+    }
+
+    #[test]
+    fn test_blocked_list_clients_are_served_in_order() {
+        let (mut client, cache ,write_commands , channel_to_subscribers) = instantiate_client();
+        let mut client_two = Client::new(client.databases.clone(), write_commands.clone(), Arc::new(Mutex::new(0)).clone(), client.notifier.clone(), None, channel_to_subscribers.clone(), Arc::new(Mutex::new(HashMap::new())), "test_rdb_dir".to_string(), "test_rdb_file".to_string());
+        let mut client_three = Client::new(client.databases.clone(), write_commands.clone(), Arc::new(Mutex::new(0)).clone(), client.notifier.clone(), None, channel_to_subscribers.clone(), Arc::new(Mutex::new(HashMap::new())), "test_rdb_dir".to_string(), "test_rdb_file".to_string());
+
+        // the first connection blocks on both keys, the second only on the one that gets pushed to
+        let first = thread::spawn(move || client.handle_command(build_command(&["BLPOP", "a", "b", "5"])));
+        thread::sleep(std::time::Duration::from_millis(50));
+        let second = thread::spawn(move || client_two.handle_command(build_command(&["BLPOP", "b", "5"])));
+        thread::sleep(std::time::Duration::from_millis(50));
+        client_three.handle_command(build_command(&["RPUSH", "b", "one"]));
+        client_three.handle_command(build_command(&["RPUSH", "b", "two"]));
+        assert!(first.join().unwrap()[0].eq(b"*2\r\n$1\r\nb\r\n$3\r\none\r\n"));
+        assert!(second.join().unwrap()[0].eq(b"*2\r\n$1\r\nb\r\n$3\r\ntwo\r\n"));
+        assert!(cache.lock().unwrap().is_empty());
+    }
+
+
+    #[test]
+    fn test_list_index_commands() {
+        let (mut client, cache ,write_commands , _) = instantiate_client();
+
+        client.handle_command(build_command(&["RPUSH", "list", "a", "b", "c", "b"]));
+        let res = client.handle_command(build_command(&["LINDEX", "list", "-1"]));
+        assert!(res[0].eq(b"$1\r\nb\r\n"));
+        let res = client.handle_command(build_command(&["LINDEX", "list", "4"]));
+        assert!(res[0].eq(b"$-1\r\n"));
+        let res = client.handle_command(build_command(&["LINDEX", "list", "x"]));
+        assert!(res[0].eq(b"-ERR value is not an integer or out of range\r\n"));
+
+        let res = client.handle_command(build_command(&["LSET", "list", "-2", "C"]));
+        assert!(res[0].eq(b"+OK\r\n"));
+        assert!(write_commands.lock().unwrap().last().unwrap().eq(b"*4\r\n$4\r\nLSET\r\n$4\r\nlist\r\n$2\r\n-2\r\n$1\r\nC\r\n"));
+        let res = client.handle_command(build_command(&["LSET", "list", "-5", "z"]));
+        assert!(res[0].eq(b"-ERR index out of range\r\n"));
+        let res = client.handle_command(build_command(&["LSET", "missing", "0", "z"]));
+        assert!(res[0].eq(b"-ERR no such key\r\n"));
+
+        let res = client.handle_command(build_command(&["LINSERT", "list", "AFTER", "b", "x"]));
+        assert!(res[0].eq(b":5\r\n"));
+        let res = client.handle_command(build_command(&["LINSERT", "list", "BEFORE", "nope", "x"]));
+        assert!(res[0].eq(b":-1\r\n"));
+        let res = client.handle_command(build_command(&["LINSERT", "missing", "BEFORE", "a", "x"]));
+        assert!(res[0].eq(b":0\r\n"));
+        let res = client.handle_command(build_command(&["LRANGE", "list", "0", "-1"]));
+        assert!(res[0].eq(b"*5\r\n$1\r\na\r\n$1\r\nb\r\n$1\r\nx\r\n$1\r\nC\r\n$1\r\nb\r\n"));
+
+        let res = client.handle_command(build_command(&["LPOS", "list", "b"]));
+        assert!(res[0].eq(b":1\r\n"));
+        let res = client.handle_command(build_command(&["LPOS", "list", "b", "RANK", "-1"]));
+        assert!(res[0].eq(b":4\r\n"));
+        let res = client.handle_command(build_command(&["LPOS", "list", "b", "COUNT", "0"]));
+        assert!(res[0].eq(b"*2\r\n:1\r\n:4\r\n"));
+        let res = client.handle_command(build_command(&["LPOS", "list", "b", "RANK", "2", "MAXLEN", "3"]));
+        assert!(res[0].eq(b"$-1\r\n"));
+        let res = client.handle_command(build_command(&["LPOS", "list", "b", "RANK", "0"]));
+        assert!(res[0].starts_with(b"-ERR RANK can't be zero"));
+
+        cache.lock().unwrap().insert("str".into(), CacheVal::String(StringCacheVal::new("v".into())));
+        let res = client.handle_command(build_command(&["LINDEX", "str", "0"]));
+        assert!(res[0].starts_with(b"-WRONGTYPE"));
+    }
+
+    #[test]
+    fn test_list_removal_commands() {
+        let (mut client, cache ,write_commands , _) = instantiate_client();
+
+        client.handle_command(build_command(&["RPUSH", "list", "a", "b", "a", "c", "a"]));
+        let res = client.handle_command(build_command(&["LREM", "list", "-2", "a"]));
+        assert!(res[0].eq(b":2\r\n"));
+        let res = client.handle_command(build_command(&["LRANGE", "list", "0", "-1"]));
+        assert!(res[0].eq(b"*3\r\n$1\r\na\r\n$1\r\nb\r\n$1\r\nc\r\n"));
+
+        let res = client.handle_command(build_command(&["LTRIM", "list", "1", "-1"]));
+        assert!(res[0].eq(b"+OK\r\n"));
+        assert!(write_commands.lock().unwrap().last().unwrap().eq(b"*4\r\n$5\r\nLTRIM\r\n$4\r\nlist\r\n$1\r\n1\r\n$2\r\n-1\r\n"));
+        let res = client.handle_command(build_command(&["LRANGE", "list", "0", "-1"]));
+        assert!(res[0].eq(b"*2\r\n$1\r\nb\r\n$1\r\nc\r\n"));
+        client.handle_command(build_command(&["LTRIM", "list", "5", "10"]));
+        assert!(!cache.lock().unwrap().contains_key(b"list".as_slice()));
+
+        client.handle_command(build_command(&["RPUSH", "list", "a", "b", "c"]));
+        let res = client.handle_command(build_command(&["RPOP", "list"]));
+        assert!(res[0].eq(b"$1\r\nc\r\n"));
+        let res = client.handle_command(build_command(&["LPOP", "list", "10"]));
+        assert!(res[0].eq(b"*2\r\n$1\r\na\r\n$1\r\nb\r\n"));
+        assert!(!cache.lock().unwrap().contains_key(b"list".as_slice()));
+        let res = client.handle_command(build_command(&["RPOP", "list", "2"]));
+        assert!(res[0].eq(b"*-1\r\n"));
+        let res = client.handle_command(build_command(&["LPOP", "list", "-1"]));
+        assert!(res[0].eq(b"-ERR value is out of range, must be positive\r\n"));
+    }
+
+    #[test]
+    fn test_list_move_and_push_commands() {
+        let (mut client, cache ,write_commands , _) = instantiate_client();
+
+        let res = client.handle_command(build_command(&["RPUSHX", "list", "a"]));
+        assert!(res[0].eq(b":0\r\n"));
+        assert!(!cache.lock().unwrap().contains_key(b"list".as_slice()));
+        client.handle_command(build_command(&["RPUSH", "list", "b"]));
+        let res = client.handle_command(build_command(&["LPUSHX", "list", "a", "z"]));
+        assert!(res[0].eq(b":3\r\n"));
+        let res = client.handle_command(build_command(&["RPUSHX", "list", "c"]));
+        assert!(res[0].eq(b":4\r\n"));
+        assert!(write_commands.lock().unwrap().last().unwrap().eq(b"*3\r\n$6\r\nRPUSHX\r\n$4\r\nlist\r\n$1\r\nc\r\n"));
+
+        let res = client.handle_command(build_command(&["LMOVE", "list", "other", "LEFT", "RIGHT"]));
+        assert!(res[0].eq(b"$1\r\nz\r\n"));
+        let res = client.handle_command(build_command(&["RPOPLPUSH", "list", "list"]));
+        assert!(res[0].eq(b"$1\r\nc\r\n"));
+        let res = client.handle_command(build_command(&["LRANGE", "list", "0", "-1"]));
+        assert!(res[0].eq(b"*3\r\n$1\r\nc\r\n$1\r\na\r\n$1\r\nb\r\n"));
+        let res = client.handle_command(build_command(&["LMOVE", "missing", "other", "LEFT", "LEFT"]));
+        assert!(res[0].eq(b"$-1\r\n"));
+        let res = client.handle_command(build_command(&["LMOVE", "list", "other", "UP", "LEFT"]));
+        assert!(res[0].eq(b"-ERR syntax error\r\n"));
+
+        let res = client.handle_command(build_command(&["LMPOP", "2", "missing", "list", "RIGHT", "COUNT", "5"]));
+        assert!(res[0].eq(b"*2\r\n$4\r\nlist\r\n*3\r\n$1\r\nb\r\n$1\r\na\r\n$1\r\nc\r\n"));
+        assert!(!cache.lock().unwrap().contains_key(b"list".as_slice()));
+        let res = client.handle_command(build_command(&["LMPOP", "1", "list", "LEFT"]));
+        assert!(res[0].eq(b"*-1\r\n"));
+        let res = client.handle_command(build_command(&["LMPOP", "1", "other", "LEFT", "COUNT", "0"]));
+        assert!(res[0].eq(b"-ERR count should be greater than 0\r\n"));
+    }
+
+
+    #[test]
+    fn test_set_options() {
+        let (mut client, cache ,write_commands , _) = instantiate_client();
+
+        let res = client.handle_command(build_command(&["SET", "lock", "a", "NX", "EX", "30"]));
+        assert!(res[0].eq(b"+OK\r\n"));
+        let expiry_time = cache.lock().unwrap().expiry(b"lock").unwrap();
+        assert!(expiry_time > now_ms() + 29_000);
+        let replicated = format!("*5\r\n$3\r\nSET\r\n$4\r\nlock\r\n$1\r\na\r\n$4\r\nPXAT\r\n{}", String::from_utf8_lossy(&create_bulk_string_resp(expiry_time.to_string())));
+        assert!(write_commands.lock().unwrap().last().unwrap().eq(replicated.as_bytes()));
+
+        let res = client.handle_command(build_command(&["SET", "lock", "b", "NX"]));
+        assert!(res[0].eq(b"$-1\r\n"));
+        let res = client.handle_command(build_command(&["SET", "lock", "b", "KEEPTTL", "GET", "XX"]));
+        assert!(res[0].eq(b"$1\r\na\r\n"));
+        match cache.lock().unwrap().get(b"lock".as_slice()) {
+            Some(CacheVal::String(val)) => assert!(val.val.eq(b"b")),
+            _ => panic!("Incorrect cache type")
+        }
+        let res = client.handle_command(build_command(&["SET", "missing", "v", "XX", "GET"]));
+        assert!(res[0].eq(b"$-1\r\n"));
+        assert!(!cache.lock().unwrap().contains_key(b"missing".as_slice()));
+
+        // an expired key does not exist as far as NX is concerned
+        client.handle_command(build_command(&["SET", "old", "v", "PXAT", "1"]));
+        let res = client.handle_command(build_command(&["SET", "old", "w", "NX"]));
+        assert!(res[0].eq(b"+OK\r\n"));
+
+        let res = client.handle_command(build_command(&["SET", "k", "v", "NX", "XX"]));
+        assert!(res[0].eq(b"-ERR syntax error\r\n"));
+        let res = client.handle_command(build_command(&["SET", "k", "v", "EX", "10", "KEEPTTL"]));
+        assert!(res[0].eq(b"-ERR syntax error\r\n"));
+        let res = client.handle_command(build_command(&["SET", "k", "v", "PX", "0"]));
+        assert!(res[0].eq(b"-ERR invalid expire time in 'set' command\r\n"));
+        let res = client.handle_command(build_command(&["SET", "k", "v", "EX", "ten"]));
+        assert!(res[0].eq(b"-ERR value is not an integer or out of range\r\n"));
+
+        client.handle_command(build_command(&["RPUSH", "list", "a"]));
+        let res = client.handle_command(build_command(&["SET", "list", "v", "GET"]));
+        assert!(res[0].starts_with(b"-WRONGTYPE"));
+    }
+
+
+    #[test]
+    fn test_string_commands() {
+        let (mut client, cache ,_ , _) = instantiate_client();
+
+        let res = client.handle_command(build_command(&["APPEND", "greeting", "Hello"]));
+        assert!(res[0].eq(b":5\r\n"));
+        let res = client.handle_command(build_command(&["APPEND", "greeting", " World"]));
+        assert!(res[0].eq(b":11\r\n"));
+        let res = client.handle_command(build_command(&["STRLEN", "greeting"]));
+        assert!(res[0].eq(b":11\r\n"));
+        let res = client.handle_command(build_command(&["GETRANGE", "greeting", "-5", "-1"]));
+        assert!(res[0].eq(b"$5\r\nWorld\r\n"));
+        let res = client.handle_command(build_command(&["GETRANGE", "greeting", "0", "100"]));
+        assert!(res[0].eq(b"$11\r\nHello World\r\n"));
+        let res = client.handle_command(build_command(&["GETRANGE", "greeting", "5", "2"]));
+        assert!(res[0].eq(b"$0\r\n\r\n"));
+
+        let res = client.handle_command(build_command(&["SETRANGE", "greeting", "6", "Redis"]));
+        assert!(res[0].eq(b":11\r\n"));
+        let res = client.handle_command(build_command(&["SETRANGE", "padded", "3", "x"]));
+        assert!(res[0].eq(b":4\r\n"));
+        let res = client.handle_command(build_command(&["GETRANGE", "padded", "0", "-1"]));
+        assert!(res[0].eq(b"$4\r\n\0\0\0x\r\n"));
+        let res = client.handle_command(build_command(&["SETRANGE", "padded", "-1", "x"]));
+        assert!(res[0].eq(b"-ERR offset is out of range\r\n"));
+
+        let res = client.handle_command(build_command(&["GETSET", "greeting", "bye"]));
+        assert!(res[0].eq(b"$11\r\nHello Redis\r\n"));
+        let res = client.handle_command(build_command(&["GETDEL", "greeting"]));
+        assert!(res[0].eq(b"$3\r\nbye\r\n"));
+        assert!(!cache.lock().unwrap().contains_key(b"greeting".as_slice()));
+
+        client.handle_command(build_command(&["RPUSH", "list", "a"]));
+        let res = client.handle_command(build_command(&["APPEND", "list", "a"]));
+        assert!(res[0].starts_with(b"-WRONGTYPE"));
+        let res = client.handle_command(build_command(&["STRLEN", "list"]));
+        assert!(res[0].starts_with(b"-WRONGTYPE"));
+    }
+
+    #[test]
+    fn test_multi_key_string_commands() {
+        let (mut client, cache ,write_commands , _) = instantiate_client();
+
+        let res = client.handle_command(build_command(&["MSET", "a", "1", "b", "2"]));
+        assert!(res[0].eq(b"+OK\r\n"));
+        let res = client.handle_command(build_command(&["MSET", "a", "1", "b"]));
+        assert!(res[0].starts_with(b"-ERR wrong number of arguments"));
+        client.handle_command(build_command(&["RPUSH", "list", "x"]));
+        let res = client.handle_command(build_command(&["MGET", "a", "missing", "list", "b"]));
+        assert!(res[0].eq(b"*4\r\n$1\r\n1\r\n$-1\r\n$-1\r\n$1\r\n2\r\n"));
+
+        let res = client.handle_command(build_command(&["MSETNX", "c", "3", "a", "9"]));
+        assert!(res[0].eq(b":0\r\n"));
+        assert!(!cache.lock().unwrap().contains_key(b"c".as_slice()));
+        let res = client.handle_command(build_command(&["MSETNX", "c", "3", "d", "4"]));
+        assert!(res[0].eq(b":1\r\n"));
+        let res = client.handle_command(build_command(&["SETNX", "c", "5"]));
+        assert!(res[0].eq(b":0\r\n"));
+        let res = client.handle_command(build_command(&["SETNX", "e", "5"]));
+        assert!(res[0].eq(b":1\r\n"));
+
+        let res = client.handle_command(build_command(&["GETEX", "a", "EX", "100"]));
+        assert!(res[0].eq(b"$1\r\n1\r\n"));
+        let expiry_time = cache.lock().unwrap().expiry(b"a").unwrap();
+        assert!(expiry_time > now_ms() + 99_000);
+        let replicated = format!("*4\r\n$5\r\nGETEX\r\n$1\r\na\r\n$4\r\nPXAT\r\n{}", String::from_utf8_lossy(&create_bulk_string_resp(expiry_time.to_string())));
+        assert!(write_commands.lock().unwrap().last().unwrap().eq(replicated.as_bytes()));
+        client.handle_command(build_command(&["GETEX", "a", "PERSIST"]));
+        assert!(cache.lock().unwrap().expiry(b"a").is_none());
+        let res = client.handle_command(build_command(&["GETEX", "a", "EX", "0"]));
+        assert!(res[0].eq(b"-ERR invalid expire time in 'getex' command\r\n"));
+        let res = client.handle_command(build_command(&["GETEX", "a", "PERSIST", "EX", "1"]));
+        assert!(res[0].eq(b"-ERR syntax error\r\n"));
+
+        // expired keys are gone for every string command
+        client.handle_command(build_command(&["SET", "old", "v", "PXAT", "1"]));
+        let res = client.handle_command(build_command(&["MGET", "old"]));
+        assert!(res[0].eq(b"*1\r\n$-1\r\n"));
+        let res = client.handle_command(build_command(&["APPEND", "old", "w"]));
+        assert!(res[0].eq(b":1\r\n"));
+    }
+
+
+    #[test]
+    fn test_numeric_string_commands() {
+        let (mut client, _ ,write_commands , _) = instantiate_client();
+
+        let res = client.handle_command(build_command(&["INCRBY", "counter", "10"]));
+        assert!(res[0].eq(b":10\r\n"));
+        let res = client.handle_command(build_command(&["DECRBY", "counter", "15"]));
+        assert!(res[0].eq(b":-5\r\n"));
+        let res = client.handle_command(build_command(&["DECR", "counter"]));
+        assert!(res[0].eq(b":-6\r\n"));
+        let res = client.handle_command(build_command(&["INCRBY", "counter", "ten"]));
+        assert!(res[0].eq(b"-ERR value is not an integer or out of range\r\n"));
+
+        // only integers in the form redis prints them back count
+        for stored in ["010", "+1", "-0", " 1", "1 "] {
+            client.handle_command(build_command(&["SET", "padded", stored]));
+            let res = client.handle_command(build_command(&["INCR", "padded"]));
+            assert!(res[0].eq(b"-ERR value is not an integer or out of range\r\n"));
+        }
+        let res = client.handle_command(build_command(&["INCRBY", "counter", "010"]));
+        assert!(res[0].eq(b"-ERR value is not an integer or out of range\r\n"));
+        client.handle_command(build_command(&["SET", "zero", "0"]));
+        let res = client.handle_command(build_command(&["DECRBY", "zero", "-0"]));
+        assert!(res[0].eq(b"-ERR value is not an integer or out of range\r\n"));
+        let res = client.handle_command(build_command(&["INCR", "zero"]));
+        assert!(res[0].eq(b":1\r\n"));
+
+        client.handle_command(build_command(&["SET", "max", &i64::MAX.to_string()]));
+        let res = client.handle_command(build_command(&["INCR", "max"]));
+        assert!(res[0].eq(b"-ERR increment or decrement would overflow\r\n"));
+        let res = client.handle_command(build_command(&["DECRBY", "max", &i64::MIN.to_string()]));
+        assert!(res[0].eq(b"-ERR decrement would overflow\r\n"));
+        let res = client.handle_command(build_command(&["GET", "max"]));
+        assert!(res[0].eq(format!("$19\r\n{}\r\n", i64::MAX).as_bytes()));
+
+        client.handle_command(build_command(&["RPUSH", "list", "a"]));
+        let res = client.handle_command(build_command(&["INCR", "list"]));
+        assert!(res[0].starts_with(b"-WRONGTYPE"));
+
+        client.handle_command(build_command(&["SET", "float", "10.50"]));
+        let res = client.handle_command(build_command(&["INCRBYFLOAT", "float", "0.1"]));
+        assert!(res[0].eq(b"$4\r\n10.6\r\n"));
+        assert!(write_commands.lock().unwrap().last().unwrap().eq(b"*4\r\n$3\r\nSET\r\n$5\r\nfloat\r\n$4\r\n10.6\r\n$7\r\nKEEPTTL\r\n"));
+        let res = client.handle_command(build_command(&["INCRBYFLOAT", "float", "-5.6"]));
+        assert!(res[0].eq(b"$1\r\n5\r\n"));
+        let res = client.handle_command(build_command(&["INCRBYFLOAT", "float", "abc"]));
+        assert!(res[0].eq(b"-ERR value is not a valid float\r\n"));
+        let res = client.handle_command(build_command(&["INCRBYFLOAT", "float", "1e308"]));
+        assert!(res[0].eq(("$309\r\n1".to_string() + &"0".repeat(308) + "\r\n").as_bytes()));
+        let res = client.handle_command(build_command(&["INCRBYFLOAT", "float", "1.7e308"]));
+        assert!(res[0].eq(b"-ERR increment would produce NaN or Infinity\r\n"));
+        let res = client.handle_command(build_command(&["INCRBYFLOAT", "list", "1"]));
+        assert!(res[0].starts_with(b"-WRONGTYPE"));
+    }
+
+
+    #[test]
+    fn test_bit_commands() {
+        let (mut client, cache ,_ , _) = instantiate_client();
+
+        let res = client.handle_command(build_command(&["SETBIT", "bits", "7", "1"]));
+        assert!(res[0].eq(b":0\r\n"));
+        let res = client.handle_command(build_command(&["SETBIT", "bits", "7", "0"]));
+        assert!(res[0].eq(b":1\r\n"));
+        // bit 0 is the most significant bit of the first byte, which is not valid utf-8 on its own
+        client.handle_command(build_command(&["SETBIT", "bits", "0", "1"]));
+        client.handle_command(build_command(&["SETBIT", "bits", "17", "1"]));
+        match cache.lock().unwrap().get(b"bits".as_slice()) {
+            Some(CacheVal::String(val)) => assert!(val.val.eq(&[0x80, 0x00, 0x40])),
+            _ => panic!("Incorrect cache type")
+        }
+        let res = client.handle_command(build_command(&["GETBIT", "bits", "17"]));
+        assert!(res[0].eq(b":1\r\n"));
+        let res = client.handle_command(build_command(&["GETBIT", "bits", "1000"]));
+        assert!(res[0].eq(b":0\r\n"));
+        let res = client.handle_command(build_command(&["SETBIT", "bits", "-1", "1"]));
+        assert!(res[0].eq(b"-ERR bit offset is not an integer or out of range\r\n"));
+        let res = client.handle_command(build_command(&["SETBIT", "bits", "1", "2"]));
+        assert!(res[0].eq(b"-ERR bit is not an integer or out of range\r\n"));
+
+        client.handle_command(build_command(&["SET", "text", "foobar"]));
+        let res = client.handle_command(build_command(&["BITCOUNT", "text"]));
+        assert!(res[0].eq(b":26\r\n"));
+        let res = client.handle_command(build_command(&["BITCOUNT", "text", "1", "1"]));
+        assert!(res[0].eq(b":6\r\n"));
+        let res = client.handle_command(build_command(&["BITCOUNT", "text", "5", "30", "BIT"]));
+        assert!(res[0].eq(b":17\r\n"));
+        let res = client.handle_command(build_command(&["BITCOUNT", "text", "1"]));
+        assert!(res[0].eq(b"-ERR syntax error\r\n"));
+
+        client.handle_command(build_command(&["SETBIT", "ones", "7", "1"]));
+        let res = client.handle_command(build_command(&["BITPOS", "ones", "1"]));
+        assert!(res[0].eq(b":7\r\n"));
+        let res = client.handle_command(build_command(&["BITPOS", "bits", "1", "1"]));
+        assert!(res[0].eq(b":17\r\n"));
+        let res = client.handle_command(build_command(&["BITPOS", "bits", "1", "2", "-1", "BYTE"]));
+        assert!(res[0].eq(b":17\r\n"));
+        let res = client.handle_command(build_command(&["BITPOS", "bits", "1", "1", "16", "BIT"]));
+        assert!(res[0].eq(b":-1\r\n"));
+        let res = client.handle_command(build_command(&["BITPOS", "missing", "0"]));
+        assert!(res[0].eq(b":0\r\n"));
+        let res = client.handle_command(build_command(&["BITPOS", "bits", "2"]));
+        assert!(res[0].eq(b"-ERR The bit argument must be 1 or 0.\r\n"));
+
+        client.handle_command(build_command(&["SETBIT", "full", "0", "1"]));
+        client.handle_command(build_command(&["BITFIELD", "full", "SET", "u8", "0", "255"]));
+        let res = client.handle_command(build_command(&["BITPOS", "full", "0"]));
+        assert!(res[0].eq(b":8\r\n"));
+        let res = client.handle_command(build_command(&["BITPOS", "full", "0", "0", "-1"]));
+        assert!(res[0].eq(b":-1\r\n"));
+    }
+
+    #[test]
+    fn test_bitop_and_bitfield_commands() {
+        let (mut client, cache ,_ , _) = instantiate_client();
+
+        client.handle_command(build_command(&["SET", "a", "abc"]));
+        client.handle_command(build_command(&["SET", "b", "a"]));
+        let res = client.handle_command(build_command(&["BITOP", "AND", "dest", "a", "b", "missing"]));
+        assert!(res[0].eq(b":3\r\n"));
+        match cache.lock().unwrap().get(b"dest".as_slice()) {
+            Some(CacheVal::String(val)) => assert!(val.val.eq(&[0, 0, 0])),
+            _ => panic!("Incorrect cache type")
+        }
+        client.handle_command(build_command(&["BITOP", "XOR", "dest", "a", "b"]));
+        match cache.lock().unwrap().get(b"dest".as_slice()) {
+            Some(CacheVal::String(val)) => assert!(val.val.eq(&[0, b'b', b'c'])),
+            _ => panic!("Incorrect cache type")
+        }
+        client.handle_command(build_command(&["BITOP", "NOT", "dest", "b"]));
+        match cache.lock().unwrap().get(b"dest".as_slice()) {
+            Some(CacheVal::String(val)) => assert!(val.val.eq(&[!b'a'])),
+            _ => panic!("Incorrect cache type")
+        }
+        let res = client.handle_command(build_command(&["BITOP", "NOT", "dest", "a", "b"]));
+        assert!(res[0].eq(b"-ERR BITOP NOT must be called with a single source key.\r\n"));
+        let res = client.handle_command(build_command(&["BITOP", "OR", "dest", "missing"]));
+        assert!(res[0].eq(b":0\r\n"));
+        assert!(!cache.lock().unwrap().contains_key(b"dest".as_slice()));
+
+        let res = client.handle_command(build_command(&["BITFIELD", "field", "SET", "i8", "0", "100", "GET", "u4", "0", "INCRBY", "i8", "0", "100"]));
+        assert!(res[0].eq(b"*3\r\n:0\r\n:6\r\n:-56\r\n"));
+        let res = client.handle_command(build_command(&["BITFIELD", "field", "OVERFLOW", "SAT", "INCRBY", "i8", "0", "-200", "OVERFLOW", "FAIL", "INCRBY", "i8", "0", "-1"]));
+        assert!(res[0].eq(b"*2\r\n:-128\r\n$-1\r\n"));
+        let res = client.handle_command(build_command(&["BITFIELD", "field", "SET", "u2", "#1", "7", "GET", "u8", "0"]));
+        assert!(res[0].eq(b"*2\r\n:0\r\n:176\r\n"));
+        let res = client.handle_command(build_command(&["BITFIELD", "field", "GET", "i64", "0", "GET", "u64", "0"]));
+        assert!(res[0].starts_with(b"-ERR Invalid bitfield type"));
+        let res = client.handle_command(build_command(&["BITFIELD", "field", "OVERFLOW", "MAYBE"]));
+        assert!(res[0].eq(b"-ERR Invalid OVERFLOW type specified\r\n"));
+        let res = client.handle_command(build_command(&["BITFIELD_RO", "field", "INCRBY", "u8", "0", "1"]));
+        assert!(res[0].eq(b"-ERR BITFIELD_RO only supports the GET subcommand\r\n"));
+        let res = client.handle_command(build_command(&["BITFIELD_RO", "missing", "GET", "u8", "0"]));
+        assert!(res[0].eq(b"*1\r\n:0\r\n"));
+        assert!(!cache.lock().unwrap().contains_key(b"missing".as_slice()));
+    }
+
+    #[test]
+    fn test_hyperloglog_commands() {
+        let (mut client, cache ,_ , _) = instantiate_client();
+
+        let res = client.handle_command(build_command(&["PFADD", "hll", "a", "b", "c", "a"]));
+        assert!(res[0].eq(b":1\r\n"));
+        let res = client.handle_command(build_command(&["PFADD", "hll", "b", "c"]));
+        assert!(res[0].eq(b":0\r\n"));
+        let res = client.handle_command(build_command(&["PFCOUNT", "hll"]));
+        assert!(res[0].eq(b":3\r\n"));
+        let res = client.handle_command(build_command(&["TYPE", "hll"]));
+        assert!(res[0].eq(b"+string\r\n"));
+        let res = client.handle_command(build_command(&["PFADD", "empty"]));
+        assert!(res[0].eq(b":1\r\n"));
+        let res = client.handle_command(build_command(&["PFCOUNT", "empty", "missing"]));
+        assert!(res[0].eq(b":0\r\n"));
+
+        // the value is a plain string, copying its bytes copies the HyperLogLog
+        let bytes = match cache.lock().unwrap().get(b"hll".as_slice()) {
+            Some(CacheVal::String(val)) => {
+                assert!(val.val.starts_with(b"HYLL\x01"));
+                val.val.clone()
+            },
+            _ => panic!("Incorrect cache type")
+        };
+        cache.lock().unwrap().insert(b"copy".to_vec(), CacheVal::String(StringCacheVal::new(bytes)));
+        let res = client.handle_command(build_command(&["PFADD", "copy", "d"]));
+        assert!(res[0].eq(b":1\r\n"));
+        let res = client.handle_command(build_command(&["PFCOUNT", "copy"]));
+        assert!(res[0].eq(b":4\r\n"));
+        let res = client.handle_command(build_command(&["PFCOUNT", "hll", "copy"]));
+        assert!(res[0].eq(b":4\r\n"));
+
+        client.handle_command(build_command(&["SET", "plain", "HYLL is not enough"]));
+        let res = client.handle_command(build_command(&["PFADD", "plain", "a"]));
+        assert!(res[0].eq(b"-WRONGTYPE Key is not a valid HyperLogLog string value.\r\n"));
+        client.handle_command(build_command(&["RPUSH", "list", "a"]));
+        let res = client.handle_command(build_command(&["PFCOUNT", "list"]));
+        assert!(res[0].starts_with(b"-WRONGTYPE"));
+    }
+
+    #[test]
+    fn test_hyperloglog_error_bounds() {
+        let (mut client, cache ,_ , _) = instantiate_client();
+
+        let encoding = |key: &str| match cache.lock().unwrap().get(key.as_bytes()) {
+            Some(CacheVal::String(val)) => val.val[4],
+            _ => panic!("Incorrect cache type")
+        };
+        let mut added = 0;
+        for (key, cardinality) in [("small", 1_000), ("large", 100_000)] {
+            while added < cardinality {
+                let elements: Vec<String> = (added..added + 1_000).map(|n| format!("element:{}", n)).collect();
+                let mut args = vec!["PFADD", key];
+                args.extend(elements.iter().map(String::as_str));
+                client.handle_command(build_command(&args));
+                added += 1_000;
+            }
+            let res = client.handle_command(build_command(&["PFCOUNT", key]));
+            let estimate: f64 = parse_arg(&res[0][1..res[0].len() - 2]).unwrap();
+            // the standard error is 0.81%, so allow for three of them
+            assert!((estimate - cardinality as f64).abs() / cardinality as f64 <= 0.025);
+            added = 0;
+        }
+        // a thousand elements still fit the sparse encoding, a hundred thousand no longer do
+        assert_eq!(encoding("small"), 1);
+        assert_eq!(encoding("large"), 0);
+
+        let res = client.handle_command(build_command(&["PFMERGE", "merged", "small", "large"]));
+        assert!(res[0].eq(b"+OK\r\n"));
+        assert_eq!(encoding("merged"), 0);
+        // the small set is a subset of the large one
+        let merged = client.handle_command(build_command(&["PFCOUNT", "merged"]));
+        let union = client.handle_command(build_command(&["PFCOUNT", "small", "large"]));
+        let large = client.handle_command(build_command(&["PFCOUNT", "large"]));
+        assert!(merged[0].eq(&union[0]) && union[0].eq(&large[0]));
+    }
+
+    #[test]
+    fn test_geo_commands() {
+        let (mut client, _ ,_ , _) = instantiate_client();
+
+        let res = client.handle_command(build_command(&["GEOADD", "Sicily", "13.361389", "38.115556", "Palermo", "15.087269", "37.502669", "Catania"]));
+        assert!(res[0].eq(b":2\r\n"));
+        let res = client.handle_command(build_command(&["GEOADD", "Sicily", "NX", "CH", "13.361389", "38.115556", "Palermo"]));
+        assert!(res[0].eq(b":0\r\n"));
+        let res = client.handle_command(build_command(&["GEOADD", "Sicily", "13.361389", "86", "Nowhere"]));
+        assert!(res[0].eq(b"-ERR invalid longitude,latitude pair 13.361389,86.000000\r\n"));
+        let res = client.handle_command(build_command(&["TYPE", "Sicily"]));
+        assert!(res[0].eq(b"+zset\r\n"));
+
+        let res = client.handle_command(build_command(&["GEODIST", "Sicily", "Palermo", "Catania"]));
+        assert!(res[0].eq(b"$11\r\n166274.1516\r\n"));
+        let res = client.handle_command(build_command(&["GEODIST", "Sicily", "Palermo", "Catania", "km"]));
+        assert!(res[0].eq(b"$8\r\n166.2742\r\n"));
+        let res = client.handle_command(build_command(&["GEODIST", "Sicily", "Palermo", "Agrigento"]));
+        assert!(res[0].eq(b"$-1\r\n"));
+        let res = client.handle_command(build_command(&["GEODIST", "Sicily", "Palermo", "Catania", "yd"]));
+        assert!(res[0].eq(b"-ERR unsupported unit provided. please use M, KM, FT, MI\r\n"));
+
+        let res = client.handle_command(build_command(&["GEOHASH", "Sicily", "Palermo", "Catania", "Agrigento"]));
+        assert!(res[0].eq(b"*3\r\n$11\r\nsqc8b49rny0\r\n$11\r\nsqdtr74hyu0\r\n$-1\r\n"));
+        let res = client.handle_command(build_command(&["GEOPOS", "Sicily", "Palermo", "Agrigento"]));
+        assert!(res[0].starts_with(b"*2\r\n*2\r\n$18\r\n13.36138933897018"));
+        assert!(res[0].ends_with(b"*-1\r\n"));
+    }
+
+    #[test]
+    fn test_geosearch_commands() {
+        let (mut client, cache ,_ , _) = instantiate_client();
+
+        client.handle_command(build_command(&["GEOADD", "Sicily", "13.361389", "38.115556", "Palermo", "15.087269", "37.502669", "Catania"]));
+        client.handle_command(build_command(&["GEOADD", "Sicily", "12.758489", "38.788135", "edge1", "17.241510", "38.788135", "edge2"]));
+
+        let res = client.handle_command(build_command(&["GEOSEARCH", "Sicily", "FROMLONLAT", "15", "37", "BYRADIUS", "200", "km", "ASC"]));
+        assert!(res[0].eq(b"*2\r\n$7\r\nCatania\r\n$7\r\nPalermo\r\n"));
+        let res = client.handle_command(build_command(&["GEOSEARCH", "Sicily", "FROMLONLAT", "15", "37", "BYBOX", "400", "400", "km", "ASC", "WITHDIST"]));
+        assert!(res[0].eq(b"*4\r\n*2\r\n$7\r\nCatania\r\n$7\r\n56.4413\r\n*2\r\n$7\r\nPalermo\r\n$8\r\n190.4424\r\n*2\r\n$5\r\nedge2\r\n$8\r\n279.7403\r\n*2\r\n$5\r\nedge1\r\n$8\r\n279.7405\r\n"));
+        let res = client.handle_command(build_command(&["GEOSEARCH", "Sicily", "FROMMEMBER", "Palermo", "BYRADIUS", "100", "km", "DESC", "WITHHASH"]));
+        assert!(res[0].eq(b"*2\r\n*2\r\n$5\r\nedge1\r\n:3479273021651468\r\n*2\r\n$7\r\nPalermo\r\n:3479099956230698\r\n"));
+        let res = client.handle_command(build_command(&["GEOSEARCH", "Sicily", "FROMLONLAT", "15", "37", "BYRADIUS", "500", "km", "COUNT", "1"]));
+        assert!(res[0].eq(b"*1\r\n$7\r\nCatania\r\n"));
+        let res = client.handle_command(build_command(&["GEOSEARCH", "Sicily", "FROMLONLAT", "15", "37", "BYRADIUS", "500", "km", "COUNT", "2", "ANY"]));
+        assert!(res[0].starts_with(b"*2\r\n"));
+        let res = client.handle_command(build_command(&["GEOSEARCH", "Sicily", "FROMMEMBER", "Agrigento", "BYRADIUS", "1", "m"]));
+        assert!(res[0].eq(b"-ERR could not decode requested zset member\r\n"));
+        let res = client.handle_command(build_command(&["GEOSEARCH", "Sicily", "BYRADIUS", "1", "m"]));
+        assert!(res[0].eq(b"-ERR exactly one of FROMMEMBER or FROMLONLAT can be specified for GEOSEARCH\r\n"));
+        let res = client.handle_command(build_command(&["GEOSEARCH", "Sicily", "FROMLONLAT", "15", "37", "BYRADIUS", "1", "m", "ANY"]));
+        assert!(res[0].eq(b"-ERR the ANY argument requires COUNT argument\r\n"));
+        let res = client.handle_command(build_command(&["GEOSEARCH", "missing", "FROMLONLAT", "15", "37", "BYRADIUS", "1", "m"]));
+        assert!(res[0].eq(b"*0\r\n"));
+
+        let res = client.handle_command(build_command(&["GEOSEARCHSTORE", "near", "Sicily", "FROMLONLAT", "15", "37", "BYRADIUS", "200", "km", "STOREDIST"]));
+        assert!(res[0].eq(b":2\r\n"));
+        match cache.lock().unwrap().get(b"near".as_slice()) {
+            Some(CacheVal::SortedSet(zset)) => assert!((zset.score(b"Catania").unwrap() - 56.4413).abs() < 0.0001),
+            _ => panic!("Incorrect cache type")
+        }
+        let res = client.handle_command(build_command(&["GEOSEARCHSTORE", "near", "Sicily", "FROMLONLAT", "15", "37", "BYRADIUS", "200", "km", "WITHDIST"]));
+        assert!(res[0].eq(b"-ERR syntax error\r\n"));
+        let res = client.handle_command(build_command(&["GEOSEARCHSTORE", "near", "Sicily", "FROMLONLAT", "0", "0", "BYRADIUS", "1", "km"]));
+        assert!(res[0].eq(b":0\r\n"));
+        assert!(!cache.lock().unwrap().contains_key(b"near".as_slice()));
+    }
+
+    #[test]
+    fn test_binary_values_round_trip() {
+        let (mut client, cache, _, _) = instantiate_client();
+        let key = b"img:\xff\x00\r\n".to_vec();
+        let value: Vec<u8> = (0..2048).map(|i| (i % 256) as u8).collect();
+        let cmd = RespType::Array(vec![RespType::String(b"SET".to_vec()), RespType::String(key.clone()), RespType::String(value.clone())]);
+
+        // goes through the parser so the payload has to survive framing, CRLFs and invalid UTF-8 included
+        let buffer = BytesMut::from(cmd.to_bytes().as_slice());
+        let (cmd, _) = RespType::parse(&buffer, 0).unwrap();
+        let res = client.handle_command(cmd);
+        assert!(res[0].eq(b"+OK\r\n"));
+        match cache.lock().unwrap().get(&key) {
+            Some(CacheVal::String(val)) => assert_eq!(val.val, value),
+            _ => panic!("Incorrect cache type")
+        }
+
+        let res = client.handle_command(RespType::Array(vec![RespType::String(b"GET".to_vec()), RespType::String(key.clone())]));
+        assert_eq!(res[0], create_bulk_string_resp(&value));
+        let res = client.handle_command(RespType::Array(vec![RespType::String(b"GETRANGE".to_vec()), RespType::String(key.clone()), RespType::String(b"254".to_vec()), RespType::String(b"257".to_vec())]));
+        assert!(res[0].eq(b"$4\r\n\xfe\xff\x00\x01\r\n"));
+        let res = client.handle_command(RespType::Array(vec![RespType::String(b"KEYS".to_vec()), RespType::String(b"*".to_vec())]));
+        assert_eq!(res[0], create_array_resp(vec![create_bulk_string_resp(&key)]));
+    }
+
+    #[test]
+    fn test_generic_key_commands() {
+        let (mut client, cache, write_commands, _) = instantiate_client();
+        client.handle_command(build_command(&["SET", "str", "v", "PX", "100000"]));
+        client.handle_command(build_command(&["RPUSH", "list", "a", "b"]));
+        client.handle_command(build_command(&["ZADD", "zset", "1", "m"]));
+        client.handle_command(build_command(&["SET", "gone", "v", "PX", "1"]));
+        std::thread::sleep(std::time::Duration::from_millis(5));
+
+        let res = client.handle_command(build_command(&["EXISTS", "str", "list", "gone", "missing", "str"]));
+        assert!(res[0].eq(b":3\r\n"));
+        let res = client.handle_command(build_command(&["DBSIZE"]));
+        assert!(res[0].eq(b":3\r\n"));
+
+        let res = client.handle_command(build_command(&["RENAME", "str", "renamed"]));
+        assert!(res[0].eq(b"+OK\r\n"));
+        assert!(cache.lock().unwrap().expiry(b"renamed").is_some());
+        let res = client.handle_command(build_command(&["RENAME", "str", "other"]));
+        assert!(res[0].eq(b"-ERR no such key\r\n"));
+        let res = client.handle_command(build_command(&["RENAMENX", "renamed", "list"]));
+        assert!(res[0].eq(b":0\r\n"));
+
+        let res = client.handle_command(build_command(&["COPY", "zset", "list"]));
+        assert!(res[0].eq(b":0\r\n"));
+        let res = client.handle_command(build_command(&["COPY", "zset", "list", "REPLACE"]));
+        assert!(res[0].eq(b":1\r\n"));
+        let res = client.handle_command(build_command(&["ZSCORE", "list", "m"]));
+        assert!(res[0].eq(b"$1\r\n1\r\n"));
+        let res = client.handle_command(build_command(&["COPY", "zset", "zset"]));
+        assert!(res[0].eq(b"-ERR source and destination objects are the same\r\n"));
+
+        let res = client.handle_command(build_command(&["DEL", "renamed", "list", "missing"]));
+        assert!(res[0].eq(b":2\r\n"));
+        let res = client.handle_command(build_command(&["UNLINK", "zset"]));
+        assert!(res[0].eq(b":1\r\n"));
+        let res = client.handle_command(build_command(&["RANDOMKEY"]));
+        assert!(res[0].eq(b"$-1\r\n"));
+        assert!(write_commands.lock().unwrap().last().unwrap().eq(b"*2\r\n$6\r\nUNLINK\r\n$4\r\nzset\r\n"));
+    }
+
+    #[test]
+    fn test_blocked_keys_stay_absent() {
+        let (mut client, cache, write_commands, channel_to_subscribers) = instantiate_client();
+        let mut client_two = Client::new(client.databases.clone(), write_commands.clone(), Arc::new(Mutex::new(0)), client.notifier.clone(), None, channel_to_subscribers.clone(), Arc::new(Mutex::new(HashMap::new())), "test_rdb_dir".to_string(), "test_rdb_file".to_string());
+
+        let waiter = thread::spawn(move || client.handle_command(build_command(&["BLPOP", "k", "5"])));
+        thread::sleep(std::time::Duration::from_millis(50));
+        let res = client_two.handle_command(build_command(&["EXISTS", "k"]));
+        assert!(res[0].eq(b":0\r\n"));
+        let res = client_two.handle_command(build_command(&["TYPE", "k"]));
+        assert!(res[0].eq(b"+none\r\n"));
+        let res = client_two.handle_command(build_command(&["HSET", "k", "f", "v"]));
+        assert!(res[0].eq(b":1\r\n"));
+        client_two.handle_command(build_command(&["DEL", "k"]));
+        let res = client_two.handle_command(build_command(&["XADD", "k", "1-1", "f", "v"]));
+        assert!(res[0].eq(b"$3\r\n1-1\r\n"));
+        // the waiter keeps waiting on a key overwritten with another type
+        client_two.handle_command(build_command(&["MSET", "k", "v"]));
+        let res = client_two.handle_command(build_command(&["LPUSH", "k", "a"]));
+        assert!(res[0].starts_with(b"-WRONGTYPE"));
+        let res = client_two.handle_command(build_command(&["XADD", "k", "*", "f", "v"]));
+        assert!(res[0].starts_with(b"-WRONGTYPE"));
+        client_two.handle_command(build_command(&["DEL", "k"]));
+        let res = client_two.handle_command(build_command(&["LPUSH", "k", "a"]));
+        assert!(res[0].eq(b":1\r\n"));
+        assert!(waiter.join().unwrap()[0].eq(b"*2\r\n$1\r\nk\r\n$1\r\na\r\n"));
+        assert!(cache.lock().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_expire_ignores_keys_only_waited_on() {
+        let (mut client, _, write_commands, channel_to_subscribers) = instantiate_client();
+        let mut client_two = Client::new(client.databases.clone(), write_commands.clone(), Arc::new(Mutex::new(0)), client.notifier.clone(), None, channel_to_subscribers.clone(), Arc::new(Mutex::new(HashMap::new())), "test_rdb_dir".to_string(), "test_rdb_file".to_string());
+
+        let waiter = thread::spawn(move || client.handle_command(build_command(&["BZPOPMIN", "k", "0.2"])));
+        thread::sleep(std::time::Duration::from_millis(50));
+        let res = client_two.handle_command(build_command(&["EXPIRE", "k", "100"]));
+        assert!(res[0].eq(b":0\r\n"));
+        let res = client_two.handle_command(build_command(&["TTL", "k"]));
+        assert!(res[0].eq(b":-2\r\n"));
+        let res = client_two.handle_command(build_command(&["PERSIST", "k"]));
+        assert!(res[0].eq(b":0\r\n"));
+        assert!(waiter.join().unwrap()[0].eq(b"*-1\r\n"));
+    }
+
+    #[test]
+    fn test_expire_any_type() {
+        let (mut client, cache, write_commands, _) = instantiate_client();
+        client.handle_command(build_command(&["RPUSH", "list", "a"]));
+
+        let res = client.handle_command(build_command(&["TTL", "list"]));
+        assert!(res[0].eq(b":-1\r\n"));
+        let res = client.handle_command(build_command(&["EXPIRE", "list", "100", "GT"]));
+        assert!(res[0].eq(b":0\r\n"));
+        let res = client.handle_command(build_command(&["EXPIRE", "list", "100", "NX"]));
+        assert!(res[0].eq(b":1\r\n"));
+        let expiry_time = cache.lock().unwrap().expiry(b"list").unwrap();
+        let replicated = format!("*3\r\n$9\r\nPEXPIREAT\r\n$4\r\nlist\r\n{}", String::from_utf8_lossy(&create_bulk_string_resp(expiry_time.to_string())));
+        assert!(write_commands.lock().unwrap().last().unwrap().eq(replicated.as_bytes()));
+        let res = client.handle_command(build_command(&["TTL", "list"]));
+        assert!(res[0].eq(b":100\r\n"));
+        let res = client.handle_command(build_command(&["PEXPIRETIME", "list"]));
+        assert_eq!(res[0], create_int_resp(expiry_time));
+        let res = client.handle_command(build_command(&["PEXPIRE", "list", "200000", "LT"]));
+        assert!(res[0].eq(b":0\r\n"));
+        let res = client.handle_command(build_command(&["EXPIRE", "list", "10", "NX", "LT"]));
+        assert!(res[0].eq(b"-ERR NX and XX, GT or LT options at the same time are not compatible\r\n"));
+        let res = client.handle_command(build_command(&["EXPIRE", "list", "9223372036854775807"]));
+        assert!(res[0].eq(b"-ERR invalid expire time in 'expire' command\r\n"));
+
+        let res = client.handle_command(build_command(&["PERSIST", "list"]));
+        assert!(res[0].eq(b":1\r\n"));
+        let res = client.handle_command(build_command(&["PTTL", "list"]));
+        assert!(res[0].eq(b":-1\r\n"));
+
+        let res = client.handle_command(build_command(&["PEXPIRE", "list", "1"]));
+        assert!(res[0].eq(b":1\r\n"));
+        std::thread::sleep(std::time::Duration::from_millis(5));
+        let res = client.handle_command(build_command(&["LRANGE", "list", "0", "-1"]));
+        assert!(res[0].eq(b"*0\r\n"));
+        let res = client.handle_command(build_command(&["TTL", "list"]));
+        assert!(res[0].eq(b":-2\r\n"));
+
+        client.handle_command(build_command(&["SADD", "set", "m"]));
+        let res = client.handle_command(build_command(&["EXPIREAT", "set", "1"]));
+        assert!(res[0].eq(b":1\r\n"));
+        assert!(write_commands.lock().unwrap().last().unwrap().eq(b"*2\r\n$3\r\nDEL\r\n$3\r\nset\r\n"));
+        let res = client.handle_command(build_command(&["EXISTS", "set"]));
+        assert!(res[0].eq(b":0\r\n"));
+    }
+
+    #[test]
+    fn test_propagated_writes_only_expire_their_keys() {
+        let (mut client, cache, write_commands, _) = instantiate_client();
+        *cache.lock().unwrap() = Keyspace::with_expire_mode(ExpireMode::Master(ReplicationLog::new(write_commands.clone(), 0)));
+        client.handle_command(build_command(&["SET", "b", "v", "PX", "1"]));
+        client.handle_command(build_command(&["SET", "dst", "v", "PX", "1"]));
+        std::thread::sleep(std::time::Duration::from_millis(5));
+        write_commands.lock().unwrap().clear();
+
+        // values and fields that name an expired key leave it alone
+        client.handle_command(build_command(&["RPUSH", "list", "b"]));
+        client.handle_command(build_command(&["HSET", "hash", "dst", "b"]));
+        client.handle_command(build_command(&["MSET", "k", "b"]));
+        assert_eq!(write_commands.lock().unwrap().len(), 3);
+        assert!(!write_commands.lock().unwrap().iter().any(|command| command.starts_with(b"*2\r\n$3\r\nDEL\r\n")));
+
+        // the DEL of an expired key reaches the replicas ahead of the write to it
+        client.handle_command(build_command(&["SUNIONSTORE", "dst", "missing"]));
+        let propagated = write_commands.lock().unwrap();
+        assert!(propagated[3].eq(b"*2\r\n$3\r\nDEL\r\n$3\r\ndst\r\n"));
+        assert!(propagated[4].starts_with(b"*3\r\n$11\r\nSUNIONSTORE\r\n"));
+    }
+
+    #[test]
+    fn test_keys_pattern_and_scan() {
+        let (mut client, _, _, _) = instantiate_client();
+        for i in 0..50 {
+            client.handle_command(build_command(&["SET", &format!("user:{}", i), "v"]));
+        }
+        client.handle_command(build_command(&["RPUSH", "user:list", "a"]));
+        client.handle_command(build_command(&["HSET", "hash", "f1", "v1", "f2", "v2"]));
+        client.handle_command(build_command(&["ZADD", "zset", "1.5", "m"]));
+
+        let res = client.handle_command(build_command(&["KEYS", "user:4?"]));
+        let (keys, _) = RespType::parse(&BytesMut::from(res[0].as_slice()), 0).unwrap();
+        match keys {
+            RespType::Array(keys) => assert_eq!(keys.len(), 10),
+            _ => panic!("Expected an array")
+        }
+        let res = client.handle_command(build_command(&["KEYS", "[hz]*"]));
+        assert!(res[0].eq(b"*2\r\n$4\r\nhash\r\n$4\r\nzset\r\n") || res[0].eq(b"*2\r\n$4\r\nzset\r\n$4\r\nhash\r\n"));
+
+        // keys added halfway through don't make the scan skip the others
+        let mut seen = HashSet::new();
+        let mut cursor = "0".to_string();
+        let mut calls = 0;
+        loop {
+            let res = client.handle_command(build_command(&["SCAN", &cursor, "COUNT", "7", "MATCH", "user:*", "TYPE", "string"]));
+            let (reply, _) = RespType::parse(&BytesMut::from(res[0].as_slice()), 0).unwrap();
+            let (next, keys) = match reply {
+                RespType::Array(mut parts) => match (parts.remove(0), parts.remove(0)) {
+                    (RespType::String(next), RespType::Array(keys)) => (next, keys),
+                    _ => panic!("Expected a cursor and keys")
+                },
+                _ => panic!("Expected an array")
+            };
+            for key in keys {
+                if let RespType::String(key) = key {
+                    seen.insert(key);
+                }
+            }
+            calls += 1;
+            if calls == 3 {
+                for i in 0..200 {
+                    client.handle_command(build_command(&["SET", &format!("other:{}", i), "v"]));
+                }
+            }
+            cursor = String::from_utf8(next).unwrap();
+            if cursor == "0" {
+                break;
+            }
+        }
+        assert_eq!(seen.len(), 50);
+        assert!(calls > 3);
+
+        let res = client.handle_command(build_command(&["HSCAN", "hash", "0", "MATCH", "f1"]));
+        assert!(res[0].eq(b"*2\r\n$1\r\n0\r\n*2\r\n$2\r\nf1\r\n$2\r\nv1\r\n"));
+        let res = client.handle_command(build_command(&["ZSCAN", "zset", "0"]));
+        assert!(res[0].eq(b"*2\r\n$1\r\n0\r\n*2\r\n$1\r\nm\r\n$3\r\n1.5\r\n"));
+        // collections page through their own slot index as members come and go
+        for i in 0..30 {
+            client.handle_command(build_command(&["SADD", "members", &i.to_string()]));
+        }
+        client.handle_command(build_command(&["SREM", "members", "0", "1", "2"]));
+        client.handle_command(build_command(&["SUNIONSTORE", "stored", "members"]));
+        let mut seen = HashSet::new();
+        let mut cursor = "0".to_string();
+        loop {
+            let res = client.handle_command(build_command(&["SSCAN", "stored", &cursor, "COUNT", "5"]));
+            let (reply, _) = RespType::parse(&BytesMut::from(res[0].as_slice()), 0).unwrap();
+            let (next, members) = match reply {
+                RespType::Array(mut parts) => match (parts.remove(0), parts.remove(0)) {
+                    (RespType::String(next), RespType::Array(members)) => (next, members),
+                    _ => panic!("Expected a cursor and members")
+                },
+                _ => panic!("Expected an array")
+            };
+            assert!(members.len() < 10);
+            for member in members {
+                if let RespType::String(member) = member {
+                    seen.insert(member);
+                }
+            }
+            cursor = String::from_utf8(next).unwrap();
+            if cursor == "0" {
+                break;
+            }
+        }
+        assert_eq!(seen.len(), 27);
+        assert!(!seen.contains(b"0".as_slice()));
+
+        let res = client.handle_command(build_command(&["SSCAN", "hash", "0"]));
+        assert!(res[0].eq(b"-WRONGTYPE Operation against a key holding the wrong kind of value\r\n"));
+        let res = client.handle_command(build_command(&["SCAN", "abc"]));
+        assert!(res[0].eq(b"-ERR invalid cursor\r\n"));
+        let res = client.handle_command(build_command(&["HSCAN", "hash", "0", "TYPE", "hash"]));
+        assert!(res[0].eq(b"-ERR syntax error\r\n"));
+    }
+
+    #[test]
+    fn test_multiple_databases() {
+        let (mut client, cache, write_commands, _) = instantiate_client();
+        client.handle_command(build_command(&["SET", "key", "zero"]));
+        let res = client.handle_command(build_command(&["SELECT", "1"]));
+        assert!(res[0].eq(b"+OK\r\n"));
+        let res = client.handle_command(build_command(&["GET", "key"]));
+        assert!(res[0].eq(b"$-1\r\n"));
+        client.handle_command(build_command(&["SET", "key", "one", "PX", "100000"]));
+        // the replicas are told to switch dbs before the write
+        let propagated: Vec<Vec<u8>> = write_commands.lock().unwrap().iter().skip(1).cloned().collect();
+        assert!(propagated[0].eq(b"*2\r\n$6\r\nSELECT\r\n$1\r\n1\r\n"));
+        assert!(propagated[1].starts_with(b"*5\r\n$3\r\nSET\r\n$3\r\nkey\r\n$3\r\none\r\n"));
+        assert_eq!(propagated.len(), 2);
+
+        let res = client.handle_command(build_command(&["SELECT", "16"]));
+        assert!(res[0].eq(b"-ERR DB index is out of range\r\n"));
+        let res = client.handle_command(build_command(&["MOVE", "key", "0"]));
+        assert!(res[0].eq(b":0\r\n"));
+        let res = client.handle_command(build_command(&["MOVE", "key", "2"]));
+        assert!(res[0].eq(b":1\r\n"));
+        let res = client.handle_command(build_command(&["COPY", "key", "copied", "DB", "1"]));
+        assert!(res[0].eq(b":0\r\n"));
+
+        // SWAPDB changes what connections on either db see
+        client.handle_command(build_command(&["SWAPDB", "0", "2"]));
+        assert!(cache.lock().unwrap().expiry(b"key").is_some());
+        client.handle_command(build_command(&["SELECT", "0"]));
+        let res = client.handle_command(build_command(&["GET", "key"]));
+        assert!(res[0].eq(b"$3\r\none\r\n"));
+        let res = client.handle_command(build_command(&["COPY", "key", "copied", "DB", "3"]));
+        assert!(res[0].eq(b":1\r\n"));
+        let res = client.handle_command(build_command(&["PTTL", "key"]));
+        assert!(!res[0].starts_with(b":-"));
+
+        let res = client.handle_command(build_command(&["FLUSHDB", "ASYNC"]));
+        assert!(res[0].eq(b"+OK\r\n"));
+        let res = client.handle_command(build_command(&["DBSIZE"]));
+        assert!(res[0].eq(b":0\r\n"));
+        client.handle_command(build_command(&["SELECT", "2"]));
+        let res = client.handle_command(build_command(&["GET", "key"]));
+        assert!(res[0].eq(b"$4\r\nzero\r\n"));
+        client.handle_command(build_command(&["FLUSHALL"]));
+        for db in ["2", "3"] {
+            client.handle_command(build_command(&["SELECT", db]));
+            let res = client.handle_command(build_command(&["DBSIZE"]));
+            assert!(res[0].eq(b":0\r\n"));
+        }
+        let res = client.handle_command(build_command(&["CONFIG", "GET", "databases"]));
+        assert!(res[0].eq(b"*2\r\n$9\r\ndatabases\r\n$2\r\n16\r\n"));
+    }
+
+    #[test]
+    fn test_hrandfield_extreme_counts() {
+        let (mut client, _, _, _) = instantiate_client();
+        client.handle_command(build_command(&["HSET", "h", "a", "1", "b", "2", "c", "3"]));
+
+        let res = client.handle_command(build_command(&["HRANDFIELD", "h", &i64::MIN.to_string()]));
+        assert!(res[0].starts_with(b"-ERR value is out of range"));
+        let res = client.handle_command(build_command(&["HRANDFIELD", "h", &(-MAX_RANDOM_REPEATS - 1).to_string(), "WITHVALUES"]));
+        assert!(res[0].starts_with(b"-ERR value is out of range"));
+        // positive counts never return more than the whole hash
+        let res = client.handle_command(build_command(&["HRANDFIELD", "h", &i64::MAX.to_string()]));
+        assert!(res[0].starts_with(b"*3\r\n"));
+        let res = client.handle_command(build_command(&["HRANDFIELD", "h", "-5", "WITHVALUES"]));
+        assert!(res[0].starts_with(b"*10\r\n"));
+        let res = client.handle_command(build_command(&["HRANDFIELD", "h", &(-MAX_RANDOM_REPEATS).to_string()]));
+        assert!(res[0].starts_with(format!("*{}\r\n", MAX_RANDOM_REPEATS).as_bytes()));
+    }
+
+    #[test]
+    fn test_float_reply_formats() {
+        let (mut client, _, _, _) = instantiate_client();
+        // increments never use exponent notation, scores do like %.17g
+        let res = client.handle_command(build_command(&["HINCRBYFLOAT", "h", "f", "1e20"]));
+        assert!(res[0].eq(b"$21\r\n100000000000000000000\r\n"));
+        let res = client.handle_command(build_command(&["INCRBYFLOAT", "n", "0.00001"]));
+        assert!(res[0].eq(b"$7\r\n0.00001\r\n"));
+        client.handle_command(build_command(&["ZADD", "z", "0.00001", "small", "1e16", "big"]));
+        let res = client.handle_command(build_command(&["ZSCORE", "z", "small"]));
+        assert!(res[0].eq(b"$5\r\n1e-05\r\n"));
+        let res = client.handle_command(build_command(&["ZSCORE", "z", "big"]));
+        assert!(res[0].eq(b"$17\r\n10000000000000000\r\n"));
+    }
+
+    #[test]
+    fn test_spop_srandmember_extreme_counts() {
+        let (mut client, _, _, _) = instantiate_client();
+        client.handle_command(build_command(&["SADD", "s", "a", "b", "c"]));
+
+        let res = client.handle_command(build_command(&["SRANDMEMBER", "s", &i64::MIN.to_string()]));
+        assert!(res[0].starts_with(b"-ERR value is out of range"));
+        let res = client.handle_command(build_command(&["SRANDMEMBER", "s", &(-MAX_RANDOM_REPEATS - 1).to_string()]));
+        assert!(res[0].starts_with(b"-ERR value is out of range"));
+        let res = client.handle_command(build_command(&["SRANDMEMBER", "s", "-7"]));
+        assert!(res[0].starts_with(b"*7\r\n"));
+        let res = client.handle_command(build_command(&["SRANDMEMBER", "s", &i64::MAX.to_string()]));
+        assert!(res[0].starts_with(b"*3\r\n"));
+        // a count beyond the set pops all of it
+        let res = client.handle_command(build_command(&["SPOP", "s", &i64::MAX.to_string()]));
+        assert!(res[0].starts_with(b"*3\r\n"));
+        let res = client.handle_command(build_command(&["EXISTS", "s"]));
+        assert!(res[0].eq(b":0\r\n"));
+    }
+
+    #[test]
+    fn test_zstore_wakes_blocked_clients() {
+        let (mut client, _, write_commands, channel_to_subscribers) = instantiate_client();
+        let mut client_two = Client::new(client.databases.clone(), write_commands.clone(), Arc::new(Mutex::new(0)), client.notifier.clone(), None, channel_to_subscribers.clone(), Arc::new(Mutex::new(HashMap::new())), "test_rdb_dir".to_string(), "test_rdb_file".to_string());
+        client.handle_command(build_command(&["ZADD", "src", "1", "a"]));
+
+        let handle = thread::spawn(move || {
+            thread::sleep(std::time::Duration::from_millis(50));
+            client_two.handle_command(build_command(&["ZUNIONSTORE", "dst", "1", "src"]));
+        });
+        let started = std::time::Instant::now();
+        let res = client.handle_command(build_command(&["BZPOPMIN", "dst", "3"]));
+        assert!(res[0].eq(b"*3\r\n$3\r\ndst\r\n$1\r\na\r\n$1\r\n1\r\n"));
+        assert!(started.elapsed() < std::time::Duration::from_millis(2000));
+        handle.join().unwrap();
+    }
+
+    #[test]
+    fn test_geosearchstore_wakes_blocked_clients() {
+        let (mut client, _, write_commands, channel_to_subscribers) = instantiate_client();
+        let mut client_two = Client::new(client.databases.clone(), write_commands.clone(), Arc::new(Mutex::new(0)), client.notifier.clone(), None, channel_to_subscribers.clone(), Arc::new(Mutex::new(HashMap::new())), "test_rdb_dir".to_string(), "test_rdb_file".to_string());
+        client.handle_command(build_command(&["GEOADD", "places", "13.361389", "38.115556", "Palermo"]));
+
+        let handle = thread::spawn(move || {
+            thread::sleep(std::time::Duration::from_millis(50));
+            client_two.handle_command(build_command(&["GEOSEARCHSTORE", "nearby", "places", "FROMLONLAT", "13", "38", "BYRADIUS", "200", "km"]));
+        });
+        let started = std::time::Instant::now();
+        let res = client.handle_command(build_command(&["BZPOPMIN", "nearby", "3"]));
+        assert!(res[0].starts_with(b"*3\r\n$6\r\nnearby\r\n$7\r\nPalermo\r\n"));
+        assert!(started.elapsed() < std::time::Duration::from_millis(2000));
+        handle.join().unwrap();
     }
 }
@@ -0,0 +1,259 @@
+use std::collections::HashSet;
+
+use crate::redis::sorted_set::{ScoreBound, SortedSetCacheVal};
+
+/// Geo members are sorted set members scored by their 52 bit geohash, the latitude and longitude
+/// cell indexes interleaved at 26 bits each, latitudes being limited to what mercator covers.
+const GEO_STEP_MAX: u32 = 26;
+const GEO_LAT_MIN: f64 = -85.05112878;
+const GEO_LAT_MAX: f64 = 85.05112878;
+const GEO_LON_MIN: f64 = -180.0;
+const GEO_LON_MAX: f64 = 180.0;
+const EARTH_RADIUS_IN_METERS: f64 = 6372797.560856;
+const MERCATOR_MAX: f64 = 20037726.37;
+const GEOHASH_ALPHABET: &[u8] = b"0123456789bcdefghjkmnpqrstuvwxyz";
+
+/// The area GEOSEARCH looks in, in meters.
+#[derive(Clone, Copy)]
+pub enum GeoShape {
+    Radius(f64),
+    Box { width: f64, height: f64 }
+}
+
+pub struct GeoMatch {
+    pub member: Vec<u8>,
+    pub score: f64,
+    pub dist: f64,
+    pub lon: f64,
+    pub lat: f64
+}
+
+/// Meters per unit, for the `m|km|ft|mi` argument of GEODIST and GEOSEARCH.
+pub fn parse_unit(arg: &[u8]) -> Option<f64> {
+    match arg.to_ascii_lowercase().as_slice() {
+        b"m" => Some(1.0),
+        b"km" => Some(1000.0),
+        b"ft" => Some(0.3048),
+        b"mi" => Some(1609.34),
+        _ => None
+    }
+}
+
+pub fn is_valid_lon_lat(lon: f64, lat: f64) -> bool {
+    (GEO_LON_MIN..=GEO_LON_MAX).contains(&lon) && (GEO_LAT_MIN..=GEO_LAT_MAX).contains(&lat)
+}
+
+fn interleave(lat_index: u64, lon_index: u64) -> u64 {
+    (0..GEO_STEP_MAX).fold(0, |bits, bit| bits | ((lat_index >> bit) & 1) << (2 * bit) | ((lon_index >> bit) & 1) << (2 * bit + 1))
+}
+
+fn deinterleave(bits: u64) -> (u64, u64) {
+    (0..GEO_STEP_MAX).fold((0, 0), |(lat_index, lon_index), bit| (lat_index | ((bits >> (2 * bit)) & 1) << bit, lon_index | ((bits >> (2 * bit + 1)) & 1) << bit))
+}
+
+/// The index of the cell `val` falls in when `[min, max]` is cut in `2^step` cells.
+fn cell_index(val: f64, min: f64, max: f64, step: u32) -> u64 {
+    let cells = 1u64 << step;
+    (((val - min) / (max - min) * cells as f64) as u64).min(cells - 1)
+}
+
+pub fn encode(lon: f64, lat: f64) -> u64 {
+    interleave(cell_index(lat, GEO_LAT_MIN, GEO_LAT_MAX, GEO_STEP_MAX), cell_index(lon, GEO_LON_MIN, GEO_LON_MAX, GEO_STEP_MAX))
+}
+
+/// The center of the cell a score stands for.
+pub fn decode(score: f64) -> (f64, f64) {
+    let (lat_index, lon_index) = deinterleave(score as u64);
+    let cells = (1u64 << GEO_STEP_MAX) as f64;
+    let lat_size = (GEO_LAT_MAX - GEO_LAT_MIN) / cells;
+    let lon_size = (GEO_LON_MAX - GEO_LON_MIN) / cells;
+    let lon = GEO_LON_MIN + (lon_index as f64 + 0.5) * lon_size;
+    let lat = GEO_LAT_MIN + (lat_index as f64 + 0.5) * lat_size;
+    (lon.clamp(GEO_LON_MIN, GEO_LON_MAX), lat.clamp(GEO_LAT_MIN, GEO_LAT_MAX))
+}
+
+/// The standard 11 character geohash, which unlike the scores spans latitudes -90 to 90.
+pub fn geohash_string(score: f64) -> String {
+    let (lon, lat) = decode(score);
+    let bits = interleave(cell_index(lat, -90.0, 90.0, GEO_STEP_MAX), cell_index(lon, GEO_LON_MIN, GEO_LON_MAX, GEO_STEP_MAX));
+    (0..11).map(|i| {
+        // 52 bits only make 10 and a half characters, the last one is padded
+        let index = if i == 10 { 0 } else { (bits >> (52 - (i + 1) * 5)) & 0x1f };
+        GEOHASH_ALPHABET[index as usize] as char
+    }).collect()
+}
+
+/// The haversine distance in meters.
+pub fn distance(lon1: f64, lat1: f64, lon2: f64, lat2: f64) -> f64 {
+    let (lat1, lat2) = (lat1.to_radians(), lat2.to_radians());
+    let u = ((lat2 - lat1) / 2.0).sin();
+    let v = ((lon2.to_radians() - lon1.to_radians()) / 2.0).sin();
+    2.0 * EARTH_RADIUS_IN_METERS * (u * u + lat1.cos() * lat2.cos() * v * v).sqrt().asin()
+}
+
+impl GeoShape {
+    /// The distance from the center to a point inside the shape, `None` when it is outside.
+    fn distance_if_inside(&self, lon: f64, lat: f64, point_lon: f64, point_lat: f64) -> Option<f64> {
+        let dist = distance(lon, lat, point_lon, point_lat);
+        match self {
+            GeoShape::Radius(radius) => (dist <= *radius).then_some(dist),
+            GeoShape::Box { width, height } => {
+                let lat_dist = EARTH_RADIUS_IN_METERS * (point_lat.to_radians() - lat.to_radians()).abs();
+                let lon_dist = distance(point_lon, point_lat, lon, point_lat);
+                (lat_dist <= height / 2.0 && lon_dist <= width / 2.0).then_some(dist)
+            }
+        }
+    }
+
+    /// The `(min_lon, min_lat, max_lon, max_lat)` box around the shape, longitudes possibly past
+    /// the antimeridian.
+    fn bounding_box(&self, lon: f64, lat: f64) -> (f64, f64, f64, f64) {
+        let half_height = match self {
+            GeoShape::Radius(radius) => *radius,
+            GeoShape::Box { height, .. } => height / 2.0
+        };
+        let lat_delta = (half_height / EARTH_RADIUS_IN_METERS).to_degrees();
+        let (min_lat, max_lat) = (lat - lat_delta, lat + lat_delta);
+        // how far the longitude can stray for a great circle distance, or every longitude once
+        // the shape reaches a pole
+        let sin_ratio = match self {
+            _ if min_lat <= -90.0 || max_lat >= 90.0 => None,
+            GeoShape::Radius(radius) => Some((radius / EARTH_RADIUS_IN_METERS).sin() / lat.to_radians().cos()),
+            GeoShape::Box { width, .. } => {
+                let half_angle = width / 4.0 / EARTH_RADIUS_IN_METERS;
+                (half_angle < std::f64::consts::FRAC_PI_2).then(|| half_angle.sin() / min_lat.abs().max(max_lat.abs()).to_radians().cos())
+            }
+        };
+        let lon_delta = match (self, sin_ratio) {
+            (GeoShape::Radius(_), Some(ratio)) if ratio < 1.0 => ratio.asin().to_degrees(),
+            (GeoShape::Box { .. }, Some(ratio)) if ratio < 1.0 => 2.0 * ratio.asin().to_degrees(),
+            _ => GEO_LON_MAX
+        };
+        (lon - lon_delta, min_lat, lon + lon_delta, max_lat)
+    }
+
+    /// The longest distance from the center to the edge of the shape.
+    fn radius(&self) -> f64 {
+        match self {
+            GeoShape::Radius(radius) => *radius,
+            GeoShape::Box { width, height } => (width / 2.0).hypot(height / 2.0)
+        }
+    }
+}
+
+/// The coarsest step whose cells are still about as wide as the radius, so that the cell of the
+/// center and its neighbors cover the whole shape.
+fn estimate_step(radius: f64, lat: f64) -> u32 {
+    if radius == 0.0 {
+        return GEO_STEP_MAX;
+    }
+    let mut radius = radius;
+    let mut step: i32 = 1;
+    while radius < MERCATOR_MAX {
+        radius *= 2.0;
+        step += 1;
+    }
+    step -= 2;
+    // cells get narrower towards the poles
+    if !(-66.0..=66.0).contains(&lat) {
+        step -= 1;
+        if !(-80.0..=80.0).contains(&lat) {
+            step -= 1;
+        }
+    }
+    step.clamp(1, GEO_STEP_MAX as i32) as u32
+}
+
+/// Whether the cell at `(lon_index, lat_index)` and its neighbors cover the bounding box.
+fn covers(step: u32, lon_index: u64, lat_index: u64, bounding_box: (f64, f64, f64, f64)) -> bool {
+    let cells = (1u64 << step) as f64;
+    let lon_size = (GEO_LON_MAX - GEO_LON_MIN) / cells;
+    let lat_size = (GEO_LAT_MAX - GEO_LAT_MIN) / cells;
+    let (min_lon, min_lat, max_lon, max_lat) = bounding_box;
+    let covered_min_lon = GEO_LON_MIN + (lon_index as f64 - 1.0) * lon_size;
+    let covered_min_lat = GEO_LAT_MIN + (lat_index as f64 - 1.0) * lat_size;
+    (3.0 * lon_size >= GEO_LON_MAX - GEO_LON_MIN || (covered_min_lon <= min_lon && covered_min_lon + 3.0 * lon_size >= max_lon))
+        && (covered_min_lat <= min_lat.max(GEO_LAT_MIN) && covered_min_lat + 3.0 * lat_size >= max_lat.min(GEO_LAT_MAX))
+}
+
+/// Finds the members inside the shape centered on `(lon, lat)` by only scanning the score ranges
+/// of the cell the center falls in and its eight neighbors. Stops at `limit` matches, in no
+/// particular order.
+pub fn search(zset: &SortedSetCacheVal, lon: f64, lat: f64, shape: GeoShape, limit: Option<usize>) -> Vec<GeoMatch> {
+    let bounding_box = shape.bounding_box(lon, lat);
+    let mut step = estimate_step(shape.radius(), lat);
+    let (mut lon_index, mut lat_index);
+    loop {
+        lon_index = cell_index(lon, GEO_LON_MIN, GEO_LON_MAX, step);
+        lat_index = cell_index(lat, GEO_LAT_MIN, GEO_LAT_MAX, step);
+        if step == 1 || covers(step, lon_index, lat_index, bounding_box) {
+            break;
+        }
+        step -= 1;
+    }
+
+    let cells = 1i64 << step;
+    let mut ranges = HashSet::new();
+    for lat_offset in -1..=1 {
+        let neighbor_lat = lat_index as i64 + lat_offset;
+        if !(0..cells).contains(&neighbor_lat) {
+            continue;
+        }
+        for lon_offset in -1..=1 {
+            let neighbor_lon = (lon_index as i64 + lon_offset).rem_euclid(cells);
+            let bits = interleave(neighbor_lat as u64, neighbor_lon as u64);
+            let shift = 2 * (GEO_STEP_MAX - step);
+            ranges.insert((bits << shift, (bits + 1) << shift));
+        }
+    }
+    let mut ranges: Vec<(u64, u64)> = ranges.into_iter().collect();
+    ranges.sort();
+
+    let mut matches = vec![];
+    for (min, max) in ranges {
+        let (start, end) = zset.score_range(&ScoreBound { value: min as f64, exclusive: false }, &ScoreBound { value: max as f64, exclusive: true });
+        for (score, member) in zset.iter_from(start).take(end - start) {
+            let (point_lon, point_lat) = decode(score);
+            if let Some(dist) = shape.distance_if_inside(lon, lat, point_lon, point_lat) {
+                matches.push(GeoMatch { member: member.clone(), score, dist, lon: point_lon, lat: point_lat });
+                if limit.is_some_and(|limit| matches.len() >= limit) {
+                    return matches;
+                }
+            }
+        }
+    }
+    matches
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_search_matches_full_scan() {
+        let mut zset = SortedSetCacheVal::new();
+        for lon in (-180..180).step_by(7) {
+            for lat in (-84..=84).step_by(4) {
+                zset.insert(format!("{},{}", lon, lat).into_bytes(), encode(lon as f64, lat as f64) as f64);
+            }
+        }
+        // near the antimeridian, the poles and the equator, from a few meters to half the globe
+        for (lon, lat) in [(179.5, 0.3), (-179.9, -41.0), (12.5, 84.9), (151.5, 68.5), (-3.7, 40.4)] {
+            for size in [100.0, 150_000.0, 900_000.0, 2_400_000.0, 9_000_000.0] {
+                for shape in [GeoShape::Radius(size), GeoShape::Box { width: size, height: size / 3.0 }, GeoShape::Box { width: size / 3.0, height: size }] {
+                    let mut found: Vec<Vec<u8>> = search(&zset, lon, lat, shape, None).into_iter().map(|geo_match| geo_match.member).collect();
+                    let mut expected: Vec<Vec<u8>> = zset.iter_from(0)
+                        .filter(|(score, _)| {
+                            let (point_lon, point_lat) = decode(*score);
+                            shape.distance_if_inside(lon, lat, point_lon, point_lat).is_some()
+                        })
+                        .map(|(_, member)| member.clone())
+                        .collect();
+                    found.sort();
+                    expected.sort();
+                    assert_eq!(found, expected);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,94 @@
+/// Matches `string` against a redis glob `pattern`: `*` matches any run of bytes, `?` a single
+/// byte, `[abc]`, `[a-z]` and `[^x]` classes and `\` escapes the next byte.
+pub fn glob_match(pattern: &[u8], string: &[u8]) -> bool {
+    let (mut p, mut s) = (0, 0);
+    // where to resume after the last `*` when what followed it failed to match
+    let mut backtrack: Option<(usize, usize)> = None;
+    while s < string.len() {
+        let matched = match pattern.get(p) {
+            Some(b'*') => {
+                backtrack = Some((p, s));
+                p += 1;
+                continue;
+            },
+            Some(b'?') => Some(p + 1),
+            Some(b'[') => match_class(pattern, p, string[s]),
+            Some(b'\\') if p + 1 < pattern.len() => (pattern[p + 1] == string[s]).then_some(p + 2),
+            Some(c) => (*c == string[s]).then_some(p + 1),
+            None => None
+        };
+        match (matched, backtrack) {
+            (Some(next), _) => {
+                p = next;
+                s += 1;
+            },
+            // let the `*` swallow one more byte
+            (None, Some((star, star_s))) => {
+                backtrack = Some((star, star_s + 1));
+                p = star + 1;
+                s = star_s + 1;
+            },
+            (None, None) => return false
+        }
+    }
+    pattern[p.min(pattern.len())..].iter().all(|c| *c == b'*')
+}
+
+/// Matches `c` against the class opening at `pattern[start]`, returning where the pattern goes on
+/// after it. An unterminated class runs to the end of the pattern.
+fn match_class(pattern: &[u8], start: usize, c: u8) -> Option<usize> {
+    let mut p = start + 1;
+    let negate = pattern.get(p) == Some(&b'^');
+    if negate {
+        p += 1;
+    }
+    let mut matched = false;
+    while p < pattern.len() && pattern[p] != b']' {
+        if pattern[p] == b'\\' && p + 1 < pattern.len() {
+            matched |= pattern[p + 1] == c;
+            p += 2;
+        } else if p + 2 < pattern.len() && pattern[p + 1] == b'-' && pattern[p + 2] != b']' {
+            let (low, high) = (pattern[p].min(pattern[p + 2]), pattern[p].max(pattern[p + 2]));
+            matched |= (low..=high).contains(&c);
+            p += 3;
+        } else {
+            matched |= pattern[p] == c;
+            p += 1;
+        }
+    }
+    (matched != negate).then_some((p + 1).min(pattern.len()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_glob_match() {
+        let cases: [(&str, &str, bool); 20] = [
+            ("*", "", true),
+            ("*", "anything", true),
+            ("h?llo", "hello", true),
+            ("h?llo", "hllo", false),
+            ("h*llo", "heeeello", true),
+            ("h*llo", "hello world", false),
+            ("h[ae]llo", "hallo", true),
+            ("h[ae]llo", "hillo", false),
+            ("h[^e]llo", "hallo", true),
+            ("h[^e]llo", "hello", false),
+            ("h[a-b]llo", "hbllo", true),
+            ("h[b-a]llo", "hallo", true),
+            ("h[a-b]llo", "hcllo", false),
+            ("user:\\*", "user:*", true),
+            ("user:\\*", "user:1", false),
+            ("[\\]]", "]", true),
+            ("*:*:end", "a:b:c:end", true),
+            ("a*b*c", "abcabd", false),
+            ("**x", "yyx", true),
+            ("key[", "key", false)
+        ];
+        for (pattern, string, expected) in cases {
+            assert_eq!(glob_match(pattern.as_bytes(), string.as_bytes()), expected, "{} against {}", pattern, string);
+        }
+    }
+}
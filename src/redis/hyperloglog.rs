@@ -0,0 +1,276 @@
+use crate::resp::create_basic_err_resp;
+
+/// 2^14 registers of 6 bits each, with the layout, hash and estimator of redis so the string
+/// values are interchangeable with it.
+const HLL_P: u32 = 14;
+const HLL_Q: u32 = 64 - HLL_P;
+const HLL_REGISTERS: usize = 1 << HLL_P;
+const HLL_BITS: usize = 6;
+const HLL_REGISTER_MAX: u8 = (1 << HLL_BITS) - 1;
+const HLL_HDR_SIZE: usize = 16;
+const HLL_DENSE_SIZE: usize = HLL_HDR_SIZE + (HLL_REGISTERS * HLL_BITS).div_ceil(8);
+const HLL_DENSE: u8 = 0;
+const HLL_SPARSE: u8 = 1;
+/// Sparse values only encode registers up to 32, and grow until they are promoted to dense.
+const HLL_SPARSE_VAL_MAX: u8 = 32;
+const HLL_SPARSE_MAX_BYTES: usize = 3000;
+const HLL_ALPHA_INF: f64 = 0.721_347_520_444_481_7;
+
+/// A HyperLogLog decoded from the string value it is stored in. The header caches the last
+/// cardinality, with the top bit of its last byte marking the cache stale.
+pub struct HyperLogLog {
+    registers: Vec<u8>,
+    sparse: bool
+}
+
+pub fn create_invalid_hll_err_resp() -> Vec<u8> {
+    create_basic_err_resp("WRONGTYPE Key is not a valid HyperLogLog string value.".to_string())
+}
+
+fn create_corrupted_hll_err_resp() -> Vec<u8> {
+    create_basic_err_resp("INVALIDOBJ Corrupted HLL object detected".to_string())
+}
+
+impl Default for HyperLogLog {
+    fn default() -> Self {
+        HyperLogLog { registers: vec![0; HLL_REGISTERS], sparse: true }
+    }
+}
+
+impl HyperLogLog {
+    pub fn decode(bytes: &[u8]) -> Result<Self, Vec<u8>> {
+        if bytes.len() < HLL_HDR_SIZE || !bytes.starts_with(b"HYLL") {
+            return Err(create_invalid_hll_err_resp());
+        }
+        let payload = &bytes[HLL_HDR_SIZE..];
+        match bytes[4] {
+            HLL_DENSE if bytes.len() == HLL_DENSE_SIZE => {
+                let registers = (0..HLL_REGISTERS).map(|index| dense_get(payload, index)).collect();
+                Ok(HyperLogLog { registers, sparse: false })
+            },
+            HLL_SPARSE => {
+                let mut registers = Vec::with_capacity(HLL_REGISTERS);
+                let mut pos = 0;
+                while pos < payload.len() {
+                    let opcode = payload[pos];
+                    let (val, len) = match opcode >> 6 {
+                        // ZERO: 00xxxxxx, a run of 1 to 64 empty registers
+                        0b00 => (0, (opcode & 0x3f) as usize + 1),
+                        // XZERO: 01xxxxxx yyyyyyyy, a run of 1 to 16384 empty registers
+                        0b01 => match payload.get(pos + 1) {
+                            Some(low) => {
+                                pos += 1;
+                                (0, (((opcode & 0x3f) as usize) << 8 | *low as usize) + 1)
+                            },
+                            None => return Err(create_corrupted_hll_err_resp())
+                        },
+                        // VAL: 1vvvvvxx, a run of 1 to 4 registers set to 1 to 32
+                        _ => (((opcode >> 2) & 0x1f) + 1, (opcode & 0x03) as usize + 1)
+                    };
+                    if registers.len() + len > HLL_REGISTERS {
+                        return Err(create_corrupted_hll_err_resp());
+                    }
+                    registers.extend(std::iter::repeat_n(val, len));
+                    pos += 1;
+                }
+                if registers.len() != HLL_REGISTERS {
+                    return Err(create_corrupted_hll_err_resp());
+                }
+                Ok(HyperLogLog { registers, sparse: true })
+            },
+            _ => Err(create_invalid_hll_err_resp())
+        }
+    }
+
+    /// Encodes sparse while that stays small, dense otherwise. Once dense it stays dense. The
+    /// cached cardinality is marked stale.
+    pub fn encode(&self) -> Vec<u8> {
+        let mut bytes = b"HYLL".to_vec();
+        bytes.extend([HLL_DENSE, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0x80]);
+        if self.sparse {
+            if let Some(payload) = self.encode_sparse() {
+                bytes[4] = HLL_SPARSE;
+                bytes.extend(payload);
+                return bytes;
+            }
+        }
+        bytes.resize(HLL_DENSE_SIZE, 0);
+        for (index, val) in self.registers.iter().enumerate() {
+            dense_set(&mut bytes[HLL_HDR_SIZE..], index, *val);
+        }
+        bytes
+    }
+
+    fn encode_sparse(&self) -> Option<Vec<u8>> {
+        let mut payload = vec![];
+        let mut index = 0;
+        while index < HLL_REGISTERS {
+            let val = self.registers[index];
+            let run = self.registers[index..].iter().take_while(|other| **other == val).count();
+            if val == 0 {
+                let mut remaining = run;
+                while remaining > 0 {
+                    let len = remaining.min(HLL_REGISTERS);
+                    if len <= 64 {
+                        payload.push((len - 1) as u8);
+                    } else {
+                        payload.push(0x40 | ((len - 1) >> 8) as u8);
+                        payload.push(((len - 1) & 0xff) as u8);
+                    }
+                    remaining -= len;
+                }
+            } else if val <= HLL_SPARSE_VAL_MAX {
+                let mut remaining = run;
+                while remaining > 0 {
+                    let len = remaining.min(4);
+                    payload.push(0x80 | ((val - 1) << 2) | (len - 1) as u8);
+                    remaining -= len;
+                }
+            } else {
+                return None;
+            }
+            if HLL_HDR_SIZE + payload.len() > HLL_SPARSE_MAX_BYTES {
+                return None;
+            }
+            index += run;
+        }
+        Some(payload)
+    }
+
+    /// Adds an element, returning whether a register changed.
+    pub fn add(&mut self, element: &[u8]) -> bool {
+        let hash = murmurhash64a(element, 0xadc8_3b19);
+        let index = (hash & (HLL_REGISTERS as u64 - 1)) as usize;
+        // the run of zeros after the index bits, capped by the bit set at position Q
+        let count = ((hash >> HLL_P) | (1 << HLL_Q)).trailing_zeros() as u8 + 1;
+        if count > self.registers[index] {
+            self.registers[index] = count;
+            if count > HLL_SPARSE_VAL_MAX {
+                self.sparse = false;
+            }
+            return true;
+        }
+        false
+    }
+
+    pub fn merge(&mut self, other: &HyperLogLog) {
+        for (register, other_register) in self.registers.iter_mut().zip(&other.registers) {
+            *register = (*register).max(*other_register);
+        }
+    }
+
+    pub fn into_dense(mut self) -> Self {
+        self.sparse = false;
+        self
+    }
+
+    /// The cardinality estimate of Ertl's "New cardinality estimation algorithms for
+    /// HyperLogLog sketches", as redis computes it.
+    pub fn count(&self) -> u64 {
+        let mut histogram = [0usize; 64];
+        for register in &self.registers {
+            histogram[*register as usize] += 1;
+        }
+        let m = HLL_REGISTERS as f64;
+        let q = HLL_Q as usize;
+        let mut z = m * tau((m - histogram[q + 1] as f64) / m);
+        for count in histogram[1..=q].iter().rev() {
+            z += *count as f64;
+            z *= 0.5;
+        }
+        z += m * sigma(histogram[0] as f64 / m);
+        (HLL_ALPHA_INF * m * m / z).round() as u64
+    }
+}
+
+/// The cardinality cached in the header of an encoded HyperLogLog, unless it is stale.
+pub fn cached_cardinality(bytes: &[u8]) -> Option<u64> {
+    if bytes.len() < HLL_HDR_SIZE || bytes[15] & 0x80 != 0 {
+        return None;
+    }
+    Some(u64::from_le_bytes(bytes[8..16].try_into().unwrap()))
+}
+
+pub fn cache_cardinality(bytes: &mut [u8], cardinality: u64) {
+    bytes[8..16].copy_from_slice(&cardinality.to_le_bytes());
+}
+
+fn dense_get(payload: &[u8], index: usize) -> u8 {
+    let bit = index * HLL_BITS;
+    let (byte, shift) = (bit / 8, bit % 8);
+    let low = (payload[byte] >> shift) as u16;
+    let high = payload.get(byte + 1).map_or(0, |high| (*high as u16) << (8 - shift));
+    ((low | high) & HLL_REGISTER_MAX as u16) as u8
+}
+
+fn dense_set(payload: &mut [u8], index: usize, val: u8) {
+    let bit = index * HLL_BITS;
+    let (byte, shift) = (bit / 8, bit % 8);
+    let val = val as u16 & HLL_REGISTER_MAX as u16;
+    payload[byte] &= !((HLL_REGISTER_MAX as u16) << shift) as u8;
+    payload[byte] |= (val << shift) as u8;
+    if shift > 8 - HLL_BITS {
+        payload[byte + 1] &= !((HLL_REGISTER_MAX as u16) >> (8 - shift)) as u8;
+        payload[byte + 1] |= (val >> (8 - shift)) as u8;
+    }
+}
+
+fn sigma(mut x: f64) -> f64 {
+    if x == 1.0 {
+        return f64::INFINITY;
+    }
+    let mut y = 1.0;
+    let mut z = x;
+    loop {
+        x *= x;
+        let z_prime = z;
+        z += x * y;
+        y += y;
+        if z_prime == z {
+            return z;
+        }
+    }
+}
+
+fn tau(mut x: f64) -> f64 {
+    if x == 0.0 || x == 1.0 {
+        return 0.0;
+    }
+    let mut y = 1.0;
+    let mut z = 1.0 - x;
+    loop {
+        x = x.sqrt();
+        let z_prime = z;
+        y *= 0.5;
+        z -= (1.0 - x).powi(2) * y;
+        if z_prime == z {
+            return z / 3.0;
+        }
+    }
+}
+
+fn murmurhash64a(key: &[u8], seed: u64) -> u64 {
+    const M: u64 = 0xc6a4_a793_5bd1_e995;
+    const R: u32 = 47;
+    let mut h = seed ^ (key.len() as u64).wrapping_mul(M);
+    let mut chunks = key.chunks_exact(8);
+    for chunk in &mut chunks {
+        let mut k = u64::from_le_bytes(chunk.try_into().unwrap());
+        k = k.wrapping_mul(M);
+        k ^= k >> R;
+        k = k.wrapping_mul(M);
+        h ^= k;
+        h = h.wrapping_mul(M);
+    }
+    let tail = chunks.remainder();
+    if !tail.is_empty() {
+        for (i, byte) in tail.iter().enumerate() {
+            h ^= (*byte as u64) << (8 * i);
+        }
+        h = h.wrapping_mul(M);
+    }
+    h ^= h >> R;
+    h = h.wrapping_mul(M);
+    h ^= h >> R;
+    h
+}
@@ -0,0 +1,352 @@
+use std::{collections::{hash_map::Entry, HashMap}, sync::{Arc, Mutex, MutexGuard}, time::{Duration, Instant}};
+
+use rand::{rngs::SmallRng, Rng, SeedableRng};
+
+use crate::{redis::{client::{CacheVal, ListCacheVal}, replication::ReplicationLog, scan::scan_elements, sorted_set::SortedSetCacheVal, stream::now_ms}, resp::{create_array_resp, create_bulk_string_resp}};
+
+/// Keys sampled per round of the active expire cycle.
+const ACTIVE_EXPIRE_SAMPLE: usize = 20;
+
+/// Who deletes expired keys. A replica leaves it to its master, which replicates a DEL for every
+/// key it expires, so both agree on when a key is gone whatever their clocks say.
+#[derive(Default)]
+pub enum ExpireMode {
+    #[default]
+    Local,
+    Master(ReplicationLog),
+    Replica
+}
+
+/// The keys and their values, with the expiry of every key that has one kept on the side so any
+/// type can expire. Keys whose expiry has passed read as absent, they get deleted the first time
+/// they are accessed mutably or by the active expire cycle.
+#[derive(Default)]
+pub struct Keyspace {
+    entries: HashMap<Vec<u8>, CacheVal>,
+    /// The expiry of every key that has one and its position in `volatile_keys`.
+    expires: HashMap<Vec<u8>, (u128, usize)>,
+    /// The keys with an expiry, for the active expire cycle to sample.
+    volatile_keys: Vec<Vec<u8>>,
+    expire_mode: ExpireMode
+}
+
+/// The logical databases, SELECT picking which one a connection works on.
+pub type Databases = Arc<Vec<Arc<Mutex<Keyspace>>>>;
+
+/// Locks two different dbs, always in index order so connections locking the same pair at once
+/// can't deadlock. The guards come back in argument order.
+pub fn lock_pair(databases: &Databases, first: usize, second: usize) -> (MutexGuard<'_, Keyspace>, MutexGuard<'_, Keyspace>) {
+    if first < second {
+        let first_guard = databases[first].lock().unwrap();
+        (first_guard, databases[second].lock().unwrap())
+    } else {
+        let second_guard = databases[second].lock().unwrap();
+        (databases[first].lock().unwrap(), second_guard)
+    }
+}
+
+/// Values holding more elements than this are freed on a background thread by UNLINK.
+pub const LAZYFREE_THRESHOLD: usize = 64;
+
+impl CacheVal {
+    /// The name TYPE replies with.
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            CacheVal::String(_) => "string",
+            CacheVal::List(_) => "list",
+            CacheVal::Stream(_) => "stream",
+            CacheVal::Hash(_) => "hash",
+            CacheVal::Set(_) => "set",
+            CacheVal::SortedSet(_) => "zset"
+        }
+    }
+
+    /// Roughly how much work dropping the value takes, in elements.
+    pub fn free_effort(&self) -> usize {
+        match self {
+            CacheVal::String(_) => 1,
+            CacheVal::List(val) => val.list.len(),
+            CacheVal::Stream(val) => val.stream.len(),
+            CacheVal::Hash(val) => val.hash.len(),
+            CacheVal::Set(val) => val.set.len(),
+            CacheVal::SortedSet(val) => val.len()
+        }
+    }
+
+    /// A copy of the value for COPY.
+    pub fn duplicate(&self) -> CacheVal {
+        match self {
+            CacheVal::String(val) => CacheVal::String(val.clone()),
+            CacheVal::List(val) => CacheVal::List(val.clone()),
+            CacheVal::Stream(val) => CacheVal::Stream(val.clone()),
+            CacheVal::Hash(val) => CacheVal::Hash(val.clone()),
+            CacheVal::Set(val) => CacheVal::Set(val.clone()),
+            CacheVal::SortedSet(val) => {
+                let mut zset = SortedSetCacheVal::new();
+                for (member, score) in &val.dict {
+                    zset.insert(member.clone(), *score);
+                }
+                CacheVal::SortedSet(zset)
+            }
+        }
+    }
+}
+
+impl Keyspace {
+    pub fn new() -> Self {
+        Keyspace::default()
+    }
+
+    pub fn with_expire_mode(expire_mode: ExpireMode) -> Self {
+        Keyspace { expire_mode, ..Default::default() }
+    }
+
+    fn is_expired(&self, key: &[u8], now: u128) -> bool {
+        self.expires.get(key).is_some_and(|(expiry_time, _)| *expiry_time <= now)
+    }
+
+    fn remove_expiry(&mut self, key: &[u8]) {
+        if let Some((_, index)) = self.expires.remove(key) {
+            self.volatile_keys.swap_remove(index);
+            if let Some(moved) = self.volatile_keys.get(index) {
+                self.expires.get_mut(moved).expect("volatile keys have an expiry").1 = index;
+            }
+        }
+    }
+
+    /// Deletes `key` if its expiry has passed, replicating the deletion on a master. Replicas keep
+    /// the key until their master's DEL arrives. Returns whether the key got deleted.
+    pub fn expire_if_needed(&mut self, key: &[u8]) -> bool {
+        if matches!(self.expire_mode, ExpireMode::Replica) || !self.is_expired(key, now_ms()) {
+            return false;
+        }
+        self.entries.remove(key);
+        self.remove_expiry(key);
+        if let ExpireMode::Master(replication_log) = &self.expire_mode {
+            replication_log.push(create_array_resp(vec![create_bulk_string_resp("DEL"), create_bulk_string_resp(key)]));
+        }
+        true
+    }
+
+    /// Samples keys with an expiry and deletes the expired ones, sampling again as long as more
+    /// than a quarter of a sample had expired and `budget` isn't spent. Returns how many keys got
+    /// deleted.
+    pub fn active_expire_cycle(&mut self, budget: Duration) -> usize {
+        if matches!(self.expire_mode, ExpireMode::Replica) {
+            return 0;
+        }
+        let start = Instant::now();
+        let mut rng = SmallRng::from_entropy();
+        let mut deleted = 0;
+        loop {
+            let sample = self.volatile_keys.len().min(ACTIVE_EXPIRE_SAMPLE);
+            let mut expired = 0;
+            for _ in 0..sample {
+                if self.volatile_keys.is_empty() {
+                    break;
+                }
+                let key = self.volatile_keys[rng.gen_range(0..self.volatile_keys.len())].clone();
+                if self.expire_if_needed(&key) {
+                    expired += 1;
+                }
+            }
+            deleted += expired;
+            if expired * 4 <= sample || start.elapsed() >= budget {
+                return deleted;
+            }
+        }
+    }
+
+    pub fn get(&self, key: &[u8]) -> Option<&CacheVal> {
+        if self.is_expired(key, now_ms()) {
+            return None;
+        }
+        self.entries.get(key)
+    }
+
+    pub fn get_mut(&mut self, key: &[u8]) -> Option<&mut CacheVal> {
+        self.expire_if_needed(key);
+        self.entries.get_mut(key)
+    }
+
+    pub fn contains_key(&self, key: &[u8]) -> bool {
+        self.get(key).is_some()
+    }
+
+    /// The value at `key`, storing `default()` there first when the key doesn't exist.
+    pub fn get_or_insert_with(&mut self, key: Vec<u8>, default: impl FnOnce() -> CacheVal) -> &mut CacheVal {
+        self.expire_if_needed(&key);
+        match self.entries.entry(key) {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => entry.insert(default())
+        }
+    }
+
+    /// Stores a new value at `key`. Like SET, overwriting a key discards its expiry.
+    pub fn insert(&mut self, key: Vec<u8>, val: CacheVal) -> Option<CacheVal> {
+        self.expire_if_needed(&key);
+        self.remove_expiry(&key);
+        self.entries.insert(key, val)
+    }
+
+    pub fn remove(&mut self, key: &[u8]) -> Option<CacheVal> {
+        self.expire_if_needed(key);
+        self.remove_expiry(key);
+        self.entries.remove(key)
+    }
+
+    /// The keys that haven't expired, with their values.
+    pub fn iter(&self) -> impl Iterator<Item = (&Vec<u8>, &CacheVal)> {
+        let now = now_ms();
+        self.entries.iter().filter(move |(key, _)| !self.is_expired(key, now))
+    }
+
+    /// One step of SCAN: about `count` keys from `cursor` on in slot order, never splitting keys
+    /// that share a slot, and the cursor to continue from, 0 once done.
+    pub fn scan(&self, cursor: u64, count: usize) -> (u64, Vec<(&Vec<u8>, &CacheVal)>) {
+        scan_elements(self.iter().map(|(key, val)| (key.as_slice(), (key, val))), cursor, count)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.iter().next().is_none()
+    }
+
+    pub fn values(&self) -> impl Iterator<Item = &CacheVal> {
+        self.iter().map(|(_, val)| val)
+    }
+
+    /// Empties the keyspace for FLUSHDB, returning the values for the caller to drop.
+    pub fn flush(&mut self) -> Vec<CacheVal> {
+        self.expires.clear();
+        self.volatile_keys.clear();
+        std::mem::take(&mut self.entries).into_values().collect()
+    }
+
+    /// Exchanges the keys of two dbs for SWAPDB.
+    pub fn swap(&mut self, other: &mut Keyspace) {
+        std::mem::swap(&mut self.entries, &mut other.entries);
+        std::mem::swap(&mut self.expires, &mut other.expires);
+        std::mem::swap(&mut self.volatile_keys, &mut other.volatile_keys);
+    }
+
+    /// When `key` expires, in milliseconds since the epoch.
+    pub fn expiry(&self, key: &[u8]) -> Option<u128> {
+        if !self.contains_key(key) {
+            return None;
+        }
+        self.expires.get(key).map(|(expiry_time, _)| *expiry_time)
+    }
+
+    /// Sets when an existing `key` expires, `None` making it persistent.
+    pub fn set_expiry(&mut self, key: &[u8], expiry_time: Option<u128>) {
+        if !self.entries.contains_key(key) {
+            return;
+        }
+        match (expiry_time, self.expires.get_mut(key)) {
+            (Some(expiry_time), Some(expiry)) => expiry.0 = expiry_time,
+            (Some(expiry_time), None) => {
+                self.expires.insert(key.to_vec(), (expiry_time, self.volatile_keys.len()));
+                self.volatile_keys.push(key.to_vec());
+            },
+            (None, _) => self.remove_expiry(key)
+        }
+    }
+}
+
+/// Removes the value at `key` along with its expiry, `None` when the key doesn't exist.
+pub fn remove_key(cache: &mut Keyspace, key: &[u8]) -> Option<(CacheVal, Option<u128>)> {
+    let expiry_time = cache.expiry(key);
+    cache.remove(key).map(|val| (val, expiry_time))
+}
+
+/// Stores `val` at `key` with the given expiry, overwriting whatever is there.
+pub fn store_key(cache: &mut Keyspace, key: Vec<u8>, val: CacheVal, expiry_time: Option<u128>) {
+    cache.insert(key.clone(), val);
+    cache.set_expiry(&key, expiry_time);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::redis::{client::StringCacheVal, replication::WriteCommands};
+
+    fn string(val: &str) -> CacheVal {
+        CacheVal::String(StringCacheVal::new(val.into()))
+    }
+
+    #[test]
+    fn test_active_expire_cycle() {
+        let write_commands = Arc::new(Mutex::new(WriteCommands::default()));
+        let mut keyspace = Keyspace::with_expire_mode(ExpireMode::Master(ReplicationLog::new(write_commands.clone(), 0)));
+        let now = now_ms();
+        for i in 0..200 {
+            let key = format!("key{}", i).into_bytes();
+            keyspace.insert(key.clone(), string("v"));
+            // every other key is already expired
+            keyspace.set_expiry(&key, Some(if i % 2 == 0 { now - 1 } else { now + 60_000 }));
+        }
+
+        let deleted = keyspace.active_expire_cycle(Duration::from_secs(1));
+        assert!(deleted > 0);
+        assert_eq!(write_commands.lock().unwrap().len(), deleted);
+        assert!(write_commands.lock().unwrap().iter().all(|command| command.starts_with(b"*2\r\n$3\r\nDEL\r\n")));
+        // the cycle stops once a quarter or less of a sample had expired
+        while keyspace.active_expire_cycle(Duration::from_secs(1)) > 0 {}
+        assert!(keyspace.volatile_keys.len() >= 100);
+        assert_eq!(keyspace.volatile_keys.len(), keyspace.expires.len());
+        for (index, key) in keyspace.volatile_keys.iter().enumerate() {
+            assert_eq!(keyspace.expires[key].1, index);
+        }
+    }
+
+    #[test]
+    fn test_active_expire_cycle_with_few_keys() {
+        let mut keyspace = Keyspace::default();
+        for i in 0..4 {
+            let key = format!("key{}", i).into_bytes();
+            keyspace.insert(key.clone(), string("v"));
+            keyspace.set_expiry(&key, Some(now_ms() - 1));
+        }
+
+        // a sample smaller than usual is judged against its own size
+        assert_eq!(keyspace.active_expire_cycle(Duration::from_secs(1)), 4);
+        assert!(keyspace.entries.is_empty());
+    }
+
+    #[test]
+    fn test_replicas_wait_for_del() {
+        let mut keyspace = Keyspace::with_expire_mode(ExpireMode::Replica);
+        keyspace.insert(b"key".to_vec(), string("v"));
+        keyspace.set_expiry(b"key", Some(now_ms() - 1));
+
+        assert!(keyspace.get(b"key").is_none());
+        assert!(!keyspace.expire_if_needed(b"key"));
+        assert_eq!(keyspace.active_expire_cycle(Duration::from_secs(1)), 0);
+        assert!(keyspace.entries.contains_key(b"key".as_slice()));
+        keyspace.remove(b"key");
+        assert!(keyspace.entries.is_empty() && keyspace.volatile_keys.is_empty());
+    }
+
+    #[test]
+    fn test_scan_pages() {
+        let mut keyspace = Keyspace::default();
+        for i in 0..100 {
+            keyspace.insert(format!("key{}", i).into_bytes(), string("v"));
+        }
+
+        let mut seen = vec![];
+        let mut cursor = 0;
+        loop {
+            let (next_cursor, keys) = keyspace.scan(cursor, 10);
+            assert_eq!(keys.len(), 10);
+            seen.extend(keys.into_iter().map(|(key, _)| key.clone()));
+            cursor = next_cursor;
+            if cursor == 0 {
+                break;
+            }
+        }
+        seen.sort();
+        seen.dedup();
+        assert_eq!(seen.len(), 100);
+    }
+}
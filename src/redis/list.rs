@@ -0,0 +1,101 @@
+use std::collections::VecDeque;
+
+use crate::{commands::parse_arg, redis::client::ListCacheVal, resp::create_basic_err_resp};
+
+/// The end of a list an element is taken from or put on, as in the LEFT/RIGHT arguments of LMOVE.
+#[derive(Clone, Copy, PartialEq)]
+pub enum ListEnd {
+    Left,
+    Right
+}
+
+impl ListEnd {
+    pub fn parse(arg: &[u8]) -> Option<Self> {
+        match arg.to_ascii_lowercase().as_slice() {
+            b"left" => Some(ListEnd::Left),
+            b"right" => Some(ListEnd::Right),
+            _ => None
+        }
+    }
+
+    pub fn as_arg(&self) -> &'static str {
+        match self {
+            ListEnd::Left => "LEFT",
+            ListEnd::Right => "RIGHT"
+        }
+    }
+
+    /// The non blocking pop replicas are sent in place of a blocking one.
+    pub fn pop_command(&self) -> &'static str {
+        match self {
+            ListEnd::Left => "LPOP",
+            ListEnd::Right => "RPOP"
+        }
+    }
+}
+
+impl ListCacheVal {
+    pub fn new() -> Self {
+        ListCacheVal { list: VecDeque::new() }
+    }
+
+    pub fn pop(&mut self, end: ListEnd) -> Option<Vec<u8>> {
+        match end {
+            ListEnd::Left => self.list.pop_front(),
+            ListEnd::Right => self.list.pop_back()
+        }
+    }
+
+    pub fn push(&mut self, end: ListEnd, val: Vec<u8>) {
+        match end {
+            ListEnd::Left => self.list.push_front(val),
+            ListEnd::Right => self.list.push_back(val)
+        }
+    }
+
+    /// Resolves an index that may count from the tail, `None` when it is out of range.
+    pub fn index(&self, index: i64) -> Option<usize> {
+        let len = self.list.len() as i64;
+        let index = if index < 0 { index + len } else { index };
+        if (0..len).contains(&index) { Some(index as usize) } else { None }
+    }
+}
+
+/// Clamps the inclusive `start` and `end` of LRANGE and LTRIM, which may count from the tail, to a
+/// list of `len` elements. `None` when nothing is in range.
+pub fn clamp_range(start: i64, end: i64, len: usize) -> Option<(usize, usize)> {
+    let len = len as i64;
+    let start = if start < 0 { (start + len).max(0) } else { start };
+    let end = if end < 0 { end + len } else { end.min(len - 1) };
+    if start >= len || start > end {
+        return None;
+    }
+    Some((start as usize, end as usize))
+}
+
+/// The keys, end and count asked for by LMPOP or BLMPOP.
+pub type MpopArgs = (Vec<Vec<u8>>, ListEnd, usize);
+
+/// Parses the `numkeys key [key ...] LEFT|RIGHT [COUNT count]` arguments shared by LMPOP and BLMPOP.
+pub fn parse_mpop_args(args: &[Vec<u8>]) -> Result<MpopArgs, Vec<u8>> {
+    let numkeys = match args.first().and_then(|numkeys| parse_arg::<usize>(numkeys)) {
+        Some(numkeys) if numkeys > 0 => numkeys,
+        _ => return Err(create_basic_err_resp("ERR numkeys should be greater than 0".to_string()))
+    };
+    if args.len() < numkeys + 2 {
+        return Err(create_basic_err_resp("ERR syntax error".to_string()));
+    }
+    let end = match ListEnd::parse(&args[numkeys + 1]) {
+        Some(end) => end,
+        None => return Err(create_basic_err_resp("ERR syntax error".to_string()))
+    };
+    let count = match &args[numkeys + 2..] {
+        [] => 1,
+        [option, count] if option.eq_ignore_ascii_case(b"count") => match parse_arg::<usize>(count) {
+            Some(count) if count > 0 => count,
+            _ => return Err(create_basic_err_resp("ERR count should be greater than 0".to_string()))
+        },
+        _ => return Err(create_basic_err_resp("ERR syntax error".to_string()))
+    };
+    Ok((args[1..=numkeys].to_vec(), end, count))
+}
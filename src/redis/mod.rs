@@ -1 +1,14 @@
-pub mod client;
\ No newline at end of file
+pub mod bitmap;
+pub mod blocking;
+pub mod client;
+pub mod geo;
+pub mod glob;
+pub mod hyperloglog;
+pub mod keyspace;
+pub mod list;
+pub mod replication;
+pub mod scan;
+pub mod skiplist;
+pub mod sorted_set;
+pub mod stream;
+pub mod string;
\ No newline at end of file
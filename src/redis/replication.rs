@@ -0,0 +1,101 @@
+use std::{ops::{Deref, DerefMut}, sync::{Arc, Mutex}};
+
+use crate::resp::{create_array_resp, create_bulk_string_resp};
+
+/// The write commands waiting to be sent to the replicas, along with the db the replication stream
+/// has selected so a SELECT only goes out when a command runs against another one.
+pub struct WriteCommands {
+    commands: Vec<Vec<u8>>,
+    /// `None` when the replicas may not all be on the same db.
+    db: Option<usize>,
+    /// The db the replicas were on when the first queued command was pushed.
+    batch_db: Option<usize>
+}
+
+impl Default for WriteCommands {
+    fn default() -> Self {
+        // replicas start out on db 0 like any connection
+        WriteCommands { commands: vec![], db: Some(0), batch_db: Some(0) }
+    }
+}
+
+impl WriteCommands {
+    /// Queues `command`, which ran against `db`.
+    pub fn push(&mut self, db: usize, command: Vec<u8>) {
+        if self.commands.is_empty() {
+            self.batch_db = self.db;
+        }
+        if self.db != Some(db) {
+            self.commands.push(select_command(db));
+            self.db = Some(db);
+        }
+        self.commands.push(command);
+    }
+
+    /// Makes the next command select its db again, for when a replica joins on db 0 while the
+    /// others may be elsewhere. The commands already queued, which the new replica gets as well,
+    /// are led by a SELECT of the db they started on.
+    pub fn reselect(&mut self) {
+        if let Some(db) = self.batch_db.filter(|db| *db != 0 && !self.commands.is_empty()) {
+            self.commands.insert(0, select_command(db));
+        }
+        self.db = None;
+    }
+}
+
+fn select_command(db: usize) -> Vec<u8> {
+    create_array_resp(vec![create_bulk_string_resp("SELECT"), create_bulk_string_resp(db.to_string())])
+}
+
+impl Deref for WriteCommands {
+    type Target = Vec<Vec<u8>>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.commands
+    }
+}
+
+impl DerefMut for WriteCommands {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.commands
+    }
+}
+
+/// Where a command queues what it replicates: the shared write commands and the db it runs against.
+#[derive(Clone)]
+pub struct ReplicationLog {
+    write_commands: Arc<Mutex<WriteCommands>>,
+    db: usize
+}
+
+impl ReplicationLog {
+    pub fn new(write_commands: Arc<Mutex<WriteCommands>>, db: usize) -> Self {
+        ReplicationLog { write_commands, db }
+    }
+
+    pub fn push(&self, command: Vec<u8>) {
+        self.write_commands.lock().unwrap().push(self.db, command);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reselect_for_a_joining_replica() {
+        let mut write_commands = WriteCommands::default();
+        write_commands.push(3, b"first".to_vec());
+        write_commands.clear();
+
+        // the replicas are on db 3, the one joining on db 0 gets the queued command too
+        write_commands.push(3, b"second".to_vec());
+        write_commands.reselect();
+        write_commands.push(3, b"third".to_vec());
+        assert_eq!(*write_commands, vec![select_command(3), b"second".to_vec(), select_command(3), b"third".to_vec()]);
+
+        write_commands.clear();
+        write_commands.reselect();
+        assert!(write_commands.is_empty());
+    }
+}
@@ -0,0 +1,75 @@
+use std::{collections::BTreeMap, hash::{DefaultHasher, Hash, Hasher}};
+
+use crate::{commands::parse_arg, redis::glob::glob_match, resp::create_basic_err_resp};
+
+/// Where an element sits in the scan order: its hash with the bits reversed. Visiting a hash table
+/// in this order is the reverse binary cursor of redis, every bucket of the grown or shrunk table
+/// holds a contiguous range of it, so a cursor stays valid whatever the size of the table and
+/// elements present for the whole iteration are returned at least once.
+pub fn scan_slot(element: &[u8]) -> u64 {
+    // the default hasher has fixed keys, slots don't change between calls
+    let mut hasher = DefaultHasher::new();
+    element.hash(&mut hasher);
+    hasher.finish().reverse_bits()
+}
+
+/// `cursor [MATCH pattern] [COUNT count] [TYPE type]`, TYPE only being allowed for SCAN.
+pub struct ScanArgs {
+    pub cursor: u64,
+    pub pattern: Option<Vec<u8>>,
+    pub count: usize,
+    pub type_name: Option<Vec<u8>>
+}
+
+impl ScanArgs {
+    pub fn parse(args: &[Vec<u8>], allow_type: bool) -> Result<Self, Vec<u8>> {
+        let cursor = match args.first().and_then(|cursor| parse_arg::<u64>(cursor)) {
+            Some(cursor) => cursor,
+            None => return Err(create_basic_err_resp("ERR invalid cursor".to_string()))
+        };
+        let mut scan_args = ScanArgs { cursor, pattern: None, count: 10, type_name: None };
+        let mut args = args[1..].iter();
+        while let Some(arg) = args.next() {
+            match (arg.to_ascii_lowercase().as_slice(), args.next()) {
+                (b"match", Some(pattern)) => scan_args.pattern = Some(pattern.clone()),
+                (b"count", Some(count)) => match parse_arg::<usize>(count) {
+                    Some(count) if count > 0 => scan_args.count = count,
+                    Some(_) => return Err(create_basic_err_resp("ERR syntax error".to_string())),
+                    None => return Err(create_basic_err_resp("ERR value is not an integer or out of range".to_string()))
+                },
+                (b"type", Some(type_name)) if allow_type => scan_args.type_name = Some(type_name.to_ascii_lowercase()),
+                _ => return Err(create_basic_err_resp("ERR syntax error".to_string()))
+            }
+        }
+        Ok(scan_args)
+    }
+
+    pub fn matches(&self, element: &[u8]) -> bool {
+        self.pattern.as_ref().is_none_or(|pattern| glob_match(pattern, element))
+    }
+}
+
+/// One step of a scan: the elements from `cursor` on in slot order, about `count` of them but
+/// never splitting elements that share a slot, and the cursor to continue from, 0 once done.
+/// Nothing is kept between calls, a single pass over the elements holds on to the `count` lowest
+/// slots from the cursor on.
+pub fn scan_elements<'a, T>(elements: impl Iterator<Item = (&'a [u8], T)>, cursor: u64, count: usize) -> (u64, Vec<T>) {
+    let mut lowest: BTreeMap<u64, Vec<T>> = BTreeMap::new();
+    for (element, item) in elements {
+        let slot = scan_slot(element);
+        if slot < cursor || (lowest.len() > count && lowest.last_key_value().is_some_and(|(last, _)| slot > *last)) {
+            continue;
+        }
+        lowest.entry(slot).or_default().push(item);
+        // one slot past the page tells where the next one starts
+        if lowest.len() > count + 1 {
+            lowest.pop_last();
+        }
+    }
+
+    let next_cursor = match lowest.len() > count {
+        true => lowest.pop_last().map_or(0, |(slot, _)| slot),
+        false => 0
+    };
+    (next_cursor, lowest.into_values().flatten().collect())
+}
@@ -0,0 +1,361 @@
+use rand::{rngs::SmallRng, Rng, SeedableRng};
+
+const MAX_LEVEL: usize = 32;
+const HEAD: usize = 0;
+const NIL: usize = usize::MAX;
+
+struct Level {
+    forward: usize,
+    span: usize
+}
+
+struct Node {
+    score: f64,
+    member: Vec<u8>,
+    backward: usize,
+    levels: Vec<Level>
+}
+
+/// Ordered index over (score, member) pairs, modelled on the redis zskiplist.
+/// Nodes live in an arena and every forward link records how many nodes it skips,
+/// which is what lets rank lookups run in O(log n).
+pub struct SkipList {
+    nodes: Vec<Node>,
+    free: Vec<usize>,
+    tail: usize,
+    level: usize,
+    len: usize,
+    rng: SmallRng
+}
+
+impl Default for SkipList {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SkipList {
+    pub fn new() -> Self {
+        let head = Node {
+            score: 0.0,
+            member: Vec::new(),
+            backward: NIL,
+            levels: (0..MAX_LEVEL).map(|_| Level { forward: NIL, span: 0 }).collect()
+        };
+        SkipList { nodes: vec![head], free: vec![], tail: NIL, level: 1, len: 0, rng: SmallRng::from_entropy() }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    fn less_than(&self, node: usize, score: f64, member: &[u8]) -> bool {
+        let node = &self.nodes[node];
+        node.score < score || (node.score == score && node.member.as_slice() < member)
+    }
+
+    fn random_level(&mut self) -> usize {
+        let mut level = 1;
+        while level < MAX_LEVEL && self.rng.gen_ratio(1, 4) {
+            level += 1;
+        }
+        level
+    }
+
+    fn alloc(&mut self, score: f64, member: Vec<u8>, level: usize) -> usize {
+        let node = Node {
+            score,
+            member,
+            backward: NIL,
+            levels: (0..level).map(|_| Level { forward: NIL, span: 0 }).collect()
+        };
+        match self.free.pop() {
+            Some(idx) => {
+                self.nodes[idx] = node;
+                idx
+            },
+            None => {
+                self.nodes.push(node);
+                self.nodes.len() - 1
+            }
+        }
+    }
+
+    /// Inserts a new pair, the caller guarantees the member is not already present.
+    pub fn insert(&mut self, score: f64, member: Vec<u8>) {
+        let mut update = [HEAD; MAX_LEVEL];
+        let mut rank = [0usize; MAX_LEVEL];
+        let mut x = HEAD;
+        for i in (0..self.level).rev() {
+            rank[i] = if i == self.level - 1 { 0 } else { rank[i + 1] };
+            loop {
+                let next = self.nodes[x].levels[i].forward;
+                if next != NIL && self.less_than(next, score, &member) {
+                    rank[i] += self.nodes[x].levels[i].span;
+                    x = next;
+                } else {
+                    break;
+                }
+            }
+            update[i] = x;
+        }
+
+        let level = self.random_level();
+        if level > self.level {
+            for i in self.level..level {
+                rank[i] = 0;
+                update[i] = HEAD;
+                self.nodes[HEAD].levels[i].span = self.len;
+            }
+            self.level = level;
+        }
+
+        let new = self.alloc(score, member, level);
+        for i in 0..level {
+            let prev = update[i];
+            self.nodes[new].levels[i].forward = self.nodes[prev].levels[i].forward;
+            self.nodes[prev].levels[i].forward = new;
+            self.nodes[new].levels[i].span = self.nodes[prev].levels[i].span - (rank[0] - rank[i]);
+            self.nodes[prev].levels[i].span = (rank[0] - rank[i]) + 1;
+        }
+        for (i, prev) in update.iter().enumerate().take(self.level).skip(level) {
+            self.nodes[*prev].levels[i].span += 1;
+        }
+
+        self.nodes[new].backward = if update[0] == HEAD { NIL } else { update[0] };
+        let next = self.nodes[new].levels[0].forward;
+        if next != NIL {
+            self.nodes[next].backward = new;
+        } else {
+            self.tail = new;
+        }
+        self.len += 1;
+    }
+
+    /// Removes the pair if present, returning whether anything was removed.
+    pub fn remove(&mut self, score: f64, member: &[u8]) -> bool {
+        let mut update = [HEAD; MAX_LEVEL];
+        let mut x = HEAD;
+        for i in (0..self.level).rev() {
+            loop {
+                let next = self.nodes[x].levels[i].forward;
+                if next != NIL && self.less_than(next, score, member) {
+                    x = next;
+                } else {
+                    break;
+                }
+            }
+            update[i] = x;
+        }
+
+        let x = self.nodes[update[0]].levels[0].forward;
+        if x == NIL || self.nodes[x].score != score || self.nodes[x].member != member {
+            return false;
+        }
+
+        for (i, prev) in update.iter().enumerate().take(self.level) {
+            if self.nodes[*prev].levels[i].forward == x {
+                self.nodes[*prev].levels[i].span += self.nodes[x].levels[i].span;
+                self.nodes[*prev].levels[i].span -= 1;
+                self.nodes[*prev].levels[i].forward = self.nodes[x].levels[i].forward;
+            } else {
+                self.nodes[*prev].levels[i].span -= 1;
+            }
+        }
+
+        let next = self.nodes[x].levels[0].forward;
+        if next != NIL {
+            self.nodes[next].backward = self.nodes[x].backward;
+        } else {
+            self.tail = self.nodes[x].backward;
+        }
+        while self.level > 1 && self.nodes[HEAD].levels[self.level - 1].forward == NIL {
+            self.level -= 1;
+        }
+
+        self.nodes[x].member = Vec::new();
+        self.nodes[x].levels.clear();
+        self.free.push(x);
+        self.len -= 1;
+        true
+    }
+
+    /// Zero based rank of the pair, if present.
+    pub fn rank(&self, score: f64, member: &[u8]) -> Option<usize> {
+        let mut x = HEAD;
+        let mut rank = 0;
+        for i in (0..self.level).rev() {
+            loop {
+                let next = self.nodes[x].levels[i].forward;
+                if next != NIL && (self.less_than(next, score, member) || (self.nodes[next].score == score && self.nodes[next].member == member)) {
+                    rank += self.nodes[x].levels[i].span;
+                    x = next;
+                } else {
+                    break;
+                }
+            }
+            if x != HEAD && self.nodes[x].score == score && self.nodes[x].member == member {
+                return Some(rank - 1);
+            }
+        }
+        None
+    }
+
+    /// Counts the leading elements matching `pred`, which must hold for a prefix of the list
+    /// (e.g. `score < min`). This gives the rank of the first element outside that prefix.
+    pub fn count_while<F>(&self, pred: F) -> usize where F: Fn(f64, &[u8]) -> bool {
+        let mut x = HEAD;
+        let mut rank = 0;
+        for i in (0..self.level).rev() {
+            loop {
+                let next = self.nodes[x].levels[i].forward;
+                if next != NIL && pred(self.nodes[next].score, &self.nodes[next].member) {
+                    rank += self.nodes[x].levels[i].span;
+                    x = next;
+                } else {
+                    break;
+                }
+            }
+        }
+        rank
+    }
+
+    fn node_at(&self, rank: usize) -> usize {
+        let target = rank + 1;
+        let mut x = HEAD;
+        let mut traversed = 0;
+        for i in (0..self.level).rev() {
+            loop {
+                let next = self.nodes[x].levels[i].forward;
+                if next != NIL && traversed + self.nodes[x].levels[i].span <= target {
+                    traversed += self.nodes[x].levels[i].span;
+                    x = next;
+                } else {
+                    break;
+                }
+            }
+            if traversed == target {
+                return x;
+            }
+        }
+        NIL
+    }
+
+    pub fn get_by_rank(&self, rank: usize) -> Option<(f64, &Vec<u8>)> {
+        match self.node_at(rank) {
+            NIL => None,
+            x => Some((self.nodes[x].score, &self.nodes[x].member))
+        }
+    }
+
+    pub fn first(&self) -> Option<(f64, &Vec<u8>)> {
+        match self.nodes[HEAD].levels[0].forward {
+            NIL => None,
+            x => Some((self.nodes[x].score, &self.nodes[x].member))
+        }
+    }
+
+    pub fn last(&self) -> Option<(f64, &Vec<u8>)> {
+        match self.tail {
+            NIL => None,
+            x => Some((self.nodes[x].score, &self.nodes[x].member))
+        }
+    }
+
+    /// Walks the list in ascending order starting at the given rank.
+    pub fn iter_from(&self, rank: usize) -> SkipListIter<'_> {
+        let node = if rank < self.len { self.node_at(rank) } else { NIL };
+        SkipListIter { list: self, node, reverse: false }
+    }
+
+    /// Walks the list in descending order starting at the given rank.
+    pub fn iter_rev_from(&self, rank: usize) -> SkipListIter<'_> {
+        let node = if rank < self.len { self.node_at(rank) } else { NIL };
+        SkipListIter { list: self, node, reverse: true }
+    }
+
+    pub fn iter(&self) -> SkipListIter<'_> {
+        SkipListIter { list: self, node: self.nodes[HEAD].levels[0].forward, reverse: false }
+    }
+}
+
+pub struct SkipListIter<'a> {
+    list: &'a SkipList,
+    node: usize,
+    reverse: bool
+}
+
+impl<'a> Iterator for SkipListIter<'a> {
+    type Item = (f64, &'a Vec<u8>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.node == NIL {
+            return None;
+        }
+        let node = &self.list.nodes[self.node];
+        self.node = if self.reverse { node.backward } else { node.levels[0].forward };
+        Some((node.score, &node.member))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn build(pairs: &[(f64, &str)]) -> SkipList {
+        let mut list = SkipList::new();
+        for (score, member) in pairs {
+            list.insert(*score, member.as_bytes().to_vec());
+        }
+        list
+    }
+
+    #[test]
+    fn test_insert_keeps_order() {
+        let list = build(&[(3.0, "c"), (1.0, "a"), (2.0, "b"), (2.0, "a")]);
+        let items: Vec<(f64, Vec<u8>)> = list.iter().map(|(score, member)| (score, member.clone())).collect();
+        assert_eq!(items, vec![(1.0, b"a".to_vec()), (2.0, b"a".to_vec()), (2.0, b"b".to_vec()), (3.0, b"c".to_vec())]);
+        assert_eq!(list.len(), 4);
+        assert_eq!(list.first().map(|(_, member)| member.as_slice()), Some(b"a".as_slice()));
+        assert_eq!(list.last().map(|(_, member)| member.as_slice()), Some(b"c".as_slice()));
+    }
+
+    #[test]
+    fn test_rank_and_get_by_rank() {
+        let mut list = SkipList::new();
+        for i in 0..1000 {
+            list.insert(i as f64, format!("m{}", i).into_bytes());
+        }
+        assert_eq!(list.rank(500.0, b"m500"), Some(500));
+        assert_eq!(list.rank(500.0, b"nope"), None);
+        assert_eq!(list.get_by_rank(999).map(|(score, _)| score), Some(999.0));
+        assert!(list.get_by_rank(1000).is_none());
+        assert_eq!(list.count_while(|score, _| score < 250.0), 250);
+
+        let reversed: Vec<f64> = list.iter_rev_from(2).map(|(score, _)| score).collect();
+        assert_eq!(reversed, vec![2.0, 1.0, 0.0]);
+    }
+
+    #[test]
+    fn test_remove() {
+        let mut list = SkipList::new();
+        for i in 0..100 {
+            list.insert(i as f64, format!("m{}", i).into_bytes());
+        }
+        for i in (0..100).step_by(2) {
+            assert!(list.remove(i as f64, format!("m{}", i).as_bytes()));
+        }
+        assert!(!list.remove(1.0, b"m2"));
+        assert_eq!(list.len(), 50);
+        assert_eq!(list.rank(51.0, b"m51"), Some(25));
+        assert_eq!(list.iter_rev_from(49).next().map(|(score, _)| score), Some(99.0));
+
+        // freed slots are reused
+        list.insert(0.5, b"half".to_vec());
+        assert_eq!(list.get_by_rank(0).map(|(_, member)| member.clone()), Some(b"half".to_vec()));
+    }
+}
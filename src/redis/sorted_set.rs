@@ -0,0 +1,146 @@
+use std::collections::HashMap;
+
+use crate::redis::skiplist::{SkipList, SkipListIter};
+
+/// A sorted set keeps a member -> score map for O(1) lookups next to the skiplist that orders them.
+#[derive(Default)]
+pub struct SortedSetCacheVal {
+    pub(crate) dict: HashMap<Vec<u8>, f64>,
+    pub(crate) index: SkipList
+}
+
+/// One end of a BYSCORE range such as `1.5`, `(1.5` or `-inf`.
+#[derive(Clone, Copy)]
+pub struct ScoreBound {
+    pub(crate) value: f64,
+    pub(crate) exclusive: bool
+}
+
+/// One end of a BYLEX range such as `[a`, `(a`, `-` or `+`.
+#[derive(Clone)]
+pub enum LexBound {
+    NegInf,
+    PosInf,
+    Inclusive(Vec<u8>),
+    Exclusive(Vec<u8>)
+}
+
+impl ScoreBound {
+    pub fn parse(arg: &[u8]) -> Option<Self> {
+        let (value, exclusive) = match arg.strip_prefix(b"(") {
+            Some(rest) => (rest, true),
+            None => (arg, false)
+        };
+        parse_score(value).map(|value| ScoreBound { value, exclusive })
+    }
+}
+
+impl LexBound {
+    pub fn parse(arg: &[u8]) -> Option<Self> {
+        match arg {
+            b"-" => Some(LexBound::NegInf),
+            b"+" => Some(LexBound::PosInf),
+            [b'[', rest @ ..] => Some(LexBound::Inclusive(rest.to_vec())),
+            [b'(', rest @ ..] => Some(LexBound::Exclusive(rest.to_vec())),
+            _ => None
+        }
+    }
+}
+
+/// Parses a score the way redis does, accepting `inf`/`+inf`/`-inf` but never NaN.
+pub fn parse_score(arg: &[u8]) -> Option<f64> {
+    let arg = std::str::from_utf8(arg).ok()?;
+    match arg.to_lowercase().as_str() {
+        "inf" | "+inf" => Some(f64::INFINITY),
+        "-inf" => Some(f64::NEG_INFINITY),
+        lowered if lowered.contains("inf") || lowered.contains("nan") => None,
+        _ => arg.parse::<f64>().ok()
+    }
+}
+
+impl SortedSetCacheVal {
+    pub fn new() -> Self {
+        SortedSetCacheVal { dict: HashMap::new(), index: SkipList::new() }
+    }
+
+    pub fn len(&self) -> usize {
+        self.dict.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.dict.is_empty()
+    }
+
+    pub fn score(&self, member: &[u8]) -> Option<f64> {
+        self.dict.get(member).copied()
+    }
+
+    /// Sets the member's score, returning true when the member was newly added.
+    pub fn insert(&mut self, member: Vec<u8>, score: f64) -> bool {
+        match self.dict.insert(member.clone(), score) {
+            Some(old_score) => {
+                if old_score != score {
+                    self.index.remove(old_score, &member);
+                    self.index.insert(score, member);
+                }
+                false
+            },
+            None => {
+                self.index.insert(score, member);
+                true
+            }
+        }
+    }
+
+    pub fn remove(&mut self, member: &[u8]) -> bool {
+        match self.dict.remove(member) {
+            Some(score) => self.index.remove(score, member),
+            None => false
+        }
+    }
+
+    /// Removes and returns the lowest scoring member, or the highest one when `max` is set.
+    pub fn pop(&mut self, max: bool) -> Option<(Vec<u8>, f64)> {
+        let (score, member) = if max { self.index.last() } else { self.index.first() }?;
+        let member = member.clone();
+        self.remove(&member);
+        Some((member, score))
+    }
+
+    pub fn rank(&self, member: &[u8]) -> Option<usize> {
+        self.dict.get(member).and_then(|score| self.index.rank(*score, member))
+    }
+
+    /// Ranks `[start, end)` of the elements whose score falls between the bounds.
+    pub fn score_range(&self, min: &ScoreBound, max: &ScoreBound) -> (usize, usize) {
+        let start = self.index.count_while(|score, _| if min.exclusive { score <= min.value } else { score < min.value });
+        let end = self.index.count_while(|score, _| if max.exclusive { score < max.value } else { score <= max.value });
+        (start, end.max(start))
+    }
+
+    /// Ranks `[start, end)` of the elements whose member falls between the bounds,
+    /// only meaningful when every member shares the same score.
+    pub fn lex_range(&self, min: &LexBound, max: &LexBound) -> (usize, usize) {
+        let start = self.index.count_while(|_, member| match min {
+            LexBound::NegInf => false,
+            LexBound::PosInf => true,
+            LexBound::Inclusive(val) => member < val.as_slice(),
+            LexBound::Exclusive(val) => member <= val.as_slice()
+        });
+        let end = self.index.count_while(|_, member| match max {
+            LexBound::NegInf => false,
+            LexBound::PosInf => true,
+            LexBound::Inclusive(val) => member <= val.as_slice(),
+            LexBound::Exclusive(val) => member < val.as_slice()
+        });
+        (start, end.max(start))
+    }
+
+    pub fn iter_from(&self, rank: usize) -> SkipListIter<'_> {
+        self.index.iter_from(rank)
+    }
+
+    pub fn iter_rev_from(&self, rank: usize) -> SkipListIter<'_> {
+        self.index.iter_rev_from(rank)
+    }
+}
@@ -0,0 +1,333 @@
+use std::{collections::{BTreeMap, BTreeSet}, fmt::Display};
+
+use crate::{commands::parse_arg, redis::client::{StreamCacheVal, StreamItem}, resp::{create_array_resp, create_basic_err_resp, create_bulk_string_resp}};
+
+/// How many entries redis packs in one stream node, approximate (`~`) trimming only ever drops whole nodes.
+const STREAM_NODE_MAX_ENTRIES: usize = 100;
+
+/// A stream entry id. Ids order numerically by milliseconds then sequence number, and are
+/// written as `ms-seq`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct StreamId {
+    pub(crate) ms: u64,
+    pub(crate) seq: u64
+}
+
+impl StreamId {
+    pub const MIN: StreamId = StreamId { ms: 0, seq: 0 };
+    pub const MAX: StreamId = StreamId { ms: u64::MAX, seq: u64::MAX };
+
+    pub fn new(ms: u64, seq: u64) -> Self {
+        StreamId { ms, seq }
+    }
+
+    /// Parses an explicit `ms-seq` id, or a bare `ms` completed with `default_seq`.
+    pub fn parse(arg: &[u8], default_seq: u64) -> Option<Self> {
+        let arg = std::str::from_utf8(arg).ok()?;
+        match arg.split_once('-') {
+            Some((ms, seq)) => Some(StreamId::new(ms.parse().ok()?, seq.parse().ok()?)),
+            None => Some(StreamId::new(arg.parse().ok()?, default_seq))
+        }
+    }
+
+    /// The smallest id bigger than this one, if there is any.
+    pub fn next(&self) -> Option<Self> {
+        match self.seq.checked_add(1) {
+            Some(seq) => Some(StreamId::new(self.ms, seq)),
+            None => self.ms.checked_add(1).map(|ms| StreamId::new(ms, 0))
+        }
+    }
+}
+
+impl Display for StreamId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}-{}", self.ms, self.seq)
+    }
+}
+
+/// A pending entry: delivered to a consumer of the group but not acknowledged yet.
+#[derive(Clone)]
+pub struct PendingEntry {
+    pub(crate) consumer: Vec<u8>,
+    pub(crate) delivery_time: u128,
+    pub(crate) delivery_count: u64
+}
+
+#[derive(Clone)]
+pub struct Consumer {
+    pub(crate) seen_time: u128,
+    pub(crate) active_time: Option<u128>,
+    pub(crate) pending: BTreeSet<StreamId>
+}
+
+/// Consumer group state. The group-wide pending entries list is the source of truth,
+/// every consumer also keeps the ids it owns so it can be listed or dropped cheaply.
+#[derive(Clone)]
+pub struct ConsumerGroup {
+    pub(crate) last_delivered_id: StreamId,
+    pub(crate) pending: BTreeMap<StreamId, PendingEntry>,
+    pub(crate) consumers: BTreeMap<Vec<u8>, Consumer>
+}
+
+pub fn now_ms() -> u128 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_millis()
+}
+
+pub fn create_invalid_id_err_resp() -> Vec<u8> {
+    create_basic_err_resp("ERR Invalid stream ID specified as stream command argument".to_string())
+}
+
+pub fn create_no_group_err_resp(key: &[u8], group: &[u8]) -> Vec<u8> {
+    create_basic_err_resp(format!("NOGROUP No such key '{}' or consumer group '{}'", String::from_utf8_lossy(key), String::from_utf8_lossy(group)))
+}
+
+/// Formats an entry as `[id, [field, value, ...]]`.
+pub fn create_stream_item_resp(item: &StreamItem) -> Vec<u8> {
+    let data: Vec<Vec<u8>> = item.key_vals.iter().flat_map(|kv_item| {
+        vec![create_bulk_string_resp(&kv_item.key), create_bulk_string_resp(&kv_item.val)]
+    }).collect();
+    create_array_resp(vec![create_bulk_string_resp(item.id.to_string()), create_array_resp(data)])
+}
+
+/// Looks an entry up by id, entries being kept sorted by id.
+pub fn find_entry(items: &[StreamItem], id: StreamId) -> Option<&StreamItem> {
+    items.binary_search_by_key(&id, |item| item.id).ok().map(|pos| &items[pos])
+}
+
+/// The entries between two bounds, found by binary search.
+pub fn entries_in<'a>(items: &'a [StreamItem], start: &IdBound, end: &IdBound) -> &'a [StreamItem] {
+    let from = items.partition_point(|item| !start.admits_from_below(item.id));
+    let to = items.partition_point(|item| end.admits_from_above(item.id));
+    &items[from..to.max(from)]
+}
+
+impl ConsumerGroup {
+    pub fn new(last_delivered_id: StreamId) -> Self {
+        ConsumerGroup { last_delivered_id, pending: BTreeMap::new(), consumers: BTreeMap::new() }
+    }
+
+    /// Looks up the consumer, creating it if needed, and marks it as seen.
+    pub fn touch_consumer(&mut self, name: &[u8], now: u128) -> &mut Consumer {
+        let consumer = self.consumers.entry(name.to_vec()).or_insert_with(|| Consumer { seen_time: now, active_time: None, pending: BTreeSet::new() });
+        consumer.seen_time = now;
+        consumer
+    }
+
+    /// Records a delivery of `id` to `consumer`, taking the entry over from whoever owned it before.
+    /// Returns the new delivery count.
+    pub fn deliver(&mut self, id: StreamId, consumer: &[u8], now: u128, increment: bool) -> u64 {
+        let delivery_count = match self.pending.get(&id) {
+            Some(entry) if increment => entry.delivery_count + 1,
+            Some(entry) => entry.delivery_count,
+            None => 1
+        };
+        self.assign(id, consumer, now, delivery_count, now);
+        delivery_count
+    }
+
+    /// Hands the entry to `consumer` with the given delivery state, adding it to the pending entries list if needed.
+    pub fn assign(&mut self, id: StreamId, consumer: &[u8], delivery_time: u128, delivery_count: u64, now: u128) {
+        if let Some(entry) = self.pending.remove(&id) {
+            if let Some(owner) = self.consumers.get_mut(&entry.consumer) {
+                owner.pending.remove(&id);
+            }
+        }
+        self.pending.insert(id, PendingEntry { consumer: consumer.to_vec(), delivery_time, delivery_count });
+        let owner = self.touch_consumer(consumer, now);
+        owner.active_time = Some(now);
+        owner.pending.insert(id);
+    }
+
+    /// Removes the entry from the pending entries list, returning whether it was pending.
+    pub fn ack(&mut self, id: StreamId) -> bool {
+        match self.pending.remove(&id) {
+            Some(entry) => {
+                if let Some(owner) = self.consumers.get_mut(&entry.consumer) {
+                    owner.pending.remove(&id);
+                }
+                true
+            },
+            None => false
+        }
+    }
+
+    /// Deletes the consumer along with its pending entries, returning how many it had.
+    pub fn remove_consumer(&mut self, name: &[u8]) -> Option<usize> {
+        let consumer = self.consumers.remove(name)?;
+        for id in &consumer.pending {
+            self.pending.remove(id);
+        }
+        Some(consumer.pending.len())
+    }
+}
+
+/// One end of an id range: `-`/`+` leave it open, a `(` prefix makes it exclusive and a bare
+/// `ms` covers every sequence number of that millisecond.
+#[derive(Clone)]
+pub enum IdBound {
+    Unbounded,
+    Inclusive(StreamId),
+    Exclusive(StreamId)
+}
+
+impl IdBound {
+    pub fn parse(arg: &[u8], is_start: bool) -> Option<Self> {
+        match arg {
+            b"-" if is_start => Some(IdBound::Unbounded),
+            b"+" if !is_start => Some(IdBound::Unbounded),
+            b"-" => Some(IdBound::Inclusive(StreamId::MIN)),
+            b"+" => Some(IdBound::Inclusive(StreamId::MAX)),
+            _ => match arg.strip_prefix(b"(") {
+                Some(id) => StreamId::parse(id, if is_start { 0 } else { u64::MAX }).map(IdBound::Exclusive),
+                None => StreamId::parse(arg, if is_start { 0 } else { u64::MAX }).map(IdBound::Inclusive)
+            }
+        }
+    }
+
+    pub fn admits_from_below(&self, id: StreamId) -> bool {
+        match self {
+            IdBound::Unbounded => true,
+            IdBound::Inclusive(start) => id >= *start,
+            IdBound::Exclusive(start) => id > *start
+        }
+    }
+
+    pub fn admits_from_above(&self, id: StreamId) -> bool {
+        match self {
+            IdBound::Unbounded => true,
+            IdBound::Inclusive(end) => id <= *end,
+            IdBound::Exclusive(end) => id < *end
+        }
+    }
+}
+
+#[derive(Clone)]
+pub enum TrimThreshold {
+    MaxLen(usize),
+    MinId(StreamId)
+}
+
+/// A parsed MAXLEN/MINID trimming request as accepted by XADD and XTRIM.
+#[derive(Clone)]
+pub struct TrimArgs {
+    pub(crate) threshold: TrimThreshold,
+    pub(crate) approximate: bool,
+    pub(crate) limit: Option<usize>
+}
+
+/// Collects `MAXLEN|MINID [=|~] threshold` and `LIMIT count` options one at a time, so the
+/// caller can mix them with its own options.
+#[derive(Default)]
+pub struct TrimArgsParser {
+    threshold: Option<TrimThreshold>,
+    approximate: bool,
+    limit: Option<usize>
+}
+
+impl TrimArgsParser {
+    /// Tries to parse a trimming option at `args[pos]`, returning how many arguments it used
+    /// or `Ok(0)` when the argument is not a trimming option.
+    pub fn parse(&mut self, args: &[Vec<u8>], pos: usize) -> Result<usize, Vec<u8>> {
+        let syntax_err = || create_basic_err_resp("ERR syntax error".to_string());
+        let option = args[pos].to_ascii_lowercase();
+        match option.as_slice() {
+            b"maxlen" | b"minid" => {
+                if self.threshold.is_some() {
+                    return Err(create_basic_err_resp("ERR syntax error, MAXLEN and MINID options at the same time are not compatible".to_string()));
+                }
+                let mut used = 1;
+                if let Some(modifier) = args.get(pos + 1).filter(|arg| arg.as_slice() == b"~" || arg.as_slice() == b"=") {
+                    self.approximate = modifier.as_slice() == b"~";
+                    used += 1;
+                }
+                let value = args.get(pos + used).ok_or_else(syntax_err)?;
+                self.threshold = Some(match option.as_slice() {
+                    b"maxlen" => match parse_arg::<i64>(value) {
+                        Some(max_len) if max_len >= 0 => TrimThreshold::MaxLen(max_len as usize),
+                        Some(_) => return Err(create_basic_err_resp("ERR The MAXLEN argument must be >= 0.".to_string())),
+                        None => return Err(create_basic_err_resp("ERR value is not an integer or out of range".to_string()))
+                    },
+                    _ => TrimThreshold::MinId(StreamId::parse(value, 0).ok_or_else(create_invalid_id_err_resp)?)
+                });
+                Ok(used + 1)
+            },
+            b"limit" => {
+                self.limit = match args.get(pos + 1).map(|limit| parse_arg::<i64>(limit)) {
+                    Some(Some(limit)) if limit >= 0 => Some(limit as usize),
+                    Some(Some(_)) => return Err(create_basic_err_resp("ERR The LIMIT argument must be >= 0.".to_string())),
+                    Some(None) => return Err(create_basic_err_resp("ERR value is not an integer or out of range".to_string())),
+                    None => return Err(syntax_err())
+                };
+                Ok(2)
+            },
+            _ => Ok(0)
+        }
+    }
+
+    pub fn finish(self) -> Result<Option<TrimArgs>, Vec<u8>> {
+        match self.threshold {
+            Some(_) if self.limit.is_some() && !self.approximate => Err(create_basic_err_resp("ERR syntax error, LIMIT cannot be used without the special ~ option".to_string())),
+            Some(threshold) => Ok(Some(TrimArgs { threshold, approximate: self.approximate, limit: self.limit })),
+            None if self.limit.is_some() => Err(create_basic_err_resp("ERR syntax error, LIMIT cannot be used without specifying a trimming strategy".to_string())),
+            None => Ok(None)
+        }
+    }
+}
+
+impl TrimArgs {
+    /// The exact equivalent of this trim once applied to `stream`, which is what replicas are sent
+    /// so they end up with the same entries no matter how approximate trimming rounded.
+    pub fn to_exact_args(&self, stream: &StreamCacheVal) -> Vec<Vec<u8>> {
+        match &self.threshold {
+            TrimThreshold::MaxLen(max_len) if self.approximate => vec![b"MAXLEN".to_vec(), b"=".to_vec(), stream.stream.len().max(*max_len).to_string().into_bytes()],
+            TrimThreshold::MaxLen(max_len) => vec![b"MAXLEN".to_vec(), b"=".to_vec(), max_len.to_string().into_bytes()],
+            TrimThreshold::MinId(min_id) if self.approximate => {
+                let first_id = stream.stream.first().map_or(*min_id, |item| item.id);
+                vec![b"MINID".to_vec(), b"=".to_vec(), first_id.min(*min_id).to_string().into_bytes()]
+            },
+            TrimThreshold::MinId(min_id) => vec![b"MINID".to_vec(), b"=".to_vec(), min_id.to_string().into_bytes()]
+        }
+    }
+}
+
+impl StreamCacheVal {
+    pub fn new(stream: Vec<StreamItem>) -> Self {
+        let last_id = stream.last().map_or(StreamId::MIN, |item| item.id);
+        let entries_added = stream.len() as u64;
+        StreamCacheVal { stream, last_id, max_deleted_id: StreamId::MIN, entries_added, groups: BTreeMap::new() }
+    }
+
+    /// Remembers the biggest id ever removed from the stream, reported by XINFO STREAM.
+    pub fn note_deleted(&mut self, id: StreamId) {
+        if id > self.max_deleted_id {
+            self.max_deleted_id = id;
+        }
+    }
+
+    /// Drops entries from the head of the stream, returning how many were removed. Approximate
+    /// trimming only removes whole nodes' worth of entries and at most `limit` of them
+    /// (defaulting to 100 nodes, 0 meaning no limit).
+    pub fn trim(&mut self, trim: &TrimArgs) -> usize {
+        let mut removable = match &trim.threshold {
+            TrimThreshold::MaxLen(max_len) => self.stream.len().saturating_sub(*max_len),
+            TrimThreshold::MinId(min_id) => self.stream.partition_point(|item| item.id < *min_id)
+        };
+        if trim.approximate {
+            let limit = match trim.limit {
+                Some(0) => usize::MAX,
+                Some(limit) => limit,
+                None => STREAM_NODE_MAX_ENTRIES * 100
+            };
+            removable = removable.min(limit);
+            removable -= removable % STREAM_NODE_MAX_ENTRIES;
+        }
+        let last_removed = self.stream.drain(..removable).next_back();
+        if let Some(item) = last_removed {
+            self.note_deleted(item.id);
+        }
+        removable
+    }
+}
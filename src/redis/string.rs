@@ -0,0 +1,43 @@
+use crate::{redis::{client::{CacheVal, StringCacheVal}, keyspace::Keyspace}, resp::create_wrong_type_err_resp};
+
+impl StringCacheVal {
+    pub fn new(val: Vec<u8>) -> Self {
+        StringCacheVal { val }
+    }
+}
+
+/// The string stored at `key`. Errors with a WRONGTYPE reply when the key holds something else.
+pub fn live_string<'a>(cache: &'a mut Keyspace, key: &[u8]) -> Result<Option<&'a mut StringCacheVal>, Vec<u8>> {
+    match cache.get_mut(key) {
+        Some(CacheVal::String(val)) => Ok(Some(val)),
+        Some(_) => Err(create_wrong_type_err_resp()),
+        None => Ok(None)
+    }
+}
+
+/// Replaces the contents of the string at `key`, which keeps its expiry, creating the key when it
+/// is missing.
+pub fn update_string(cache: &mut Keyspace, key: &[u8], val: Vec<u8>) {
+    match cache.get_mut(key) {
+        Some(CacheVal::String(string_cache_val)) => string_cache_val.val = val,
+        _ => {
+            cache.insert(key.to_vec(), CacheVal::String(StringCacheVal::new(val)));
+        }
+    }
+}
+
+/// Clamps the inclusive `start` and `end` offsets of GETRANGE, BITCOUNT and BITPOS, which may
+/// count from the end, to a value of `len` bytes or bits. Unlike LRANGE, offsets before the start
+/// are clamped to the first one. `None` when nothing is in range.
+pub fn clamp_offsets(start: i64, end: i64, len: usize) -> Option<(usize, usize)> {
+    let len = len as i64;
+    if len == 0 || (start < 0 && end < 0 && start > end) {
+        return None;
+    }
+    let start = if start < 0 { (start + len).max(0) } else { start };
+    let end = if end < 0 { (end + len).max(0) } else { end.min(len - 1) };
+    if start > end {
+        return None;
+    }
+    Some((start as usize, end as usize))
+}
@@ -7,6 +7,8 @@ pub enum RespError {
     InvalidBulkString(String),
     InvalidSimpleString(String),
     InvalidArray(String),
+    /// The buffer ends before the value does, more has to be read first.
+    Incomplete,
     Other(String)
 }
 
@@ -16,36 +18,53 @@ impl std::fmt::Display for RespError {
             RespError::InvalidBulkString(msg) => msg.as_str().fmt(f),
             RespError::InvalidSimpleString(msg) => msg.as_str().fmt(f),
             RespError::InvalidArray(msg) => msg.as_str().fmt(f),
+            RespError::Incomplete => "Incomplete value".fmt(f),
             RespError::Other(msg) => msg.as_str().fmt(f)
         }
     }
 }
 
-pub fn create_simple_string_resp(str: String) -> String {
-    return format!("+{}\r\n", str);
+pub fn create_simple_string_resp(str: String) -> Vec<u8> {
+    format!("+{}\r\n", str).into_bytes()
 }
 
-pub fn create_basic_err_resp(str: String) -> String {
-    return format!("-{}\r\n", str);
+pub fn create_basic_err_resp(str: String) -> Vec<u8> {
+    format!("-{}\r\n", str).into_bytes()
 }
 
-pub fn create_bulk_string_resp(str: String) -> String {
-    let len = str.len();
-    return format!("${}\r\n{}\r\n", len, str);
+/// Bulk strings are binary safe, anything from text to raw values can be replied with.
+pub fn create_bulk_string_resp<T>(val: T) -> Vec<u8> where T : AsRef<[u8]> {
+    let val = val.as_ref();
+    let mut resp = format!("${}\r\n", val.len()).into_bytes();
+    resp.extend_from_slice(val);
+    resp.extend_from_slice(b"\r\n");
+    resp
 }
 
-pub fn create_null_bulk_string_resp() -> String {
-    return "$-1\r\n".to_string();
+pub fn create_null_bulk_string_resp() -> Vec<u8> {
+    b"$-1\r\n".to_vec()
 }
 
-pub fn create_int_resp<T>(n: T) -> String where T : Display {
-    return format!(":{}\r\n", n);
+pub fn create_int_resp<T>(n: T) -> Vec<u8> where T : Display {
+    format!(":{}\r\n", n).into_bytes()
 }
 
-pub fn create_array_resp(items: Vec<String>) -> String {
-    let mut str = format!("*{}\r\n", items.len());
+pub fn create_array_resp(items: Vec<Vec<u8>>) -> Vec<u8> {
+    let mut resp = format!("*{}\r\n", items.len()).into_bytes();
     for item in items {
-        str.push_str(&item);
+        resp.extend(item);
     }
-    str
-}
\ No newline at end of file
+    resp
+}
+
+pub fn create_null_array_resp() -> Vec<u8> {
+    b"*-1\r\n".to_vec()
+}
+
+pub fn create_wrong_type_err_resp() -> Vec<u8> {
+    create_basic_err_resp("WRONGTYPE Operation against a key holding the wrong kind of value".to_string())
+}
+
+pub fn create_wrong_args_err_resp(command: &str) -> Vec<u8> {
+    create_basic_err_resp(format!("ERR wrong number of arguments for '{}' command", command))
+}
@@ -5,7 +5,7 @@ use crate::{resp::{create_array_resp, create_basic_err_resp, create_bulk_string_
 
 #[derive(Debug, Clone)]
 pub enum RespType {
-    String(String),
+    String(Vec<u8>),
     Error(String),
     Int(i64),
     Array(Vec<RespType>),
@@ -16,18 +16,24 @@ pub enum RespType {
 
 impl RespType {
 
-    pub fn to_string(&self) -> String {
+    pub fn to_bytes(&self) -> Vec<u8> {
         match self {
-            RespType::String(s) => create_bulk_string_resp(s.to_string()),
+            RespType::String(s) => create_bulk_string_resp(s),
             RespType::Error(e) => create_basic_err_resp(e.to_string()),
             RespType::Int(i) => create_int_resp(i),
-            RespType::Array(resp_types) => create_array_resp(resp_types.iter().map(|x| x.to_string()).collect()),
+            RespType::Array(resp_types) => create_array_resp(resp_types.iter().map(|x| x.to_bytes()).collect()),
             RespType::NullArray => create_array_resp(vec![]),
             RespType::NullBulkString => create_null_bulk_string_resp(),
-            RespType::Other => "".into()
+            RespType::Other => vec![]
         }
     }
+
+    /// Parses the value starting at `pos`, returning it with the position right after it.
+    /// `RespError::Incomplete` means the buffer ends before the value does.
     pub fn parse(buffer: &BytesMut, pos: usize) -> Result<(RespType, usize), RespError> {
+        if pos >= buffer.len() {
+            return Err(RespError::Incomplete);
+        }
         let c = buffer[pos] as char;
         match c {
             '$' => Self::bulk_string(buffer, pos + 1),
@@ -37,87 +43,79 @@ impl RespType {
         }
     }
 
-    fn word(buffer: &BytesMut, pos: usize) -> Option<(String, usize)> {
-        if buffer.len() < pos {
-            return None;
+    /// Parses the RDB file a master sends after FULLRESYNC, framed like a bulk string without
+    /// the trailing CRLF.
+    pub fn rdb_file(buffer: &BytesMut, pos: usize) -> Result<(RespType, usize), RespError> {
+        if pos >= buffer.len() {
+            return Err(RespError::Incomplete);
+        }
+        let (size, bytes_read) = Self::int(buffer, pos + 1)?;
+        let offset = pos + 1 + bytes_read;
+        if size < 0 || buffer.len() < offset + size as usize {
+            return Err(RespError::Incomplete);
         }
+        Ok((RespType::String(buffer[offset..offset + size as usize].to_vec()), offset + size as usize))
+    }
 
-        let mut word: Option<&[u8]> = None;
-        let mut bytes_read = 0;
-        for i in pos..buffer.len() {
+    fn word(buffer: &BytesMut, pos: usize) -> Option<(&[u8], usize)> {
+        for i in (pos + 1)..buffer.len() {
             if buffer[i - 1] == b'\r' && buffer[i] == b'\n' {
-                word = Some(&buffer[pos..(i-1)]);
-                bytes_read = i + 1 - pos;   
-                break;  
+                return Some((&buffer[pos..(i - 1)], i + 1 - pos));
             }
         }
-
-        if word.is_none() {
-            return None;
-        }
-
-        let utf8_str= String::from_utf8(word.expect("to be present").to_vec());
-        match utf8_str {
-            Ok(s) => Some((s, bytes_read)),
-            Err(_) => None
-        }
+        None
     }
 
-    fn int(buffer: &BytesMut, pos: usize) -> Option<(i64, usize)> {
-        let word = Self::word(buffer, pos);
-        if word.is_none(){
-            return None;
-        }
-        let (word_str, bytes_read) = word.expect("to be here");
-        let int = word_str.parse::<i64>();
-        match int {
-            Ok(n) => {
-                Some((n, bytes_read))
-            },
-            Err(_) => {
-                None
-            }
+    fn int(buffer: &BytesMut, pos: usize) -> Result<(i64, usize), RespError> {
+        let (word, bytes_read) = Self::word(buffer, pos).ok_or(RespError::Incomplete)?;
+        match std::str::from_utf8(word).ok().and_then(|word| word.parse::<i64>().ok()) {
+            Some(n) => Ok((n, bytes_read)),
+            None => Err(RespError::Other(String::from("Invalid integer")))
         }
     }
 
     fn simple_string(buffer: &BytesMut, pos: usize) -> Result<(RespType, usize), RespError> {
-        if let Some((word_str, len)) = Self::word(&buffer, pos) {
-            Ok((RespType::String(word_str), len + 1))
-        } else {
-            Err(RespError::InvalidSimpleString(String::from("Invalid value for simple string")))
+        match Self::word(buffer, pos) {
+            Some((word, len)) => Ok((RespType::String(word.to_vec()), pos + len)),
+            None => Err(RespError::Incomplete)
         }
     }
 
     fn bulk_string(buffer: &BytesMut, pos: usize) -> Result<(RespType, usize), RespError> {
         let (str_size, bytes_read) = match Self::int(buffer, pos) {
-            Some(res) => res,
-            None => return Err(RespError::InvalidBulkString(String::from("Bad Bulk str"))),
+            Ok(res) => res,
+            Err(RespError::Incomplete) => return Err(RespError::Incomplete),
+            Err(_) => return Err(RespError::InvalidBulkString(String::from("Bad Bulk str"))),
         };
+        if str_size < 0 {
+            return Ok((RespType::NullBulkString, pos + bytes_read));
+        }
 
         let offset = pos + bytes_read;
-        let word = &buffer[offset..(offset+str_size as usize)];
-        let utf8_str= String::from_utf8(word.to_vec());
-        match utf8_str {
-            Ok(s) => Ok((RespType::String(s), bytes_read + 2 + (str_size as usize + 1))),
-            Err(_) => Ok((RespType::Other, bytes_read + (str_size as usize + 1)))
+        let end = offset + str_size as usize;
+        if buffer.len() < end + 2 {
+            return Err(RespError::Incomplete);
         }
+        Ok((RespType::String(buffer[offset..end].to_vec()), end + 2))
     }
 
     fn array(buffer: &BytesMut, pos: usize) -> Result<(RespType, usize), RespError> {
         let (array_size, bytes_read) = match Self::int(buffer, pos) {
-            Some(i) => i,
-            None => return Err(RespError::InvalidArray(String::from("Bad Array length"))),
+            Ok(res) => res,
+            Err(RespError::Incomplete) => return Err(RespError::Incomplete),
+            Err(_) => return Err(RespError::InvalidArray(String::from("Bad Array length"))),
         };
 
-        let mut values = Vec::with_capacity(array_size as usize);
+        let mut values = Vec::with_capacity(array_size.max(0) as usize);
         let mut curr_pos = pos + bytes_read;
 
         for _ in 0..array_size {
             match Self::parse(buffer, curr_pos) {
-                Ok(res) => {
-                    values.push(res.0);
-                    curr_pos += res.1;
+                Ok((value, end)) => {
+                    values.push(value);
+                    curr_pos = end;
                 },
+                Err(RespError::Incomplete) => return Err(RespError::Incomplete),
                 Err(_) => return Err(RespError::InvalidArray(String::from("Bad array")))
             }
         }
@@ -142,7 +140,7 @@ mod tests {
         
         let (resp, _) = result.unwrap();
         match resp {
-            RespType::String(s) => assert_eq!(s, "OK"),
+            RespType::String(s) => assert_eq!(s, b"OK"),
             _ => panic!("Expected String type")
         }
     }
@@ -157,7 +155,7 @@ mod tests {
         
         let (resp, _) = result.unwrap();
         match resp {
-            RespType::String(s) => assert_eq!(s, "hello"),
+            RespType::String(s) => assert_eq!(s, b"hello"),
             _ => panic!("Expected String type")
         }
     }
@@ -176,11 +174,11 @@ mod tests {
             RespType::Array(arr) => {
                 assert_eq!(arr.len(), 2);
                 match &arr[0] {
-                    RespType::String(s) => assert_eq!(s, "ECHO"),
+                    RespType::String(s) => assert_eq!(s, b"ECHO"),
                     _ => panic!("Expected String type")
                 }
                 match &arr[1] {
-                    RespType::String(s) => assert_eq!(s, "hello"),
+                    RespType::String(s) => assert_eq!(s, b"hello"),
                     _ => panic!("Expected String type")
                 }
             },
@@ -203,11 +201,30 @@ mod tests {
             RespType::Array(arr) => {
                 assert_eq!(arr.len(), 1);
                 match &arr[0] {
-                    RespType::String(s) => assert_eq!(s, "PING"),
+                    RespType::String(s) => assert_eq!(s, b"PING"),
                     _ => panic!("Expected String type")
                 }
             },
             _ => panic!("Expected Array type")
         }
     }
+
+    #[test]
+    fn test_binary_and_partial_bulk_string() {
+        let mut buffer = BytesMut::new();
+        buffer.extend_from_slice(b"*1\r\n$6\r\n\xff\x00\r\n\xfe");
+        assert!(matches!(RespType::parse(&buffer, 0), Err(RespError::Incomplete)));
+
+        buffer.extend_from_slice(b"a\r\n");
+        let (resp, end) = RespType::parse(&buffer, 0).unwrap();
+        assert_eq!(end, buffer.len());
+        assert_eq!(resp.to_bytes(), buffer.to_vec());
+        match resp {
+            RespType::Array(arr) => match &arr[0] {
+                RespType::String(s) => assert_eq!(s, b"\xff\x00\r\n\xfea"),
+                _ => panic!("Expected String type")
+            },
+            _ => panic!("Expected Array type")
+        }
+    }
 }